@@ -0,0 +1,145 @@
+use std::collections::BTreeMap;
+use chrono::prelude::{DateTime, Local};
+
+use crate::units::day::Day;
+use crate::units::interval::DATETIME_FMT;
+use crate::utils::work_summary::WorkSummary;
+
+/// What happens next in a day being built.
+#[derive(Debug,Clone)]
+enum Event {
+    Task(String, DateTime<Local>),
+    Break(String, DateTime<Local>),
+}
+
+/// Builds a [`Day`] step by step, the way it would have been punched: a start, then tasks
+/// and breaks in order, then an optional end. Nothing is written to disk.
+///
+/// ```
+/// use chrono::prelude::{Local, TimeZone};
+/// use punch::prelude::*;
+///
+/// let at = |hour: u32, min: u32| Local.with_ymd_and_hms(2024, 3, 4, hour, min, 0).unwrap();
+/// let day: Day = Day::builder()
+///     .start(at(9, 0), "coding")
+///     .time_to_do(450)
+///     .pause(at(12, 0), "lunch")
+///     .task(at(12, 30), "review")
+///     .end(at(17, 0))
+///     .build()
+///     .unwrap();
+///
+/// assert!(day.has_ended());
+/// assert_eq!(day.get_total_break_time_secs(), Some(30 * 60));
+/// assert_eq!(day.get_time_done_secs(), Some(7 * 60 * 60 + 30 * 60));
+/// assert_eq!(day.get_time_left_secs(), Some(0));
+/// assert_eq!(day.get_tasks_in_chronological_order(), vec!["coding", "lunch", "review"]);
+/// ```
+#[derive(Debug,Clone,Default)]
+pub struct DayBuilder {
+    start: Option<(DateTime<Local>, String)>,
+    events: Vec<Event>,
+    end: Option<DateTime<Local>>,
+    time_to_do: u64,
+    paid_break_minutes: u64,
+    summaries: Vec<WorkSummary>,
+    meta: BTreeMap<String, String>,
+}
+
+impl Day {
+    pub fn builder() -> DayBuilder {
+        return DayBuilder::default();
+    }
+}
+
+impl DayBuilder {
+    /// When the day starts, and on what.
+    pub fn start(mut self, at: DateTime<Local>, task: &str) -> Self {
+        self.start = Some((at, task.to_string()));
+        return self;
+    }
+
+    /// Switches to `task` at `at`, which also ends a break.
+    pub fn task(mut self, at: DateTime<Local>, task: &str) -> Self {
+        self.events.push(Event::Task(task.to_string(), at));
+        return self;
+    }
+
+    /// Takes a break called `name` at `at`, until the next task or the end.
+    pub fn pause(mut self, at: DateTime<Local>, name: &str) -> Self {
+        self.events.push(Event::Break(name.to_string(), at));
+        return self;
+    }
+
+    pub fn end(mut self, at: DateTime<Local>) -> Self {
+        self.end = Some(at);
+        return self;
+    }
+
+    /// The minutes to work that day. 0 unless set.
+    pub fn time_to_do(mut self, minutes: u64) -> Self {
+        self.time_to_do = minutes;
+        return self;
+    }
+
+    /// How many minutes of the day's breaks count as time done.
+    pub fn paid_break_minutes(mut self, minutes: u64) -> Self {
+        self.paid_break_minutes = minutes;
+        return self;
+    }
+
+    /// A summary of a task, as with `punch add-summary`.
+    pub fn summary(mut self, category: &str, project: &str, task: &str, summary: &str) -> Self {
+        self.summaries.push(WorkSummary::new(category.to_string(), project.to_string(), task.to_string(), summary.to_string()));
+        return self;
+    }
+
+    /// A key-value, as with `punch meta`.
+    pub fn meta(mut self, key: &str, value: &str) -> Self {
+        self.meta.insert(key.to_string(), value.to_string());
+        return self;
+    }
+
+    /// The day, or why it can't be built: a missing start, or times out of order.
+    ///
+    /// ```
+    /// use chrono::prelude::{Local, TimeZone};
+    /// use punch::prelude::*;
+    ///
+    /// let at = |hour: u32| Local.with_ymd_and_hms(2024, 3, 4, hour, 0, 0).unwrap();
+    /// assert!(Day::builder().task(at(9), "coding").build().is_err());
+    /// assert!(Day::builder().start(at(9), "coding").task(at(8), "review").build().is_err());
+    /// assert!(!Day::builder().start(at(9), "coding").build().unwrap().has_ended());
+    /// ```
+    pub fn build(self) -> Result<Day, String> {
+        let (start, first_task): (DateTime<Local>, String) = self.start.ok_or("A day needs a start!")?;
+        let mut day: Day = Day::new(&start, first_task, self.time_to_do);
+        let mut latest: DateTime<Local> = start;
+        for event in self.events {
+            let (Event::Task(_, at) | Event::Break(_, at)) = &event;
+            check_in_order(&latest, at)?;
+            latest = *at;
+            match event {
+                Event::Task(task, at) => day.start_new_block(task, &at)?,
+                Event::Break(name, at) => day.start_break_at(name, &at)?,
+            }
+        }
+        if let Some(end) = self.end {
+            check_in_order(&latest, &end)?;
+            day.end_day_at(&end)?;
+        }
+        day.paid_break_minutes = self.paid_break_minutes;
+        day.summaries = self.summaries;
+        day.meta = self.meta;
+        return Ok(day);
+    }
+}
+
+fn check_in_order(before: &DateTime<Local>, after: &DateTime<Local>) -> Result<(), String> {
+    if after < before {
+        return Err(format!(
+            "'{}' comes before '{}': a day's times should be in order!",
+            after.format(DATETIME_FMT), before.format(DATETIME_FMT)));
+    }
+    return Ok(());
+}
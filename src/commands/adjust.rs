@@ -0,0 +1,51 @@
+use std::process::exit;
+use chrono::prelude::{DateTime, Local};
+
+use crate::units::interval::Dt;
+use crate::utils::adjustments::{BalanceAdjustment, format_adjustment, read_adjustments, write_adjustments};
+use crate::utils::state::{State, get_state, update_state};
+
+/// `punch adjust-balance +120 "comp day granted"`: credits (or with a minus, debits) the
+/// balance by that many minutes, keeping the reason. Without args, lists the adjustments so far.
+pub fn adjust_balance(now: &DateTime<Local>, other_args: Vec<String>) {
+    let (minutes_str, reason): (&String, String) = match other_args.split_first() {
+        None => {
+            list_adjustments();
+            return;
+        },
+        Some((minutes_str, reason_args)) if !reason_args.is_empty() => (minutes_str, reason_args.join(" ")),
+        Some(_) => {
+            eprintln!("'punch adjust-balance' needs a reason too, e.g. 'punch adjust-balance +120 \"comp day granted\"'");
+            exit(1);
+        },
+    };
+    let minutes: i64 = match minutes_str.trim_start_matches('+').parse::<i64>() {
+        Ok(minutes) if minutes != 0 => minutes,
+        _ => {
+            eprintln!("Expected a number of minutes to credit like '+120', or to debit like '-30'! Given: '{}'", minutes_str);
+            exit(1);
+        },
+    };
+
+    let mut adjustments: Vec<BalanceAdjustment> = read_adjustments();
+    adjustments.push(BalanceAdjustment {time: Dt::new(*now), minutes: minutes, reason: reason.clone()});
+    write_adjustments(&adjustments);
+    let mut state: State = get_state();
+    state.update_minutes_behind(-minutes);
+    update_state(&state);
+    let minutes_behind: i64 = state.minutes_behind();
+    println!("Adjusted the balance by {} for '{}'. Minutes behind overall: {}", format_adjustment(minutes), reason, minutes_behind);
+}
+
+fn list_adjustments() {
+    let adjustments: Vec<BalanceAdjustment> = read_adjustments();
+    if adjustments.is_empty() {
+        println!("No adjustments yet. Add one with 'punch adjust-balance +120 \"comp day granted\"'.");
+        return;
+    }
+    for adjustment in &adjustments {
+        println!("{}  {:>10}  {}", adjustment.time.as_dt().format("%Y-%m-%d %H:%M"), format_adjustment(adjustment.minutes), adjustment.reason);
+    }
+    let total: i64 = adjustments.iter().map(|adjustment: &BalanceAdjustment| adjustment.minutes).sum();
+    println!("Total: {}", format_adjustment(total));
+}
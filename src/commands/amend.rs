@@ -0,0 +1,53 @@
+use std::process::exit;
+use chrono::prelude::{DateTime, Local};
+
+use crate::units::day::{Day, write_day};
+use crate::utils::args::get_flag_value;
+use crate::utils::clock::parse_time_on;
+use crate::utils::state::{State, get_state, update_state};
+
+/// Moves the most recent event of the day (the punch in, last pause, resume or task
+/// switch, or the punch out) to the time given with `--at`.
+pub fn amend(now: &DateTime<Local>, mut day: Day, other_args: Vec<String>) {
+    let (event, event_time): (&str, DateTime<Local>) = day.get_last_event();
+    let at: DateTime<Local> = match get_flag_value(&other_args, "--at") {
+        Some(at_str) => parse_time_on(&at_str, &event_time.date_naive()).unwrap_or_else(|msg: String| {
+            eprintln!("{}", msg);
+            exit(1);
+        }),
+        None => {
+            eprintln!("'punch amend' needs the new time for the {}, e.g. '--at 13:05'", describe_event(event));
+            exit(1);
+        },
+    };
+    if at > *now {
+        eprintln!("Can't move the {} into the future!", describe_event(event));
+        exit(1);
+    }
+    if event == "in" && at.date_naive() != event_time.date_naive() {
+        eprintln!("Can't move the punch in to another day!");
+        exit(1);
+    }
+
+    let time_left_before: Option<i64> = day.get_time_left_secs();
+    if let Err(msg) = day.amend_last_event_at(&at) {
+        eprintln!("Can't move the {} to {}. {}", describe_event(event), at.format("%H:%M"), msg);
+        exit(1);
+    }
+    write_day(&day);
+    println!("Moved the {} from {} to {}", describe_event(event), event_time.format("%H:%M"), at.format("%H:%M"));
+
+    // Finished days have already counted towards minutes behind, so only the change is applied.
+    if let (Some(before), Some(after)) = (time_left_before, day.get_time_left_secs()) {
+        let mut state: State = get_state();
+        state.update_minutes_behind(after / 60 - before / 60);
+        update_state(&state);
+    }
+}
+
+fn describe_event(event: &str) -> String {
+    return match event {
+        "in" | "out" => format!("punch {}", event),
+        other => format!("last {}", other),
+    };
+}
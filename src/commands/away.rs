@@ -0,0 +1,94 @@
+use std::io::{IsTerminal, stdin};
+use std::process::exit;
+use chrono::prelude::{DateTime, Local};
+
+use crate::units::components::AwaySpan;
+use crate::units::day::{Day, get_current_day, write_day};
+use crate::utils::aggregate::format_secs;
+use crate::utils::args::has_flag;
+use crate::utils::prompt::choose;
+use crate::commands::core::punch_out;
+
+/// `punch away`: goes through the time the daemon counted as a break while you were away,
+/// asking whether it was one. `--work` or `--break` answer for all of it at once.
+pub fn away(now: &DateTime<Local>, other_args: Vec<String>) {
+    let day: Day = get_current_day(now).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+    if day.away_spans.is_empty() {
+        println!("There's no time away to go through.");
+        return;
+    }
+    let answer: Option<&str> = match (has_flag(&other_args, "--work"), has_flag(&other_args, "--break")) {
+        (true, true) => {
+            eprintln!("Pass one of '--work' and '--break', not both!");
+            exit(1);
+        },
+        (true, false) => Some("w"),
+        (false, true) => Some("b"),
+        (false, false) if stdin().is_terminal() => None,
+        (false, false) => {
+            for span in &day.away_spans {
+                println!("{}", describe_span(span));
+            }
+            println!("Run `punch away --work` or `punch away --break` to settle them.");
+            return;
+        },
+    };
+    settle_away_spans(day, answer);
+}
+
+/// Asks about the current day's time away before running a command, if there is any and
+/// punch is running on a terminal.
+pub fn review_away_time_if_any(now: &DateTime<Local>) {
+    if !stdin().is_terminal() {
+        return;
+    }
+    let Ok(day) = get_current_day(now) else {
+        return;
+    };
+    if day.away_spans.is_empty() || day.check_editable(false).is_err() {
+        return;
+    }
+    settle_away_spans(day, None);
+}
+
+/// Settles each of the day's away spans, asking about each one unless given an `answer`:
+/// "b" leaves it as a break, "w" gives it back to the task from before and "e" ends the day
+/// when you left. Writes the day afterwards.
+fn settle_away_spans(mut day: Day, answer: Option<&str>) {
+    let spans: Vec<AwaySpan> = std::mem::take(&mut day.away_spans);
+    for span in spans {
+        let chosen: String = match answer {
+            Some(answer) => answer.to_string(),
+            None => {
+                let options: [(&str, &str); 3] = [("b", "It was a break"), ("w", "I was working"), ("e", "I'd finished for the day")];
+                choose(&describe_span(&span), &options)
+            },
+        };
+        let (start, end): (DateTime<Local>, DateTime<Local>) = (span.start.as_dt(), span.end.as_dt());
+        match chosen.as_str() {
+            "w" => {
+                let task: String = day.get_work_task_before(&start).unwrap_or(day.get_task_name(0));
+                match day.set_span(&start, &end, &task, false) {
+                    Ok(()) => println!("Counted {}-{} as work on '{}'.", start.format("%H:%M"), end.format("%H:%M"), task),
+                    Err(msg) => eprintln!("Couldn't change {}-{}: {}", start.format("%H:%M"), end.format("%H:%M"), msg),
+                }
+            },
+            "e" => match day.drop_blocks_from(&start) {
+                // Any later time away goes with the rest of the day.
+                Ok(()) => return punch_out(&start, day),
+                Err(msg) => eprintln!("Couldn't end the day at {}: {}", start.format("%H:%M"), msg),
+            },
+            _ => {},
+        }
+    }
+    write_day(&day);
+}
+fn describe_span(span: &AwaySpan) -> String {
+    let (start, end): (DateTime<Local>, DateTime<Local>) = (span.start.as_dt(), span.end.as_dt());
+    return format!(
+        "You were away ({}) from {} to {} ({}), which was counted as a break.",
+        span.reason, start.format("%H:%M"), end.format("%H:%M"), format_secs((end - start).num_seconds()));
+}
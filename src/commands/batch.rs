@@ -0,0 +1,201 @@
+use std::collections::BTreeMap;
+use std::io::{BufRead, stdin};
+use std::process::exit;
+use chrono::prelude::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+use chrono::Duration;
+
+use crate::commands::core::create_day;
+use crate::units::day::{Day, read_day_for_date, try_write_day};
+use crate::utils::args::{get_flag_value, get_positional_args, split_command_line};
+use crate::utils::config::{Config, get_config};
+use crate::utils::ledger::check_week_open;
+use crate::utils::state::{State, get_state, update_state};
+use crate::utils::mqtt::publish_event;
+
+const BATCH_TIME_FMT: &str = "%Y-%m-%d %H:%M";
+
+/// Days touched by the batch so far, and the change to minutes behind. Nothing is
+/// written to disk until `commit` is called.
+#[derive(Default)]
+pub struct BatchState {
+    days: BTreeMap<NaiveDate, Day>,
+    minutes_behind_delta: i64,
+    /// State-changing events to publish once committed: (event, date of its day, time).
+    events: Vec<(String, NaiveDate, DateTime<Local>)>,
+}
+
+impl BatchState {
+    pub fn new() -> Self {
+        return Self::default();
+    }
+
+    /// Writes every day touched, unless one of them is in a closed week, in which case
+    /// none of them are.
+    pub fn commit(&self) -> Result<(), String> {
+        for date in self.days.keys() {
+            check_week_open(date)?;
+        }
+        for day in self.days.values() {
+            try_write_day(day)?;
+        }
+        if self.minutes_behind_delta != 0 {
+            let mut state: State = get_state();
+            state.update_minutes_behind(self.minutes_behind_delta);
+            update_state(&state);
+        }
+        for (event, date, at) in &self.events {
+            publish_event(event, self.days.get(date).expect("Events are only recorded for touched days"), at);
+        }
+        return Ok(());
+    }
+
+    pub fn num_days(&self) -> usize {
+        return self.days.len();
+    }
+
+    fn get_day(&self, date: &NaiveDate) -> Option<Day> {
+        return match self.days.get(date) {
+            Some(day) => Some(day.clone()),
+            None => read_day_for_date(date).ok(),
+        };
+    }
+
+    fn get_current_day(&self, at: &DateTime<Local>) -> Result<Day, String> {
+        let today: NaiveDate = at.date_naive();
+        let day: Day = self.get_day(&today)
+            .or(self.get_day(&(today - Duration::days(1))))
+            .ok_or("Can't get current day. Have you punched in?".to_string())?;
+        day.check_editable(false)?;
+        return Ok(day);
+    }
+
+    fn put_day(&mut self, day: Day) {
+        self.days.insert(day.get_day_start().as_dt().date_naive(), day);
+    }
+}
+
+/// Reads one command per line from stdin and applies them all, or none of them if any fails.
+/// Each line is a subcommand with its arguments, optionally with `--at "YYYY-MM-DD HH:MM"`.
+/// Blank lines and lines starting with `#` are skipped.
+pub fn batch(now: &DateTime<Local>) {
+    let config: Config = get_config();
+    let mut state: BatchState = BatchState::new();
+    let mut applied: u64 = 0;
+    for (ind, line_result) in stdin().lock().lines().enumerate() {
+        let line: String = line_result.unwrap_or_else(|err| {
+            eprintln!("Couldn't read stdin: {}", err);
+            exit(1);
+        });
+        if line.trim().is_empty() || line.trim().starts_with('#') {
+            continue;
+        }
+        match split_command_line(&line).and_then(|args: Vec<String>| apply_args(&mut state, args, now, &config)) {
+            Ok(msg) => {
+                println!("{}", msg);
+                applied += 1;
+            },
+            Err(msg) => {
+                eprintln!("Line {}: {}", ind + 1, msg);
+                eprintln!("No changes were made.");
+                exit(1);
+            },
+        }
+    }
+
+    if let Err(msg) = state.commit() {
+        eprintln!("{}", msg);
+        eprintln!("No changes were made.");
+        exit(1);
+    }
+    println!("Applied {} commands to {} days.", applied, state.num_days());
+}
+
+/// Applies a single command, given as its args, to the state in memory.
+pub fn apply_args(state: &mut BatchState, args: Vec<String>, now: &DateTime<Local>, config: &Config) -> Result<String, String> {
+    let at: DateTime<Local> = match get_flag_value(&args, "--at") {
+        Some(at_str) => parse_batch_time(&at_str)?,
+        None => *now,
+    };
+    let positional: Vec<String> = get_positional_args(&args, &["--at"]);
+    let (command, rest): (&String, &[String]) = positional.split_first().ok_or("Empty command".to_string())?;
+    let single_arg = |default: Option<String>| -> Result<String, String> {
+        return match (rest.len(), &default) {
+            (0, Some(value)) => Ok(value.clone()),
+            (1, _) => Ok(rest[0].clone()),
+            (_, _) => Err(format!("'{}' takes {} argument", command, if default.is_some() {"at most one"} else {"exactly one"})),
+        };
+    };
+
+    if command == "in" {
+        if state.get_day(&at.date_naive()).is_some() {
+            return Err(format!("Already clocked in on {}", at.date_naive()));
+        }
+        check_week_open(&at.date_naive())?;
+        let task: String = single_arg(Some(config.get_default_punch_in_task().to_string()))?;
+        let (mut day, _): (Day, String) = create_day(&at, task, config);
+        if let Ok(Some(expected_start)) = config.expected_start() {
+            day.record_lateness(&expected_start);
+        }
+        state.put_day(day);
+        state.events.push(("in".to_string(), at.date_naive(), at));
+        return Ok(format!("Clocked in at {}", at));
+    }
+
+    let mut day: Day = state.get_current_day(&at)?;
+    let msg: String = match command.as_str() {
+        "out" => {
+            day.end_day_at(&at).map_err(|x: &str| x.to_string())?;
+            state.minutes_behind_delta += day.get_time_left_secs().expect("Day has ended") / 60;
+            format!("Punched out at {}", at)
+        },
+        "pause" => {
+            let name: String = single_arg(Some(config.get_default_break_task().to_string()))?;
+            day.start_break_at(name, &at).map_err(|x: &str| x.to_string())?;
+            format!("Took a break at {}", at)
+        },
+        "resume" => {
+            let name: String = single_arg(Some(day.get_task_name(-2)))?;
+            day.start_new_block(name, &at).map_err(|x: &str| x.to_string())?;
+            format!("Back to work at {}", at)
+        },
+        "task" => {
+            let name: String = single_arg(None)?;
+            day.start_new_block(name.clone(), &at).map_err(|x: &str| x.to_string())?;
+            format!("Now working on '{}' from {}", name, at)
+        },
+        "update-task" => {
+            let name: String = single_arg(None)?;
+            day.update_current_task_name(name.clone()).map_err(|x: &str| x.to_string())?;
+            format!("Updated the current task to '{}'", name)
+        },
+        "note" => {
+            let msg: String = single_arg(None)?;
+            day.add_note(&at, &msg);
+            format!("Added note '{}' at {}", msg, at)
+        },
+        "add-summary" => {
+            if rest.len() != 4 {
+                return Err("'add-summary' takes exactly 4 arguments: category, project, task and summary.".to_string());
+            }
+            // Only a strict taxonomy stops a summary here, since there's no one to warn.
+            if let Some(taxonomy) = config.taxonomy() {
+                taxonomy.check(&rest[0], &rest[1])?;
+            }
+            day.add_summary(rest[0].clone(), rest[1].clone(), rest[2].clone(), rest[3].clone());
+            "Added summary".to_string()
+        },
+        other => return Err(format!("'{}' can't be used in a batch", other)),
+    };
+    if ["out", "pause", "resume", "task"].contains(&command.as_str()) {
+        state.events.push((command.clone(), day.get_day_start().as_dt().date_naive(), at));
+    }
+    state.put_day(day);
+    return Ok(msg);
+}
+
+fn parse_batch_time(at_str: &str) -> Result<DateTime<Local>, String> {
+    let naive: NaiveDateTime = NaiveDateTime::parse_from_str(at_str.trim(), BATCH_TIME_FMT)
+        .map_err(|_| format!("Expected a time like '2024-01-31 09:00'! Given: '{}'", at_str))?;
+    return Local.from_local_datetime(&naive).single()
+        .ok_or(format!("'{}' isn't a valid local time", at_str));
+}
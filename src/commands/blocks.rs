@@ -0,0 +1,119 @@
+use std::process::exit;
+use chrono::Duration;
+use chrono::prelude::{DateTime, Local};
+
+use crate::units::components::TimeBlock;
+use crate::units::day::{Day, write_day};
+use crate::utils::args::{get_flag_value, get_positional_args, has_flag};
+use crate::utils::clock::parse_time_on;
+use crate::utils::mqtt::publish_event;
+
+/// Splits one of today's blocks (numbered from 1, as in `punch today`) in two at the time
+/// given with `--at`, optionally giving the second half another task with `--task`.
+pub fn split(now: &DateTime<Local>, mut day: Day, other_args: Vec<String>) {
+    let ind: usize = get_block_index(&day, &other_args, &["--at", "--task"]).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+    let block_start: DateTime<Local> = day.timeblocks[ind].get_start().as_dt();
+    let at: DateTime<Local> = match get_flag_value(&other_args, "--at") {
+        Some(at_str) => parse_time_on(&at_str, &block_start.date_naive()).unwrap_or_else(|msg: String| {
+            eprintln!("{}", msg);
+            exit(1);
+        }),
+        None => {
+            eprintln!("'punch split' needs the time to split the block at, e.g. '--at 14:00'");
+            exit(1);
+        },
+    };
+    if at > *now {
+        eprintln!("Can't split a block in the future!");
+        exit(1);
+    }
+
+    let task: Option<String> = get_flag_value(&other_args, "--task");
+    if let Err(msg) = day.split_block_at(ind, &at, task) {
+        eprintln!("{}", msg);
+        exit(1);
+    }
+    write_day(&day);
+    println!(
+        "Split block {} at {}: '{}' until then, '{}' after.",
+        ind + 1, at.format("%H:%M"), day.get_task_name(ind as isize), day.get_task_name(ind as isize + 1));
+}
+
+/// Attaches a remark to one of today's blocks, e.g. `punch annotate 2 "pairing with Sam"`.
+pub fn annotate(mut day: Day, other_args: Vec<String>) {
+    let ind: usize = get_block_index(&day, &other_args, &[]).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+    let text: String = get_positional_args(&other_args, &[])[1..].join(" ");
+    if text.trim().is_empty() {
+        eprintln!("'punch annotate' needs the text to attach, e.g. 'punch annotate {} \"pairing with Sam\"'", ind + 1);
+        exit(1);
+    }
+    day.annotate_block(ind, text).expect("Block index was already checked");
+    write_day(&day);
+    println!("Annotated block {} ('{}').", ind + 1, day.get_task_name(ind as isize));
+}
+
+/// Marks one of today's blocks as billable (`--billable`) or not (`--non-billable`).
+pub fn mark(mut day: Day, other_args: Vec<String>) {
+    let ind: usize = get_block_index(&day, &other_args, &[]).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+    let billable: bool = match (has_flag(&other_args, "--billable"), has_flag(&other_args, "--non-billable")) {
+        (true, false) => true,
+        (false, true) => false,
+        (_, _) => {
+            eprintln!("'punch mark' needs exactly one of '--billable' and '--non-billable'");
+            exit(1);
+        },
+    };
+    day.timeblocks[ind].set_billable(billable);
+    write_day(&day);
+    println!(
+        "Marked block {} ('{}') as {}.",
+        ind + 1, day.get_task_name(ind as isize), if billable {"billable"} else {"non-billable"});
+}
+
+/// Starts a focus session of `<minutes>` as a new block, on the current task unless another is given.
+pub fn focus(now: &DateTime<Local>, mut day: Day, other_args: Vec<String>) {
+    let positional: Vec<String> = get_positional_args(&other_args, &[]);
+    let focus_minutes: u64 = match positional.first().map(|mins_str: &String| mins_str.parse::<u64>()) {
+        Some(Ok(mins)) if mins > 0 => mins,
+        _ => {
+            eprintln!("'punch focus' needs how many minutes to focus for, e.g. 'punch focus 90 [task]'");
+            exit(1);
+        },
+    };
+    let task: String = match positional.get(1) {
+        Some(task) => task.clone(),
+        None => day.get_task_name(-1),
+    };
+    let billable: bool = day.was_task_billable(&task);
+    if let Err(msg) = day.start_new_block(task.clone(), now) {
+        eprintln!("{}", msg);
+        exit(1);
+    }
+    let block: &mut TimeBlock = day.timeblocks.last_mut().expect("A block was just started");
+    block.set_billable(billable);
+    block.set_focus_minutes(Some(focus_minutes));
+    write_day(&day);
+    publish_event("task", &day, now);
+    println!(
+        "Focusing on '{}' for {} m, until {}.",
+        task, focus_minutes, (*now + Duration::minutes(focus_minutes as i64)).format("%H:%M"));
+}
+
+/// Reads the block number, counted from 1, from the first positional arg.
+fn get_block_index(day: &Day, other_args: &Vec<String>, flags_with_values: &[&str]) -> Result<usize, String> {
+    let positional: Vec<String> = get_positional_args(other_args, flags_with_values);
+    let block_str: &String = positional.first().ok_or("Which block? Give its number from 'punch today'.".to_string())?;
+    return match block_str.parse::<usize>() {
+        Ok(n) if n >= 1 && n <= day.timeblocks.len() => Ok(n - 1),
+        _ => Err(format!("'{}' isn't a block of today. Pick one from 1 to {}.", block_str, day.timeblocks.len())),
+    };
+}
@@ -0,0 +1,43 @@
+use std::process::exit;
+use chrono::prelude::{DateTime, Local};
+
+use crate::utils::args::get_flag_value;
+use crate::utils::budget::get_budget_usage;
+use crate::utils::config::{Config, get_config};
+use crate::utils::index::{DaySummary, get_summaries_in_range};
+use crate::utils::period::{DateRange, month_of, parse_month};
+use crate::utils::report::{Report, ReportSection, ReportValue};
+use crate::commands::report::print_report;
+
+/// Shows how much of each category's monthly budget has been used, this month or the
+/// month given with `--month YYYY-MM`.
+pub fn budget(now: &DateTime<Local>, other_args: Vec<String>) {
+    let config: Config = get_config();
+    if config.budgets().is_empty() {
+        println!("No budgets set. Add some under 'budgets' in the config, e.g.:");
+        println!("budgets:\n- category: meetings\n  max_monthly_minutes: 1200");
+        return;
+    }
+    let month: DateRange = match get_flag_value(&other_args, "--month") {
+        Some(month_str) => parse_month(&month_str).unwrap_or_else(|msg: String| {
+            eprintln!("{}", msg);
+            exit(1);
+        }),
+        None => month_of(now.date_naive()),
+    };
+    let summaries: Vec<DaySummary> = get_summaries_in_range(&month, now);
+
+    let mut report: Report = Report::new(format!("Budgets for {}", month.from.format("%B %Y")));
+    let mut budgets_section: ReportSection = ReportSection::new("budgets", &["category", "used", "budget", "left", "flags"]);
+    for (budget, used_secs, left_secs) in get_budget_usage(&summaries, config.budgets()) {
+        budgets_section.add_row(vec![
+            budget.category.clone().into(),
+            ReportValue::Secs(used_secs),
+            ReportValue::Secs(budget.get_max_secs()),
+            ReportValue::Secs(left_secs),
+            (if left_secs < 0 {"over budget"} else {""}).into(),
+        ]);
+    }
+    report.add_section(budgets_section);
+    print_report(report, &other_args, now);
+}
@@ -0,0 +1,81 @@
+use std::process::exit;
+use chrono::prelude::{DateTime, Local, NaiveDate};
+
+use crate::units::components::Note;
+use crate::units::day::{Day, write_day};
+use crate::utils::args::{get_positional_args, has_flag};
+use crate::utils::calendar::{CalendarConfig, fetch_events_on};
+use crate::utils::config::get_config;
+use crate::utils::ics::CalendarEvent;
+use crate::utils::prompt::confirm;
+
+const DEFAULT_MEETING_CATEGORY: &str = "meetings";
+
+/// Pulls today's events from the configured calendar, e.g. `punch import gcal`. Meetings
+/// that are over can be logged as blocks, with a summary in the meetings category, and
+/// the ones still to come can be added to today's plans. `--yes` accepts them all.
+pub fn import(now: &DateTime<Local>, mut day: Day, other_args: Vec<String>) {
+    match get_positional_args(&other_args, &[]).first().map(|source: &String| source.as_str()) {
+        Some("gcal") => {},
+        _ => {
+            eprintln!("'punch import' needs something to import from. Try: gcal");
+            exit(1);
+        },
+    }
+    let calendar: CalendarConfig = match get_config().calendar() {
+        Some(calendar) => calendar.clone(),
+        None => {
+            eprintln!("Add a 'calendar' section to the config to say where your calendar is. See 'punch edit-config'.");
+            exit(1);
+        },
+    };
+    let day_start: DateTime<Local> = day.get_day_start().as_dt();
+    let date: NaiveDate = day_start.date_naive();
+    let events: Vec<CalendarEvent> = fetch_events_on(&calendar, &date).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+    let accept_all: bool = has_flag(&other_args, "--yes");
+    let category: String = calendar.meeting_category.clone().unwrap_or(DEFAULT_MEETING_CATEGORY.to_string());
+
+    let mut num_logged: usize = 0;
+    let mut num_planned: usize = 0;
+    for event in events {
+        let times: String = format!("{}-{}", event.start.format("%H:%M"), event.end.format("%H:%M"));
+        if event.end <= day_start {
+            continue;
+        }
+        else if event.end <= *now && !day.has_ended() {
+            let start: DateTime<Local> = event.start.max(day_start);
+            let already_logged: bool = day.timeblocks.iter()
+                .any(|block| block.get_task_name() == event.summary && block.get_start().as_dt() == start);
+            if already_logged {
+                continue;
+            }
+            if !accept_all && !confirm(&format!("Log '{}' ({}) as a block?", event.summary, times)) {
+                continue;
+            }
+            if let Err(msg) = day.relabel_span(&start, &event.end, &event.summary) {
+                eprintln!("Couldn't log '{}': {}", event.summary, msg);
+                continue;
+            }
+            if !day.summaries.iter().any(|summary| summary.get_task() == &event.summary) {
+                day.add_summary(category.clone(), String::new(), event.summary.clone(), format!("Meeting: {}", event.summary));
+            }
+            num_logged += 1;
+        }
+        else if event.end > *now {
+            let plan: String = format!("{} {}", times, event.summary);
+            if day.plans.iter().any(|existing: &Note| existing.get_msg() == plan) {
+                continue;
+            }
+            if !accept_all && !confirm(&format!("Add '{}' ({}) to today's plans?", event.summary, times)) {
+                continue;
+            }
+            day.plans.push(Note::new(now, &plan));
+            num_planned += 1;
+        }
+    }
+    write_day(&day);
+    println!("Logged {} meetings as blocks and added {} to today's plans.", num_logged, num_planned);
+}
@@ -0,0 +1,49 @@
+use std::io::{IsTerminal, stdin};
+use chrono::prelude::{DateTime, Local, NaiveDate};
+
+use crate::units::components::Note;
+use crate::units::day::{Day, list_recorded_dates, read_day_for_date, write_day};
+use crate::utils::args::has_flag;
+use crate::utils::prompt::confirm;
+use crate::utils::work_summary::WorkSummary;
+
+/// Lists the tasks the last day before today left unfinished (see `punch add-summary
+/// --unfinished`) and offers to add each to today's plans. `--yes` adds them all.
+pub fn carry(now: &DateTime<Local>, mut day: Day, other_args: Vec<String>) {
+    let today_date: NaiveDate = day.get_day_start().as_dt().date_naive();
+    let Some((date, previous)) = list_recorded_dates().into_iter().rev()
+        .find(|date: &NaiveDate| *date < today_date)
+        .and_then(|date: NaiveDate| read_day_for_date(&date).ok().map(|previous: Day| (date, previous))) else {
+        println!("There's no day before today to carry tasks over from.");
+        return;
+    };
+    let unfinished: Vec<&WorkSummary> = previous.summaries.iter().filter(|summary: &&WorkSummary| summary.is_unfinished()).collect();
+    if unfinished.is_empty() {
+        println!("Nothing was left unfinished on {}.", date);
+        return;
+    }
+    let accept_all: bool = has_flag(&other_args, "--yes");
+    if !accept_all && !stdin().is_terminal() {
+        println!("Left unfinished on {}:", date);
+        for summary in &unfinished {
+            println!("- {}: {}", summary.get_task(), summary.get_summary());
+        }
+        println!("Run 'punch carry' in a terminal to pick which to carry over, or 'punch carry --yes' for all of them.");
+        return;
+    }
+    let mut carried: usize = 0;
+    for summary in unfinished {
+        let text: String = format!("{}: {}", summary.get_task(), summary.get_summary());
+        if day.plans.iter().any(|plan: &Note| plan.get_msg() == text) {
+            continue;
+        }
+        if accept_all || confirm(&format!("Carry '{}' over from {}?", text, date)) {
+            day.plans.push(Note::new(now, &text));
+            carried += 1;
+        }
+    }
+    if carried > 0 {
+        write_day(&day);
+    }
+    println!("Added {} unfinished tasks to today's plans.", carried);
+}
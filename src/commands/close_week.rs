@@ -0,0 +1,71 @@
+use std::process::exit;
+use chrono::Duration;
+use chrono::prelude::{DateTime, Local, NaiveDate};
+
+use crate::units::day::{Day, list_recorded_dates, read_day_for_date};
+use crate::units::interval::DATE_FMT;
+use crate::utils::aggregate::format_secs;
+use crate::utils::args::{get_flag_value, has_flag};
+use crate::utils::ledger::{ClosedWeek, close_week as add_week_close, get_closed_week, get_closed_weeks};
+use crate::utils::period::parse_date;
+use crate::utils::week_plans::get_week_start;
+
+/// `punch close-week` closes last week (or, with `--week`, the week of a date), keeping its
+/// totals in the ledger. Its days are read-only after that unless you pass `--reopen`, and each
+/// change to them goes in the ledger as a correction. `--list` shows the closed weeks.
+pub fn close_week(now: &DateTime<Local>, other_args: Vec<String>) {
+    if has_flag(&other_args, "--list") {
+        list_closed_weeks();
+        return;
+    }
+    let today: NaiveDate = now.date_naive();
+    let week_start: NaiveDate = match get_flag_value(&other_args, "--week").as_deref() {
+        None | Some("last") => Ok(get_week_start(&today) - Duration::weeks(1)),
+        Some(date_str) => parse_date(date_str).map(|date: NaiveDate| get_week_start(&date)),
+    }.unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+    if let Some(week) = get_closed_week(&week_start) {
+        eprintln!("The week of {} was already closed on {}.", week_start.format(DATE_FMT), week.closed.as_dt().format(DATE_FMT));
+        exit(1);
+    }
+    if week_start + Duration::weeks(1) > today {
+        eprintln!("The week of {} isn't over yet, so it can't be closed.", week_start.format(DATE_FMT));
+        exit(1);
+    }
+    let ongoing: Option<NaiveDate> = list_recorded_dates().into_iter()
+        .filter(|date: &NaiveDate| *date >= week_start && *date < week_start + Duration::weeks(1))
+        .find(|date: &NaiveDate| read_day_for_date(date).is_ok_and(|day: Day| !day.has_ended()));
+    if let Some(date) = ongoing {
+        eprintln!("The day for {} hasn't been punched out yet, so its week can't be closed.", date.format(DATE_FMT));
+        exit(1);
+    }
+
+    let week: ClosedWeek = add_week_close(&week_start, now);
+    println!(
+        "Closed the week of {}: {} done of {} to do ({} behind). Its days can only be changed with --reopen now.",
+        week_start.format(DATE_FMT), format_secs(week.done_minutes * 60), format_secs(week.to_do_minutes * 60),
+        format_secs(week.get_balance_minutes() * 60));
+}
+
+fn list_closed_weeks() {
+    let weeks: Vec<ClosedWeek> = get_closed_weeks();
+    if weeks.is_empty() {
+        println!("No weeks have been closed yet. Close last week with 'punch close-week'.");
+        return;
+    }
+    for week in &weeks {
+        let corrections: String = match week.num_corrections {
+            0 => String::new(),
+            1 => "  (1 correction)".to_string(),
+            n => format!("  ({} corrections)", n),
+        };
+        println!(
+            "{}  closed {}  done {:>10}  to do {:>10}  behind {:>10}{}",
+            week.week_start.format(DATE_FMT), week.closed.as_dt().format(DATE_FMT), format_secs(week.done_minutes * 60),
+            format_secs(week.to_do_minutes * 60), format_secs(week.get_balance_minutes() * 60), corrections);
+    }
+    let total: i64 = weeks.iter().map(|week: &ClosedWeek| week.get_balance_minutes()).sum();
+    println!("Behind over the closed weeks: {}", format_secs(total * 60));
+}
@@ -0,0 +1,85 @@
+use std::collections::BTreeMap;
+use std::process::exit;
+use chrono::prelude::{DateTime, Datelike, Local, NaiveDate, Weekday};
+use chrono::Duration;
+
+use crate::units::interval::DATE_FMT;
+use crate::utils::args::get_flag_value;
+use crate::utils::compliance::{WorkingTimeLimit, get_rolling_totals};
+use crate::utils::config::get_config;
+use crate::utils::index::{DaySummary, get_summaries_in_range};
+use crate::utils::period::{DateRange, resolve_range_from_args};
+use crate::utils::report::{Report, ReportSection, ReportValue};
+use crate::commands::report::print_report;
+
+/// `punch compliance`: the average hours a week over the rolling window ending each Sunday
+/// of the period (and on its last day), flagging the windows over the limit. The limit and
+/// window come from `working_time_limit` in the config, or `--max-weekly-hours` and
+/// `--window-weeks`, or `--window-days` for windows like 28 days.
+pub fn compliance(now: &DateTime<Local>, other_args: Vec<String>) {
+    let limit: WorkingTimeLimit = get_limit_from_args(&other_args).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+    let window_days: i64 = match get_flag_value(&other_args, "--window-days") {
+        Some(days_str) => days_str.parse().ok().filter(|days: &i64| *days > 0).unwrap_or_else(|| {
+            eprintln!("'--window-days' should be a positive whole number! Given: '{}'", days_str);
+            exit(1);
+        }),
+        None => limit.get_window_days(),
+    };
+    let range: DateRange = resolve_range_from_args(now, &other_args).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+    let history: DateRange = DateRange {from: range.from - Duration::days(window_days - 1), to: range.to};
+    let done_secs_by_date: BTreeMap<NaiveDate, i64> = get_summaries_in_range(&history, now).iter()
+        .filter_map(|summary: &DaySummary| NaiveDate::parse_from_str(&summary.date, DATE_FMT).ok().map(|date: NaiveDate| (date, summary.done_secs)))
+        .collect();
+    let mut ends: Vec<NaiveDate> = range.dates().into_iter().filter(|date: &NaiveDate| date.weekday() == Weekday::Sun).collect();
+    if ends.last() != Some(&range.to) {
+        ends.push(range.to);
+    }
+    let totals: Vec<i64> = get_rolling_totals(&done_secs_by_date, &ends, window_days);
+    print_report(build_compliance_report(&range, &ends, &totals, window_days, limit.get_max_weekly_hours()), &other_args, now);
+}
+
+fn get_limit_from_args(other_args: &Vec<String>) -> Result<WorkingTimeLimit, String> {
+    let mut limit: WorkingTimeLimit = get_config().working_time_limit().cloned().unwrap_or_default();
+    if let Some(hours_str) = get_flag_value(other_args, "--max-weekly-hours") {
+        limit.max_weekly_hours = Some(hours_str.parse().map_err(|_| format!("'--max-weekly-hours' should be a number! Given: '{}'", hours_str))?);
+    }
+    if let Some(weeks_str) = get_flag_value(other_args, "--window-weeks") {
+        limit.window_weeks = Some(weeks_str.parse().ok().filter(|weeks: &u64| *weeks > 0)
+            .ok_or(format!("'--window-weeks' should be a positive whole number! Given: '{}'", weeks_str))?);
+    }
+    return Ok(limit);
+}
+
+fn build_compliance_report(range: &DateRange, ends: &Vec<NaiveDate>, totals: &Vec<i64>, window_days: i64, max_weekly_hours: f64) -> Report {
+    let mut report: Report = Report::new(format!(
+        "Average hours a week over {} days, for windows ending {}", window_days, range.as_string()));
+    let max_weekly_secs: i64 = (max_weekly_hours * 3600.0).round() as i64;
+    let mut windows_section: ReportSection = ReportSection::new("rolling_average", &["window", "total", "average a week", "over limit"]);
+    let mut num_over: i64 = 0;
+    for (end, total_secs) in ends.iter().zip(totals) {
+        let weekly_secs: i64 = total_secs * 7 / window_days;
+        let over_secs: i64 = (weekly_secs - max_weekly_secs).max(0);
+        if over_secs > 0 {
+            num_over += 1;
+        }
+        let start: NaiveDate = *end - Duration::days(window_days - 1);
+        windows_section.add_row(vec![
+            format!("{} to {}", start.format(DATE_FMT), end.format(DATE_FMT)).into(),
+            ReportValue::Secs(*total_secs),
+            ReportValue::Secs(weekly_secs),
+            if over_secs > 0 {ReportValue::Secs(over_secs)} else {"".into()},
+        ]);
+    }
+    report.add_section(windows_section);
+    let mut totals_section: ReportSection = ReportSection::new("totals", &["total", "value"]);
+    totals_section.add_row(vec!["Limit a week".into(), ReportValue::Secs(max_weekly_secs)]);
+    totals_section.add_row(vec!["Windows over the limit".into(), ReportValue::Number(num_over)]);
+    report.add_section(totals_section);
+    return report;
+}
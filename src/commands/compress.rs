@@ -0,0 +1,31 @@
+use std::process::exit;
+use chrono::prelude::{DateTime, Local, NaiveDate};
+
+use crate::utils::args::get_flag_value;
+use crate::utils::compression::compress_days_before;
+use crate::utils::config::get_config;
+use crate::utils::period::parse_age;
+
+/// Compresses the files of days older than `--older-than`, e.g. `punch compress --older-than 90d`,
+/// or without it, the config's `compression` age. They still read as before.
+pub fn compress(now: &DateTime<Local>, other_args: Vec<String>) {
+    let older_than: String = match (get_flag_value(&other_args, "--older-than"), get_config().compression()) {
+        (Some(older_than), _) => older_than,
+        (None, Some(compression)) => compression.older_than.clone(),
+        (None, None) => {
+            eprintln!("'punch compress' needs an age, e.g. 'punch compress --older-than 90d', or 'compression' set in the config.");
+            exit(1);
+        },
+    };
+    let oldest_kept: NaiveDate = parse_age(&older_than, now.date_naive()).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+    match compress_days_before(&oldest_kept) {
+        Ok(num_compressed) => println!("Compressed {} files of days from before {}.", num_compressed, oldest_kept),
+        Err(msg) => {
+            eprintln!("{}", msg);
+            exit(1);
+        },
+    }
+}
@@ -0,0 +1,44 @@
+use std::process::exit;
+use serde_yaml::Value;
+
+use crate::commands::core::edit_config;
+use crate::utils::args::get_positional_args;
+use crate::utils::config::{Config, get_config, update_config};
+
+/// `punch config get <setting>`, `punch config set <setting> <value>` or `punch config edit`.
+/// Settings nested in others are given as dotted paths, like `self_update.enabled`, and values
+/// are read as YAML, so `null` unsets a setting and `[Mon, Tue]` is a list. Setting a value
+/// only rewrites that setting in the file, keeping your comments.
+pub fn config(other_args: Vec<String>) {
+    let positional: Vec<String> = get_positional_args(&other_args, &[]);
+    let positional_strs: Vec<&str> = positional.iter().map(|x: &String| x.as_str()).collect();
+    match positional_strs.as_slice() {
+        ["get", path] => get_setting(path),
+        ["set", path, value] => set_setting(path, value),
+        ["edit"] => edit_config(),
+        _ => {
+            eprintln!("Usage: punch config get <setting> | set <setting> <value> | edit");
+            exit(1);
+        },
+    }
+}
+
+fn get_setting(path: &str) {
+    match get_config().get_setting(path) {
+        Some(value) => print!("{}", serde_yaml::to_string(&value).expect("A setting is serializable")),
+        None => {
+            eprintln!("'{}' isn't a setting.", path);
+            exit(1);
+        },
+    }
+}
+
+fn set_setting(path: &str, value_str: &str) {
+    let value: Value = serde_yaml::from_str(value_str).unwrap_or(Value::String(value_str.to_string()));
+    let config: Config = get_config().with_setting(path, value).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+    update_config(config);
+    println!("Set '{}' to {}.", path, value_str);
+}
@@ -1,47 +1,128 @@
 use std::collections::HashMap;
 use std::process::exit;
-use chrono::prelude::{DateTime, Local};
+use chrono::prelude::{DateTime, Local, NaiveDate, NaiveTime};
+use chrono::Duration;
 use crate::utils::file_io::SafeFileEdit;
+use crate::units::components::Note;
 
 use crate::units::day::{
     Day,
+    get_current_day,
+    list_recorded_dates,
     read_day,
+    read_day_for_date,
+    read_recorded_day,
     write_day};
 
-use crate::utils::config::{Config, get_config, update_config};
+use crate::utils::aggregate::format_secs;
+use crate::utils::args::{get_flag_value, get_positional_args, has_flag};
+use crate::utils::config::{Config, get_config};
+use crate::utils::state::{State, get_state, update_state};
+use crate::utils::compliance::check_break_rules;
+use crate::utils::i18n::tr;
+use crate::utils::suggestions::get_nearest_match;
+use crate::utils::reminders::TIME_OF_DAY_FMT;
+use crate::utils::location::{LOCATION_KEY, parse_location};
+use crate::utils::templates::{DayTemplate, read_template};
+use crate::utils::index::{DaySummary, get_summaries_in_range};
+use crate::utils::mqtt::publish_event;
+use crate::utils::terminal::print_paged;
+use crate::utils::period::{DateRange, parse_date};
+use crate::utils::holidays::Holidays;
+use crate::utils::work_summary::WorkSummary;
+use crate::utils::week_plans::{WeekPlan, get_week_start, read_week_plan};
+use crate::commands::week_plan::format_week_plan_progress;
+use crate::utils::picker::{can_pick, pick};
+use crate::commands::suggest::suggest_tasks_for_unlabeled_time;
+use crate::commands::template::{apply_template, get_first_planned_task};
+use crate::commands::status::{get_daily_cap_warning, print_break_budget, print_projected_finish};
+
+/// How many of the latest recorded days to offer when picking a day or a task.
+const PICKER_RECENT_DAYS: usize = 60;
 
 pub fn punch_in(now: &DateTime<Local>, other_args: Vec<String>) {
     if let Ok(_) = read_day(now) {
-        println!("You've already clocked in for the day!");
+        println!("{}", tr("already_clocked_in", &[]));
     }
     else{
-        let parsed_args: (String, u64) = get_other_args_for_punch_in(other_args);
-        let new_day: Day = Day::new(&now, parsed_args.0, parsed_args.1);
-        println!("Clocking in for the day at '{}'", &new_day.get_day_start_as_str());
+        let location: Option<String> = get_flag_value(&other_args, "--location")
+            .or(get_config().default_location().cloned())
+            .map(|location: String| parse_location(&location).unwrap_or_else(|msg: String| {
+                eprintln!("{}", msg);
+                exit(1);
+            }));
+        let template: Option<DayTemplate> = get_flag_value(&other_args, "--template")
+            .map(|name: String| read_template(&name).unwrap_or_else(|msg: String| {
+                eprintln!("{}", msg);
+                exit(1);
+            }));
+        let mut positional_args: Vec<String> = get_positional_args(&other_args, &["--location", "--template"]);
+        if let Some(first_task) = template.as_ref().and_then(get_first_planned_task).filter(|_| positional_args.is_empty()) {
+            positional_args.push(first_task);
+        }
+        let punch_in_task: String = positional_args.first().cloned().unwrap_or(get_default_punch_in_task());
+        let (mut new_day, time_to_do_msg): (Day, String) = create_day(now, punch_in_task.clone(), &get_config());
+        println!("{}", time_to_do_msg);
+        if positional_args.is_empty() {
+            println!("{}", tr("default_punch_in_task", &[&punch_in_task]));
+        }
+        println!("{}", tr("remember_edit", &[]));
+        println!("{}", tr("clocking_in", &[&new_day.get_day_start_as_str()]));
+        if let Some(template) = template {
+            println!("{}", tr("using_template", &[&template.planned_blocks.len()]));
+            apply_template(&mut new_day, template);
+        }
+        if let Some(location) = location {
+            println!("{}", tr("working_from", &[&location]));
+            new_day.meta.insert(LOCATION_KEY.to_string(), location);
+        }
+        record_lateness(&mut new_day);
         write_day(&new_day);
+        publish_event("in", &new_day, now);
     }
 }
 
-fn get_other_args_for_punch_in(other_args: Vec<String>) -> (String, u64) {
-    let default_time_to_do: u64 = get_default_day_in_minutes();
-    println!("Using the default time to do for the day: {} minutes", default_time_to_do);
-    let punch_in_task: String; 
-    if other_args.len() == 0 {
-        punch_in_task = get_default_punch_in_task();
-        println!(
-            "No start task for the day provided. Using the default value: '{}'", 
-            punch_in_task);
+fn record_lateness(day: &mut Day) {
+    match get_config().expected_start() {
+        Ok(Some(expected_start)) => {
+            day.record_lateness(&expected_start);
+            let lateness_secs: i64 = day.lateness_secs.expect("Lateness was just recorded");
+            if lateness_secs > 0 {
+                println!("{}", tr("late_for_expected_start", &[&format_secs(lateness_secs), &expected_start.format("%H:%M")]));
+            }
+            else {
+                println!("{}", tr("on_time_for_expected_start", &[&expected_start.format("%H:%M")]));
+            }
+        },
+        Ok(None) => {},
+        Err(msg) => eprintln!("{}", tr("warning", &[&msg])),
     }
-    else {
-        punch_in_task = other_args[0].to_owned();
-    }
-    println!("Remember: You can use `punch edit` to change anything about the day.");
-    return (punch_in_task, default_time_to_do)
+}
 
+/// A new day started at `at`, with the time to do from `get_default_time_to_do` and the
+/// config's paid break. Every way of starting a day goes through here, so they all agree.
+/// Also gives the message saying where the time to do came from.
+pub fn create_day(at: &DateTime<Local>, task: String, config: &Config) -> (Day, String) {
+    let (time_to_do, msg): (u64, String) = get_default_time_to_do(&at.date_naive(), config);
+    let mut day: Day = Day::new(at, task, time_to_do);
+    day.paid_break_minutes = config.paid_break_minutes().unwrap_or(0);
+    return (day, msg);
 }
 
-fn get_default_day_in_minutes() -> u64 {
-    return get_config().day_in_minutes() as u64;
+/// The config's `day_in_minutes`, or the holiday target on a public holiday, or nothing on
+/// a day that isn't one of the `work_days`, along with a message saying which it is.
+fn get_default_time_to_do(date: &NaiveDate, config: &Config) -> (u64, String) {
+    match Holidays::load(config) {
+        Ok(holidays) => if let Some(name) = holidays.get_holiday(date) {
+            return (holidays.target_minutes(), tr("holiday_time_to_do", &[&name, &holidays.target_minutes()]));
+        },
+        Err(msg) => eprintln!("{}", tr("warning", &[&msg])),
+    }
+    if !config.is_work_day(date) {
+        return (0, tr("day_off_time_to_do", &[&date.format("%A")]));
+    }
+    let default_time_to_do: u64 = config.day_in_minutes() as u64;
+    return (default_time_to_do, tr("default_time_to_do", &[&default_time_to_do]));
 }
 
 fn get_default_punch_in_task() -> String {
@@ -50,12 +131,28 @@ fn get_default_punch_in_task() -> String {
 
 pub fn punch_out(now: &DateTime<Local>, mut day: Day) {
     if let Ok(_) = day.end_day_at(&now) {
-        println!("Punching out for the day at '{}'", &day.get_day_end_as_str().unwrap().trim());
+        println!("{}", tr("punching_out", &[&day.get_day_end_as_str().unwrap().trim()]));
+        suggest_tasks_for_unlabeled_time(&mut day);
         write_day(&day);
+        publish_event("out", &day, now);
+        warn_about_break_rules(now, &day);
+        warn_if_over_daily_cap(&day);
         update_time_behind(day);
     }
     else {
-        println!("Can't punch out: Already punched out for the day!");
+        println!("{}", tr("already_punched_out", &[]));
+    }
+}
+
+fn warn_about_break_rules(now: &DateTime<Local>, day: &Day) {
+    for violation in check_break_rules(&DaySummary::from_day(day, now), get_config().break_rules()) {
+        eprintln!("{}", tr("warning", &[&violation]));
+    }
+}
+
+fn warn_if_over_daily_cap(day: &Day) {
+    if let Some(msg) = get_daily_cap_warning(day, &get_config()) {
+        eprintln!("{}", tr("warning", &[&msg]));
     }
 }
 
@@ -69,12 +166,12 @@ pub fn take_break(now: &DateTime<Local>, other_args: Vec<String>, mut day: Day)
         resolved_break_name.expect("break_name error should already have been handled"), &now
     );
     if let Ok(_) = break_result {
-        println!("Taking a break at '{}'", &now);
+        println!("{}", tr("taking_break", &[&now]));
         write_day(&day);
+        publish_event("pause", &day, now);
 
         if !day.has_ended() {day.end_day_at(&now).expect("We should be able to end the day");}
-        let mut config: Config = get_config();
-        summarise_time(&day, &mut config);
+        summarise_time(&day, &mut get_state());
     }
     else {
         let msg = break_result.unwrap_err();
@@ -103,11 +200,12 @@ pub fn resume(now: &DateTime<Local>, other_args: Vec<String>, mut day: Day) {
     let new_block_task: String = new_block_task_result.expect("We've precluded no arguments");
     let resume_result: Result<(), &str> = day.start_new_block(new_block_task, &now);
     if let Ok(_) = resume_result {
-        println!("Back to work at '{}'", &now);
+        println!("{}", tr("back_to_work", &[&now]));
         write_day(&day);
+        publish_event("resume", &day, now);
         if !day.has_ended() {day.end_day_at(&now).expect("We should be able to end the day");}
-        let mut config: Config = get_config();
-        summarise_time(&day, &mut config);
+        summarise_time(&day, &mut get_state());
+        warn_if_over_break_budget(&day, &get_config());
     }
     else {
         let msg = resume_result.unwrap_err();
@@ -116,29 +214,61 @@ pub fn resume(now: &DateTime<Local>, other_args: Vec<String>, mut day: Day) {
     }
 }
 
+fn warn_if_over_break_budget(day: &Day, config: &Config) {
+    let break_secs: i64 = day.get_total_break_time_secs().expect("Day is closed so break time is known");
+    if let Some(left) = config.break_budget_left_secs(break_secs) {
+        if left < 0 {
+            eprintln!("{}", tr("over_break_budget", &[&(-left / 60)]));
+        }
+    }
+}
+
 fn get_resume_task_from_args(other_args: Vec<String>, day: Day) -> Result<String, String> {
     return match other_args.len() {
         0 => Ok(day.get_task_name(-2)),
         1 => Ok(other_args[0].to_owned()),
-        _ => Err("'punch resume' should have at most one argument!".to_string()),
+        _ => Err(tr("resume_args", &[])),
     }
 }
 
+/// Without a task name, one of the tasks from recent days can be picked at a terminal.
 pub fn switch_to_new_task(now: &DateTime<Local>, mut day: Day, other_args: Vec<String>) {
-    let new_block_task_result: Result<String, String> = get_new_task_block_from_args(other_args);
+    let mut positional_args: Vec<String> = get_positional_args(&other_args, &["--category", "--project"]);
+    if positional_args.is_empty() && can_pick() {
+        if let Some(task) = pick("Task", &get_recent_tasks()) {
+            positional_args.push(task);
+        }
+    }
+    let new_block_task_result: Result<String, String> = get_new_task_block_from_args(positional_args);
     if let Err(msg) = new_block_task_result {
         eprintln!("{}", msg);
         exit(1);
     } 
 
     let new_block_task: String = new_block_task_result.expect("We've handled errors");
+    let billable: bool = match (has_flag(&other_args, "--billable"), has_flag(&other_args, "--non-billable")) {
+        (true, true) => {
+            eprintln!("{}", tr("billable_and_non_billable", &[]));
+            exit(1);
+        },
+        (true, false) => true,
+        (false, true) => false,
+        (false, false) => day.was_task_billable(&new_block_task),
+    };
+    let category: Option<String> = get_flag_value(&other_args, "--category");
+    let project: Option<String> = get_flag_value(&other_args, "--project");
     let result: Result<(), &str> = day.start_new_block(new_block_task.to_owned(), &now);
     if let Ok(_) = result {
-        println!("Now working on '{}' from '{}'", &new_block_task, &now);
+        day.timeblocks.last_mut().expect("A block was just started").set_billable(billable);
+        if category.is_some() || project.is_some() {
+            set_task_category_and_project(&mut day, &new_block_task, category, project);
+        }
+        println!("{}", tr("now_working_on", &[&new_block_task, &now]));
+        warn_if_likely_typo(&new_block_task);
         write_day(&day);
+        publish_event("task", &day, now);
         if !day.has_ended() {day.end_day_at(&now).expect("We should be able to end the day");}
-        let mut config: Config = get_config();
-        summarise_time(&day, &mut config);
+        summarise_time(&day, &mut get_state());
     }
     else {
         let msg = result.unwrap_err();
@@ -147,78 +277,246 @@ pub fn switch_to_new_task(now: &DateTime<Local>, mut day: Day, other_args: Vec<S
     }
 }
 
+/// Files the task under `category` and `project` in its summary, adding an empty summary if
+/// it doesn't have one yet. Whichever isn't given stays as it was.
+fn set_task_category_and_project(day: &mut Day, task: &String, category: Option<String>, project: Option<String>) {
+    let existing: Option<&WorkSummary> = day.get_task_summary(task);
+    let category: String = category.or(existing.map(|summary: &WorkSummary| summary.get_category().clone())).unwrap_or_default();
+    let project: String = project.or(existing.map(|summary: &WorkSummary| summary.get_project().clone())).unwrap_or_default();
+    check_taxonomy(&category, &project);
+    if existing.is_none() {
+        day.add_summary(category, project, task.clone(), String::new());
+        return;
+    }
+    for summary in day.summaries.iter_mut().filter(|summary: &&mut WorkSummary| summary.get_task() == task) {
+        summary.set_category(category.clone());
+        summary.set_project(project.clone());
+    }
+}
+
+/// Warns about a category or project that isn't in the config's `taxonomy`, or stops if it's strict.
+fn check_taxonomy(category: &str, project: &str) {
+    let Some(taxonomy) = get_config().taxonomy().cloned() else {
+        return;
+    };
+    match taxonomy.check(category, project) {
+        Ok(Some(msg)) => eprintln!("{}", tr("warning", &[&msg])),
+        Ok(None) => {},
+        Err(msg) => {
+            eprintln!("{}", msg);
+            exit(1);
+        },
+    }
+}
+
 fn get_new_task_block_from_args(other_args: Vec<String>) -> Result<String, String> {
     return match other_args.len() {
-        0 => Err("'punch task' needs a new task name!".to_string()),
+        0 => Err(tr("task_needs_name", &[])),
         1 => Ok(other_args[0].to_owned()),
-        _ => Err("'punch task' should have at most one argument!".to_string()),
+        _ => Err(tr("task_args", &[])),
     };
 }
 
-pub fn view_day(day: Day) {
-    println!("Here's the day so far: \n");
-    println!("{}", day.as_string());
+/// Points out when a task that's new is close to a recent one, as it's likely a typo that
+/// would otherwise split the time between the two.
+fn warn_if_likely_typo(task: &String) {
+    let recent_tasks: Vec<String> = get_recent_tasks();
+    if recent_tasks.contains(task) {
+        return;
+    }
+    if let Some(nearest) = get_nearest_match(task, &recent_tasks) {
+        println!("{}", tr("did_you_mean_task", &[task, nearest, nearest]));
+    }
+}
+
+/// Tasks worked on in the last `PICKER_RECENT_DAYS` recorded days, latest first.
+fn get_recent_tasks() -> Vec<String> {
+    let mut tasks: Vec<String> = Vec::new();
+    for date in list_recorded_dates().into_iter().rev().take(PICKER_RECENT_DAYS) {
+        let Ok(day) = read_day_for_date(&date) else {
+            continue;
+        };
+        for (ind, block) in day.timeblocks.iter().enumerate().rev() {
+            if !day.is_break(ind) && !tasks.contains(&block.get_task_name()) {
+                tasks.push(block.get_task_name());
+            }
+        }
+    }
+    return tasks;
 }
 
-pub fn edit_day(day: Day) {
+/// `punch view [date]`: shows the day on the date given, or else one picked from the recent
+/// days when at a terminal, or else the current day.
+pub fn view(now: &DateTime<Local>, other_args: Vec<String>) {
+    match get_day_from_args(now, &other_args) {
+        Ok(day) => view_day(day),
+        Err(msg) => {
+            eprintln!("{}", msg);
+            exit(1);
+        },
+    }
+}
+
+/// `punch edit [date]`: opens a day in the editor, found like `punch view` finds it.
+pub fn edit(now: &DateTime<Local>, other_args: Vec<String>, force: bool) {
+    let day: Day = get_day_from_args(now, &other_args)
+        .and_then(|day: Day| day.check_editable(force).map(|_| day))
+        .unwrap_or_else(|msg: String| {
+            eprintln!("{}", msg);
+            exit(1);
+        });
+    edit_day(day);
+}
+
+fn get_day_from_args(now: &DateTime<Local>, other_args: &Vec<String>) -> Result<Day, String> {
+    match get_positional_args(other_args, &[]).as_slice() {
+        [date_str] => {
+            let date: NaiveDate = parse_date(date_str)?;
+            return read_recorded_day(&date);
+        },
+        [] => {},
+        _ => return Err("Give at most one date, like '2024-01-31'.".to_string()),
+    }
+    if !can_pick() {
+        return get_current_day(now);
+    }
+    // The days are listed latest first, so the current day is picked by just pressing enter.
+    let options: Vec<String> = list_recorded_dates().into_iter().rev().take(PICKER_RECENT_DAYS)
+        .filter_map(|date: NaiveDate| read_day_for_date(&date).ok().map(|day: Day| describe_day_for_picker(&date, &day)))
+        .collect();
+    if options.is_empty() {
+        return get_current_day(now);
+    }
+    let picked: String = pick("Day", &options).ok_or("Nothing was picked.".to_string())?;
+    let date: NaiveDate = parse_date(picked.split_whitespace().next().unwrap_or_default())?;
+    return read_recorded_day(&date);
+}
+
+/// Like "2024-01-31 Wed  09:00-17:30  coding, review".
+fn describe_day_for_picker(date: &NaiveDate, day: &Day) -> String {
+    let end: String = day.get_day_end().map(|end| end.as_dt().format("%H:%M").to_string()).unwrap_or("now".to_string());
+    let tasks: Vec<String> = day.get_tasks_in_chronological_order().into_iter()
+        .filter(|task: &String| day.timeblocks.iter().enumerate().any(|(ind, block)| block.get_task_name() == *task && !day.is_break(ind)))
+        .collect();
+    return format!(
+        "{} {}  {}-{}  {}",
+        date, date.format("%a"), day.get_day_start().as_dt().format("%H:%M"), end, tasks.join(", "));
+}
+
+fn view_day(day: Day) {
+    print_paged(&format!("{}\n{}\n", tr("day_so_far", &[]), day.as_string()));
+}
+
+fn edit_day(day: Day) {
     day.safe_edit_from_file();
 }
 
 pub fn summary(now: &DateTime<Local>, mut day: Day) {
-    let end_result: Result<(), &str> = day.end_day_at(&now);
-    match end_result {
-        Ok(_) => (),
-        _ => (),
+    let still_working: bool = !day.has_ended();
+    // A day that's already over keeps its end.
+    let _ = day.end_day_at(&now);
+    let config: Config = get_config();
+    summarise_time(&day, &mut get_state());
+    print_plan_vs_actual(&day);
+    print_week_plan_progress(now, &day);
+    print_break_budget(&day, &config);
+    if still_working {
+        print_projected_finish(now, &day, &config);
+    }
+}
+
+
+/// Each planned or worked task's time planned next to its time done, for days with a plan.
+fn print_plan_vs_actual(day: &Day) {
+    if day.planned_blocks.is_empty() {
+        return;
+    }
+    println!("{}", tr("plan_vs_actual_header", &[]));
+    for (task, planned_secs, actual_secs) in day.get_plan_vs_actual_secs() {
+        let variance_secs: i64 = actual_secs - planned_secs;
+        let sign: &str = if variance_secs < 0 {"-"} else {"+"};
+        println!("\t{}: {} / {} ({}{})", task, format_secs(planned_secs), format_secs(actual_secs), sign, format_secs(variance_secs.abs()));
+    }
+    let planned_secs: i64 = day.get_planned_secs();
+    if planned_secs > 0 {
+        println!("{}", tr("plan_accuracy", &[&(day.get_plan_matched_secs() * 100 / planned_secs)]));
     }
-    let mut config: Config = get_config();
-    summarise_time(&day, &mut config);
 }
 
+/// Each project's time so far this week next to its time in the week's plan, if there is one.
+fn print_week_plan_progress(now: &DateTime<Local>, day: &Day) {
+    let today: NaiveDate = now.date_naive();
+    let week_start: NaiveDate = get_week_start(&today);
+    let plan: WeekPlan = match read_week_plan(&week_start) {
+        Ok(Some(plan)) => plan,
+        Ok(None) => return,
+        Err(msg) => {
+            eprintln!("{}", tr("warning", &[&msg]));
+            return;
+        },
+    };
+    let mut summaries: Vec<DaySummary> = match today > week_start {
+        true => get_summaries_in_range(&DateRange {from: week_start, to: today - Duration::days(1)}, now),
+        false => Vec::new(),
+    };
+    summaries.push(DaySummary::from_day(day, now));
+    println!("{}", tr("week_plan_header", &[]));
+    for line in format_week_plan_progress(&plan, &summaries) {
+        println!("\t{}", line);
+    }
+}
 
+/// `--unfinished` marks the task as having more to do, for `punch carry`.
 pub fn add_summary_to_today(mut day: Day, other_args: Vec<String>) {
-    if other_args.len() != 4 {
-        println!("'punch add-summary' takes exactly 4 arguments: category, project, task and summary.")
+    let positional_args: Vec<String> = get_positional_args(&other_args, &[]);
+    if positional_args.len() != 4 {
+        println!("{}", tr("add_summary_args", &[]))
     }
     else {
         let (category, project, task, summary) = (
-            other_args[0].to_string(), other_args[1].to_string(), other_args[2].to_string(), other_args[3].to_string()
+            positional_args[0].to_string(), positional_args[1].to_string(), positional_args[2].to_string(), positional_args[3].to_string()
         );
+        check_taxonomy(&category, &project);
         day.add_summary(category, project, task, summary);
+        if has_flag(&other_args, "--unfinished") {
+            day.summaries.last_mut().expect("A summary was just added").set_unfinished(true);
+        }
         write_day(&day);
     }
 }
 
 
-fn summarise_time(day: &Day, config: &mut Config) {
+/// Prints how the day went, and counts its time left towards `state`'s minutes behind.
+fn summarise_time(day: &Day, state: &mut State) {
     let time_left: i64 = day.get_time_left_secs().expect("Day is over so we should be able to calculate time left!");
     let break_time: i64 = day.get_total_break_time_secs().expect("Day is over so we should be able to calculate total break time!");
     let task_summaries: HashMap<String, (i64, u64)> = day.get_task_times_secs_and_num_blocks();
     let total_blocks: u64 = day.get_total_timeblocks();
     let total_blocks_without_breaks: u64 = day.get_total_timeblocks_without_breaks();
-    config.update_minutes_behind(time_left / 60);
+    state.update_minutes_behind(time_left / 60);
 
     let time_done_secs = day.get_time_done_secs().unwrap();
-    println!("Time done today: {} m {} s", time_done_secs / 60, time_done_secs % 60);
-    println!("Total time spent on break: {} m {} s", break_time / 60, break_time % 60);
-    println!("Time left today: {} m {} s", time_left / 60, time_left % 60);
-    println!("Total task blocks (including breaks): {}", total_blocks);
-    println!("Total task blocks (excluding breaks): {}", total_blocks_without_breaks);
-    println!("Latest task: '{}'", day.get_latest_task_name());
-    println!("Task times, blocks:");
+    println!("{}", tr("time_done_today", &[&(time_done_secs / 60), &(time_done_secs % 60)]));
+    println!("{}", tr("break_time_today", &[&(break_time / 60), &(break_time % 60)]));
+    println!("{}", tr("time_left_today", &[&(time_left / 60), &(time_left % 60)]));
+    println!("{}", tr("blocks_with_breaks", &[&total_blocks]));
+    println!("{}", tr("blocks_without_breaks", &[&total_blocks_without_breaks]));
+    println!("{}", tr("latest_task", &[&day.get_latest_task_name()]));
+    println!("{}", tr("task_times_header", &[]));
     for task_name in day.get_tasks_in_chronological_order() {
         let (time, blocks) = task_summaries.get(&task_name).unwrap();
-        println!("\t{}: {} m {} s, {} blocks", task_name, time / 60, time % 60, blocks);
+        println!("{}", tr("task_time_line", &[&task_name, &(time / 60), &(time % 60), blocks]));
     }
-    println!("Minutes behind overall: {}", config.minutes_behind());
-    println!("Minutes behind since last fall behind: {}", config.minutes_behind_non_neg());
+    println!("{}", tr("minutes_behind_overall", &[&state.minutes_behind()]));
+    println!("{}", tr("minutes_behind_since", &[&state.minutes_behind_non_neg()]));
 }
 
 
 fn update_time_behind(day: Day) {
     if day.has_ended() {
-        let mut config: Config = get_config();
-        summarise_time(&day, &mut config);
-        update_config(config);
+        let mut state: State = get_state();
+        summarise_time(&day, &mut state);
+        update_state(&state);
     }
     else {
         panic!("Can't update time behind: The day isn't over yet")
@@ -226,7 +524,7 @@ fn update_time_behind(day: Day) {
 }
 
 pub fn view_config() {
-    println!("Here's the current config: \n");
+    println!("{}", tr("current_config", &[]));
     let config: Config = get_config();
     println!("{}", config.as_string());
 }
@@ -236,21 +534,46 @@ pub fn edit_config() {
     config.safe_edit_from_file();
 }
 
+/// `--remind HH:MM` has the daemon remind you of the note at that time today.
 pub fn add_note_to_today(now: &DateTime<Local>, mut day: Day, other_args: Vec<String>) {
-    if other_args.len() == 0 {
-        eprintln!("'punch note' requires a msg argument!");
+    let positional_args: Vec<String> = get_positional_args(&other_args, &["--remind"]);
+    if positional_args.is_empty() {
+        eprintln!("{}", tr("note_needs_msg", &[]));
         exit(1);
     }
-    else if other_args.len() > 1 {
-        eprintln!("'punch note' takes a single argument. Consider wrapping your message in quotes.");
+    else if positional_args.len() > 1 {
+        eprintln!("{}", tr("note_args", &[]));
         exit(1);
     }
     else {
-        let msg: String = (&other_args[0]).to_string();
-        day.add_note(now, &msg);
+        let remind_at: Option<DateTime<Local>> = get_flag_value(&other_args, "--remind")
+            .map(|time_str: String| get_reminder_time(now, &time_str).unwrap_or_else(|msg: String| {
+                eprintln!("{}", msg);
+                exit(1);
+            }));
+        let msg: String = (&positional_args[0]).to_string();
+        let note: &mut Note = day.add_note(now, &msg);
+        if let Some(remind_at) = remind_at {
+            note.set_remind_at(&remind_at);
+        }
         write_day(&day);
-        println!("New note '{}' added to today at '{}'.", msg, now);
+        println!("{}", tr("note_added", &[&msg, &now]));
+        if let Some(remind_at) = remind_at {
+            println!("{}", tr("note_reminder_set", &[&remind_at.format("%H:%M")]));
+        }
+    }
+}
+
+/// A time later today, given as "HH:MM".
+fn get_reminder_time(now: &DateTime<Local>, time_str: &str) -> Result<DateTime<Local>, String> {
+    let time: NaiveTime = NaiveTime::parse_from_str(time_str.trim(), TIME_OF_DAY_FMT)
+        .map_err(|_| format!("'--remind {}' should be a time like 15:00.", time_str))?;
+    let remind_at: DateTime<Local> = now.date_naive().and_time(time).and_local_timezone(Local).earliest()
+        .ok_or(format!("{} doesn't exist today.", time_str))?;
+    if remind_at <= *now {
+        return Err(format!("{} has already gone by today.", time_str.trim()));
     }
+    return Ok(remind_at);
 }
 
 pub fn update_current_task_name(now: &DateTime<Local>, mut day: Day, other_args: Vec<String>) {
@@ -263,11 +586,10 @@ pub fn update_current_task_name(now: &DateTime<Local>, mut day: Day, other_args:
     let change_task_result: Result<(), &str> = day.update_current_task_name(task_name.clone());
     
     if let Ok(_) = change_task_result {
-        println!("Updated the current task to '{}'", &task_name);
+        println!("{}", tr("task_updated", &[&task_name]));
         write_day(&day);
         if !day.has_ended() {day.end_day_at(&now).expect("We should be able to end the day");}
-        let mut config: Config = get_config();
-        summarise_time(&day, &mut config);
+        summarise_time(&day, &mut get_state());
     }
     else {
         let msg = change_task_result.unwrap_err();
@@ -278,8 +600,8 @@ pub fn update_current_task_name(now: &DateTime<Local>, mut day: Day, other_args:
 
 fn get_new_task_name_from_args(other_args: Vec<String>) -> Result<String, String> {
     return match other_args.len() {
-        0 => Err("'punch update-task' needs a new task name!".to_string()),
+        0 => Err(tr("update_task_needs_name", &[])),
         1 => Ok(other_args[0].to_owned()),
-        _ => Err("'punch update-task' should have at most one argument!".to_string()),
+        _ => Err(tr("update_task_args", &[])),
     };
 }
@@ -0,0 +1,382 @@
+use std::collections::HashSet;
+use std::process::exit;
+use std::thread;
+use std::thread::sleep;
+use std::time::Duration as StdDuration;
+use chrono::prelude::{DateTime, Local, NaiveDate};
+use chrono::Duration;
+
+use crate::units::components::{AwaySpan, BreakReminder};
+use crate::units::day::{Day, get_current_day, read_day, try_write_day};
+use crate::utils::aggregate::{close_day_at, format_secs};
+use crate::utils::args::{get_flag_value, get_interval_secs, has_flag};
+use crate::utils::clock::SharedClock;
+use crate::utils::config::{Config, get_config};
+use crate::utils::file_io::{DataLock, lock_data_dir};
+use crate::utils::notify::send_notification;
+use crate::utils::period::parse_age;
+use crate::utils::reminders::BreakReminderConfig;
+use crate::utils::retention::{PruneOutcome, prune_days_before};
+use crate::utils::compression::compress_days_before;
+use crate::utils::activity::{active_window_title, idle_secs, is_screen_locked};
+use crate::utils::away::{AwayConfig, was_suspended};
+use crate::utils::samples::{prune_samples, record_sample};
+#[cfg(feature = "meeting-detection")]
+use crate::utils::meetings::MeetingDetectionConfig;
+use crate::utils::mqtt::publish_event;
+use crate::commands::serve::serve_metrics;
+use crate::commands::status::get_daily_cap_warning;
+
+pub const DEFAULT_DAEMON_INTERVAL_SECS: u64 = 60;
+const NOTIFICATION_TITLE: &str = "punch";
+/// A note's reminder is only sent this long after it's due, so that starting the daemon
+/// late in the day doesn't send the morning's all at once.
+const NOTE_REMINDER_WINDOW_MINS: i64 = 30;
+
+/// Runs in the foreground, checking the current day every interval and sending a
+/// notification the first time each alert applies on a given day.
+pub fn daemon(other_args: Vec<String>, clock: SharedClock) {
+    let interval_secs: u64 = get_interval_secs(&other_args).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    }).unwrap_or(DEFAULT_DAEMON_INTERVAL_SECS);
+    if let Some(reminder_ind) = get_flag_value(&other_args, "--reminder") {
+        send_reminder(&reminder_ind, &clock.now());
+        return;
+    }
+    println!("punch daemon started. Checking every {} s.", interval_secs);
+    if let Some(address) = get_flag_value(&other_args, "--metrics") {
+        let metrics_clock: SharedClock = clock.clone();
+        thread::spawn(move || serve_metrics(&address, metrics_clock));
+    }
+    run_daemon_loop(interval_secs, clock, !has_flag(&other_args, "--no-reminders"));
+}
+
+/// `with_reminders` is false when the reminders are sent by systemd timers instead.
+pub fn run_daemon_loop(interval_secs: u64, clock: SharedClock, with_reminders: bool) {
+    for reminder in get_config().reminders() {
+        if let Err(msg) = reminder.get_time() {
+            eprintln!("Warning: {}", msg);
+        }
+    }
+
+    let mut sent_alerts: HashSet<String> = HashSet::new();
+    #[cfg(feature = "meeting-detection")]
+    let mut switched_from: Option<String> = None;
+    let mut last_check: Option<DateTime<Local>> = None;
+    let mut screen_lock: ScreenLockState = ScreenLockState::default();
+    let mut idle_since: Option<DateTime<Local>> = None;
+    loop {
+        let now: DateTime<Local> = clock.now();
+        let config: Config = get_config();
+        if let (Some(away), Some(last_check)) = (config.away(), last_check) {
+            if away.pause_on_suspend && was_suspended(&last_check, &now, interval_secs) {
+                record_away_time(&last_check, &now, "asleep", &config);
+            }
+        }
+        last_check = Some(now);
+        if let Some(away) = config.away().filter(|away: &&AwayConfig| away.pause_on_lock) {
+            track_screen_lock(&now, away, &config, &mut screen_lock);
+        }
+        if let Some(idle_minutes) = config.away().and_then(|away: &AwayConfig| away.idle_minutes) {
+            track_idle_time(&now, idle_minutes, &config, &mut idle_since);
+        }
+        #[cfg(feature = "meeting-detection")]
+        if let Some(meeting_detection) = config.meeting_detection() {
+            track_meetings(&now, meeting_detection, &mut switched_from);
+        }
+        if let Some(window_sampling) = config.window_sampling() {
+            sample_window(&now);
+            if sent_alerts.insert(format!("{}-prune-samples", now.date_naive())) {
+                prune_samples(&(now.date_naive() - Duration::days(window_sampling.get_retention_days() as i64)));
+            }
+        }
+        if let Some(break_reminder) = config.break_reminder() {
+            remind_to_take_break(&now, break_reminder);
+        }
+        if config.retention().is_some() && sent_alerts.insert(format!("{}-retention", now.date_naive())) {
+            enforce_retention(&now, &config);
+        }
+        if config.compression().is_some() && sent_alerts.insert(format!("{}-compression", now.date_naive())) {
+            compress_old_days(&now, &config);
+        }
+        for (key, msg) in get_alerts(&now, &config, with_reminders) {
+            let dated_key: String = format!("{}-{}", now.date_naive(), key);
+            if sent_alerts.insert(dated_key) {
+                send_notification(NOTIFICATION_TITLE, &msg);
+            }
+        }
+        sleep(StdDuration::from_secs(interval_secs));
+    }
+}
+
+/// Switches to the meeting task while a meeting is detected, and back to the task from
+/// before once it's over. Only switches back if it was the daemon that switched.
+#[cfg(feature = "meeting-detection")]
+fn track_meetings(now: &DateTime<Local>, meeting_detection: &MeetingDetectionConfig, switched_from: &mut Option<String>) {
+    let Ok(day) = get_current_day(now) else {
+        return;
+    };
+    if day.has_ended() || day.on_break {
+        *switched_from = None;
+        return;
+    }
+    let meeting_task: String = meeting_detection.get_task();
+    let current_task: String = day.get_latest_task_name();
+    let detected: Option<String> = meeting_detection.detect();
+    let switch_to: String = match (&detected, &switched_from) {
+        (Some(_), _) if current_task != meeting_task => meeting_task.clone(),
+        (None, Some(previous_task)) if current_task == meeting_task => previous_task.clone(),
+        (None, Some(_)) => {
+            // The task was changed by hand during the meeting, so leave it be.
+            *switched_from = None;
+            return;
+        },
+        (_, _) => return,
+    };
+    let switched: Result<(), String> = lock_data_dir().and_then(|_lock: DataLock| {
+        let mut day: Day = get_current_day(now)?;
+        day.start_new_block(switch_to.clone(), now).map_err(|msg: &str| msg.to_string())?;
+        try_write_day(&day)?;
+        publish_event("task", &day, now);
+        return Ok(());
+    });
+    if let Err(msg) = switched {
+        eprintln!("Warning: Couldn't switch to '{}': {}", switch_to, msg);
+        return;
+    }
+    match detected {
+        Some(reason) => {
+            *switched_from = Some(current_task);
+            send_notification(NOTIFICATION_TITLE, &format!("Looks like you're in a meeting ({}). Switched to '{}'.", reason, switch_to));
+        },
+        None => {
+            *switched_from = None;
+            send_notification(NOTIFICATION_TITLE, &format!("Meeting over. Switched back to '{}'.", switch_to));
+        },
+    }
+}
+
+/// What the daemon knows about the screen being locked.
+#[derive(Default)]
+struct ScreenLockState {
+    locked_since: Option<DateTime<Local>>,
+    /// Whether the grace period passed during this lock, after which pausing is only tried once.
+    grace_passed: bool,
+    /// The task to go back to on unlocking, if the daemon paused for the lock.
+    paused_from: Option<String>,
+}
+
+/// Pauses once the screen has been locked for the grace period, with the break starting when
+/// it was locked, and resumes the task from before once it's unlocked.
+fn track_screen_lock(now: &DateTime<Local>, away: &AwayConfig, config: &Config, state: &mut ScreenLockState) {
+    let Some(locked) = is_screen_locked() else {
+        return;
+    };
+    if locked {
+        let locked_since: DateTime<Local> = *state.locked_since.get_or_insert(*now);
+        if state.grace_passed || *now - locked_since < Duration::minutes(away.get_lock_grace_minutes() as i64) {
+            return;
+        }
+        state.grace_passed = true;
+        let paused: Result<Option<String>, String> = lock_data_dir().and_then(|_lock: DataLock| {
+            let mut day: Day = get_current_day(now)?;
+            let current_start: DateTime<Local> = day.timeblocks.last().expect("A day has blocks").get_start().as_dt();
+            if day.has_ended() || day.on_break || current_start > locked_since || day.submitted.is_some() {
+                return Ok(None);
+            }
+            let task: String = day.get_latest_task_name();
+            day.start_break_at(config.get_default_break_task().to_string(), &locked_since).map_err(|msg: &str| msg.to_string())?;
+            try_write_day(&day)?;
+            publish_event("pause", &day, now);
+            return Ok(Some(task));
+        });
+        match paused {
+            Ok(paused_from) => state.paused_from = paused_from,
+            Err(msg) => eprintln!("Warning: Couldn't pause for the locked screen: {}", msg),
+        }
+        return;
+    }
+    let (Some(locked_since), Some(task)) = (state.locked_since, state.paused_from.clone()) else {
+        *state = ScreenLockState::default();
+        return;
+    };
+    *state = ScreenLockState::default();
+    let resumed: Result<bool, String> = lock_data_dir().and_then(|_lock: DataLock| {
+        let mut day: Day = get_current_day(now)?;
+        let current_start: DateTime<Local> = day.timeblocks.last().expect("A day has blocks").get_start().as_dt();
+        // Leave the day alone if you've already resumed or punched out yourself.
+        if day.has_ended() || !day.on_break || current_start.timestamp() != locked_since.timestamp() {
+            return Ok(false);
+        }
+        day.start_new_block(task.clone(), now).map_err(|msg: &str| msg.to_string())?;
+        try_write_day(&day)?;
+        publish_event("resume", &day, now);
+        return Ok(true);
+    });
+    match resumed {
+        Ok(true) => send_notification(NOTIFICATION_TITLE, &format!(
+            "Welcome back! You were on a break while the screen was locked ({}-{}). Back to '{}'.",
+            locked_since.format("%H:%M"), now.format("%H:%M"), task)),
+        Ok(false) => {},
+        Err(msg) => eprintln!("Warning: Couldn't resume after the locked screen: {}", msg),
+    }
+}
+
+/// Notes when you went idle once there's been no input for `idle_minutes`, and counts the
+/// time until the next input as a break when you're back.
+fn track_idle_time(now: &DateTime<Local>, idle_minutes: u64, config: &Config, idle_since: &mut Option<DateTime<Local>>) {
+    let Some(idle_secs) = idle_secs() else {
+        return;
+    };
+    let last_input: DateTime<Local> = *now - Duration::seconds(idle_secs as i64);
+    if idle_secs >= idle_minutes * 60 {
+        idle_since.get_or_insert(last_input);
+    }
+    else if let Some(since) = idle_since.take() {
+        record_away_time(&since, &last_input, "idle", config);
+    }
+}
+
+/// Counts the time from `start` to `end` as a break, if you were working then, and keeps it
+/// on the day for `punch away` to confirm.
+fn record_away_time(start: &DateTime<Local>, end: &DateTime<Local>, reason: &str, config: &Config) {
+    let recorded: Result<bool, String> = lock_data_dir().and_then(|_lock: DataLock| {
+        let mut day: Day = get_current_day(end)?;
+        if day.has_ended() || day.on_break || day.get_day_start().as_dt() > *start || day.submitted.is_some() {
+            return Ok(false);
+        }
+        day.set_span(start, end, &config.get_default_break_task().to_string(), true)?;
+        day.away_spans.push(AwaySpan::new(start, end, reason));
+        try_write_day(&day)?;
+        return Ok(true);
+    });
+    match recorded {
+        Ok(true) => send_notification(NOTIFICATION_TITLE, &format!(
+            "You were away ({}) from {} to {}, so that's counted as a break. Run `punch away` if you were working.",
+            reason, start.format("%H:%M"), end.format("%H:%M"))),
+        Ok(false) => {},
+        Err(msg) => eprintln!("Warning: Couldn't record the time away: {}", msg),
+    }
+}
+
+/// Reminds you to take a break once you've worked `after_minutes` without one, once for each
+/// stretch of work. The reminder is kept on the day, so reports can tell whether you took it.
+fn remind_to_take_break(now: &DateTime<Local>, break_reminder: &BreakReminderConfig) {
+    if get_current_day(now).ok().and_then(|day: Day| get_due_break_reminder_mins(&day, now, break_reminder)).is_none() {
+        return;
+    }
+    let reminded: Result<Option<i64>, String> = lock_data_dir().and_then(|_lock: DataLock| {
+        let mut day: Day = get_current_day(now)?;
+        let Some(worked_mins) = get_due_break_reminder_mins(&day, now, break_reminder) else {
+            return Ok(None);
+        };
+        day.break_reminders.push(BreakReminder::new(now, break_reminder.get_followed_within_minutes()));
+        try_write_day(&day)?;
+        return Ok(Some(worked_mins));
+    });
+    match reminded {
+        Ok(Some(worked_mins)) => send_notification(NOTIFICATION_TITLE, &format!(
+            "You've been working for {} without a break. Time to take one!", format_secs(worked_mins * 60))),
+        Ok(None) => {},
+        Err(msg) => eprintln!("Warning: Couldn't record the break reminder: {}", msg),
+    }
+}
+
+/// How long you've worked without a break, if it's time for a reminder that hasn't been sent yet.
+fn get_due_break_reminder_mins(day: &Day, now: &DateTime<Local>, break_reminder: &BreakReminderConfig) -> Option<i64> {
+    let work_start: DateTime<Local> = day.get_continuous_work_start()?.as_dt();
+    let worked_mins: i64 = (*now - work_start).num_minutes();
+    let already_sent: bool = day.break_reminders.iter().any(|reminder: &BreakReminder| reminder.sent.as_dt() >= work_start);
+    if worked_mins < break_reminder.after_minutes as i64 || already_sent || day.submitted.is_some() {
+        return None;
+    }
+    return Some(worked_mins);
+}
+
+/// Notes the focused window's title while the day is ongoing and not on a break.
+fn sample_window(now: &DateTime<Local>) {
+    let Ok(day) = get_current_day(now) else {
+        return;
+    };
+    if day.has_ended() || day.on_break {
+        return;
+    }
+    if let Some(title) = active_window_title() {
+        record_sample(now, &title);
+    }
+}
+
+/// Prunes old days according to the config's `retention` settings.
+fn enforce_retention(now: &DateTime<Local>, config: &Config) {
+    let Some(retention) = config.retention() else {
+        return;
+    };
+    let pruned: Result<PruneOutcome, String> = parse_age(&retention.older_than, now.date_naive())
+        .and_then(|oldest_kept: NaiveDate| {
+            let _lock: DataLock = lock_data_dir()?;
+            return Ok(prune_days_before(&oldest_kept, retention.keep_aggregates));
+        });
+    match pruned {
+        Ok(outcome) if outcome.deleted + outcome.anonymized > 0 => println!(
+            "Pruned days older than {}: deleted {}, anonymized {}.", retention.older_than, outcome.deleted, outcome.anonymized),
+        Ok(_) => {},
+        Err(msg) => eprintln!("Warning: Couldn't prune old days: {}", msg),
+    }
+}
+
+/// Compresses the files of old days according to the config's `compression` settings.
+fn compress_old_days(now: &DateTime<Local>, config: &Config) {
+    let Some(compression) = config.compression() else {
+        return;
+    };
+    let compressed: Result<usize, String> = parse_age(&compression.older_than, now.date_naive())
+        .and_then(|oldest_kept: NaiveDate| {
+            let _lock: DataLock = lock_data_dir()?;
+            return compress_days_before(&oldest_kept);
+        });
+    match compressed {
+        Ok(num_compressed) if num_compressed > 0 => println!(
+            "Compressed {} files of days older than {}.", num_compressed, compression.older_than),
+        Ok(_) => {},
+        Err(msg) => eprintln!("Warning: Couldn't compress old days: {}", msg),
+    }
+}
+
+/// Sends the reminder at `reminder_ind` in the config if it's due, for a timer that goes
+/// off at the reminder's time.
+fn send_reminder(reminder_ind: &str, now: &DateTime<Local>) {
+    let config: Config = get_config();
+    let Some(reminder) = reminder_ind.parse::<usize>().ok().and_then(|ind: usize| config.reminders().get(ind)) else {
+        eprintln!("There's no reminder {} in the config!", reminder_ind);
+        exit(1);
+    };
+    if let Some(msg) = reminder.get_due_message(now, read_day(now).ok().as_ref()) {
+        send_notification(NOTIFICATION_TITLE, &msg);
+    }
+}
+
+/// Returns (key, message) pairs for everything that currently needs flagging.
+fn get_alerts(now: &DateTime<Local>, config: &Config, with_reminders: bool) -> Vec<(String, String)> {
+    let mut alerts: Vec<(String, String)> = Vec::new();
+    let today: Option<Day> = read_day(now).ok();
+    for (ind, reminder) in config.reminders().iter().enumerate().filter(|_| with_reminders) {
+        if let Some(msg) = reminder.get_due_message(now, today.as_ref()) {
+            alerts.push((format!("reminder-{}", ind), msg));
+        }
+    }
+    if let Ok(day) = get_current_day(now) {
+        for (remind_at, msg) in day.get_note_reminders() {
+            if remind_at <= *now && *now - remind_at < Duration::minutes(NOTE_REMINDER_WINDOW_MINS) {
+                alerts.push((format!("note-{}", remind_at.timestamp()), format!("Reminder: {}", msg)));
+            }
+        }
+        if !day.has_ended() {
+            let closed_day: Day = close_day_at(day, now);
+            if let Some(msg) = get_daily_cap_warning(&closed_day, config) {
+                alerts.push(("daily-cap".to_string(), msg));
+            }
+        }
+    }
+    return alerts;
+}
@@ -0,0 +1,59 @@
+use std::process::exit;
+use chrono::prelude::NaiveDate;
+
+use crate::units::day::{Day, delete_day_for_date, read_recorded_day};
+use crate::utils::aggregate::format_secs;
+use crate::utils::args::{get_positional_args, has_flag};
+use crate::utils::state::{State, get_state, update_state};
+use crate::utils::period::parse_date;
+use crate::utils::prompt::confirm;
+
+/// Deletes the day recorded on the given date, after asking (unless `--yes` is given), and
+/// takes back what it added to minutes behind when it was punched out. Submitted days
+/// can only be deleted if `force`.
+pub fn delete(other_args: Vec<String>, force: bool) {
+    let positional: Vec<String> = get_positional_args(&other_args, &[]);
+    let date: NaiveDate = match positional.as_slice() {
+        [date_str] => parse_date(date_str).unwrap_or_else(|msg: String| {
+            eprintln!("{}", msg);
+            exit(1);
+        }),
+        _ => {
+            eprintln!("'punch delete' needs the date of the day to delete, e.g. 'punch delete 2024-01-31'");
+            exit(1);
+        },
+    };
+    let day: Day = read_recorded_day(&date).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+
+    if let Err(msg) = day.check_editable(force) {
+        eprintln!("{}", msg);
+        exit(1);
+    }
+
+    let description: String = match day.get_day_end() {
+        Some(end) => format!("{} to {}", day.get_day_start().as_dt().format("%H:%M"), end.as_dt().format("%H:%M")),
+        None => format!("from {}, not punched out", day.get_day_start().as_dt().format("%H:%M")),
+    };
+    if !has_flag(&other_args, "--yes") && !confirm(&format!("Delete the day for {} ({})?", date, description)) {
+        println!("Nothing was deleted.");
+        return;
+    }
+
+    if let Err(err) = delete_day_for_date(&date) {
+        eprintln!("Couldn't delete the day for {}: {}", date, err);
+        exit(1);
+    }
+    println!("Deleted the day for {}.", date);
+    if let Some(time_left_secs) = day.get_time_left_secs() {
+        let delta_mins: i64 = -(time_left_secs / 60);
+        if delta_mins != 0 {
+            let mut state: State = get_state();
+            state.update_minutes_behind(delta_mins);
+            update_state(&state);
+            println!("Time behind changed by {}.", format_secs(delta_mins * 60));
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use std::process::exit;
+use chrono::prelude::{DateTime, Datelike, Local, NaiveDate, Weekday};
+use chrono::Duration;
+
+use crate::units::day::{Day, create_daily_dir_if_not_exists, write_day};
+use crate::utils::args::{get_flag_value, has_flag};
+use crate::utils::clock::parse_time_on;
+use crate::utils::config::{Config, create_default_config_if_not_exists, get_config};
+use crate::utils::state::{State, get_state, update_state};
+use crate::utils::file_io::{create_base_dir_if_not_exists, path_exists};
+use crate::utils::location::{LOCATION_KEY, LOCATIONS};
+
+const DEFAULT_DEMO_DAYS: i64 = 120;
+const DEMO_USAGE: &str = "Usage: punch demo-data [--days <n>] [--dir <folder>] [--seed <n>]";
+/// The only options, which each take a value.
+const DEMO_FLAGS: [&str; 3] = ["--days", "--dir", "--seed"];
+/// Category, project and task of the made-up work.
+const DEMO_TASKS: [(&str, &str, &str); 7] = [
+    ("Development", "Website", "coding"),
+    ("Development", "Website", "code review"),
+    ("Development", "Mobile app", "coding"),
+    ("Meetings", "Internal", "planning"),
+    ("Meetings", "Client", "client call"),
+    ("Support", "Website", "bug triage"),
+    ("Admin", "Internal", "email"),
+];
+const DEMO_SUMMARIES: [&str; 5] = [
+    "Made good progress",
+    "Finished the first draft",
+    "Went through the open comments",
+    "Sorted out the blockers",
+    "Wrapped up loose ends",
+];
+
+/// A small xorshift generator, so the demo data doesn't need a crate for randomness.
+struct DemoRng(u64);
+
+impl DemoRng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        return self.0;
+    }
+
+    /// A number from `low` up to but not including `high`.
+    fn between(&mut self, low: i64, high: i64) -> i64 {
+        return low + (self.next() % (high - low) as u64) as i64;
+    }
+}
+
+/// `punch demo-data [--days 120] [--dir <dir>] [--seed <n>]`: fills a new, separate profile with
+/// made-up weekdays over the last `--days` days, for trying out reports or taking screenshots
+/// without your own data. The profile goes in a temporary folder unless `--dir` is given.
+pub fn demo_data(now: &DateTime<Local>, other_args: Vec<String>) {
+    if has_flag(&other_args, "--help") || has_flag(&other_args, "-h") {
+        println!("{}", DEMO_USAGE);
+        return;
+    }
+    if let Some(unknown) = find_unknown_arg(&other_args) {
+        eprintln!("'{}' isn't an option of 'punch demo-data'.\n{}", unknown, DEMO_USAGE);
+        exit(1);
+    }
+    let num_days: i64 = match get_flag_value(&other_args, "--days") {
+        Some(days_str) => days_str.parse().ok().filter(|days: &i64| *days > 0).unwrap_or_else(|| {
+            eprintln!("'--days' should be a positive whole number! Given: '{}'", days_str);
+            exit(1);
+        }),
+        None => DEFAULT_DEMO_DAYS,
+    };
+    let seed: u64 = match get_flag_value(&other_args, "--seed") {
+        Some(seed_str) => seed_str.parse().unwrap_or_else(|_| {
+            eprintln!("'--seed' should be a whole number! Given: '{}'", seed_str);
+            exit(1);
+        }),
+        None => now.timestamp() as u64,
+    };
+    let dir: String = get_flag_value(&other_args, "--dir").unwrap_or_else(|| {
+        std::env::temp_dir().join(format!("punch-demo-{}", now.format("%Y%m%d%H%M%S"))).to_string_lossy().to_string()
+    });
+    if path_exists(&(dir.clone() + "/.punch-card")) {
+        eprintln!("'{}' already has a punch profile! Pick another folder with '--dir'.", dir);
+        exit(1);
+    }
+
+    // Everything below goes to the demo profile, as punch finds its data through HOME.
+    std::env::set_var("HOME", &dir);
+    create_base_dir_if_not_exists();
+    create_default_config_if_not_exists();
+    create_daily_dir_if_not_exists();
+
+    // The seed is mixed up so that small seeds don't all start out the same.
+    let mut rng: DemoRng = DemoRng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1);
+    let config: Config = get_config();
+    let mut state: State = get_state();
+    let mut num_written: usize = 0;
+    for days_back in (1..=num_days).rev() {
+        let date: NaiveDate = now.date_naive() - Duration::days(days_back);
+        if matches!(date.weekday(), Weekday::Sat | Weekday::Sun) || rng.between(0, 20) == 0 {
+            continue;
+        }
+        let day: Day = get_demo_day(&date, &config, &mut rng);
+        state.update_minutes_behind(day.get_time_left_secs().expect("Demo days have ended") / 60);
+        write_day(&day);
+        num_written += 1;
+    }
+    update_state(&state);
+    println!("Made up {} days over the last {} days in '{}'.", num_written, num_days, dir);
+    println!("Try them out with: HOME=\"{}\" punch report --period last-month", dir);
+}
+
+/// The first arg that isn't one of `DEMO_FLAGS` or its value, as a typo shouldn't quietly
+/// make up a profile with the defaults.
+fn find_unknown_arg(args: &Vec<String>) -> Option<String> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if !DEMO_FLAGS.contains(&arg.as_str()) {
+            return Some(arg.clone());
+        }
+        args.next();
+    }
+    return None;
+}
+
+/// A day starting between 8:00 and 9:45 with a few tasks, a lunch break and a bit over or
+/// under the target.
+fn get_demo_day(date: &NaiveDate, config: &Config, rng: &mut DemoRng) -> Day {
+    let start: DateTime<Local> = parse_time_on("08:00", date).expect("08:00 is a valid time") + Duration::minutes(rng.between(0, 106));
+    let lunch_at: DateTime<Local> = parse_time_on("12:00", date).expect("12:00 is a valid time") + Duration::minutes(rng.between(0, 61));
+    let work_mins: i64 = config.day_in_minutes() + rng.between(-60, 61);
+
+    let mut task_ind: usize = rng.between(0, DEMO_TASKS.len() as i64) as usize;
+    let mut day: Day = Day::new(&start, DEMO_TASKS[task_ind].2.to_string(), config.day_in_minutes() as u64);
+    day.paid_break_minutes = config.paid_break_minutes().unwrap_or(0);
+    let mut used_tasks: Vec<usize> = vec![task_ind];
+    let mut at: DateTime<Local> = start;
+    let mut worked_mins: i64 = 0;
+    let mut had_lunch: bool = false;
+    while worked_mins < work_mins {
+        let block_mins: i64 = rng.between(30, 151).min(work_mins - worked_mins);
+        at += Duration::minutes(block_mins);
+        worked_mins += block_mins;
+        if worked_mins >= work_mins {
+            break;
+        }
+        if !had_lunch && at >= lunch_at {
+            day.start_break_at(config.get_default_break_task().to_string(), &at).expect("Demo day isn't on a break");
+            at += Duration::minutes(rng.between(30, 61));
+            had_lunch = true;
+        }
+        task_ind = rng.between(0, DEMO_TASKS.len() as i64) as usize;
+        day.start_new_block(DEMO_TASKS[task_ind].2.to_string(), &at).expect("Demo day hasn't ended");
+        if !used_tasks.contains(&task_ind) {
+            used_tasks.push(task_ind);
+        }
+    }
+    day.end_day_at(&at).expect("Demo day hasn't ended");
+
+    for ind in used_tasks {
+        let (category, project, task): (&str, &str, &str) = DEMO_TASKS[ind];
+        let summary: &str = DEMO_SUMMARIES[rng.between(0, DEMO_SUMMARIES.len() as i64) as usize];
+        if day.get_task_summary(&task.to_string()).is_none() {
+            day.add_summary(category.to_string(), project.to_string(), task.to_string(), summary.to_string());
+        }
+    }
+    let location: &str = LOCATIONS[rng.between(0, 2) as usize];
+    day.meta.insert(LOCATION_KEY.to_string(), location.to_string());
+    if rng.between(0, 3) > 0 {
+        day.add_mood(&(start + Duration::hours(4)), rng.between(2, 6) as u8, None);
+    }
+    return day;
+}
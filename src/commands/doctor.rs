@@ -0,0 +1,141 @@
+use std::io::ErrorKind;
+use std::process::exit;
+use chrono::prelude::{DateTime, Local, NaiveDate};
+use chrono::Duration;
+use serde::de::DeserializeOwned;
+
+use crate::units::day::{Day, get_day_file_path_for_date, list_recorded_dates, read_day_for_date, try_write_day};
+use crate::utils::access::is_read_only;
+use crate::utils::aggregate::format_secs;
+use crate::utils::args::has_flag;
+use crate::utils::checksums::{Checksummed, check_checksum, write_checksum};
+use crate::utils::compliance::check_break_rules;
+use crate::utils::config::{Config, get_config};
+use crate::utils::event_log::{DayEvent, find_unreadable_logs, get_log_checksummed, list_log_paths};
+use crate::utils::file_io::{DataLock, FromString, lock_data_dir, read_file};
+use crate::utils::holidays::Holidays;
+use crate::utils::index::DaySummary;
+use crate::utils::ledger::{LedgerEntry, get_ledger_path};
+use crate::utils::state::{State, get_state_path};
+
+/// Looks through every recorded day for problems and prints what it finds, along with an
+/// event log, the ledger or the state changed outside punch. With `--update-checksums`,
+/// files you changed by hand that still read fine get their checksums updated first, so
+/// they're no longer reported.
+pub fn doctor(now: &DateTime<Local>, other_args: Vec<String>) {
+    let config: Config = get_config();
+    let holidays: Option<Holidays> = Holidays::load(&config).map_err(|msg: String| eprintln!("Not checking holidays. {}", msg)).ok();
+    let dates: Vec<NaiveDate> = list_recorded_dates();
+    let update_checksums: bool = has_flag(&other_args, "--update-checksums");
+    if update_checksums && is_read_only() {
+        eprintln!("Can't update checksums with --read-only.");
+        exit(1);
+    }
+    // Held to the end, so that the days checked afterwards are brought up to date with the
+    // logs just accepted.
+    let _lock: Option<DataLock> = match update_checksums {
+        true => Some(lock_data_dir().unwrap_or_else(|msg: String| {
+            eprintln!("{}", msg);
+            exit(1);
+        })),
+        false => None,
+    };
+    if update_checksums {
+        // Logs first, so changes to day files can be logged.
+        let mut num_updated: usize = dates.iter().flat_map(|date: &NaiveDate| list_log_paths(date))
+            .filter(|path: &String| accept_file::<Vec<DayEvent>>(&get_log_checksummed(path), path))
+            .count();
+        num_updated += usize::from(accept_file::<Vec<LedgerEntry>>(&Checksummed::Ledger, &get_ledger_path()));
+        num_updated += usize::from(accept_file::<State>(&Checksummed::State, &get_state_path()));
+        num_updated += dates.iter().filter(|date: &&NaiveDate| update_checksum(date)).count();
+        println!("Updated the checksums of {} files.", num_updated);
+    }
+    let mut problems: Vec<String> = Vec::new();
+    for (file, path) in [(Checksummed::Ledger, get_ledger_path()), (Checksummed::State, get_state_path())] {
+        if let Some(Err(msg)) = read_file(&path).ok().map(|yaml_str: String| check_checksum(&file, &yaml_str)) {
+            problems.push(msg);
+        }
+    }
+    for date in &dates {
+        for problem in check_day(now, date, &config, holidays.as_ref()) {
+            problems.push(format!("{}: {}", date, problem));
+        }
+    }
+
+    println!("Checked {} days.", dates.len());
+    if problems.is_empty() {
+        println!("No problems found.");
+    }
+    else {
+        println!("Found {} problems:", problems.len());
+        for problem in problems {
+            println!("\t{}", problem);
+        }
+    }
+}
+
+/// Takes the day file for the date as it is, if it doesn't match its checksum but still
+/// reads as a day. It goes in the event log as a change to the day, so it's what the day
+/// reads as from then on.
+fn update_checksum(date: &NaiveDate) -> bool {
+    let Ok(yaml_str) = read_file(&get_day_file_path_for_date(date)) else {
+        return false;
+    };
+    if check_checksum(&Checksummed::Day(*date), &yaml_str).is_ok() {
+        return false;
+    }
+    let Ok(day) = Day::try_from_string(&yaml_str) else {
+        return false;
+    };
+    if let Err(msg) = try_write_day(&day) {
+        eprintln!("{}", msg);
+        return false;
+    }
+    return true;
+}
+
+/// Takes a file other than a day file as it is, if it doesn't match its checksum but still
+/// reads as what it should.
+fn accept_file<T: DeserializeOwned>(file: &Checksummed, path: &str) -> bool {
+    let Ok(yaml_str) = read_file(path) else {
+        return false;
+    };
+    if check_checksum(file, &yaml_str).is_ok() || serde_yaml::from_str::<T>(&yaml_str).is_err() {
+        return false;
+    }
+    write_checksum(file, &yaml_str);
+    return true;
+}
+
+fn check_day(now: &DateTime<Local>, date: &NaiveDate, config: &Config, holidays: Option<&Holidays>) -> Vec<String> {
+    let mut problems: Vec<String> = Vec::new();
+    if let Some(Err(msg)) = read_file(&get_day_file_path_for_date(date)).ok().map(|yaml_str: String| check_checksum(&Checksummed::Day(*date), &yaml_str)) {
+        problems.push(msg);
+    }
+    problems.extend(find_unreadable_logs(date));
+    let day: Day = match read_day_for_date(date) {
+        Ok(day) => day,
+        // Deleted in its event log.
+        Err(err) if err.kind() == ErrorKind::NotFound => return problems,
+        Err(err) => {
+            problems.push(format!("Couldn't read the day: {}", err));
+            return problems;
+        },
+    };
+
+    if !day.has_ended() {
+        if *date < now.date_naive() - Duration::days(1) {
+            problems.push("Never punched out".to_string());
+        }
+        return problems;
+    }
+    problems.extend(check_break_rules(&DaySummary::from_day(&day, now), config.break_rules()));
+    // Time worked on a holiday is usually paid as overtime, so it's worth a second look.
+    let done_secs: i64 = day.get_time_done_secs().expect("Day has ended");
+    if let Some(name) = holidays.and_then(|holidays: &Holidays| holidays.get_holiday(date)) {
+        if done_secs > 0 {
+            problems.push(format!("Worked {} on a holiday ({})", format_secs(done_secs), name));
+        }
+    }
+    return problems;
+}
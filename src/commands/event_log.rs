@@ -0,0 +1,101 @@
+use std::process::exit;
+use chrono::prelude::{DateTime, Local, NaiveDate};
+
+use crate::units::day::{Day, delete_day_for_date, get_current_day, read_day_file_for_date, write_day};
+use crate::units::interval::DATE_FMT;
+use crate::utils::aggregate::format_secs;
+use crate::utils::args::get_positional_args;
+use crate::utils::event_log::{DayEvent, find_unreadable_logs, is_same_day, list_logged_dates, read_events, replay, undo_last_change};
+use crate::utils::ledger::check_week_open;
+use crate::utils::period::parse_date;
+use crate::utils::state::{State, get_state, update_state};
+
+fn get_date_arg(other_args: &Vec<String>, command: &str) -> Result<Option<NaiveDate>, String> {
+    return match get_positional_args(other_args, &[]).as_slice() {
+        [] => Ok(None),
+        [date_str] => parse_date(date_str).map(Some),
+        _ => Err(format!("'punch {}' takes at most one date, e.g. 'punch {} 2024-01-31'", command, command)),
+    };
+}
+
+/// `punch undo` takes back the latest change to the current day (or the day of the date
+/// given), from whichever device made it, and fixes minutes behind if it changed how the
+/// day ended. Running it again takes back the change before that.
+pub fn undo(now: &DateTime<Local>, other_args: Vec<String>, force: bool) {
+    let date: NaiveDate = match get_date_arg(&other_args, "undo") {
+        Ok(Some(date)) => date,
+        Ok(None) => get_current_day(now).map(|day: Day| day.get_day_start().as_dt().date_naive()).unwrap_or(now.date_naive()),
+        Err(msg) => {
+            eprintln!("{}", msg);
+            exit(1);
+        },
+    };
+    let before: Option<Day> = replay(&read_events(&date));
+    if let Some(Err(msg)) = before.as_ref().map(|day: &Day| day.check_editable(force)) {
+        eprintln!("{}", msg);
+        exit(1);
+    }
+    let (undone, after): (DayEvent, Option<Day>) = undo_last_change(&date).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+    write_logged_day(&date, after.as_ref());
+    println!("Undid the {} for {}.", undone.change.describe(), date.format(DATE_FMT));
+
+    // Only a finished day counts towards minutes behind.
+    let get_minutes_left = |day: Option<&Day>| day.and_then(|day: &Day| day.get_time_left_secs()).unwrap_or(0) / 60;
+    let delta_mins: i64 = get_minutes_left(after.as_ref()) - get_minutes_left(before.as_ref());
+    if delta_mins != 0 {
+        let mut state: State = get_state();
+        state.update_minutes_behind(delta_mins);
+        update_state(&state);
+        println!("Time behind changed by {}.", format_secs(delta_mins * 60));
+    }
+}
+
+/// `punch recalc` rebuilds day files from their event logs, which brings in changes synced
+/// from other devices. It does every day with a log, or just the date given.
+pub fn recalc(other_args: Vec<String>) {
+    let dates: Vec<NaiveDate> = match get_date_arg(&other_args, "recalc") {
+        Ok(Some(date)) => vec![date],
+        Ok(None) => list_logged_dates(),
+        Err(msg) => {
+            eprintln!("{}", msg);
+            exit(1);
+        },
+    };
+    let mut num_changed: usize = 0;
+    for date in dates {
+        if !find_unreadable_logs(&date).is_empty() {
+            println!("{}: skipped, as one of its event logs can't be read. Run 'punch doctor' for details.", date.format(DATE_FMT));
+            continue;
+        }
+        let logged: Option<Day> = replay(&read_events(&date));
+        if logged.is_none() && read_events(&date).is_empty() {
+            continue;
+        }
+        if is_same_day(logged.as_ref(), read_day_file_for_date(&date).ok().as_ref()) {
+            continue;
+        }
+        if let Err(msg) = check_week_open(&date) {
+            println!("{}: skipped. {}", date.format(DATE_FMT), msg);
+            continue;
+        }
+        write_logged_day(&date, logged.as_ref());
+        println!("{}: {}", date.format(DATE_FMT), if logged.is_some() {"rebuilt"} else {"deleted"});
+        num_changed += 1;
+    }
+    match num_changed {
+        0 => println!("Every day file already matches its event log."),
+        _ => println!("Rebuilt {} day file(s) from their event logs.", num_changed),
+    }
+}
+
+fn write_logged_day(date: &NaiveDate, day: Option<&Day>) {
+    match day {
+        Some(day) => write_day(day),
+        None => {
+            let _ = delete_day_for_date(date);
+        },
+    }
+}
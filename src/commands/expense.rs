@@ -0,0 +1,111 @@
+use std::process::exit;
+use chrono::prelude::{DateTime, Local};
+
+use crate::units::components::Expense;
+use crate::units::day::{Day, DayIterator, get_current_day, write_day};
+use crate::units::interval::DATE_FMT;
+use crate::utils::args::{get_flag_value, get_positional_args};
+use crate::utils::config::get_config;
+use crate::utils::expenses::{ExpenseConfig, format_cents, parse_amount_cents};
+use crate::utils::period::{DateRange, month_of, parse_month};
+use crate::utils::report::{Report, ReportSection};
+use crate::commands::report::print_report;
+
+/// Adds an expense to today, like `punch expense 12.50 "Train to the client"`, or a distance
+/// driven with `punch expense --km 30 "Drive to the client"`. `punch expense report` lists
+/// the expenses of a month, and with no args, today's are listed.
+pub fn expense(now: &DateTime<Local>, other_args: Vec<String>, force: bool) {
+    let positional_args: Vec<String> = get_positional_args(&other_args, &["--km", "--month", "--format", "--output", "--section"]);
+    if positional_args.first().is_some_and(|x: &String| x == "report") {
+        print_report(build_expense_report(now, &other_args), &other_args, now);
+        return;
+    }
+    let mut day: Day = get_current_day(now).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+    let config: ExpenseConfig = get_config().expenses().cloned().unwrap_or_default();
+    if positional_args.is_empty() && get_flag_value(&other_args, "--km").is_none() {
+        for expense in &day.expenses {
+            println!("{}  {:>10}  {}", expense.time.as_dt().format("%H:%M"), format_cents(expense.amount_cents), describe(expense));
+        }
+        return;
+    }
+    if let Err(msg) = day.check_editable(force) {
+        eprintln!("{}", msg);
+        exit(1);
+    }
+    let expense: Expense = get_expense(now, &other_args, positional_args, &config).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+    println!("Added {} {}for '{}' to today.",
+        format_cents(expense.amount_cents), get_currency_suffix(&config), expense.description);
+    day.expenses.push(expense);
+    write_day(&day);
+}
+
+fn get_expense(now: &DateTime<Local>, other_args: &Vec<String>, mut positional_args: Vec<String>, config: &ExpenseConfig) -> Result<Expense, String> {
+    let (amount_cents, km): (i64, Option<f64>) = match get_flag_value(other_args, "--km") {
+        Some(km_str) => {
+            let km: f64 = km_str.trim().parse().map_err(|_| format!("'--km {}' should be a distance like '30' or '12.5'.", km_str))?;
+            let rate: f64 = config.mileage_rate.ok_or(
+                "Set 'mileage_rate' under 'expenses' in the config to say what a kilometre is paid back at, e.g. 'expenses: {mileage_rate: 0.30}'.")?;
+            ((km * rate * 100.0).round() as i64, Some(km))
+        },
+        None => (parse_amount_cents(&positional_args.remove(0))?, None),
+    };
+    let description: String = positional_args.join(" ");
+    if description.trim().is_empty() {
+        return Err("'punch expense' needs a description, e.g. 'punch expense 12.50 \"Train to the client\"'.".to_string());
+    }
+    return Ok(Expense::new(now, amount_cents, description, km));
+}
+
+/// The expenses of each day in this month, or the month given with `--month YYYY-MM`.
+fn build_expense_report(now: &DateTime<Local>, other_args: &Vec<String>) -> Report {
+    let config: ExpenseConfig = get_config().expenses().cloned().unwrap_or_default();
+    let month: DateRange = match get_flag_value(other_args, "--month") {
+        Some(month_str) => parse_month(&month_str).unwrap_or_else(|msg: String| {
+            eprintln!("{}", msg);
+            exit(1);
+        }),
+        None => month_of(now.date_naive()),
+    };
+    let mut report: Report = Report::new(format!("Expenses for {}", month.from.format("%B %Y")));
+    let mut expenses_section: ReportSection = ReportSection::new("expenses", &["date", "description", "km", "amount"]);
+    let (mut total_cents, mut total_km): (i64, f64) = (0, 0.0);
+    for day in DayIterator::new(&month) {
+        for expense in &day.expenses {
+            total_cents += expense.amount_cents;
+            total_km += expense.km.unwrap_or(0.0);
+            expenses_section.add_row(vec![
+                day.get_day_start().as_dt().format(DATE_FMT).to_string().into(),
+                expense.description.clone().into(),
+                expense.km.map(|km: f64| km.to_string()).unwrap_or_default().into(),
+                format_cents(expense.amount_cents).into(),
+            ]);
+        }
+    }
+    report.add_section(expenses_section);
+    let mut totals_section: ReportSection = ReportSection::new("totals", &["total", "value"]);
+    totals_section.add_row(vec!["Distance (km)".into(), total_km.to_string().into()]);
+    totals_section.add_row(vec![
+        "Expenses".into(),
+        (format_cents(total_cents) + " " + &get_currency_suffix(&config)).trim_end().to_string().into(),
+    ]);
+    report.add_section(totals_section);
+    return report;
+}
+
+fn describe(expense: &Expense) -> String {
+    return match expense.km {
+        Some(km) => format!("{} ({} km)", expense.description, km),
+        None => expense.description.clone(),
+    };
+}
+
+/// The currency and a space, or nothing if there's no currency set.
+fn get_currency_suffix(config: &ExpenseConfig) -> String {
+    return config.currency.as_ref().map(|currency: &String| currency.clone() + " ").unwrap_or_default();
+}
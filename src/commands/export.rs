@@ -0,0 +1,225 @@
+use std::process::exit;
+use chrono::Duration;
+use chrono::prelude::{DateTime, Datelike, Local, NaiveDate};
+
+use crate::commands::obsidian::export_to_obsidian;
+use crate::commands::report::write_output;
+use crate::units::components::TimeBlock;
+use crate::units::day::{Day, DayIterator};
+use crate::units::interval::DATE_FMT;
+use crate::utils::aggregate::{close_day_at, format_secs};
+use crate::utils::args::{get_flag_value, get_positional_args, get_where_conditions, has_flag};
+use crate::utils::pdf::PdfDocument;
+use crate::utils::period::{DateRange, resolve_range_from_args};
+use crate::utils::xlsx::{XlsxCell, XlsxSheet, build_xlsx};
+
+const EXPORT_FORMATS: [&str; 5] = ["pdf", "xlsx", "timeclock", "org", "obsidian"];
+const ORG_TIMESTAMP_FMT: &str = "[%Y-%m-%d %a %H:%M]";
+const TIMECLOCK_FMT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Writes the days of a period to a file for sharing, e.g. `punch export pdf --period last-month`.
+/// The file goes to `--output` (`-` for stdout), or to a name made from the format and period.
+pub fn export(now: &DateTime<Local>, other_args: Vec<String>) {
+    let format: String = match get_positional_args(&other_args, &["--period", "--from", "--to", "--output", "--vault", "--folder", "--where"]).first() {
+        Some(format) => format.clone(),
+        None => {
+            eprintln!("'punch export' needs a format. Try one of: {}", EXPORT_FORMATS.join(", "));
+            exit(1);
+        },
+    };
+    let range: DateRange = resolve_range_from_args(now, &other_args).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+    let conditions: Vec<(String, String)> = get_where_conditions(&other_args).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+    let days: Vec<Day> = DayIterator::new(&range)
+        .filter(|day: &Day| day.matches_meta(&conditions))
+        .map(|day: Day| close_day_at(day, now))
+        .collect();
+    if format == "obsidian" {
+        export_to_obsidian(&days, &other_args);
+        return;
+    }
+    let contents: Vec<u8> = match format.as_str() {
+        "pdf" => build_pdf_timesheet(&range, &days).into_bytes(),
+        "xlsx" => build_xlsx(&build_weekly_sheets(&days)),
+        "timeclock" => build_timeclock(&days).into_bytes(),
+        "org" => build_org(&range, &days).into_bytes(),
+        other => {
+            eprintln!("'{}' isn't a format 'punch export' knows. Try one of: {}", other, EXPORT_FORMATS.join(", "));
+            exit(1);
+        },
+    };
+    let output: String = get_flag_value(&other_args, "--output")
+        .unwrap_or(format!("timesheet-{}-to-{}.{}", range.from, range.to, format));
+    if output == "-" && has_flag(&other_args, "--sign") {
+        eprintln!("Only files can be signed, not stdout. Leave out '--output -' or '--sign'.");
+        exit(1);
+    }
+    if output == "-" {
+        print!("{}", String::from_utf8_lossy(&contents));
+        return;
+    }
+    write_output(&output, &contents, has_flag(&other_args, "--sign"), now);
+    println!("Exported {} days to '{}'.", days.len(), output);
+}
+
+/// A timesheet with a table of blocks for each day and lines to sign at the end.
+fn build_pdf_timesheet(range: &DateRange, days: &Vec<Day>) -> String {
+    let mut pdf: PdfDocument = PdfDocument::new();
+    pdf.add_line("Timesheet", 16.0, true);
+    pdf.add_line(&format!("Period: {}", range.as_string()), 10.0, false);
+    pdf.add_space(10.0);
+
+    let mut total_secs: i64 = 0;
+    for day in days {
+        let done_secs: i64 = day.get_time_done_secs().expect("Day is closed so time done is known");
+        let break_secs: i64 = day.get_total_break_time_secs().expect("Day is closed so break time is known");
+        total_secs += done_secs;
+        // Keep a day's heading with at least the first few rows of its table.
+        pdf.keep_together(80.0);
+        pdf.add_line(&day.get_day_start().as_dt().format("%A %Y-%m-%d").to_string(), 11.0, true);
+        if !day.meta.is_empty() {
+            let meta: Vec<String> = day.meta.iter().map(|(key, value)| format!("{}: {}", key, value)).collect();
+            pdf.add_line(&meta.join("    "), 9.0, false);
+        }
+        pdf.add_line(&format!("{:<7}{:<7}{:<40}{}", "Start", "End", "Task", "Duration"), 9.0, true);
+        for (ind, block) in day.timeblocks.iter().enumerate() {
+            let end: String = block.get_end().map(|end| end.as_dt().format("%H:%M").to_string()).unwrap_or_default();
+            pdf.add_line(&format!(
+                "{:<7}{:<7}{:<40}{}",
+                block.get_start().as_dt().format("%H:%M"), end, get_task_cell(block, day.is_break(ind)),
+                format_secs(block.get_length_secs().unwrap_or(0))), 9.0, false);
+        }
+        pdf.add_line(&format!("Worked: {}    Breaks: {}", format_secs(done_secs), format_secs(break_secs)), 9.0, false);
+        pdf.add_space(10.0);
+    }
+
+    pdf.keep_together(120.0);
+    pdf.add_line(&format!("Total worked: {} over {} days", format_secs(total_secs), days.len()), 11.0, true);
+    pdf.add_space(40.0);
+    pdf.add_line("Employee signature: ______________________________   Date: ____________", 10.0, false);
+    pdf.add_space(30.0);
+    pdf.add_line("Approved by:        ______________________________   Date: ____________", 10.0, false);
+    return pdf.render();
+}
+
+/// A sheet for each week with a row for each block and formulas adding up the hours.
+fn build_weekly_sheets(days: &Vec<Day>) -> Vec<XlsxSheet> {
+    let mut sheets: Vec<XlsxSheet> = Vec::new();
+    let mut current_week: Option<NaiveDate> = None;
+    for day in days {
+        let date: NaiveDate = day.get_day_start().as_dt().date_naive();
+        let week_start: NaiveDate = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+        if current_week != Some(week_start) {
+            current_week = Some(week_start);
+            sheets.push(new_timesheet_sheet(&format!("Week of {}", week_start.format(DATE_FMT))));
+        }
+        let sheet: &mut XlsxSheet = sheets.last_mut().expect("A sheet was just added for the week");
+        for (ind, block) in day.timeblocks.iter().enumerate() {
+            let hours: f64 = (block.get_length_secs().unwrap_or(0) as f64 / 36.0).round() / 100.0;
+            let (work_hours, break_hours): (XlsxCell, XlsxCell) = match day.is_break(ind) {
+                true => (XlsxCell::Empty, XlsxCell::Number(hours)),
+                false => (XlsxCell::Number(hours), XlsxCell::Empty),
+            };
+            sheet.add_row(vec![
+                XlsxCell::Text(date.format(DATE_FMT).to_string()),
+                XlsxCell::Text(block.get_start().as_dt().format("%H:%M").to_string()),
+                XlsxCell::Text(block.get_end().map(|end| end.as_dt().format("%H:%M").to_string()).unwrap_or_default()),
+                XlsxCell::Text(block.get_task_name()),
+                work_hours,
+                break_hours,
+            ], false);
+        }
+    }
+    // A workbook needs at least one sheet, even for a period with nothing recorded.
+    if sheets.is_empty() {
+        sheets.push(new_timesheet_sheet("Timesheet"));
+    }
+    for sheet in sheets.iter_mut() {
+        let last_row: usize = sheet.rows.len();
+        let get_total = |col: &str| -> XlsxCell {
+            return match last_row {
+                1 => XlsxCell::Number(0.0),
+                _ => XlsxCell::Formula(format!("SUM({}2:{}{})", col, col, last_row)),
+            };
+        };
+        sheet.add_row(vec![
+            XlsxCell::Text("Total".to_string()),
+            XlsxCell::Empty,
+            XlsxCell::Empty,
+            XlsxCell::Empty,
+            get_total("E"),
+            get_total("F"),
+        ], true);
+    }
+    return sheets;
+}
+
+/// Clock-in and clock-out lines for each block that isn't a break, in the timeclock format
+/// read by hledger and ledger, with the task as the account.
+fn build_timeclock(days: &Vec<Day>) -> String {
+    let mut out: String = String::new();
+    for day in days {
+        for (ind, block) in day.timeblocks.iter().enumerate() {
+            let end: DateTime<Local> = match (day.is_break(ind), block.get_end()) {
+                (false, Some(end)) => end.as_dt(),
+                (_, _) => continue,
+            };
+            // Two spaces would end the account name and start a description.
+            let account: String = block.get_task_name().split_whitespace().collect::<Vec<&str>>().join(" ");
+            out += &format!("i {} {}\n", block.get_start().as_dt().format(TIMECLOCK_FMT), account);
+            out += &format!("o {}\n", end.format(TIMECLOCK_FMT));
+        }
+    }
+    return out;
+}
+
+/// An org-mode outline with a heading for each day and each task under it, the task's
+/// blocks as CLOCK lines in its LOGBOOK drawer and a clocktable at the top for Emacs to fill in.
+fn build_org(range: &DateRange, days: &Vec<Day>) -> String {
+    let mut out: String = format!("#+TITLE: Time tracked {}\n\n", range.as_string());
+    out += "#+BEGIN: clocktable :scope file :maxlevel 2\n#+END:\n";
+    for day in days {
+        out += &format!("\n* {}\n", day.get_day_start().as_dt().format("%Y-%m-%d %A"));
+        if !day.meta.is_empty() {
+            let properties: Vec<String> = day.meta.iter().map(|(key, value)| format!(":{}: {}", key, value)).collect();
+            out += &format!(":PROPERTIES:\n{}\n:END:\n", properties.join("\n"));
+        }
+        for task in day.get_tasks_in_chronological_order() {
+            let clocks: Vec<String> = day.timeblocks.iter().enumerate()
+                .filter(|(ind, block)| !day.is_break(*ind) && block.get_task_name() == task)
+                .filter_map(|(_, block)| block.get_end().map(|end| format!(
+                    "CLOCK: {}--{} => {:>2}:{:02}",
+                    block.get_start().as_dt().format(ORG_TIMESTAMP_FMT), end.as_dt().format(ORG_TIMESTAMP_FMT),
+                    block.get_length_mins().unwrap_or(0) / 60, block.get_length_mins().unwrap_or(0) % 60)))
+                .collect();
+            if clocks.is_empty() {
+                continue;
+            }
+            out += &format!("** {}\n:LOGBOOK:\n{}\n:END:\n", task, clocks.join("\n"));
+        }
+    }
+    return out;
+}
+
+fn new_timesheet_sheet(name: &str) -> XlsxSheet {
+    let mut sheet: XlsxSheet = XlsxSheet::new(name);
+    sheet.add_row(["Date", "Start", "End", "Task", "Hours", "Break hours"]
+        .map(|header: &str| XlsxCell::Text(header.to_string())).into(), true);
+    return sheet;
+}
+
+fn get_task_cell(block: &TimeBlock, is_break: bool) -> String {
+    let mut task: String = block.get_task_name();
+    if is_break {
+        task += " (break)";
+    }
+    if task.chars().count() > 38 {
+        task = task.chars().take(35).collect::<String>() + "...";
+    }
+    return task;
+}
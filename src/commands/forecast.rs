@@ -0,0 +1,135 @@
+use std::process::exit;
+use chrono::prelude::{DateTime, Local, NaiveDate};
+use chrono::Duration;
+
+use crate::units::interval::DATE_FMT;
+use crate::utils::aggregate::format_secs;
+use crate::utils::args::get_flag_value;
+use crate::utils::state::get_state;
+use crate::utils::index::{DaySummary, get_summaries_in_range};
+use crate::utils::period::DateRange;
+use crate::utils::report::{Report, ReportSection, ReportValue};
+use crate::commands::report::print_report;
+
+const DEFAULT_FORECAST_DAYS: i64 = 28;
+
+/// How the forecast came out. Whatever couldn't be worked out, like when you're not behind
+/// or have no days to go by, is left as `None`.
+struct Forecast {
+    minutes_behind: i64,
+    window_days: i64,
+    extra_mins: i64,
+    days_worked: Option<i64>,
+    /// How far over (or, if negative, short of) the target you finished, on average.
+    surplus_secs: Option<i64>,
+    days_needed: Option<i64>,
+    caught_up_on: Option<NaiveDate>,
+}
+
+/// `punch forecast`: when your minutes behind will be back to zero, going by how far ahead or
+/// behind you finished each day worked over the last 28 days (or `--days`). `--extra 30`
+/// asks what happens if you work 30 more minutes each of those days. With `--format`, it's
+/// given as a report.
+pub fn forecast(now: &DateTime<Local>, other_args: Vec<String>) {
+    let window_days: i64 = parse_flag(&other_args, "--days", DEFAULT_FORECAST_DAYS).filter(|days: &i64| *days > 0)
+        .unwrap_or_else(|| {
+            eprintln!("'--days' should be a positive whole number of days!");
+            exit(1);
+        });
+    let extra_mins: i64 = parse_flag(&other_args, "--extra", 0).unwrap_or_else(|| {
+        eprintln!("'--extra' should be a whole number of minutes, like '--extra 30'!");
+        exit(1);
+    });
+    let forecast: Forecast = get_forecast(now, window_days, extra_mins);
+    if get_flag_value(&other_args, "--format").is_some() {
+        print_report(build_forecast_report(&forecast), &other_args, now);
+        return;
+    }
+    print_forecast(&forecast);
+}
+
+fn get_forecast(now: &DateTime<Local>, window_days: i64, extra_mins: i64) -> Forecast {
+    let mut forecast: Forecast = Forecast {
+        minutes_behind: get_state().minutes_behind(),
+        window_days: window_days,
+        extra_mins: extra_mins,
+        days_worked: None,
+        surplus_secs: None,
+        days_needed: None,
+        caught_up_on: None,
+    };
+    if forecast.minutes_behind <= 0 {
+        return forecast;
+    }
+
+    // Today is left out, since it isn't over yet.
+    let today: NaiveDate = now.date_naive();
+    let range: DateRange = DateRange {from: today - Duration::days(window_days), to: today - Duration::days(1)};
+    let summaries: Vec<DaySummary> = get_summaries_in_range(&range, now);
+    if summaries.is_empty() {
+        return forecast;
+    }
+    let days_worked: i64 = summaries.len() as i64;
+    let surplus_secs: i64 = summaries.iter().map(|summary: &DaySummary| summary.done_secs - summary.to_do_secs).sum::<i64>() / days_worked;
+    forecast.days_worked = Some(days_worked);
+    forecast.surplus_secs = Some(surplus_secs);
+    let daily_secs: i64 = surplus_secs + extra_mins * 60;
+    if daily_secs <= 0 {
+        return forecast;
+    }
+    let days_needed: i64 = (forecast.minutes_behind * 60 + daily_secs - 1) / daily_secs;
+    // Days worked are spread over the calendar the way they were over the window.
+    let calendar_days: i64 = (days_needed * window_days + days_worked - 1) / days_worked;
+    forecast.days_needed = Some(days_needed);
+    forecast.caught_up_on = Some(today + Duration::days(calendar_days));
+    return forecast;
+}
+
+fn print_forecast(forecast: &Forecast) {
+    if forecast.minutes_behind <= 0 {
+        println!("You're not behind: you're {} ahead.", format_secs(-forecast.minutes_behind * 60));
+        return;
+    }
+    let (Some(days_worked), Some(surplus_secs)) = (forecast.days_worked, forecast.surplus_secs) else {
+        println!("No days worked in the last {} days to forecast from. Try a longer '--days'.", forecast.window_days);
+        return;
+    };
+    let daily_secs: i64 = surplus_secs + forecast.extra_mins * 60;
+    println!("{} behind. Over the last {} days you worked {} days, finishing {} on average{}.",
+        format_secs(forecast.minutes_behind * 60), forecast.window_days, days_worked, describe_daily_secs(surplus_secs),
+        if forecast.extra_mins != 0 {format!(", or {} with {} m extra", describe_daily_secs(daily_secs), forecast.extra_mins)} else {String::new()});
+    let (Some(days_needed), Some(caught_up_on)) = (forecast.days_needed, forecast.caught_up_on) else {
+        println!("At that rate you won't catch up. Try '--extra' to see what more time a day would do.");
+        return;
+    };
+    println!("You'll be back to zero after {} more days worked, around {}.", days_needed, caught_up_on.format(DATE_FMT));
+}
+
+/// The forecast as a single section of figures. What couldn't be worked out is left empty.
+fn build_forecast_report(forecast: &Forecast) -> Report {
+    let optional = |value: Option<ReportValue>| value.unwrap_or(ReportValue::Text(String::new()));
+    let mut report: Report = Report::new(format!("Forecast from the last {} days", forecast.window_days));
+    let mut forecast_section: ReportSection = ReportSection::new("forecast", &["figure", "value"]);
+    forecast_section.add_row(vec!["Time behind".into(), ReportValue::Secs(forecast.minutes_behind * 60)]);
+    forecast_section.add_row(vec!["Days worked".into(), optional(forecast.days_worked.map(ReportValue::Number))]);
+    forecast_section.add_row(vec!["Average over target".into(), optional(forecast.surplus_secs.map(ReportValue::Secs))]);
+    forecast_section.add_row(vec!["Extra a day".into(), ReportValue::Secs(forecast.extra_mins * 60)]);
+    forecast_section.add_row(vec!["Days to catch up".into(), optional(forecast.days_needed.map(ReportValue::Number))]);
+    forecast_section.add_row(vec![
+        "Caught up around".into(),
+        optional(forecast.caught_up_on.map(|date: NaiveDate| date.format(DATE_FMT).to_string().into())),
+    ]);
+    report.add_section(forecast_section);
+    return report;
+}
+
+fn describe_daily_secs(secs: i64) -> String {
+    return format!("{} {}", format_secs(secs.abs()), if secs < 0 {"short"} else {"over"});
+}
+
+fn parse_flag(other_args: &Vec<String>, flag: &str, default: i64) -> Option<i64> {
+    return match get_flag_value(other_args, flag) {
+        Some(value_str) => value_str.trim_start_matches('+').parse().ok(),
+        None => Some(default),
+    };
+}
@@ -0,0 +1,232 @@
+use chrono::prelude::{DateTime, Local, NaiveDate};
+
+use crate::units::components::{Note, TimeBlock};
+use crate::units::day::{Day, DayIterator, read_day_for_date};
+use crate::units::interval::{DATE_FMT, Dt};
+use crate::utils::graphql::{GraphqlField, parse_graphql};
+use crate::utils::index::DaySummary;
+use crate::utils::json::JsonValue;
+use crate::utils::period::{DateRange, parse_date, resolve_range_from_args};
+use crate::utils::work_summary::WorkSummary;
+
+/// The schema `run_graphql` answers queries against, for `GET /graphql` to show.
+pub const GRAPHQL_SCHEMA: &str = "\
+type Query {
+  # Defaults to this week. `where` keeps days with all the given `punch meta` values, like \"location=office\".
+  days(from: String, to: String, period: String, where: [String]): [Day!]!
+  # Defaults to today.
+  day(date: String): Day
+}
+
+type Day {
+  date: String!
+  start: String!
+  end: String
+  doneSecs: Int!
+  breakSecs: Int!
+  toDoSecs: Int!
+  submitted: Boolean!
+  meta: [Meta!]!
+  blocks(task: String, category: String, project: String, breaks: Boolean): [Block!]!
+  notes(task: String): [Note!]!
+  summaries(task: String, category: String, project: String): [Summary!]!
+}
+
+type Meta { key: String!, value: String! }
+
+type Block {
+  task: String!
+  start: String!
+  end: String
+  lengthSecs: Int
+  isBreak: Boolean!
+  billable: Boolean!
+  category: String
+  project: String
+  notes: [Note!]!
+  annotations: [String!]!
+}
+
+type Note { time: String!, text: String!, task: String! }
+
+type Summary { task: String!, category: String!, project: String!, summary: String!, unfinished: Boolean! }
+";
+
+/// Answers a GraphQL query over your days with only the fields it asks for. Times are RFC 3339,
+/// and a block's category and project come from the summary of its task.
+pub fn run_graphql(query: &str, variables: &JsonValue, now: &DateTime<Local>) -> Result<JsonValue, String> {
+    let fields: Vec<GraphqlField> = parse_graphql(query, variables)?;
+    return resolve_object("Query", &fields, |field: &GraphqlField| match field.name.as_str() {
+        "days" => {
+            let mut range_args: Vec<String> = Vec::new();
+            for key in ["period", "from", "to"] {
+                if let Some(value) = field.get_str_arg(key)? {
+                    range_args.push(format!("--{}", key));
+                    range_args.push(value);
+                }
+            }
+            let range: DateRange = resolve_range_from_args(now, &range_args)?;
+            let conditions: Vec<(String, String)> = field.get_str_list_arg("where")?.iter()
+                .map(|condition: &String| match condition.split_once('=') {
+                    Some((key, value)) => Ok((key.trim().to_string(), value.trim().to_string())),
+                    None => Err(format!("'where' values should look like \"key=value\". Given: '{}'", condition)),
+                })
+                .collect::<Result<Vec<(String, String)>, String>>()?;
+            let days: Vec<Day> = DayIterator::new(&range).filter(|day: &Day| day.matches_meta(&conditions)).collect();
+            Ok(Some(resolve_list(field, &days, |day: &Day, fields| resolve_day(day, fields, now))?))
+        },
+        "day" => {
+            let date: NaiveDate = match field.get_str_arg("date")? {
+                Some(date_str) => parse_date(&date_str)?,
+                None => now.date_naive(),
+            };
+            Ok(Some(match read_day_for_date(&date) {
+                Ok(day) => resolve_fields(field, |fields| resolve_day(&day, fields, now))?,
+                Err(_) => JsonValue::Null,
+            }))
+        },
+        _ => Ok(None),
+    });
+}
+
+fn resolve_day(day: &Day, fields: &Vec<GraphqlField>, now: &DateTime<Local>) -> Result<JsonValue, String> {
+    let summary: DaySummary = DaySummary::from_day(day, now);
+    return resolve_object("Day", fields, |field: &GraphqlField| Ok(match field.name.as_str() {
+        "date" => Some(scalar(field, JsonValue::String(day.get_day_start().as_dt().format(DATE_FMT).to_string()))?),
+        "start" => Some(scalar(field, format_time(&day.get_day_start()))?),
+        "end" => Some(scalar(field, day.get_day_end().map(|end: Dt| format_time(&end)).unwrap_or(JsonValue::Null))?),
+        "doneSecs" => Some(scalar(field, JsonValue::Number(summary.done_secs))?),
+        "breakSecs" => Some(scalar(field, JsonValue::Number(summary.break_secs))?),
+        "toDoSecs" => Some(scalar(field, JsonValue::Number(summary.to_do_secs))?),
+        "submitted" => Some(scalar(field, JsonValue::Bool(day.submitted.is_some()))?),
+        "meta" => {
+            let meta: Vec<(&String, &String)> = day.meta.iter().collect();
+            Some(resolve_list(field, &meta, |(key, value): &(&String, &String), fields| resolve_object("Meta", fields, |field: &GraphqlField| Ok(match field.name.as_str() {
+                "key" => Some(scalar(field, JsonValue::String(key.to_string()))?),
+                "value" => Some(scalar(field, JsonValue::String(value.to_string()))?),
+                _ => None,
+            })))?)
+        },
+        "blocks" => {
+            let task: Option<String> = field.get_str_arg("task")?;
+            let category: Option<String> = field.get_str_arg("category")?;
+            let project: Option<String> = field.get_str_arg("project")?;
+            let with_breaks: bool = field.get_arg("breaks") != Some(&JsonValue::Bool(false));
+            let blocks: Vec<(usize, &TimeBlock)> = day.timeblocks.iter().enumerate()
+                .filter(|(ind, _)| with_breaks || !day.is_break(*ind))
+                .filter(|(_, block)| task.as_ref().is_none_or(|task: &String| block.get_task_name() == *task))
+                .filter(|(_, block)| category.as_ref().is_none_or(|category: &String| get_task_summary(day, block).is_some_and(|x: &WorkSummary| x.get_category() == category)))
+                .filter(|(_, block)| project.as_ref().is_none_or(|project: &String| get_task_summary(day, block).is_some_and(|x: &WorkSummary| x.get_project() == project)))
+                .collect();
+            Some(resolve_list(field, &blocks, |(ind, block): &(usize, &TimeBlock), fields| resolve_block(day, *ind, block, fields))?)
+        },
+        "notes" => {
+            let task: Option<String> = field.get_str_arg("task")?;
+            let notes: Vec<(String, &Note)> = day.timeblocks.iter()
+                .filter(|block: &&TimeBlock| task.as_ref().is_none_or(|task: &String| block.get_task_name() == *task))
+                .flat_map(|block: &TimeBlock| block.get_notes().iter().map(|note: &Note| (block.get_task_name(), note)))
+                .collect();
+            Some(resolve_list(field, &notes, |(task, note): &(String, &Note), fields| resolve_note(task, note, fields))?)
+        },
+        "summaries" => {
+            let task: Option<String> = field.get_str_arg("task")?;
+            let category: Option<String> = field.get_str_arg("category")?;
+            let project: Option<String> = field.get_str_arg("project")?;
+            let summaries: Vec<&WorkSummary> = day.summaries.iter()
+                .filter(|summary: &&WorkSummary| task.as_ref().is_none_or(|task: &String| summary.get_task() == task))
+                .filter(|summary: &&WorkSummary| category.as_ref().is_none_or(|category: &String| summary.get_category() == category))
+                .filter(|summary: &&WorkSummary| project.as_ref().is_none_or(|project: &String| summary.get_project() == project))
+                .collect();
+            Some(resolve_list(field, &summaries, |summary: &&WorkSummary, fields| resolve_summary(summary, fields))?)
+        },
+        _ => None,
+    }));
+}
+
+fn resolve_block(day: &Day, ind: usize, block: &TimeBlock, fields: &Vec<GraphqlField>) -> Result<JsonValue, String> {
+    let task_summary: Option<&WorkSummary> = get_task_summary(day, block);
+    return resolve_object("Block", fields, |field: &GraphqlField| Ok(match field.name.as_str() {
+        "task" => Some(scalar(field, JsonValue::String(block.get_task_name()))?),
+        "start" => Some(scalar(field, format_time(&block.get_start()))?),
+        "end" => Some(scalar(field, block.get_end().map(|end: Dt| format_time(&end)).unwrap_or(JsonValue::Null))?),
+        "lengthSecs" => Some(scalar(field, block.get_length_secs().map(JsonValue::Number).unwrap_or(JsonValue::Null))?),
+        "isBreak" => Some(scalar(field, JsonValue::Bool(day.is_break(ind)))?),
+        "billable" => Some(scalar(field, JsonValue::Bool(block.is_billable()))?),
+        "category" => Some(scalar(field, task_summary.map(|x: &WorkSummary| JsonValue::String(x.get_category().clone())).unwrap_or(JsonValue::Null))?),
+        "project" => Some(scalar(field, task_summary.map(|x: &WorkSummary| JsonValue::String(x.get_project().clone())).unwrap_or(JsonValue::Null))?),
+        "notes" => {
+            let notes: Vec<&Note> = block.get_notes().iter().collect();
+            Some(resolve_list(field, &notes, |note: &&Note, fields| resolve_note(&block.get_task_name(), note, fields))?)
+        },
+        "annotations" => Some(scalar(field, JsonValue::Array(
+            block.get_annotations().iter().map(|x: &String| JsonValue::String(x.clone())).collect()))?),
+        _ => None,
+    }));
+}
+
+fn resolve_note(task: &str, note: &Note, fields: &Vec<GraphqlField>) -> Result<JsonValue, String> {
+    return resolve_object("Note", fields, |field: &GraphqlField| Ok(match field.name.as_str() {
+        "time" => Some(scalar(field, format_time(&note.get_time()))?),
+        "text" => Some(scalar(field, JsonValue::String(note.get_msg()))?),
+        "task" => Some(scalar(field, JsonValue::String(task.to_string()))?),
+        _ => None,
+    }));
+}
+
+fn resolve_summary(summary: &WorkSummary, fields: &Vec<GraphqlField>) -> Result<JsonValue, String> {
+    return resolve_object("Summary", fields, |field: &GraphqlField| Ok(match field.name.as_str() {
+        "task" => Some(scalar(field, JsonValue::String(summary.get_task().clone()))?),
+        "category" => Some(scalar(field, JsonValue::String(summary.get_category().clone()))?),
+        "project" => Some(scalar(field, JsonValue::String(summary.get_project().clone()))?),
+        "summary" => Some(scalar(field, JsonValue::String(summary.get_summary().clone()))?),
+        "unfinished" => Some(scalar(field, JsonValue::Bool(summary.is_unfinished()))?),
+        _ => None,
+    }));
+}
+
+fn get_task_summary<'a>(day: &'a Day, block: &TimeBlock) -> Option<&'a WorkSummary> {
+    return day.summaries.iter().find(|summary: &&WorkSummary| *summary.get_task() == block.get_task_name());
+}
+
+fn format_time(time: &Dt) -> JsonValue {
+    return JsonValue::String(time.as_dt().to_rfc3339());
+}
+
+/// The asked-for fields of an object, found by `resolve`, which gives `None` for fields the
+/// type doesn't have.
+fn resolve_object<F>(type_name: &str, fields: &Vec<GraphqlField>, resolve: F) -> Result<JsonValue, String>
+where F: Fn(&GraphqlField) -> Result<Option<JsonValue>, String> {
+    let mut values: Vec<(String, JsonValue)> = Vec::new();
+    for field in fields {
+        let value: JsonValue = match field.name.as_str() {
+            "__typename" => JsonValue::String(type_name.to_string()),
+            _ => resolve(field)?.ok_or(format!("Cannot query field '{}' on type '{}'", field.name, type_name))?,
+        };
+        values.push((field.key.clone(), value));
+    }
+    return Ok(JsonValue::Object(values));
+}
+
+/// An object field, which has to ask for some of the object's fields.
+fn resolve_fields<F>(field: &GraphqlField, resolve: F) -> Result<JsonValue, String>
+where F: Fn(&Vec<GraphqlField>) -> Result<JsonValue, String> {
+    if field.selections.is_empty() {
+        return Err(format!("Field '{}' needs a selection of subfields, like '{} {{ ... }}'", field.name, field.name));
+    }
+    return resolve(&field.selections);
+}
+
+fn resolve_list<T, F>(field: &GraphqlField, items: &Vec<T>, resolve: F) -> Result<JsonValue, String>
+where F: Fn(&T, &Vec<GraphqlField>) -> Result<JsonValue, String> {
+    return resolve_fields(field, |fields: &Vec<GraphqlField>| items.iter()
+        .map(|item: &T| resolve(item, fields))
+        .collect::<Result<Vec<JsonValue>, String>>()
+        .map(JsonValue::Array));
+}
+
+fn scalar(field: &GraphqlField, value: JsonValue) -> Result<JsonValue, String> {
+    if !field.selections.is_empty() {
+        return Err(format!("Field '{}' is a scalar and has no subfields", field.name));
+    }
+    return Ok(value);
+}
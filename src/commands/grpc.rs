@@ -0,0 +1,161 @@
+use std::process::exit;
+
+use crate::utils::clock::SharedClock;
+#[cfg(feature = "grpc")]
+use std::net::TcpListener;
+#[cfg(feature = "grpc")]
+use std::thread;
+#[cfg(feature = "grpc")]
+use chrono::prelude::{DateTime, Local};
+#[cfg(feature = "grpc")]
+use crate::commands::serve::{WRITE_METHODS, handle_request};
+#[cfg(feature = "grpc")]
+use crate::utils::access::{Scope, check_access};
+#[cfg(feature = "grpc")]
+use crate::utils::http2::{Http2Request, Http2Response, serve_http2_connection};
+#[cfg(feature = "grpc")]
+use crate::utils::json::{JsonValue, parse_json};
+#[cfg(feature = "grpc")]
+use crate::utils::protobuf::ProtoMessage;
+
+#[cfg(feature = "grpc")]
+const SERVICE_PATH: &str = "/punch.v1.Punch/";
+/// The RPCs of `proto/punch.proto`, with the requests of `punch serve` they make.
+#[cfg(feature = "grpc")]
+const RPC_METHODS: [(&str, &str); 12] = [
+    ("Status", "status"), ("GetDay", "day"), ("Report", "report"), ("Query", "graphql"),
+    ("In", "in"), ("Out", "out"), ("Pause", "pause"), ("Resume", "resume"), ("Task", "task"),
+    ("UpdateTask", "update-task"), ("Note", "note"), ("AddSummary", "add-summary"),
+];
+#[cfg(feature = "grpc")]
+const INVALID_ARGUMENT: u32 = 3;
+#[cfg(feature = "grpc")]
+const PERMISSION_DENIED: u32 = 7;
+#[cfg(feature = "grpc")]
+const UNIMPLEMENTED: u32 = 12;
+#[cfg(feature = "grpc")]
+const UNAUTHENTICATED: u32 = 16;
+
+/// Serves the `punch.v1.Punch` service of `proto/punch.proto` over cleartext HTTP/2. Each RPC
+/// goes through the same requests as the socket and HTTP APIs, and replies with their JSON.
+#[cfg(feature = "grpc")]
+pub fn serve_grpc(address: &String, clock: SharedClock) {
+    let listener: TcpListener = TcpListener::bind(address).unwrap_or_else(|err| {
+        eprintln!("Couldn't listen on '{}': {}", address, err);
+        exit(1);
+    });
+    println!("Serving gRPC on {}", address);
+    for stream in listener.incoming().map_while(Result::ok) {
+        let connection_clock: SharedClock = clock.clone();
+        thread::spawn(move || {
+            let result: Result<(), String> = serve_http2_connection(
+                stream, |request: Http2Request| get_grpc_response(&request, &connection_clock.now()));
+            if let Err(msg) = result {
+                eprintln!("gRPC connection failed: {}", msg);
+            }
+        });
+    }
+}
+
+#[cfg(not(feature = "grpc"))]
+pub fn serve_grpc(_address: &String, _clock: SharedClock) {
+    eprintln!("'punch serve --grpc' needs punch to be built with the 'grpc' feature: cargo build --release --features grpc");
+    exit(1);
+}
+
+#[cfg(feature = "grpc")]
+fn get_grpc_response(request: &Http2Request, now: &DateTime<Local>) -> Http2Response {
+    let (body, status, msg): (Vec<u8>, u32, String) = match get_reply(request, now) {
+        Ok(result) => {
+            let mut reply: ProtoMessage = ProtoMessage::default();
+            reply.add_string(1, &result.as_string());
+            (frame_message(&reply.encode()), 0, String::new())
+        },
+        Err((status, msg)) => (Vec::new(), status, msg),
+    };
+    return Http2Response {
+        headers: vec![(":status".to_string(), "200".to_string()), ("content-type".to_string(), "application/grpc".to_string())],
+        body: body,
+        trailers: vec![("grpc-status".to_string(), status.to_string()), ("grpc-message".to_string(), encode_grpc_message(&msg))],
+    };
+}
+
+#[cfg(feature = "grpc")]
+fn get_reply(request: &Http2Request, now: &DateTime<Local>) -> Result<JsonValue, (u32, String)> {
+    let method: &str = request.path.strip_prefix(SERVICE_PATH)
+        .and_then(|rpc: &str| RPC_METHODS.iter().find(|(name, _)| *name == rpc))
+        .map(|(_, method)| *method)
+        .ok_or((UNIMPLEMENTED, format!("Unknown method '{}'", request.path)))?;
+    let token: Option<String> = request.get_header("authorization")
+        .and_then(|x: &String| x.strip_prefix("Bearer "))
+        .map(|x: &str| x.trim().to_string());
+    let scope: Scope = if WRITE_METHODS.contains(&method) {Scope::Write} else {Scope::Read};
+    check_access(token.as_ref(), scope).map_err(|(status, msg): (u16, String)| match status {
+        401 => (UNAUTHENTICATED, msg),
+        _ => (PERMISSION_DENIED, msg),
+    })?;
+    let message: ProtoMessage = read_message(&request.body)
+        .and_then(|bytes: &[u8]| ProtoMessage::decode(bytes))
+        .map_err(|msg: String| (INVALID_ARGUMENT, msg))?;
+    let get_string = |field_number: u32| message.get_string(field_number).map_err(|msg: String| (INVALID_ARGUMENT, msg));
+    let mut params: Vec<(String, JsonValue)> = Vec::new();
+    match method {
+        "status" => (),
+        "day" => params.push(("date".to_string(), JsonValue::String(get_string(1)?))),
+        "report" => {
+            for (field_number, key) in [(1, "period"), (2, "from"), (3, "to")] {
+                params.push((key.to_string(), JsonValue::String(get_string(field_number)?)));
+            }
+        },
+        "graphql" => {
+            params.push(("query".to_string(), JsonValue::String(get_string(1)?)));
+            if !get_string(2)?.is_empty() {
+                let variables: JsonValue = parse_json(&get_string(2)?)
+                    .map_err(|msg: String| (INVALID_ARGUMENT, format!("Invalid JSON in 'variables': {}", msg)))?;
+                params.push(("variables".to_string(), variables));
+            }
+        },
+        method if WRITE_METHODS.contains(&method) => {
+            let args: Vec<String> = message.get_strings(1).map_err(|msg: String| (INVALID_ARGUMENT, msg))?;
+            params.push(("args".to_string(), JsonValue::Array(args.into_iter().map(JsonValue::String).collect())));
+            params.push(("at".to_string(), JsonValue::String(get_string(2)?)));
+        },
+        _ => unreachable!("Every RPC is a request 'punch serve' takes"),
+    }
+    // proto3 can't tell an empty string from one that wasn't sent, and neither is meant.
+    params.retain(|(_, value): &(String, JsonValue)| *value != JsonValue::String(String::new()));
+    return handle_request(method, &JsonValue::Object(params), now).map_err(|msg: String| (INVALID_ARGUMENT, msg));
+}
+
+/// The message of a gRPC request body: a byte saying whether it's compressed, which isn't
+/// supported, its length in 4 bytes and then the message.
+#[cfg(feature = "grpc")]
+fn read_message(body: &[u8]) -> Result<&[u8], String> {
+    if body.is_empty() {
+        return Ok(&[]);
+    }
+    let [compressed, len_bytes @ ..] = body.get(..5).ok_or("gRPC message is cut short".to_string())? else {
+        unreachable!("Took 5 bytes");
+    };
+    if *compressed != 0 {
+        return Err("Compressed gRPC messages aren't supported".to_string());
+    }
+    let len: usize = u32::from_be_bytes(len_bytes.try_into().expect("Took 4 bytes")) as usize;
+    return body.get(5..5 + len).ok_or("gRPC message is cut short".to_string());
+}
+
+#[cfg(feature = "grpc")]
+fn frame_message(message: &[u8]) -> Vec<u8> {
+    return [&[0], &(message.len() as u32).to_be_bytes()[..], message].concat();
+}
+
+/// Percent-encodes what `grpc-message` can't have as it is, as the gRPC spec asks.
+#[cfg(feature = "grpc")]
+fn encode_grpc_message(msg: &str) -> String {
+    return msg.bytes()
+        .map(|byte: u8| match byte {
+            b' '..=b'~' if byte != b'%' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect();
+}
@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+use std::process::exit;
+use chrono::prelude::{DateTime, Datelike, Local, NaiveDate};
+use chrono::Duration;
+
+use crate::utils::aggregate::{format_secs, get_done_secs_by_date};
+use crate::utils::args::{get_flag_value, get_positional_args};
+use crate::utils::index::get_summaries_in_range;
+use crate::utils::color::{GREEN, colors_enabled, paint};
+use crate::utils::config::get_config;
+use crate::utils::period::DateRange;
+use crate::utils::report::{Report, ReportSection, ReportValue};
+use crate::commands::report::print_report;
+
+const EMPTY_CELL: &str = "·";
+/// Cells for less than a quarter, half and three quarters of a day done, and then more.
+const LEVEL_CELLS: [&str; 4] = ["░", "▒", "▓", "█"];
+const WEEKDAY_LABELS: [&str; 7] = ["Mon", "   ", "Wed", "   ", "Fri", "   ", "Sun"];
+
+pub fn heatmap(now: &DateTime<Local>, other_args: Vec<String>) {
+    let positional: Vec<String> = get_positional_args(&other_args, &["--format", "--section", "--output"]);
+    let year: i32 = match positional.first() {
+        Some(year_str) => year_str.parse().unwrap_or_else(|_| {
+            eprintln!("Expected a year like '2024'! Given: '{}'", year_str);
+            exit(1);
+        }),
+        None => now.year(),
+    };
+    let range: DateRange = DateRange::new(
+        NaiveDate::from_ymd_opt(year, 1, 1).expect("The first of January always exists"),
+        NaiveDate::from_ymd_opt(year, 12, 31).expect("The last of December always exists"),
+    ).expect("January comes before December");
+    let done_by_date: HashMap<NaiveDate, i64> = get_done_secs_by_date(&get_summaries_in_range(&range, now));
+    if get_flag_value(&other_args, "--format").is_some() {
+        let mut report: Report = Report::new(format!("Daily hours for {}", year));
+        let mut days_section: ReportSection = ReportSection::new("days", &["date", "done"]);
+        for date in range.dates() {
+            if let Some(secs) = done_by_date.get(&date) {
+                days_section.add_row(vec![date.format("%Y-%m-%d").to_string().into(), ReportValue::Secs(*secs)]);
+            }
+        }
+        report.add_section(days_section);
+        print_report(report, &other_args, now);
+        return;
+    }
+    let use_color: bool = colors_enabled(&other_args);
+    let day_secs: i64 = (get_config().day_in_minutes() * 60).max(1);
+
+    // Columns are weeks starting on Monday, so the grid starts on the Monday before new year.
+    let grid_start: NaiveDate = range.from - Duration::days(range.from.weekday().num_days_from_monday() as i64);
+    let num_weeks: i64 = (range.to - grid_start).num_days() / 7 + 1;
+
+    let mut month_row: String = String::new();
+    let mut last_month: u32 = 0;
+    for week in 0..num_weeks {
+        let week_end: NaiveDate = grid_start + Duration::days(week * 7 + 6);
+        let column: usize = 4 + week as usize;
+        if week_end.month() != last_month && week_end.year() == year && month_row.len() <= column {
+            last_month = week_end.month();
+            month_row += &" ".repeat(column - month_row.len());
+            month_row += &week_end.format("%b").to_string();
+        }
+    }
+    println!("{}", year);
+    println!("{}", month_row);
+    for weekday in 0..7 {
+        let mut row: String = format!("{} ", WEEKDAY_LABELS[weekday as usize]);
+        for week in 0..num_weeks {
+            let date: NaiveDate = grid_start + Duration::days(week * 7 + weekday);
+            row += &match (range.contains(&date), done_by_date.get(&date)) {
+                (false, _) => " ".to_string(),
+                (true, None) => EMPTY_CELL.to_string(),
+                (true, Some(secs)) => paint(get_cell(*secs, day_secs), GREEN, use_color),
+            };
+        }
+        println!("{}", row);
+    }
+
+    let total: i64 = done_by_date.values().sum();
+    println!(
+        "Less {} {} More    {} days worked, {} in total",
+        EMPTY_CELL, LEVEL_CELLS.join(" "), done_by_date.len(), format_secs(total));
+}
+
+/// The cell for a day, scaled to the length of a day in the config, so an 8 hour day steps
+/// up every 2 hours and a 4 hour one every hour.
+fn get_cell(done_secs: i64, day_secs: i64) -> &'static str {
+    let level: usize = ((done_secs.max(0) * LEVEL_CELLS.len() as i64 / day_secs) as usize).min(LEVEL_CELLS.len() - 1);
+    return LEVEL_CELLS[level];
+}
@@ -0,0 +1,171 @@
+use std::process::exit;
+use chrono::prelude::{DateTime, Datelike, Local, NaiveDate, Weekday};
+use chrono::Duration;
+
+use crate::commands::core::create_day;
+use crate::units::day::{Day, read_day_for_date, write_day};
+use crate::utils::aggregate::format_secs;
+use crate::utils::args::{get_flag_value, get_flag_values, get_positional_args, has_flag};
+use crate::utils::clock::parse_time_on;
+use crate::utils::config::{Config, get_config};
+use crate::utils::state::{State, get_state, update_state};
+use crate::utils::holidays::Holidays;
+use crate::utils::ledger::check_week_open;
+use crate::utils::period::{DateRange, parse_date};
+
+/// `punch add-day <date> --in 09:00 --out 17:30 [--break 12:00-12:30]... [--task x]`: records
+/// a whole day that's already over in one go, e.g. from before punch was used, and adds it to
+/// minutes behind like a punch out would.
+pub fn add_day(now: &DateTime<Local>, other_args: Vec<String>) {
+    let positional_args: Vec<String> = get_positional_args(&other_args, &["--in", "--out", "--break", "--task"]);
+    let date: NaiveDate = match positional_args.as_slice() {
+        [date_str] => parse_date(date_str).unwrap_or_else(|msg: String| {
+            eprintln!("{}", msg);
+            exit(1);
+        }),
+        _ => {
+            eprintln!("Usage: punch add-day <date> --in 09:00 --out 17:30 [--break 12:00-12:30] [--task <task>]");
+            exit(1);
+        },
+    };
+    let (Some(in_str), Some(out_str)) = (get_flag_value(&other_args, "--in"), get_flag_value(&other_args, "--out")) else {
+        eprintln!("'punch add-day' needs both '--in' and '--out', e.g. '--in 09:00 --out 17:30'");
+        exit(1);
+    };
+    let config: Config = get_config();
+    let task: String = get_flag_value(&other_args, "--task").unwrap_or(config.get_default_punch_in_task().to_string());
+    let day: Day = build_past_day(now, &date, &in_str, &out_str, &get_flag_values(&other_args, "--break"), task, &config)
+        .unwrap_or_else(|msg: String| {
+            eprintln!("{}", msg);
+            exit(1);
+        });
+    write_day(&day);
+    let time_left_secs: i64 = day.get_time_left_secs().expect("Day has ended");
+    let mut state: State = get_state();
+    state.update_minutes_behind(time_left_secs / 60);
+    update_state(&state);
+    println!(
+        "Added {} from {} to {}, with {} done. Time behind changed by {}.",
+        date, in_str, out_str, format_secs(day.get_time_done_secs().expect("Day has ended")), format_secs(time_left_secs / 60 * 60));
+}
+
+/// `punch backfill --from <date> [--to <date>] --in 09:00 --out 17:00 [--weekdays-only]`: adds
+/// the same day, like `punch add-day` would, on every date in the range that has nothing
+/// recorded, isn't a holiday, from the config's `holidays` or `holiday_calendar`, and falls
+/// on one of the `work_days`. `--to` defaults to yesterday. Nothing is written unless every
+/// day can be added.
+pub fn backfill(now: &DateTime<Local>, other_args: Vec<String>) {
+    let range: DateRange = get_backfill_range(now, &other_args).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+    let (Some(in_str), Some(out_str)) = (get_flag_value(&other_args, "--in"), get_flag_value(&other_args, "--out")) else {
+        eprintln!("'punch backfill' needs both '--in' and '--out', e.g. '--in 09:00 --out 17:00'");
+        exit(1);
+    };
+    let config: Config = get_config();
+    let holidays: Holidays = Holidays::load(&config).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+    let task: String = get_flag_value(&other_args, "--task").unwrap_or(config.get_default_punch_in_task().to_string());
+    let break_strs: Vec<String> = get_flag_values(&other_args, "--break");
+    let weekdays_only: bool = has_flag(&other_args, "--weekdays-only");
+
+    let mut days: Vec<Day> = Vec::new();
+    let mut num_recorded: usize = 0;
+    let mut num_skipped: usize = 0;
+    for date in range.dates() {
+        if holidays.is_holiday(&date) || !config.is_work_day(&date) || (weekdays_only && matches!(date.weekday(), Weekday::Sat | Weekday::Sun)) {
+            num_skipped += 1;
+            continue;
+        }
+        if read_day_for_date(&date).is_ok() {
+            num_recorded += 1;
+            continue;
+        }
+        match check_week_open(&date).and_then(|_| build_past_day(now, &date, &in_str, &out_str, &break_strs, task.clone(), &config)) {
+            Ok(day) => days.push(day),
+            Err(msg) => {
+                eprintln!("Couldn't add {}, so nothing was added: {}", date, msg);
+                exit(1);
+            },
+        }
+    }
+
+    let mut time_left_mins: i64 = 0;
+    for day in &days {
+        write_day(day);
+        time_left_mins += day.get_time_left_secs().expect("Day has ended") / 60;
+    }
+    let mut state: State = get_state();
+    state.update_minutes_behind(time_left_mins);
+    update_state(&state);
+    println!(
+        "Added {} days from {}. Skipped {} already recorded and {} days off. Time behind changed by {}.",
+        days.len(), range.as_string(), num_recorded, num_skipped, format_secs(time_left_mins * 60));
+}
+
+fn get_backfill_range(now: &DateTime<Local>, other_args: &Vec<String>) -> Result<DateRange, String> {
+    let Some(from_str) = get_flag_value(other_args, "--from") else {
+        return Err("'punch backfill' needs the first date to fill in, e.g. '--from 2024-01-01'".to_string());
+    };
+    let to: NaiveDate = match get_flag_value(other_args, "--to") {
+        Some(to_str) => parse_date(&to_str)?,
+        None => now.date_naive() - Duration::days(1),
+    };
+    return DateRange::new(parse_date(&from_str)?, to);
+}
+
+/// A finished day on `date` working on `task` from `in_str` to `out_str`, with a break for each
+/// span like "12:00-12:30". Fails if there's already a day on that date, or the times don't fit.
+fn build_past_day(
+    now: &DateTime<Local>,
+    date: &NaiveDate,
+    in_str: &str,
+    out_str: &str,
+    break_strs: &Vec<String>,
+    task: String,
+    config: &Config,
+) -> Result<Day, String> {
+    if read_day_for_date(date).is_ok() {
+        return Err(format!("There's already a day recorded for {}. Change it with 'punch edit' instead.", date));
+    }
+    let start: DateTime<Local> = parse_time_on(in_str, date)?;
+    let end: DateTime<Local> = parse_time_on(out_str, date)?;
+    if end <= start {
+        return Err(format!("The punch out ({}) should be after the punch in ({})!", out_str, in_str));
+    }
+    if end > *now {
+        return Err(format!("{} {} hasn't happened yet! Only days that are over can be added.", date, out_str));
+    }
+    let mut breaks: Vec<(DateTime<Local>, DateTime<Local>)> = Vec::new();
+    for break_str in break_strs {
+        let Some((break_start_str, break_end_str)) = break_str.split_once('-') else {
+            return Err(format!("Expected a break like '12:00-12:30'! Given: '{}'", break_str));
+        };
+        breaks.push((parse_time_on(break_start_str, date)?, parse_time_on(break_end_str, date)?));
+    }
+    breaks.sort();
+    let mut last_end: DateTime<Local> = start;
+    for (break_start, break_end) in &breaks {
+        if *break_start <= last_end || break_end <= break_start || *break_end >= end {
+            return Err(format!(
+                "Breaks should be inside the day, and not touch each other or the punch in and out! Check the one at {}.",
+                break_start.format("%H:%M")));
+        }
+        last_end = *break_end;
+    }
+
+    let (mut day, _): (Day, String) = create_day(&start, task.clone(), config);
+    day.paid_break_minutes = config.paid_break_minutes().unwrap_or(0);
+    for (break_start, break_end) in &breaks {
+        day.start_break_at(config.get_default_break_task().to_string(), break_start)?;
+        day.start_new_block(task.clone(), break_end)?;
+    }
+    day.end_day_at(&end)?;
+    if let Ok(Some(expected_start)) = config.expected_start() {
+        day.record_lateness(&expected_start);
+    }
+    return Ok(day);
+}
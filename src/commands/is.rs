@@ -0,0 +1,21 @@
+use std::process::exit;
+use chrono::prelude::{DateTime, Local};
+
+use crate::units::day::{Day, get_current_day};
+
+/// `punch is in|out|on-break`: answers only with the exit code, 0 for yes and 1 for no, so
+/// scripts can branch on it without parsing any output. Anything else exits with 2.
+pub fn is(now: &DateTime<Local>, other_args: Vec<String>) {
+    let day: Option<Day> = get_current_day(now).ok();
+    let is_in: bool = day.as_ref().is_some_and(|day: &Day| !day.has_ended());
+    let answer: bool = match other_args.first().map(|x: &String| x.as_str()) {
+        Some("in") if other_args.len() == 1 => is_in,
+        Some("out") if other_args.len() == 1 => !is_in,
+        Some("on-break") if other_args.len() == 1 => is_in && day.is_some_and(|day: Day| day.on_break),
+        _ => {
+            eprintln!("Usage: punch is in|out|on-break");
+            exit(2);
+        },
+    };
+    exit(if answer {0} else {1});
+}
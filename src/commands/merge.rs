@@ -0,0 +1,176 @@
+use std::collections::HashSet;
+use std::process::exit;
+use chrono::prelude::{DateTime, Local, NaiveDate};
+
+use crate::units::components::{MoodEntry, TimeBlock};
+use crate::units::interval::Dt;
+use crate::units::day::{Day, list_day_records_for_date, write_day};
+use crate::utils::aggregate::format_secs;
+use crate::utils::args::{get_positional_args, has_flag};
+use crate::utils::config::get_config;
+use crate::utils::state::{State, get_state, update_state};
+use crate::utils::file_io::{FromString, delete_file, read_file};
+use crate::utils::period::parse_date;
+use crate::utils::prompt::{choose, confirm};
+use crate::utils::work_summary::WorkSummary;
+
+/// Combines every record of a date (the day file and copies of it, e.g. from sync conflicts)
+/// into the one day file. Overlapping blocks and gaps between them are resolved by asking,
+/// or with the first option for each when `--yes` is given.
+pub fn merge(other_args: Vec<String>, force: bool) {
+    let positional: Vec<String> = get_positional_args(&other_args, &[]);
+    let date: NaiveDate = match positional.as_slice() {
+        [date_str] => parse_date(date_str).unwrap_or_else(|msg: String| {
+            eprintln!("{}", msg);
+            exit(1);
+        }),
+        _ => {
+            eprintln!("'punch merge' needs the date of the records to merge, e.g. 'punch merge 2024-01-31'");
+            exit(1);
+        },
+    };
+    let interactive: bool = !has_flag(&other_args, "--yes");
+
+    let paths: Vec<String> = list_day_records_for_date(&date);
+    if paths.len() < 2 {
+        println!("There's {} record for {}, so there's nothing to merge.", if paths.is_empty() {"no"} else {"only one"}, date);
+        return;
+    }
+    let mut records: Vec<Day> = Vec::new();
+    for path in &paths {
+        let day_result: Result<Day, String> = read_file(path)
+            .map_err(|err| err.to_string())
+            .and_then(|yaml_str: String| Day::try_from_string(&yaml_str).map_err(|err| err.to_string()));
+        match day_result {
+            Ok(day) => records.push(day),
+            Err(msg) => {
+                eprintln!("Couldn't read '{}': {}", path, msg);
+                exit(1);
+            },
+        }
+    }
+    for record in &records {
+        if let Err(msg) = record.check_editable(force) {
+            eprintln!("{}", msg);
+            exit(1);
+        }
+    }
+    println!("Merging {} records for {}.", records.len(), date);
+
+    let merged: Day = merge_records(&records, interactive);
+    println!("\nThe merged day:");
+    for (ind, block) in merged.timeblocks.iter().enumerate() {
+        println!("\t{}{}", block.describe(), if merged.is_break(ind) {" (break)"} else {""});
+    }
+    if interactive && !confirm("Replace the records with this day?") {
+        println!("Nothing was changed.");
+        return;
+    }
+
+    // Each finished record counted towards minutes behind when it was punched out.
+    let counted_mins: i64 = records.iter()
+        .filter_map(|day: &Day| day.get_time_left_secs())
+        .map(|secs: i64| secs / 60)
+        .sum();
+    let delta_mins: i64 = merged.get_time_left_secs().map_or(0, |secs: i64| secs / 60) - counted_mins;
+
+    write_day(&merged);
+    for path in &paths[1..] {
+        if let Err(err) = delete_file(path) {
+            eprintln!("Warning: Couldn't remove '{}': {}", path, err);
+        }
+    }
+    println!("Merged {} records into the day for {}.", paths.len(), date);
+    if delta_mins != 0 {
+        let mut state: State = get_state();
+        state.update_minutes_behind(delta_mins);
+        update_state(&state);
+        println!("Time behind changed by {}.", format_secs(delta_mins * 60));
+    }
+}
+
+fn merge_records(records: &Vec<Day>, interactive: bool) -> Day {
+    let mut blocks: Vec<(TimeBlock, bool)> = Vec::new();
+    for day in records {
+        for (ind, block) in day.timeblocks.iter().enumerate() {
+            let is_duplicate: bool = blocks.iter().any(|(other, _)| {
+                other.get_task_name() == block.get_task_name()
+                    && other.get_start_as_str() == block.get_start_as_str()
+                    && other.get_end_as_str() == block.get_end_as_str()
+            });
+            if !is_duplicate {
+                blocks.push((block.clone(), day.is_break(ind)));
+            }
+        }
+    }
+    blocks.sort_by_key(|(block, _)| block.get_start().as_dt());
+
+    let mut merged: Vec<(TimeBlock, bool)> = Vec::new();
+    for (mut block, is_break) in blocks {
+        let Some((previous, _)) = merged.last_mut() else {
+            merged.push((block, is_break));
+            continue;
+        };
+        let start: DateTime<Local> = block.get_start().as_dt();
+        let previous_end: DateTime<Local> = match previous.get_end() {
+            Some(end) => end.as_dt(),
+            None => {
+                // Only the last block of a day is open, so a later block means it ended there.
+                previous.end_at(&start);
+                start
+            },
+        };
+        if previous_end > start {
+            let question: String = format!(
+                "These blocks overlap:\n  1: {}\n  2: {}", previous.describe(), block.describe());
+            let options: [(&str, &str); 2] = [("1", "Keep 1 and shorten 2"), ("2", "Keep 2 and shorten 1")];
+            let keep_first: bool = !interactive || choose(&question, &options) == "1";
+            if keep_first {
+                match block.get_end() {
+                    Some(end) if end.as_dt() <= previous_end => continue,
+                    _ => block.start_at(&previous_end),
+                }
+            }
+            else if previous.get_start().as_dt() >= start {
+                merged.pop();
+            }
+            else {
+                previous.end_at(&start);
+            }
+        }
+        else if previous_end < start {
+            let question: String = format!(
+                "Nothing was recorded between {} and {}.", previous_end.format("%H:%M"), start.format("%H:%M"));
+            let options: [(&str, &str); 2] = [
+                ("b", "Count it as a break"),
+                ("w", "Count it as work on the block before it"),
+            ];
+            if !interactive || choose(&question, &options) == "b" {
+                let mut gap: TimeBlock = TimeBlock::new(get_config().get_default_break_task().to_string(), &previous_end);
+                gap.end_at(&start);
+                merged.push((gap, true));
+            }
+            else {
+                previous.end_at(&start);
+            }
+        }
+        merged.push((block, is_break));
+    }
+
+    let mut seen_summaries: HashSet<String> = HashSet::new();
+    let summaries: Vec<WorkSummary> = records.iter()
+        .flat_map(|day: &Day| day.summaries.clone())
+        .filter(|summary: &WorkSummary| seen_summaries.insert(summary.as_short_string()))
+        .collect();
+    let mut merged_day: Day = Day::from_blocks(merged, records[0].get_time_to_do(), summaries);
+    merged_day.lateness_secs = records[0].lateness_secs
+        .map(|secs: i64| secs + (merged_day.get_day_start().as_dt() - records[0].get_day_start().as_dt()).num_seconds());
+    merged_day.moods = records.iter().flat_map(|day: &Day| day.moods.clone()).collect();
+    merged_day.moods.sort_by_key(|mood: &MoodEntry| mood.get_time().as_dt());
+    merged_day.plans = records.iter().flat_map(|day: &Day| day.plans.clone()).collect();
+    merged_day.blockers = records.iter().flat_map(|day: &Day| day.blockers.clone()).collect();
+    merged_day.paid_break_minutes = records[0].paid_break_minutes;
+    merged_day.break_reminders = records.iter().flat_map(|day: &Day| day.break_reminders.clone()).collect();
+    merged_day.submitted = records.iter().filter_map(|day: &Day| day.submitted).min_by_key(|submitted: &Dt| submitted.as_dt());
+    return merged_day;
+}
@@ -0,0 +1,35 @@
+use std::process::exit;
+
+use crate::units::day::{Day, write_day};
+use crate::utils::args::get_positional_args;
+
+/// Keeps free-form key-values on today, like `punch meta set location office`, which reports
+/// and exports can pick days out by with `--where location=office`.
+pub fn meta(mut day: Day, other_args: Vec<String>) {
+    let positional: Vec<String> = get_positional_args(&other_args, &[]);
+    match positional.iter().map(|x: &String| x.as_str()).collect::<Vec<&str>>().as_slice() {
+        [] | ["list"] => {
+            for (key, value) in &day.meta {
+                println!("{}: {}", key, value);
+            }
+            return;
+        },
+        ["set", key, value @ ..] if !value.is_empty() => {
+            let value: String = value.join(" ");
+            println!("Set '{}' to '{}' for today.", key, value);
+            day.meta.insert(key.to_string(), value);
+        },
+        ["unset", key] => {
+            if day.meta.remove(*key).is_none() {
+                eprintln!("Today has no '{}' to unset.", key);
+                exit(1);
+            }
+            println!("Unset '{}' for today.", key);
+        },
+        _ => {
+            eprintln!("Usage: punch meta [list] | punch meta set <key> <value> | punch meta unset <key>");
+            exit(1);
+        },
+    }
+    write_day(&day);
+}
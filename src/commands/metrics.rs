@@ -0,0 +1,38 @@
+use chrono::prelude::{DateTime, Local};
+
+use crate::units::day::{Day, get_current_day};
+use crate::utils::aggregate::close_day_at;
+use crate::utils::state::{State, get_state};
+
+/// Current state in the Prometheus text exposition format.
+pub fn get_metrics_text(now: &DateTime<Local>) -> String {
+    let state: State = get_state();
+    let day: Option<Day> = get_current_day(now).ok();
+    let punched_in: bool = day.as_ref().is_some_and(|x: &Day| !x.has_ended());
+    let on_break: bool = day.as_ref().is_some_and(|x: &Day| !x.has_ended() && x.on_break);
+    let (worked, on_break_secs, left): (i64, i64, i64) = match &day {
+        Some(day) => {
+            let closed_day: Day = close_day_at(day.clone(), now);
+            (
+                closed_day.get_time_done_secs().expect("Day is closed"),
+                closed_day.get_total_break_time_secs().expect("Day is closed"),
+                closed_day.get_time_left_secs().expect("Day is closed"),
+            )
+        },
+        None => (0, 0, 0),
+    };
+
+    let metrics: [(&str, &str, i64); 6] = [
+        ("punch_punched_in", "Whether you're currently punched in (1) or not (0).", punched_in as i64),
+        ("punch_on_break", "Whether you're currently on a break (1) or not (0).", on_break as i64),
+        ("punch_seconds_worked_today", "Seconds worked in the current day.", worked),
+        ("punch_break_seconds_today", "Seconds spent on break in the current day.", on_break_secs),
+        ("punch_seconds_left_today", "Seconds left to reach the current day's target.", left),
+        ("punch_minutes_behind_total", "Minutes behind overall, across all days.", state.minutes_behind()),
+    ];
+    let mut text: String = String::new();
+    for (name, help, value) in metrics {
+        text += &format!("# HELP {} {}\n# TYPE {} gauge\n{} {}\n", name, help, name, name, value);
+    }
+    return text;
+}
@@ -1 +1,54 @@
 pub mod core;
+pub mod report;
+pub mod status;
+pub mod doctor;
+pub mod daemon;
+pub mod timeline;
+pub mod heatmap;
+pub mod batch;
+pub mod serve;
+pub mod metrics;
+pub mod amend;
+pub mod delete;
+pub mod history;
+pub mod demo;
+pub mod adjust;
+pub mod compliance;
+pub mod target;
+pub mod week_plan;
+pub mod forecast;
+pub mod merge;
+pub mod blocks;
+pub mod budget;
+pub mod project;
+pub mod rename;
+pub mod mood;
+pub mod meta;
+pub mod oncall;
+pub mod expense;
+pub mod template;
+pub mod carry;
+pub mod todo;
+pub mod reminders;
+pub mod timer;
+pub mod is;
+pub mod standup;
+pub mod export;
+pub mod obsidian;
+pub mod calendar;
+pub mod suggest;
+pub mod prune;
+pub mod team;
+pub mod submit;
+pub mod verify;
+pub mod tray;
+pub mod service;
+pub mod away;
+pub mod self_update;
+pub mod setup;
+pub mod config;
+pub mod close_week;
+pub mod graphql;
+pub mod grpc;
+pub mod event_log;
+pub mod compress;
@@ -0,0 +1,24 @@
+use std::process::exit;
+use chrono::prelude::{DateTime, Local};
+
+use crate::units::day::{Day, write_day};
+use crate::utils::args::get_positional_args;
+
+/// Records how you're feeling right now, e.g. `punch mood 4 "good momentum"`.
+pub fn mood(now: &DateTime<Local>, mut day: Day, other_args: Vec<String>) {
+    let positional: Vec<String> = get_positional_args(&other_args, &[]);
+    let score: u8 = match positional.first().map(|score_str: &String| score_str.parse::<u8>()) {
+        Some(Ok(score)) if (1..=5).contains(&score) => score,
+        _ => {
+            eprintln!("'punch mood' needs a score from 1 to 5, e.g. 'punch mood 4 \"good momentum\"'");
+            exit(1);
+        },
+    };
+    let note: Option<String> = match positional[1..].join(" ") {
+        text if text.trim().is_empty() => None,
+        text => Some(text),
+    };
+    day.add_mood(now, score, note);
+    write_day(&day);
+    println!("Mood {}/5 recorded at {}.", score, now.format("%H:%M"));
+}
@@ -0,0 +1,98 @@
+use std::path::PathBuf;
+use std::process::exit;
+
+use crate::units::day::Day;
+use crate::units::interval::DATE_FMT;
+use crate::utils::aggregate::format_secs;
+use crate::utils::args::get_flag_value;
+
+const START_MARKER: &str = "<!-- punch:start -->";
+const END_MARKER: &str = "<!-- punch:end -->";
+
+/// Writes a time tracking section into the daily note of each day, in `--vault` or its
+/// `--folder` for daily notes. The section sits between markers, so running this again
+/// replaces it instead of adding another, and the rest of the note is left alone.
+pub fn export_to_obsidian(days: &Vec<Day>, other_args: &Vec<String>) {
+    let vault: PathBuf = match get_flag_value(other_args, "--vault") {
+        Some(vault) => PathBuf::from(vault),
+        None => {
+            eprintln!("'punch export obsidian' needs the path to your vault, e.g. '--vault ~/notes'");
+            exit(1);
+        },
+    };
+    if !vault.is_dir() {
+        eprintln!("Can't find the vault at '{}'", vault.display());
+        exit(1);
+    }
+    let notes_dir: PathBuf = match get_flag_value(other_args, "--folder") {
+        Some(folder) => vault.join(folder),
+        None => vault,
+    };
+    if let Err(err) = std::fs::create_dir_all(&notes_dir) {
+        eprintln!("Couldn't create '{}': {}", notes_dir.display(), err);
+        exit(1);
+    }
+
+    for day in days {
+        let path: PathBuf = notes_dir.join(format!("{}.md", day.get_day_start().as_dt().format(DATE_FMT)));
+        let existing: String = std::fs::read_to_string(&path).unwrap_or_default();
+        let updated: String = put_section(&existing, &build_section(day));
+        if let Err(err) = std::fs::write(&path, updated) {
+            eprintln!("Couldn't write '{}': {}", path.display(), err);
+            exit(1);
+        }
+    }
+    println!("Updated {} daily notes in '{}'.", days.len(), notes_dir.display());
+}
+
+/// Replaces the section between the markers, or adds it to the end if there isn't one yet.
+fn put_section(note: &str, section: &str) -> String {
+    if let (Some(start), Some(end)) = (note.find(START_MARKER), note.find(END_MARKER)) {
+        if start < end {
+            return format!("{}{}{}", &note[..start], section, &note[end + END_MARKER.len()..]);
+        }
+    }
+    return match note.trim_end() {
+        "" => format!("{}\n", section),
+        text => format!("{}\n\n{}\n", text, section),
+    };
+}
+
+fn build_section(day: &Day) -> String {
+    let mut lines: Vec<String> = vec![START_MARKER.to_string(), "## Time tracking".to_string(), String::new(), "### Timeline".to_string()];
+    for (ind, block) in day.timeblocks.iter().enumerate() {
+        lines.push(format!(
+            "- {} ({}){}",
+            block.describe(), format_secs(block.get_length_secs().unwrap_or(0)), if day.is_break(ind) {" *break*"} else {""}));
+    }
+    lines.push(String::new());
+    lines.push("### Totals".to_string());
+    lines.push(format!("- Worked: {}", format_secs(day.get_time_done_secs().unwrap_or(0))));
+    lines.push(format!("- Breaks: {}", format_secs(day.get_total_break_time_secs().unwrap_or(0))));
+    let task_times = day.get_task_times_secs_and_num_blocks();
+    for task in day.get_tasks_in_chronological_order() {
+        let is_break_task: bool = day.timeblocks.iter().enumerate()
+            .all(|(ind, block)| block.get_task_name() != task || day.is_break(ind));
+        if is_break_task {
+            continue;
+        }
+        lines.push(format!("- {}: {}", task, format_secs(task_times.get(&task).map(|x| x.0).unwrap_or(0))));
+    }
+
+    let notes: Vec<String> = day.timeblocks.iter()
+        .flat_map(|block| block.get_notes().iter().map(move |note| format!(
+            "- {} [{}] {}", note.get_time().as_dt().format("%H:%M"), block.get_task_name(), note.get_msg())))
+        .collect();
+    if !notes.is_empty() {
+        lines.push(String::new());
+        lines.push("### Notes".to_string());
+        lines.extend(notes);
+    }
+    if !day.meta.is_empty() {
+        lines.push(String::new());
+        lines.push("### Metadata".to_string());
+        lines.extend(day.meta.iter().map(|(key, value)| format!("- {}:: {}", key, value)));
+    }
+    lines.push(END_MARKER.to_string());
+    return lines.join("\n");
+}
@@ -0,0 +1,109 @@
+use std::process::exit;
+use chrono::prelude::{DateTime, Local, NaiveDate};
+
+use crate::units::components::TimeBlock;
+use crate::units::day::{Day, read_day_for_date};
+use crate::units::interval::{DATE_FMT, Interval};
+use crate::utils::aggregate::{close_day_at, format_secs};
+use crate::utils::args::get_positional_args;
+use crate::utils::config::get_config;
+use crate::utils::oncall::{OnCallConfig, get_day_bounds, get_oncall_secs_between, read_oncall_periods, write_oncall_periods};
+use crate::utils::period::{DateRange, resolve_range_from_args};
+use crate::utils::report::{Report, ReportSection, ReportValue};
+use crate::commands::report::print_report;
+
+/// `punch oncall start|stop` keeps track of when you're on call, apart from the day's blocks,
+/// and `punch oncall report` works out how much of it is credited. With no args, says
+/// whether you're on call now.
+pub fn oncall(now: &DateTime<Local>, other_args: Vec<String>) {
+    let positional_args: Vec<String> = get_positional_args(&other_args, &["--period", "--from", "--to", "--format", "--output"]);
+    let mut periods: Vec<Interval> = read_oncall_periods();
+    let is_on_call: bool = periods.last().is_some_and(|period: &Interval| !period.has_end());
+    match positional_args.first().map(|x: &String| x.as_str()) {
+        None => match periods.last() {
+            Some(period) if is_on_call => println!("On call since {}.", period.get_start_as_str()),
+            _ => println!("Not on call."),
+        },
+        Some("start") => {
+            if is_on_call {
+                eprintln!("Already on call since {}.", periods.last().expect("On call").get_start_as_str());
+                exit(1);
+            }
+            periods.push(Interval::new(now));
+            write_oncall_periods(&periods);
+            println!("On call from {}.", now.format("%Y-%m-%d %H:%M"));
+        },
+        Some("stop") => {
+            if !is_on_call {
+                eprintln!("Not on call, so there's nothing to stop. Start with 'punch oncall start'.");
+                exit(1);
+            }
+            let period: &mut Interval = periods.last_mut().expect("On call");
+            period.end_at(now);
+            let length_secs: i64 = period.get_length_secs().unwrap_or(0);
+            write_oncall_periods(&periods);
+            println!("Off call at {}, after {}.", now.format("%Y-%m-%d %H:%M"), format_secs(length_secs));
+        },
+        Some("report") => {
+            let range: DateRange = resolve_range_from_args(now, &other_args).unwrap_or_else(|msg: String| {
+                eprintln!("{}", msg);
+                exit(1);
+            });
+            let config: OnCallConfig = get_config().oncall().cloned().unwrap_or_default();
+            print_report(build_compensation_report(&range, &periods, &config, now), &other_args, now);
+        },
+        Some(other) => {
+            eprintln!("'{}' isn't something 'punch oncall' does. Use 'start', 'stop' or 'report'.", other);
+            exit(1);
+        },
+    }
+}
+
+/// The time on call each day, less the time worked then, weighted by that day's rate.
+fn build_compensation_report(range: &DateRange, periods: &Vec<Interval>, config: &OnCallConfig, now: &DateTime<Local>) -> Report {
+    let mut report: Report = Report::new(format!("On-call compensation for {}", range.as_string()));
+    let mut days_section: ReportSection = ReportSection::new("on_call", &["date", "on call", "worked", "rate (%)", "credited"]);
+    let (mut total_oncall_secs, mut total_worked_secs, mut total_credited_secs): (i64, i64, i64) = (0, 0, 0);
+    for date in range.dates() {
+        let (day_start, day_end): (DateTime<Local>, DateTime<Local>) = get_day_bounds(&date);
+        let oncall_secs: i64 = get_oncall_secs_between(periods, &day_start, &day_end, now);
+        if oncall_secs == 0 {
+            continue;
+        }
+        let worked_secs: i64 = get_worked_secs_on_call(&date, periods, now);
+        let rate_percent: u64 = config.get_rate_percent(&date);
+        let credited_secs: i64 = (oncall_secs - worked_secs) * rate_percent as i64 / 100;
+        total_oncall_secs += oncall_secs;
+        total_worked_secs += worked_secs;
+        total_credited_secs += credited_secs;
+        days_section.add_row(vec![
+            date.format(DATE_FMT).to_string().into(),
+            ReportValue::Secs(oncall_secs),
+            ReportValue::Secs(worked_secs),
+            ReportValue::Number(rate_percent as i64),
+            ReportValue::Secs(credited_secs),
+        ]);
+    }
+    report.add_section(days_section);
+    let mut totals_section: ReportSection = ReportSection::new("totals", &["total", "value"]);
+    totals_section.add_row(vec!["On call".into(), ReportValue::Secs(total_oncall_secs)]);
+    totals_section.add_row(vec!["Worked while on call".into(), ReportValue::Secs(total_worked_secs)]);
+    totals_section.add_row(vec!["Credited".into(), ReportValue::Secs(total_credited_secs)]);
+    report.add_section(totals_section);
+    return report;
+}
+
+/// How much of the day's work, leaving out breaks, was done while on call.
+fn get_worked_secs_on_call(date: &NaiveDate, periods: &Vec<Interval>, now: &DateTime<Local>) -> i64 {
+    let Ok(day) = read_day_for_date(date) else {
+        return 0;
+    };
+    let day: Day = close_day_at(day, now);
+    return day.timeblocks.iter().enumerate()
+        .filter(|(ind, _)| !day.is_break(*ind))
+        .map(|(_, block): (usize, &TimeBlock)| match block.get_end() {
+            Some(end) => get_oncall_secs_between(periods, &block.get_start().as_dt(), &end.as_dt(), now),
+            None => 0,
+        })
+        .sum();
+}
@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+use std::process::exit;
+use chrono::prelude::{DateTime, Local};
+
+use crate::units::day::{DayIterator, list_recorded_dates, write_day};
+use crate::utils::aggregate::get_project_secs;
+use crate::utils::args::{get_positional_args, has_flag};
+use crate::utils::config::{Config, get_config, update_config};
+use crate::utils::index::{DaySummary, get_summaries_in_range};
+use crate::utils::period::DateRange;
+use crate::utils::report::{Report, ReportSection, ReportValue};
+use crate::utils::suggestions::get_nearest_match;
+use crate::utils::work_summary::WorkSummary;
+use crate::commands::report::print_report;
+
+/// `punch project list|archive|unarchive|rename`, for managing the projects in your summaries.
+pub fn project(now: &DateTime<Local>, other_args: Vec<String>, force: bool) {
+    let positional: Vec<String> = get_positional_args(&other_args, &["--format", "--section", "--output"]);
+    let positional_strs: Vec<&str> = positional.iter().map(|x: &String| x.as_str()).collect();
+    if let ["archive" | "unarchive" | "rename", name, ..] = positional_strs.as_slice() {
+        check_known_project(now, name);
+    }
+    match positional_strs.as_slice() {
+        [] | ["list"] => list_projects(now, &other_args),
+        ["archive", name] => set_archived(name, true),
+        ["unarchive", name] => set_archived(name, false),
+        ["rename", old, new] => rename_project(old, new, force),
+        _ => {
+            eprintln!("Usage: punch project list [--names] | archive <project> | unarchive <project> | rename <old> <new>");
+            exit(1);
+        },
+    }
+}
+
+/// Every project with time recorded against it, plus those listed in the config.
+fn get_known_projects(now: &DateTime<Local>) -> BTreeMap<String, (i64, String)> {
+    let mut projects: BTreeMap<String, (i64, String)> = match list_recorded_dates().first() {
+        Some(first) => {
+            let history: DateRange = DateRange::new(*first, now.date_naive().max(*first)).expect("Range is ordered");
+            let summaries: Vec<DaySummary> = get_summaries_in_range(&history, now);
+            get_project_secs(&summaries)
+        },
+        None => BTreeMap::new(),
+    };
+    for project in get_config().projects() {
+        projects.entry(project.clone()).or_default();
+    }
+    return projects;
+}
+
+/// Stops with a suggestion when `name` isn't a known project but is close to one, as it's
+/// likely a typo. Names that aren't close to any are let through, to archive ahead of time.
+fn check_known_project(now: &DateTime<Local>, name: &str) {
+    let projects: Vec<String> = get_known_projects(now).into_keys().collect();
+    if projects.iter().any(|project: &String| project == name) {
+        return;
+    }
+    if let Some(nearest) = get_nearest_match(name, &projects) {
+        eprintln!("There's no project '{}'. Did you mean '{}'?", name, nearest);
+        exit(1);
+    }
+}
+
+/// Lists active projects, then archived ones. With `--names`, only prints the names of the
+/// active projects, one per line, for shell completion.
+fn list_projects(now: &DateTime<Local>, other_args: &Vec<String>) {
+    let config: Config = get_config();
+    let projects: BTreeMap<String, (i64, String)> = get_known_projects(now);
+    if has_flag(other_args, "--names") {
+        for name in projects.keys().filter(|name: &&String| !config.is_project_archived(name)) {
+            println!("{}", name);
+        }
+        return;
+    }
+
+    let mut report: Report = Report::new("Projects".to_string());
+    let columns: [&str; 3] = ["project", "time", "last_worked"];
+    let mut active_section: ReportSection = ReportSection::new("projects", &columns);
+    let mut archived_section: ReportSection = ReportSection::new("archived", &columns);
+    for (name, (secs, last_date)) in projects {
+        let section: &mut ReportSection = match config.is_project_archived(&name) {
+            true => &mut archived_section,
+            false => &mut active_section,
+        };
+        section.add_row(vec![name.into(), ReportValue::Secs(secs), last_date.into()]);
+    }
+    report.add_section(active_section);
+    report.add_section(archived_section);
+    print_report(report, other_args, now);
+}
+
+fn set_archived(name: &str, archived: bool) {
+    let mut config: Config = get_config();
+    let changed: bool = match archived {
+        true => config.archive_project(name.to_string()),
+        false => config.unarchive_project(&name.to_string()),
+    };
+    if !changed {
+        println!("'{}' is already {}.", name, if archived {"archived"} else {"active"});
+        return;
+    }
+    update_config(config);
+    println!("{} '{}'.", if archived {"Archived"} else {"Unarchived"}, name);
+}
+
+/// Renames a project in the summaries of every recorded day and in the config. Submitted
+/// days are left alone unless `force`.
+fn rename_project(old: &str, new: &str, force: bool) {
+    let (old, new): (String, String) = (old.to_string(), new.to_string());
+    let mut num_days: usize = 0;
+    let mut num_submitted: usize = 0;
+    for mut day in DayIterator::all() {
+        if day.check_editable(force).is_err() {
+            num_submitted += day.summaries.iter().any(|summary: &WorkSummary| summary.get_project() == &old) as usize;
+            continue;
+        }
+        let mut changed: bool = false;
+        for summary in day.summaries.iter_mut().filter(|summary: &&mut WorkSummary| summary.get_project() == &old) {
+            summary.set_project(new.clone());
+            changed = true;
+        }
+        if changed {
+            write_day(&day);
+            num_days += 1;
+        }
+    }
+    let mut config: Config = get_config();
+    config.rename_project(&old, &new);
+    update_config(config);
+    println!("Renamed project '{}' to '{}' in {} days.", old, new, num_days);
+    if num_submitted > 0 {
+        println!("Left {} submitted days alone. Pass --force to rename it in those too.", num_submitted);
+    }
+}
@@ -0,0 +1,42 @@
+use std::process::exit;
+use chrono::prelude::{DateTime, Local, NaiveDate};
+
+use crate::utils::args::{get_flag_value, has_flag};
+use crate::utils::config::get_config;
+use crate::utils::period::parse_age;
+use crate::utils::prompt::confirm;
+use crate::utils::retention::{PruneOutcome, RetentionConfig, get_dates_to_prune, prune_days_before};
+
+/// Deletes days older than `--older-than`, e.g. `punch prune --older-than 2y`, or with
+/// `--keep-aggregates` strips what you wrote about them but keeps their times. Without
+/// `--older-than`, uses the config's `retention` settings. Asks first unless given `--yes`.
+pub fn prune(now: &DateTime<Local>, other_args: Vec<String>) {
+    let retention: RetentionConfig = match (get_flag_value(&other_args, "--older-than"), get_config().retention()) {
+        (Some(older_than), _) => RetentionConfig {older_than: older_than, keep_aggregates: has_flag(&other_args, "--keep-aggregates")},
+        (None, Some(retention)) => retention.clone(),
+        (None, None) => {
+            eprintln!("'punch prune' needs an age, e.g. 'punch prune --older-than 2y', or 'retention' set in the config.");
+            exit(1);
+        },
+    };
+    let oldest_kept: NaiveDate = parse_age(&retention.older_than, now.date_naive()).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+    let dates: Vec<NaiveDate> = get_dates_to_prune(&oldest_kept);
+    if !dates.is_empty() {
+        let action: &str = if retention.keep_aggregates {"Anonymize"} else {"Delete"};
+        let question: String = format!("{} {} days from {} to {}?", action, dates.len(), dates[0], dates[dates.len() - 1]);
+        if !has_flag(&other_args, "--yes") && !confirm(&question) {
+            println!("Nothing was pruned.");
+            return;
+        }
+    }
+    print_outcome(&prune_days_before(&oldest_kept, retention.keep_aggregates), &oldest_kept);
+}
+
+fn print_outcome(outcome: &PruneOutcome, oldest_kept: &NaiveDate) {
+    println!(
+        "Deleted {} days and anonymized {} from before {}, and deleted {} days of window samples.",
+        outcome.deleted, outcome.anonymized, oldest_kept, outcome.sample_days_deleted);
+}
@@ -0,0 +1,20 @@
+use chrono::prelude::{DateTime, Local};
+
+use crate::units::day::{Day, get_current_day};
+
+/// Lists today's notes with a reminder still to come, from `punch note --remind`.
+pub fn reminders(now: &DateTime<Local>) {
+    let pending: Vec<(DateTime<Local>, String)> = get_current_day(now)
+        .map(|day: Day| day.get_note_reminders())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(remind_at, _)| remind_at > now)
+        .collect();
+    if pending.is_empty() {
+        println!("No reminders to come today. Add one with 'punch note \"...\" --remind 15:00'.");
+        return;
+    }
+    for (remind_at, msg) in pending {
+        println!("{}  {}", remind_at.format("%H:%M"), msg);
+    }
+}
@@ -0,0 +1,91 @@
+use std::process::exit;
+use chrono::prelude::{DateTime, Local};
+
+use crate::units::day::{Day, DayIterator, write_day};
+use crate::utils::args::{get_flag_value, get_positional_args, has_flag};
+use crate::utils::period::{DateRange, resolve_range_from_args};
+use crate::utils::suggestions::get_nearest_match;
+
+const RANGE_FLAGS: [&str; 3] = ["--from", "--to", "--period"];
+
+/// Renames a task in every recorded day, or only in the days picked with `--from`/`--to`
+/// or `--period`. With `--dry-run`, prints what would change without writing anything.
+/// Submitted days are left alone unless `force`.
+pub fn rename_task(now: &DateTime<Local>, other_args: Vec<String>, force: bool) {
+    let positional: Vec<String> = get_positional_args(&other_args, &RANGE_FLAGS);
+    let (old_task, new_task): (&String, &String) = match positional.as_slice() {
+        [old_task, new_task] => (old_task, new_task),
+        _ => {
+            eprintln!("Usage: punch rename-task <old> <new> [--from YYYY-MM-DD] [--to YYYY-MM-DD] [--dry-run]");
+            exit(1);
+        },
+    };
+    let days: DayIterator = match RANGE_FLAGS.iter().any(|flag: &&str| get_flag_value(&other_args, flag).is_some()) {
+        true => {
+            let range: DateRange = resolve_range_from_args(now, &other_args).unwrap_or_else(|msg: String| {
+                eprintln!("{}", msg);
+                exit(1);
+            });
+            DayIterator::new(&range)
+        },
+        false => DayIterator::all(),
+    };
+    let dry_run: bool = has_flag(&other_args, "--dry-run");
+
+    let mut num_days: usize = 0;
+    let mut num_changes: usize = 0;
+    let mut num_submitted: usize = 0;
+    let mut known_tasks: Vec<String> = Vec::new();
+    for day in days {
+        for block in day.timeblocks.iter() {
+            if !known_tasks.contains(&block.get_task_name()) {
+                known_tasks.push(block.get_task_name());
+            }
+        }
+        let mut renamed_day: Day = day.clone();
+        let day_changes: usize = renamed_day.rename_task(old_task, new_task);
+        if day_changes == 0 {
+            continue;
+        }
+        if day.check_editable(force).is_err() {
+            num_submitted += 1;
+            continue;
+        }
+        if dry_run {
+            print_diff(&day, &renamed_day);
+        }
+        else {
+            write_day(&renamed_day);
+        }
+        num_days += 1;
+        num_changes += day_changes;
+    }
+
+    match (num_days, dry_run) {
+        (0, _) => match get_nearest_match(old_task, &known_tasks) {
+            Some(nearest) if num_submitted == 0 => println!("No blocks or summaries for '{}' found. Did you mean '{}'?", old_task, nearest),
+            _ => println!("No blocks or summaries for '{}' found.", old_task),
+        },
+        (_, true) => println!("Would rename '{}' to '{}' in {} blocks and summaries over {} days.", old_task, new_task, num_changes, num_days),
+        (_, false) => println!("Renamed '{}' to '{}' in {} blocks and summaries over {} days.", old_task, new_task, num_changes, num_days),
+    }
+    if num_submitted > 0 {
+        println!("Left {} submitted days alone. Pass --force to rename it in those too.", num_submitted);
+    }
+}
+
+fn print_diff(before: &Day, after: &Day) {
+    println!("{}", before.get_day_start().as_dt().format("%Y-%m-%d"));
+    for (old_block, new_block) in before.timeblocks.iter().zip(after.timeblocks.iter()) {
+        if old_block.get_task_name() != new_block.get_task_name() {
+            println!("-\t{}", old_block.describe());
+            println!("+\t{}", new_block.describe());
+        }
+    }
+    for (old_summary, new_summary) in before.summaries.iter().zip(after.summaries.iter()) {
+        if old_summary.as_short_string() != new_summary.as_short_string() {
+            println!("-\t{}", old_summary.as_short_string());
+            println!("+\t{}", new_summary.as_short_string());
+        }
+    }
+}
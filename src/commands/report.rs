@@ -0,0 +1,569 @@
+use std::collections::BTreeMap;
+use std::process::exit;
+use chrono::Duration;
+use chrono::prelude::{DateTime, Datelike, Local, NaiveDate};
+
+use crate::units::interval::DATE_FMT;
+use crate::units::day::DayIterator;
+use crate::utils::aggregate::{PeriodTotals, close_day_at, correlation, format_time_of_day, get_focus_secs_by_week, get_focus_streaks, get_project_secs};
+use crate::utils::args::{get_flag_value, get_where_conditions, has_flag};
+use crate::utils::budget::get_budget_usage;
+use crate::utils::compliance::check_break_rules;
+use crate::utils::config::{Config, get_config};
+use crate::utils::email::{EmailConfig, send_email};
+use crate::utils::index::{DaySummary, get_summaries_in_range, rebuild_index};
+use crate::utils::location::{LOCATION_KEY, is_remote};
+use crate::utils::period::{DateRange, parse_range, resolve_range_from_args};
+use crate::utils::report::{Report, ReportSection, ReportValue, render_report_from_args};
+use crate::utils::signing::sign_file;
+use crate::utils::terminal::print_paged;
+use crate::utils::shifts::{REGULAR_SHIFT, Shift, check_shifts, get_shift_secs};
+use crate::utils::adjustments::{BalanceAdjustment, format_adjustment, read_adjustments};
+use crate::utils::ledger::{ClosedWeek, get_closed_weeks};
+use crate::utils::week_plans::{get_week_start, read_week_plan};
+
+pub fn report(now: &DateTime<Local>, other_args: Vec<String>) {
+    let range_result: Result<DateRange, String> = resolve_range_from_args(now, &other_args);
+    if let Err(msg) = range_result {
+        eprintln!("{}", msg);
+        exit(1);
+    }
+    let range: DateRange = range_result.expect("Error already handled!");
+    let conditions: Vec<(String, String)> = get_where_conditions(&other_args).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+    let mut summaries: Vec<DaySummary> = get_summaries_in_range(&range, now);
+    if has_flag(&other_args, "--submitted-only") {
+        summaries.retain(|summary: &DaySummary| summary.submitted);
+    }
+    summaries.retain(|summary: &DaySummary| summary.matches_meta(&conditions));
+    let mut report: Report = build_period_report(&range, &summaries);
+    if let Some(shifts) = get_config().shifts().filter(|shifts: &&Vec<Shift>| !shifts.is_empty()) {
+        let shifts_section: ReportSection = get_shifts_section(now, &summaries, shifts).unwrap_or_else(|msg: String| {
+            eprintln!("{}", msg);
+            exit(1);
+        });
+        report.add_section(shifts_section);
+    }
+    print_report(report, &other_args, now);
+}
+
+/// Time worked in each shift over the days, and that time weighted by the shift's multiplier.
+fn get_shifts_section(now: &DateTime<Local>, summaries: &Vec<DaySummary>, shifts: &Vec<Shift>) -> Result<ReportSection, String> {
+    check_shifts(shifts)?;
+    let dates: Vec<NaiveDate> = summaries.iter()
+        .filter_map(|summary: &DaySummary| NaiveDate::parse_from_str(&summary.date, DATE_FMT).ok())
+        .collect();
+    let mut secs_by_shift: Vec<i64> = vec![0; shifts.len() + 1];
+    for day in DayIterator::from_dates(dates) {
+        for (ind, (_, secs)) in get_shift_secs(&close_day_at(day, now), shifts)?.into_iter().enumerate() {
+            secs_by_shift[ind] += secs;
+        }
+    }
+    let mut shifts_section: ReportSection = ReportSection::new("shifts", &["shift", "multiplier", "time", "weighted"]);
+    let mut weighted_total_secs: i64 = 0;
+    let names_and_multipliers = shifts.iter()
+        .map(|shift: &Shift| (shift.name.as_str(), shift.multiplier))
+        .chain([(REGULAR_SHIFT, 1.0)]);
+    for ((name, multiplier), secs) in names_and_multipliers.zip(&secs_by_shift) {
+        let weighted_secs: i64 = (*secs as f64 * multiplier).round() as i64;
+        weighted_total_secs += weighted_secs;
+        shifts_section.add_row(vec![name.into(), format!("{}", multiplier).into(), ReportValue::Secs(*secs), ReportValue::Secs(weighted_secs)]);
+    }
+    shifts_section.add_row(vec![
+        "total".into(), "".into(), ReportValue::Secs(secs_by_shift.iter().sum()), ReportValue::Secs(weighted_total_secs)]);
+    return Ok(shifts_section);
+}
+
+pub fn build_period_report(range: &DateRange, summaries: &Vec<DaySummary>) -> Report {
+    let config: Config = get_config();
+    let mut report: Report = Report::new(format!("Report for {}", range.as_string()));
+    let mut days_section: ReportSection = ReportSection::new("days", &["date", "done", "billable", "break", "target", "flags"]);
+    let mut compliance_section: ReportSection = ReportSection::new("break_rule_compliance", &["date", "violation"]);
+    let mut days_within_break_budget: i64 = 0;
+    let mut secs_over_cap: i64 = 0;
+    for summary in summaries {
+        let date: String = summary.date.clone();
+        let done_secs: i64 = summary.done_secs;
+        let break_secs: i64 = summary.break_secs;
+        let mut flags: Vec<&str> = Vec::new();
+        if config.break_budget_left_secs(break_secs).is_some_and(|left: i64| left < 0) {
+            flags.push("over break budget");
+        }
+        else {
+            days_within_break_budget += 1;
+        }
+        if config.secs_over_daily_cap(done_secs) > 0 {
+            flags.push("over daily cap");
+            secs_over_cap += config.secs_over_daily_cap(done_secs);
+        }
+        for violation in check_break_rules(summary, config.break_rules()) {
+            compliance_section.add_row(vec![date.clone().into(), violation.into()]);
+        }
+        days_section.add_row(vec![
+            date.into(),
+            ReportValue::Secs(done_secs),
+            ReportValue::Secs(summary.billable_secs),
+            ReportValue::Secs(break_secs),
+            ReportValue::Secs(summary.to_do_secs),
+            flags.join("; ").into(),
+        ]);
+    }
+    report.add_section(days_section);
+
+    let totals: PeriodTotals = PeriodTotals::from_summaries(summaries);
+    let mut totals_section: ReportSection = ReportSection::new("totals", &["total", "value"]);
+    totals_section.add_row(vec!["Days worked".into(), ReportValue::Number(totals.days_worked as i64)]);
+    totals_section.add_row(vec!["Time done".into(), ReportValue::Secs(totals.done_secs)]);
+    totals_section.add_row(vec!["Billable".into(), ReportValue::Secs(totals.billable_secs)]);
+    totals_section.add_row(vec!["Non-billable".into(), ReportValue::Secs(totals.non_billable_secs())]);
+    totals_section.add_row(vec!["Time spent on break".into(), ReportValue::Secs(totals.break_secs)]);
+    if totals.paid_break_secs > 0 || config.paid_break_minutes().is_some() {
+        totals_section.add_row(vec!["Paid breaks (in time done)".into(), ReportValue::Secs(totals.paid_break_secs)]);
+    }
+    totals_section.add_row(vec!["Target".into(), ReportValue::Secs(totals.to_do_secs)]);
+    totals_section.add_row(vec!["Time behind".into(), ReportValue::Secs(totals.secs_behind())]);
+    if let (Some(lateness_secs), Some(on_time_percent)) = (totals.average_lateness_secs(), totals.on_time_percent()) {
+        totals_section.add_row(vec!["Average lateness".into(), ReportValue::Secs(lateness_secs)]);
+        totals_section.add_row(vec!["Days on time (%)".into(), ReportValue::Number(on_time_percent)]);
+    }
+    if let Some(completion_percent) = totals.completion_percent() {
+        totals_section.add_row(vec!["Summarized tasks finished (%)".into(), ReportValue::Number(completion_percent)]);
+    }
+    if totals.focus_secs > 0 || config.daily_focus_goal_minutes().is_some() {
+        totals_section.add_row(vec!["Focus".into(), ReportValue::Secs(totals.focus_secs)]);
+    }
+    if let Some(goal_minutes) = config.daily_focus_goal_minutes() {
+        let (current_streak, longest_streak): (u64, u64) = get_focus_streaks(summaries, goal_minutes as i64 * 60);
+        totals_section.add_row(vec!["Focus goal streak (days)".into(), ReportValue::Number(current_streak as i64)]);
+        totals_section.add_row(vec!["Longest focus goal streak (days)".into(), ReportValue::Number(longest_streak as i64)]);
+    }
+    if config.break_budget_minutes().is_some() {
+        totals_section.add_row(vec!["Days within break budget".into(), ReportValue::Number(days_within_break_budget)]);
+    }
+    if config.max_daily_minutes().is_some() {
+        totals_section.add_row(vec!["Overtime beyond daily cap".into(), ReportValue::Secs(secs_over_cap)]);
+    }
+    report.add_section(totals_section);
+
+    let mut tasks_section: ReportSection = ReportSection::new("tasks", &["task", "time"]);
+    for task in totals.get_tasks() {
+        let task_secs: i64 = totals.get_task_secs(&task);
+        tasks_section.add_row(vec![task.into(), ReportValue::Secs(task_secs)]);
+    }
+    report.add_section(tasks_section);
+
+    // Archived projects are kept apart so they don't crowd out the ones still going.
+    let columns: [&str; 2] = ["project", "time"];
+    let mut projects_section: ReportSection = ReportSection::new("projects", &columns);
+    let mut archived_section: ReportSection = ReportSection::new("archived_projects", &columns);
+    for (project, (secs, _)) in get_project_secs(summaries) {
+        let section: &mut ReportSection = match config.is_project_archived(&project) {
+            true => &mut archived_section,
+            false => &mut projects_section,
+        };
+        section.add_row(vec![project.into(), ReportValue::Secs(secs)]);
+    }
+    report.add_section(projects_section);
+    if !archived_section.rows.is_empty() {
+        report.add_section(archived_section);
+    }
+    if totals.focus_secs > 0 || config.daily_focus_goal_minutes().is_some() {
+        let mut focus_section: ReportSection = ReportSection::new("focus", &["week", "total", "average"]);
+        for (week_start, (focus_secs, days_worked)) in get_focus_secs_by_week(summaries) {
+            focus_section.add_row(vec![
+                week_start.format("%Y-%m-%d").to_string().into(),
+                ReportValue::Secs(focus_secs),
+                ReportValue::Secs(focus_secs / days_worked as i64),
+            ]);
+        }
+        report.add_section(focus_section);
+    }
+    let mood_summaries: Vec<&DaySummary> = summaries.iter().filter(|summary: &&DaySummary| summary.mood.is_some()).collect();
+    if !mood_summaries.is_empty() {
+        report.add_section(get_mood_section(&mood_summaries));
+        report.add_section(get_mood_correlation_section(&mood_summaries));
+    }
+    let planned_summaries: Vec<&DaySummary> = summaries.iter().filter(|summary: &&DaySummary| summary.planned_secs > 0).collect();
+    if !planned_summaries.is_empty() {
+        report.add_section(get_planning_accuracy_section(&planned_summaries));
+    }
+    if let Some(week_plan_section) = get_week_plan_section(range, summaries) {
+        report.add_section(week_plan_section);
+    }
+    if config.break_reminder().is_some() || summaries.iter().any(|summary: &DaySummary| summary.break_reminders > 0) {
+        report.add_section(get_break_hygiene_section(summaries));
+    }
+    if summaries.iter().any(|summary: &DaySummary| summary.meta.contains_key(LOCATION_KEY)) {
+        report.add_section(get_location_split_section(summaries));
+    }
+    if !config.break_rules().is_empty() {
+        report.add_section(compliance_section);
+    }
+    if !config.budgets().is_empty() {
+        report.add_section(get_budget_overruns_section(summaries, &config));
+    }
+    let adjustments: Vec<BalanceAdjustment> = read_adjustments().into_iter()
+        .filter(|adjustment: &BalanceAdjustment| range.contains(&adjustment.time.as_dt().date_naive()))
+        .collect();
+    if !adjustments.is_empty() {
+        let mut adjustments_section: ReportSection = ReportSection::new("balance_adjustments", &["date", "adjustment", "reason"]);
+        for adjustment in adjustments {
+            adjustments_section.add_row(vec![
+                adjustment.time.as_dt().format(DATE_FMT).to_string().into(),
+                format_adjustment(adjustment.minutes).into(),
+                adjustment.reason.into(),
+            ]);
+        }
+        report.add_section(adjustments_section);
+    }
+    let closed_weeks: Vec<ClosedWeek> = get_closed_weeks().into_iter()
+        .filter(|week: &ClosedWeek| range.contains(&week.week_start) || range.contains(&(week.week_start + Duration::days(6))))
+        .collect();
+    if !closed_weeks.is_empty() {
+        // The ledger's totals, which only change through corrections, so payroll can square up against them.
+        let mut closed_weeks_section: ReportSection = ReportSection::new("closed_weeks", &["week", "closed", "done", "to do", "behind", "corrections"]);
+        for week in closed_weeks {
+            closed_weeks_section.add_row(vec![
+                week.week_start.format(DATE_FMT).to_string().into(),
+                week.closed.as_dt().format(DATE_FMT).to_string().into(),
+                ReportValue::Secs(week.done_minutes * 60),
+                ReportValue::Secs(week.to_do_minutes * 60),
+                ReportValue::Secs(week.get_balance_minutes() * 60),
+                week.num_corrections.to_string().into(),
+            ]);
+        }
+        report.add_section(closed_weeks_section);
+    }
+    return report;
+}
+
+/// Average mood per week next to the average time done and on break on those days.
+fn get_mood_section(mood_summaries: &Vec<&DaySummary>) -> ReportSection {
+    let mut mood_section: ReportSection = ReportSection::new("mood", &["week", "mood", "done", "break"]);
+    let mut weeks: BTreeMap<NaiveDate, Vec<&DaySummary>> = BTreeMap::new();
+    for summary in mood_summaries {
+        if let Ok(date) = NaiveDate::parse_from_str(&summary.date, DATE_FMT) {
+            let week_start: NaiveDate = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+            weeks.entry(week_start).or_default().push(summary);
+        }
+    }
+    for (week_start, week_summaries) in weeks {
+        let n: i64 = week_summaries.len() as i64;
+        let mood: f64 = week_summaries.iter().filter_map(|summary: &&DaySummary| summary.mood).sum::<f64>() / n as f64;
+        mood_section.add_row(vec![
+            week_start.format(DATE_FMT).to_string().into(),
+            format!("{:.1}", mood).into(),
+            ReportValue::Secs(week_summaries.iter().map(|summary: &&DaySummary| summary.done_secs).sum::<i64>() / n),
+            ReportValue::Secs(week_summaries.iter().map(|summary: &&DaySummary| summary.break_secs).sum::<i64>() / n),
+        ]);
+    }
+    return mood_section;
+}
+
+/// How mood goes along with time done and time on break, from -1 (opposite) to 1 (together).
+fn get_mood_correlation_section(mood_summaries: &Vec<&DaySummary>) -> ReportSection {
+    let mut correlation_section: ReportSection = ReportSection::new("mood_correlation", &["mood with", "correlation"]);
+    let measures: [(&str, Vec<i64>); 2] = [
+        ("time done", mood_summaries.iter().map(|summary: &&DaySummary| summary.done_secs).collect()),
+        ("time on break", mood_summaries.iter().map(|summary: &&DaySummary| summary.break_secs).collect()),
+    ];
+    for (label, values) in measures {
+        let pairs: Vec<(f64, f64)> = mood_summaries.iter().zip(values)
+            .map(|(summary, value): (&&DaySummary, i64)| (summary.mood.expect("Only days with a mood"), value as f64))
+            .collect();
+        let value: String = match correlation(&pairs) {
+            Some(r) => format!("{:.2}", r),
+            None => "not enough data".to_string(),
+        };
+        correlation_section.add_row(vec![label.into(), value.into()]);
+    }
+    return correlation_section;
+}
+
+/// Time planned each week next to time done, and the share of the plan that went as planned,
+/// counting only days with a plan.
+fn get_planning_accuracy_section(planned_summaries: &Vec<&DaySummary>) -> ReportSection {
+    let mut accuracy_section: ReportSection = ReportSection::new("planning_accuracy", &["week", "planned", "done", "as planned (%)"]);
+    // Planned, done and matched seconds for each week.
+    let mut weeks: BTreeMap<NaiveDate, (i64, i64, i64)> = BTreeMap::new();
+    for summary in planned_summaries {
+        if let Ok(date) = NaiveDate::parse_from_str(&summary.date, DATE_FMT) {
+            let week_start: NaiveDate = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+            let entry: &mut (i64, i64, i64) = weeks.entry(week_start).or_default();
+            entry.0 += summary.planned_secs;
+            entry.1 += summary.done_secs;
+            entry.2 += summary.plan_matched_secs;
+        }
+    }
+    for (week_start, (planned_secs, done_secs, matched_secs)) in weeks {
+        accuracy_section.add_row(vec![
+            week_start.format(DATE_FMT).to_string().into(),
+            ReportValue::Secs(planned_secs),
+            ReportValue::Secs(done_secs),
+            ReportValue::Number(matched_secs * 100 / planned_secs),
+        ]);
+    }
+    return accuracy_section;
+}
+
+/// Each project's time done next to its time planned with `punch plan-week`, for the weeks
+/// in the range that have a plan. `None` if none do.
+fn get_week_plan_section(range: &DateRange, summaries: &Vec<DaySummary>) -> Option<ReportSection> {
+    let mut plan_section: ReportSection = ReportSection::new("week_plan", &["week", "project", "planned", "done", "done (%)"]);
+    let mut week_start: NaiveDate = get_week_start(&range.from);
+    while week_start <= range.to {
+        match read_week_plan(&week_start) {
+            Ok(Some(plan)) => {
+                let week_end: NaiveDate = week_start + Duration::days(6);
+                let week_summaries: Vec<DaySummary> = summaries.iter()
+                    .filter(|summary: &&DaySummary| NaiveDate::parse_from_str(&summary.date, DATE_FMT)
+                        .is_ok_and(|date: NaiveDate| date >= week_start && date <= week_end))
+                    .cloned()
+                    .collect();
+                for (project, planned_secs, done_secs) in plan.get_progress(&week_summaries) {
+                    plan_section.add_row(vec![
+                        week_start.format(DATE_FMT).to_string().into(),
+                        project.into(),
+                        ReportValue::Secs(planned_secs),
+                        ReportValue::Secs(done_secs),
+                        if planned_secs > 0 {ReportValue::Number(done_secs * 100 / planned_secs)} else {"".into()},
+                    ]);
+                }
+            },
+            Ok(None) => {},
+            Err(msg) => eprintln!("Warning: {}", msg),
+        }
+        week_start += Duration::weeks(1);
+    }
+    return match plan_section.rows.is_empty() {
+        true => None,
+        false => Some(plan_section),
+    };
+}
+
+/// How many break reminders the daemon sent each week, and how many of them you took.
+fn get_break_hygiene_section(summaries: &Vec<DaySummary>) -> ReportSection {
+    let mut hygiene_section: ReportSection = ReportSection::new("break_hygiene", &["week", "reminders", "followed", "followed (%)"]);
+    // Reminders sent and followed for each week.
+    let mut weeks: BTreeMap<NaiveDate, (i64, i64)> = BTreeMap::new();
+    for summary in summaries {
+        if let Ok(date) = NaiveDate::parse_from_str(&summary.date, DATE_FMT) {
+            let week_start: NaiveDate = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+            let entry: &mut (i64, i64) = weeks.entry(week_start).or_default();
+            entry.0 += summary.break_reminders as i64;
+            entry.1 += summary.break_reminders_followed as i64;
+        }
+    }
+    for (week_start, (num_sent, num_followed)) in weeks {
+        hygiene_section.add_row(vec![
+            week_start.format(DATE_FMT).to_string().into(),
+            ReportValue::Number(num_sent),
+            ReportValue::Number(num_followed),
+            if num_sent > 0 {ReportValue::Number(num_followed * 100 / num_sent)} else {"".into()},
+        ]);
+    }
+    return hygiene_section;
+}
+
+/// How many days each month were worked remotely and on site, from the location `punch in`
+/// recorded, for hybrid-work policies that ask for the split.
+fn get_location_split_section(summaries: &Vec<DaySummary>) -> ReportSection {
+    let mut split_section: ReportSection = ReportSection::new(
+        "location_split", &["month", "remote days", "on-site days", "untagged days", "remote (%)"]);
+    // Remote, on-site and untagged days for each month.
+    let mut months: BTreeMap<String, (i64, i64, i64)> = BTreeMap::new();
+    for summary in summaries {
+        let counts: &mut (i64, i64, i64) = months.entry(summary.date[..7].to_string()).or_default();
+        match summary.meta.get(LOCATION_KEY) {
+            Some(location) if is_remote(location) => counts.0 += 1,
+            Some(_) => counts.1 += 1,
+            None => counts.2 += 1,
+        }
+    }
+    for (month, (remote, on_site, untagged)) in months {
+        let remote_percent: i64 = match remote + on_site {
+            0 => 0,
+            tagged => (remote * 100 + tagged / 2) / tagged,
+        };
+        split_section.add_row(vec![
+            month.into(),
+            ReportValue::Number(remote),
+            ReportValue::Number(on_site),
+            ReportValue::Number(untagged),
+            ReportValue::Number(remote_percent),
+        ]);
+    }
+    return split_section;
+}
+
+/// Categories over their monthly budget, counting the days of each month that are in the report.
+fn get_budget_overruns_section(summaries: &Vec<DaySummary>, config: &Config) -> ReportSection {
+    let mut overruns_section: ReportSection = ReportSection::new("budget_overruns", &["month", "category", "used", "budget"]);
+    let mut months: Vec<String> = summaries.iter().map(|summary: &DaySummary| summary.date[..7].to_string()).collect();
+    months.dedup();
+    for month in months {
+        let month_summaries: Vec<DaySummary> = summaries.iter()
+            .filter(|summary: &&DaySummary| summary.date.starts_with(&month))
+            .cloned()
+            .collect();
+        for (budget, used_secs, left_secs) in get_budget_usage(&month_summaries, config.budgets()) {
+            if left_secs < 0 {
+                overruns_section.add_row(vec![
+                    month.clone().into(),
+                    budget.category.clone().into(),
+                    ReportValue::Secs(used_secs),
+                    ReportValue::Secs(budget.get_max_secs()),
+                ]);
+            }
+        }
+    }
+    return overruns_section;
+}
+
+pub fn compare(now: &DateTime<Local>, other_args: Vec<String>) {
+    let a_spec: String = get_flag_value(&other_args, "--a").unwrap_or("last-week".to_string());
+    let b_spec: String = get_flag_value(&other_args, "--b").unwrap_or("this-week".to_string());
+    let ranges_result: Result<(DateRange, DateRange), String> = parse_range(&a_spec, now)
+        .and_then(|a: DateRange| Ok((a, parse_range(&b_spec, now)?)));
+    if let Err(msg) = ranges_result {
+        eprintln!("{}", msg);
+        exit(1);
+    }
+    let (a_range, b_range): (DateRange, DateRange) = ranges_result.expect("Error already handled!");
+    let a: PeriodTotals = PeriodTotals::from_summaries(&get_summaries_in_range(&a_range, now));
+    let b: PeriodTotals = PeriodTotals::from_summaries(&get_summaries_in_range(&b_range, now));
+
+    let mut report: Report = Report::new(
+        format!("Comparing A ({}) with B ({})", a_range.as_string(), b_range.as_string()));
+    let mut totals_section: ReportSection = ReportSection::new("totals", &["total", "a", "b", "change"]);
+    totals_section.add_row(vec![
+        "Days worked".into(),
+        ReportValue::Number(a.days_worked as i64),
+        ReportValue::Number(b.days_worked as i64),
+        ReportValue::Number(b.days_worked as i64 - a.days_worked as i64),
+    ]);
+    totals_section.add_row(get_secs_delta_row("Time done", a.done_secs, b.done_secs));
+    totals_section.add_row(get_secs_delta_row("Billable", a.billable_secs, b.billable_secs));
+    totals_section.add_row(get_secs_delta_row("Non-billable", a.non_billable_secs(), b.non_billable_secs()));
+    totals_section.add_row(get_secs_delta_row("Focus", a.focus_secs, b.focus_secs));
+    totals_section.add_row(get_secs_delta_row("Time spent on break", a.break_secs, b.break_secs));
+    totals_section.add_row(get_secs_delta_row("Time behind", a.secs_behind(), b.secs_behind()));
+    if let (Some(a_start), Some(b_start)) = (a.average_start_secs(), b.average_start_secs()) {
+        totals_section.add_row(vec![
+            "Average start time".into(),
+            format_time_of_day(a_start).into(),
+            format_time_of_day(b_start).into(),
+            ReportValue::Secs(b_start - a_start),
+        ]);
+    }
+    if let (Some(a_lateness), Some(b_lateness)) = (a.average_lateness_secs(), b.average_lateness_secs()) {
+        totals_section.add_row(get_secs_delta_row("Average lateness", a_lateness, b_lateness));
+    }
+    if let (Some(a_on_time), Some(b_on_time)) = (a.on_time_percent(), b.on_time_percent()) {
+        totals_section.add_row(vec![
+            "Days on time (%)".into(),
+            ReportValue::Number(a_on_time),
+            ReportValue::Number(b_on_time),
+            ReportValue::Number(b_on_time - a_on_time),
+        ]);
+    }
+    if let (Some(a_completion), Some(b_completion)) = (a.completion_percent(), b.completion_percent()) {
+        totals_section.add_row(vec![
+            "Summarized tasks finished (%)".into(),
+            ReportValue::Number(a_completion),
+            ReportValue::Number(b_completion),
+            ReportValue::Number(b_completion - a_completion),
+        ]);
+    }
+    report.add_section(totals_section);
+
+    let mut tasks_section: ReportSection = ReportSection::new("tasks", &["task", "a", "b", "change"]);
+    let mut tasks: Vec<String> = a.get_tasks();
+    for task in b.get_tasks() {
+        if !tasks.contains(&task) {
+            tasks.push(task);
+        }
+    }
+    for task in tasks {
+        tasks_section.add_row(get_secs_delta_row(&task, a.get_task_secs(&task), b.get_task_secs(&task)));
+    }
+    report.add_section(tasks_section);
+    print_report(report, &other_args, now);
+}
+
+fn get_secs_delta_row(label: &str, a_secs: i64, b_secs: i64) -> Vec<ReportValue> {
+    return vec![label.into(), ReportValue::Secs(a_secs), ReportValue::Secs(b_secs), ReportValue::Secs(b_secs - a_secs)];
+}
+
+/// Renders the report for a period and emails it, e.g. from a weekly cron job.
+pub fn email_report(now: &DateTime<Local>, other_args: Vec<String>) {
+    let email: EmailConfig = match get_config().email() {
+        Some(email) => email.clone(),
+        None => {
+            eprintln!("Add an 'email' section to the config to say where to send reports. See 'punch edit-config'.");
+            exit(1);
+        },
+    };
+    let range: DateRange = resolve_range_from_args(now, &other_args).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+    let summaries: Vec<DaySummary> = get_summaries_in_range(&range, now);
+    let report: Report = build_period_report(&range, &summaries);
+    let subject: String = report.title.clone();
+    let body: String = render_report_from_args(report, &other_args).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+    if let Err(msg) = send_email(&email, &subject, &body, now) {
+        eprintln!("{}", msg);
+        exit(1);
+    }
+    println!("Sent the report for {} to {}.", range.as_string(), email.to);
+}
+
+/// Prints the report in the format asked for, or writes it to `--output`, signing the
+/// file too if given `--sign`.
+pub fn print_report(report: Report, other_args: &Vec<String>, now: &DateTime<Local>) {
+    let rendered: String = render_report_from_args(report, other_args).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+    match get_flag_value(other_args, "--output") {
+        Some(output) => write_output(&output, rendered.as_bytes(), has_flag(other_args, "--sign"), now),
+        None if has_flag(other_args, "--sign") => {
+            eprintln!("'--sign' needs a file to sign. Use '--output <file>' too.");
+            exit(1);
+        },
+        None => print_paged(&rendered),
+    }
+}
+
+/// Writes an export or report to a file, and signs it with the configured key if `sign`.
+pub fn write_output(output: &str, contents: &[u8], sign: bool, now: &DateTime<Local>) {
+    if let Err(err) = std::fs::write(output, contents) {
+        eprintln!("Couldn't write '{}': {}", output, err);
+        exit(1);
+    }
+    if !sign {
+        return;
+    }
+    let Some(signing) = get_config().signing().cloned() else {
+        eprintln!("Add a 'signing' section to the config with your private key to sign files. See 'punch edit-config'.");
+        exit(1);
+    };
+    match sign_file(output, &signing, now) {
+        Ok(signature_path) => println!("Signed '{}' in '{}'.", output, signature_path),
+        Err(msg) => {
+            eprintln!("Couldn't sign '{}': {}", output, msg);
+            exit(1);
+        },
+    }
+}
+
+pub fn reindex() {
+    let num_days: usize = rebuild_index();
+    println!("Rebuilt the index from {} finished days.", num_days);
+}
@@ -0,0 +1,156 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, exit, id};
+
+use crate::utils::args::has_flag;
+use crate::utils::config::get_config;
+use crate::utils::file_io::expand_path;
+use crate::utils::signing::{Signature, get_sha256, verify_file};
+use crate::utils::updates::{
+    Release, ReleaseAsset, UpdateConfig, download, download_text, fetch_latest_release, find_checksum, is_newer_version};
+
+/// `punch self-update`: installs the latest release from GitHub in place of the running
+/// binary, once its checksum (and signature, if a key is configured) checks out. With
+/// `--check`, only says whether there's a newer one.
+pub fn self_update(current_version: &str, other_args: Vec<String>) {
+    let update_config: UpdateConfig = get_config().self_update().cloned().unwrap_or_default();
+    if !update_config.enabled {
+        eprintln!("Update checks are turned off in the config ('self_update: enabled: false').");
+        exit(1);
+    }
+    let release: Release = fetch_latest_release(update_config.get_repository()).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+    if !is_newer_version(release.get_version(), current_version) && !has_flag(&other_args, "--force") {
+        println!("punch {} is up to date (the latest release is {}).", current_version, release.tag);
+        return;
+    }
+    if has_flag(&other_args, "--check") {
+        println!("punch {} is out. You have {}. Run 'punch self-update' to install it.", release.get_version(), current_version);
+        return;
+    }
+
+    let Some(asset) = release.get_binary_asset() else {
+        eprintln!(
+            "Release {} has no build for {} on {}. Its assets are: {}", release.tag, std::env::consts::OS,
+            std::env::consts::ARCH, release.assets.iter().map(|x: &ReleaseAsset| x.name.clone()).collect::<Vec<String>>().join(", "));
+        exit(1);
+    };
+    let work_dir: PathBuf = std::env::temp_dir().join(format!("punch-update-{}", id()));
+    let result: Result<(), String> = std::fs::create_dir_all(&work_dir)
+        .map_err(|err| format!("Couldn't make '{}': {}", work_dir.display(), err))
+        .and_then(|_| install_release(&release, asset, &update_config, &work_dir));
+    let _ = std::fs::remove_dir_all(&work_dir);
+    if let Err(msg) = result {
+        eprintln!("{}", msg);
+        eprintln!("punch {} was left as it is.", current_version);
+        exit(1);
+    }
+    println!("Updated punch from {} to {}.", current_version, release.get_version());
+}
+
+fn install_release(release: &Release, asset: &ReleaseAsset, update_config: &UpdateConfig, work_dir: &Path) -> Result<(), String> {
+    let download_path: String = work_dir.join(&asset.name).to_string_lossy().to_string();
+    println!("Downloading {}", asset.name);
+    download(asset, &download_path)?;
+    check_download(release, asset, update_config, &download_path)?;
+    let binary: PathBuf = match asset.name.ends_with(".tar.gz") || asset.name.ends_with(".tgz") || asset.name.ends_with(".zip") {
+        true => extract_binary(&download_path, work_dir)?,
+        false => PathBuf::from(&download_path),
+    };
+    let exe: PathBuf = std::env::current_exe().map_err(|err| format!("Couldn't find the running punch: {}", err))?;
+    return replace_exe(&binary, &exe);
+}
+
+/// Checks the download against the release's checksum, which it must have, and against its
+/// signature when the config gives a key to check it with.
+fn check_download(release: &Release, asset: &ReleaseAsset, update_config: &UpdateConfig, path: &str) -> Result<(), String> {
+    let checksum_asset: &ReleaseAsset = release.get_checksum_asset(asset)
+        .ok_or(format!("Release {} publishes no checksum for {}, so it can't be checked.", release.tag, asset.name))?;
+    let expected: String = find_checksum(&download_text(checksum_asset)?, &asset.name)
+        .ok_or(format!("{} has no checksum for {}.", checksum_asset.name, asset.name))?;
+    if get_sha256(path)? != expected {
+        return Err(format!("{} doesn't match its checksum in {}. It may be corrupt or tampered with.", asset.name, checksum_asset.name));
+    }
+    println!("Checksum OK");
+
+    let Some(key_path) = &update_config.public_key else {
+        return Ok(());
+    };
+    let public_key_pem: String = std::fs::read_to_string(expand_path(key_path))
+        .map_err(|err| format!("Couldn't read '{}': {}", key_path, err))?;
+    let signature_asset: &ReleaseAsset = release.get_signature_asset(asset)
+        .ok_or(format!("Release {} has no signature for {}, and the config asks for one.", release.tag, asset.name))?;
+    let signature: Signature = Signature::from_string(&download_text(signature_asset)?)?;
+    verify_file(path, &signature, Some(&public_key_pem))
+        .map_err(|_| format!("{} isn't signed by the key in '{}'.", asset.name, key_path))?;
+    println!("Signature OK");
+    return Ok(());
+}
+
+/// Unpacks an archive with `tar` (or `unzip`) and finds the punch binary in it.
+fn extract_binary(archive: &str, work_dir: &Path) -> Result<PathBuf, String> {
+    let extract_dir: PathBuf = work_dir.join("extracted");
+    std::fs::create_dir_all(&extract_dir).map_err(|err| err.to_string())?;
+    let extract_dir_str: String = extract_dir.to_string_lossy().to_string();
+    let status: ExitStatus = match (archive.ends_with(".zip"), cfg!(windows)) {
+        (true, false) => Command::new("unzip").args(["-q", "-o", archive, "-d", &extract_dir_str]).status(),
+        (true, true) => Command::new("tar").args(["-xf", archive, "-C", &extract_dir_str]).status(),
+        (false, _) => Command::new("tar").args(["-xzf", archive, "-C", &extract_dir_str]).status(),
+    }.map_err(|err| format!("Couldn't unpack {}: {}", archive, err))?;
+    if !status.success() {
+        return Err(format!("Couldn't unpack {}", archive));
+    }
+    let binary_name: &str = if cfg!(windows) {"punch.exe"} else {"punch"};
+    return find_file(&extract_dir, binary_name)
+        .ok_or(format!("There's no '{}' in {}", binary_name, archive));
+}
+
+fn find_file(dir: &Path, name: &str) -> Option<PathBuf> {
+    for entry in std::fs::read_dir(dir).ok()?.flatten() {
+        let path: PathBuf = entry.path();
+        if path.is_dir() {
+            if let Some(found) = find_file(&path, name) {
+                return Some(found);
+            }
+        }
+        else if entry.file_name() == name {
+            return Some(path);
+        }
+    }
+    return None;
+}
+
+/// Puts `binary` in place of `exe`. The new binary is copied next to it first, so the swap
+/// itself is only renames, and the old one is put back if the second fails. Windows won't
+/// delete a running binary, so there the old one is left as `<exe>.old`.
+fn replace_exe(binary: &Path, exe: &Path) -> Result<(), String> {
+    let staged: PathBuf = PathBuf::from(format!("{}.new", exe.display()));
+    let old: PathBuf = PathBuf::from(format!("{}.old", exe.display()));
+    std::fs::copy(binary, &staged)
+        .map_err(|err| format!("Couldn't write '{}': {}. You may need to run this with the rights to change it.", staged.display(), err))?;
+    make_executable(&staged)?;
+    if let Err(err) = std::fs::rename(exe, &old) {
+        let _ = std::fs::remove_file(&staged);
+        return Err(format!("Couldn't move '{}' aside: {}", exe.display(), err));
+    }
+    if let Err(err) = std::fs::rename(&staged, exe) {
+        let _ = std::fs::rename(&old, exe);
+        let _ = std::fs::remove_file(&staged);
+        return Err(format!("Couldn't put the new binary in place of '{}': {}", exe.display(), err));
+    }
+    let _ = std::fs::remove_file(&old);
+    return Ok(());
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    return std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+        .map_err(|err| format!("Couldn't make '{}' executable: {}", path.display(), err));
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<(), String> {
+    return Ok(());
+}
@@ -0,0 +1,395 @@
+use std::net::{TcpListener, TcpStream};
+use std::process::exit;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use chrono::Duration;
+use chrono::prelude::{DateTime, Local, NaiveDate};
+
+use crate::units::components::Note;
+use crate::units::day::{Day, DayIterator, get_current_day, read_recorded_day};
+use crate::units::interval::DATE_FMT;
+use crate::utils::aggregate::close_day_at;
+use crate::utils::access::{Scope, check_access, check_api_tokens, is_read_only};
+use crate::utils::args::{get_flag_value, has_flag};
+use crate::utils::clock::{Clock, SharedClock};
+use crate::utils::config::get_config;
+use crate::utils::file_io::{DataLock, lock_data_dir};
+use crate::utils::http::{HttpRequest, set_connection_timeouts, write_response};
+use crate::utils::ics::{FeedEvent, build_ics};
+use crate::utils::index::get_summaries_in_range;
+use crate::utils::json::{JsonValue, parse_json};
+use crate::utils::period::{DateRange, parse_date, resolve_range_from_args};
+use crate::commands::batch::{BatchState, apply_args};
+use crate::commands::daemon::{DEFAULT_DAEMON_INTERVAL_SECS, run_daemon_loop};
+use crate::commands::graphql::{GRAPHQL_SCHEMA, run_graphql};
+use crate::commands::grpc::serve_grpc;
+use crate::commands::metrics::get_metrics_text;
+use crate::commands::report::build_period_report;
+use crate::commands::status::get_status_json;
+
+const JSON_CONTENT_TYPE: &str = "application/json";
+const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+const CALENDAR_CONTENT_TYPE: &str = "text/calendar; charset=utf-8";
+const DEFAULT_CALENDAR_DAYS: i64 = 90;
+#[cfg(feature = "web")]
+const DASHBOARD_HTML: &str = include_str!("../../assets/dashboard.html");
+/// How many HTTP connections are served at once. Any more are turned away with a 503.
+const MAX_CONNECTIONS: usize = 64;
+
+static OPEN_CONNECTIONS: AtomicUsize = AtomicUsize::new(0);
+pub const WRITE_METHODS: [&str; 8] = ["in", "out", "pause", "resume", "task", "update-task", "note", "add-summary"];
+
+pub fn serve(other_args: Vec<String>, clock: SharedClock) {
+    if let Err(msg) = check_api_tokens() {
+        eprintln!("{}", msg);
+        exit(1);
+    }
+    let socket_path: Option<String> = get_flag_value(&other_args, "--socket");
+    let http_address: Option<String> = get_flag_value(&other_args, "--http");
+    if has_flag(&other_args, "--daemon") {
+        if is_read_only() {
+            eprintln!("'punch serve --daemon' changes your days as it tracks them, and punch is running with --read-only.");
+            exit(1);
+        }
+        let daemon_clock: SharedClock = clock.clone();
+        thread::spawn(move || run_daemon_loop(DEFAULT_DAEMON_INTERVAL_SECS, daemon_clock, true));
+    }
+    let grpc_address: Option<String> = get_flag_value(&other_args, "--grpc");
+    let mut servers: Vec<Box<dyn FnOnce() + Send>> = Vec::new();
+    if let Some(path) = socket_path {
+        let socket_clock: SharedClock = clock.clone();
+        servers.push(Box::new(move || serve_socket(&path, socket_clock)));
+    }
+    if let Some(address) = grpc_address {
+        let grpc_clock: SharedClock = clock.clone();
+        servers.push(Box::new(move || serve_grpc(&address, grpc_clock)));
+    }
+    if let Some(address) = http_address {
+        servers.push(Box::new(move || serve_http(&address, clock)));
+    }
+    // The last server runs in the foreground, keeping the others going.
+    let Some(foreground) = servers.pop() else {
+        eprintln!("'punch serve' needs a '--socket <path>', an '--http <address>' and/or a '--grpc <address>' to listen on!");
+        exit(1);
+    };
+    for server in servers {
+        thread::spawn(server);
+    }
+    foreground();
+}
+
+/// One of the `MAX_CONNECTIONS` HTTP connections served at a time, given back when dropped.
+struct ConnectionSlot;
+
+impl ConnectionSlot {
+    fn take() -> Option<Self> {
+        if OPEN_CONNECTIONS.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+            OPEN_CONNECTIONS.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+        return Some(Self);
+    }
+}
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        OPEN_CONNECTIONS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn serve_http(address: &String, clock: SharedClock) {
+    let listener: TcpListener = TcpListener::bind(address).unwrap_or_else(|err| {
+        eprintln!("Couldn't listen on '{}': {}", address, err);
+        exit(1);
+    });
+    println!("Listening on http://{}", address);
+    for stream_result in listener.incoming() {
+        match stream_result {
+            Ok(stream) => {
+                set_connection_timeouts(&stream);
+                let Some(slot) = ConnectionSlot::take() else {
+                    write_json_error(&stream, 503, "Too many connections at once. Try again shortly.");
+                    continue;
+                };
+                let connection_clock: SharedClock = clock.clone();
+                thread::spawn(move || {
+                    handle_http_connection(stream, connection_clock.as_ref());
+                    drop(slot);
+                });
+            },
+            Err(err) => eprintln!("Connection failed: {}", err),
+        }
+    }
+}
+
+/// Serves only `/metrics`, for the daemon.
+pub fn serve_metrics(address: &String, clock: SharedClock) {
+    let listener: TcpListener = TcpListener::bind(address).unwrap_or_else(|err| {
+        eprintln!("Couldn't listen on '{}': {}", address, err);
+        exit(1);
+    });
+    println!("Serving metrics on http://{}/metrics", address);
+    for stream in listener.incoming().map_while(Result::ok) {
+        set_connection_timeouts(&stream);
+        match HttpRequest::read_from(&stream) {
+            Ok(request) if request.method == "GET" && request.path == "/metrics" => {
+                match check_access(get_request_token(&request).as_ref(), Scope::Read) {
+                    Ok(()) => write_response(&stream, 200, METRICS_CONTENT_TYPE, &get_metrics_text(&clock.now())),
+                    Err((status, msg)) => write_json_error(&stream, status, &msg),
+                }
+            },
+            Ok(_) => write_json_error(&stream, 404, "Not found"),
+            Err((status, msg)) => write_json_error(&stream, status, &msg),
+        }
+    }
+}
+
+fn handle_http_connection(stream: TcpStream, clock: &dyn Clock) {
+    let request: HttpRequest = match HttpRequest::read_from(&stream) {
+        Ok(request) => request,
+        Err((status, msg)) => return write_json_error(&stream, status, &msg),
+    };
+    let path_parts: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+    #[cfg(feature = "web")]
+    if request.method == "GET" && path_parts == [""] {
+        return write_response(&stream, 200, "text/html; charset=utf-8", DASHBOARD_HTML);
+    }
+    let scope: Scope = match (request.method.as_str(), path_parts.as_slice()) {
+        ("POST", [method]) if WRITE_METHODS.contains(method) => Scope::Write,
+        _ => Scope::Read,
+    };
+    if let Err((status, msg)) = check_access(get_request_token(&request).as_ref(), scope) {
+        return write_json_error(&stream, status, &msg);
+    }
+    if request.method == "GET" && path_parts == ["metrics"] {
+        return write_response(&stream, 200, METRICS_CONTENT_TYPE, &get_metrics_text(&clock.now()));
+    }
+    if request.method == "GET" && path_parts == ["calendar.ics"] {
+        return match get_calendar_feed(&request, &clock.now()) {
+            Ok(ics) => write_response(&stream, 200, CALENDAR_CONTENT_TYPE, &ics),
+            Err(msg) => write_json_error(&stream, 400, &msg),
+        };
+    }
+    if path_parts == ["graphql"] && ["GET", "POST"].contains(&request.method.as_str()) {
+        return handle_graphql_request(&stream, &request, &clock.now());
+    }
+    let (method, params): (String, JsonValue) = match (request.method.as_str(), path_parts.as_slice()) {
+        ("GET", ["status"]) => ("status".to_string(), JsonValue::Null),
+        ("GET", ["day"]) => ("day".to_string(), JsonValue::Null),
+        ("GET", ["day", date]) => (
+            "day".to_string(),
+            JsonValue::Object(vec![("date".to_string(), JsonValue::String(date.to_string()))]),
+        ),
+        ("GET", ["report"]) => (
+            "report".to_string(),
+            JsonValue::Object(request.query.iter()
+                .map(|(key, value)| (key.clone(), JsonValue::String(value.clone())))
+                .collect()),
+        ),
+        ("POST", [write_method]) if WRITE_METHODS.contains(write_method) => {
+            let params: JsonValue = match request.body.trim() {
+                "" => JsonValue::Null,
+                body => match parse_json(body) {
+                    Ok(params) => params,
+                    Err(msg) => return write_json_error(&stream, 400, &format!("Invalid JSON: {}", msg)),
+                },
+            };
+            (write_method.to_string(), params)
+        },
+        ("GET", _) | ("POST", _) => return write_json_error(&stream, 404, "Not found"),
+        (_, _) => return write_json_error(&stream, 405, "Only GET and POST are supported"),
+    };
+    match handle_request(&method, &params, &clock.now()) {
+        Ok(result) => write_response(&stream, 200, JSON_CONTENT_TYPE, &result.as_string()),
+        Err(msg) => write_json_error(&stream, 400, &msg),
+    }
+}
+
+/// The blocks worked over the last `?days=` days (90 by default) as an iCalendar feed, for
+/// subscribing to from a calendar app at `webcal://<address>/calendar.ics`. Breaks are left out.
+fn get_calendar_feed(request: &HttpRequest, now: &DateTime<Local>) -> Result<String, String> {
+    let num_days: i64 = match request.query.get("days") {
+        Some(days_str) => days_str.parse().ok().filter(|days: &i64| *days > 0)
+            .ok_or(format!("Expected a number of days above 0! Given: '{}'", days_str))?,
+        None => DEFAULT_CALENDAR_DAYS,
+    };
+    let today: NaiveDate = now.date_naive();
+    let range: DateRange = DateRange::new(today - Duration::days(num_days - 1), today)?;
+    let mut events: Vec<FeedEvent> = Vec::new();
+    for day in DayIterator::new(&range).map(|day: Day| close_day_at(day, now)) {
+        let date: String = day.get_day_start().as_dt().format(DATE_FMT).to_string();
+        for (ind, block) in day.timeblocks.iter().enumerate() {
+            let Some(end) = block.get_end().filter(|_| !day.is_break(ind)) else {
+                continue;
+            };
+            events.push(FeedEvent {
+                uid: format!("{}-{}@punch", date, ind),
+                summary: block.get_task_name(),
+                description: block.get_notes().iter().map(|note: &Note| note.get_msg()).collect::<Vec<String>>().join("\n"),
+                start: block.get_start().as_dt(),
+                end: end.as_dt(),
+            });
+        }
+    }
+    return Ok(build_ics("punch", &events, now));
+}
+
+/// GraphQL queries come as `{"query": .., "variables": {..}}` in a POST body, or as `?query=`
+/// (and `&variables=`) on a GET, which shows the schema when there's no query.
+fn handle_graphql_request(stream: &TcpStream, request: &HttpRequest, now: &DateTime<Local>) {
+    let params: JsonValue = match (request.method.as_str(), request.query.get("query")) {
+        ("GET", None) => return write_response(stream, 200, "text/plain; charset=utf-8", GRAPHQL_SCHEMA),
+        ("GET", Some(query)) => {
+            let variables: JsonValue = match request.query.get("variables").map(|x: &String| parse_json(x)) {
+                Some(Ok(variables)) => variables,
+                Some(Err(msg)) => return write_json_error(stream, 400, &format!("Invalid JSON in 'variables': {}", msg)),
+                None => JsonValue::Null,
+            };
+            JsonValue::Object(vec![("query".to_string(), JsonValue::String(query.clone())), ("variables".to_string(), variables)])
+        },
+        _ => match parse_json(&request.body) {
+            Ok(params) => params,
+            Err(msg) => return write_json_error(stream, 400, &format!("Invalid JSON: {}", msg)),
+        },
+    };
+    let body: JsonValue = match handle_request("graphql", &params, now) {
+        Ok(data) => JsonValue::Object(vec![("data".to_string(), data)]),
+        Err(msg) => JsonValue::Object(vec![(
+            "errors".to_string(),
+            JsonValue::Array(vec![JsonValue::Object(vec![("message".to_string(), JsonValue::String(msg))])]),
+        )]),
+    };
+    write_response(stream, 200, JSON_CONTENT_TYPE, &body.as_string());
+}
+
+/// The token of a request, from an `Authorization: Bearer` header or else a `?token=`, for
+/// calendar apps and dashboards that can't send headers.
+fn get_request_token(request: &HttpRequest) -> Option<String> {
+    return request.get_bearer_token().or(request.query.get("token").cloned());
+}
+
+fn write_json_error(stream: &TcpStream, status: u16, msg: &str) {
+    let body: JsonValue = JsonValue::Object(vec![("error".to_string(), JsonValue::String(msg.to_string()))]);
+    write_response(stream, status, JSON_CONTENT_TYPE, &body.as_string());
+}
+
+#[cfg(unix)]
+fn serve_socket(socket_path: &String, clock: SharedClock) {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    let _ = std::fs::remove_file(socket_path);
+    let listener: UnixListener = UnixListener::bind(socket_path).unwrap_or_else(|err| {
+        eprintln!("Couldn't listen on '{}': {}", socket_path, err);
+        exit(1);
+    });
+    println!("Listening on '{}'", socket_path);
+    for stream_result in listener.incoming() {
+        let stream: UnixStream = match stream_result {
+            Ok(stream) => stream,
+            Err(err) => {
+                eprintln!("Connection failed: {}", err);
+                continue;
+            },
+        };
+        let connection_clock: SharedClock = clock.clone();
+        thread::spawn(move || {
+            let mut writer: UnixStream = stream.try_clone().expect("Should be able to clone the stream");
+            for line in BufReader::new(stream).lines().map_while(Result::ok) {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let response: JsonValue = handle_request_line(&line, &connection_clock.now());
+                if writeln!(writer, "{}", response.as_string()).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+#[cfg(not(unix))]
+fn serve_socket(_socket_path: &String, _clock: SharedClock) {
+    eprintln!("'punch serve --socket' needs Unix sockets, which aren't available on this platform.");
+    exit(1);
+}
+
+/// Handles one request line of the protocol: `{"id": .., "method": .., "params": {..}}`.
+/// Responds with `{"id": .., "result": ..}` or `{"id": .., "error": ".."}`.
+pub fn handle_request_line(line: &str, now: &DateTime<Local>) -> JsonValue {
+    let request: JsonValue = match parse_json(line) {
+        Ok(request) => request,
+        Err(msg) => return get_response(JsonValue::Null, Err(format!("Invalid JSON: {}", msg))),
+    };
+    let id: JsonValue = request.get("id").cloned().unwrap_or(JsonValue::Null);
+    let result: Result<JsonValue, String> = match request.get("method").and_then(|x: &JsonValue| x.as_str()) {
+        Some(method) => handle_request(method, request.get("params").unwrap_or(&JsonValue::Null), now),
+        None => Err("Requests need a 'method'".to_string()),
+    };
+    return get_response(id, result);
+}
+
+fn get_response(id: JsonValue, result: Result<JsonValue, String>) -> JsonValue {
+    return match result {
+        Ok(value) => JsonValue::Object(vec![("id".to_string(), id), ("result".to_string(), value)]),
+        Err(msg) => JsonValue::Object(vec![("id".to_string(), id), ("error".to_string(), JsonValue::String(msg))]),
+    };
+}
+
+pub fn handle_request(method: &str, params: &JsonValue, now: &DateTime<Local>) -> Result<JsonValue, String> {
+    let get_param = |key: &str| -> Option<String> {
+        return params.get(key).and_then(|x: &JsonValue| x.as_str()).map(|x: &str| x.to_string());
+    };
+    return match method {
+        "status" => {
+            let day: Day = get_current_day(now)?;
+            Ok(get_status_json(now, &day))
+        },
+        "day" => {
+            let day: Day = match get_param("date") {
+                Some(date_str) => {
+                    let date: NaiveDate = parse_date(&date_str)?;
+                    read_recorded_day(&date)?
+                },
+                None => get_current_day(now)?,
+            };
+            Ok(JsonValue::from_serializable(&day))
+        },
+        "report" => {
+            let mut range_args: Vec<String> = Vec::new();
+            for key in ["period", "from", "to"] {
+                if let Some(value) = get_param(key) {
+                    range_args.push(format!("--{}", key));
+                    range_args.push(value);
+                }
+            }
+            let range: DateRange = resolve_range_from_args(now, &range_args)?;
+            Ok(build_period_report(&range, &get_summaries_in_range(&range, now)).as_json())
+        },
+        "graphql" => {
+            let query: String = get_param("query").ok_or("'graphql' needs a 'query' param".to_string())?;
+            run_graphql(&query, params.get("variables").unwrap_or(&JsonValue::Null), now)
+        },
+        method if WRITE_METHODS.contains(&method) && is_read_only() => {
+            Err(format!("'{}' changes your days, and punch is running with --read-only.", method))
+        },
+        method if WRITE_METHODS.contains(&method) => {
+            let mut args: Vec<String> = vec![method.to_string()];
+            if let Some(JsonValue::Array(values)) = params.get("args") {
+                for value in values {
+                    args.push(value.as_str().ok_or("'args' should be a list of strings".to_string())?.to_string());
+                }
+            }
+            if let Some(at) = get_param("at") {
+                args.push("--at".to_string());
+                args.push(at);
+            }
+            let _lock: DataLock = lock_data_dir()?;
+            let mut state: BatchState = BatchState::new();
+            let msg: String = apply_args(&mut state, args, now, &get_config())?;
+            state.commit()?;
+            Ok(JsonValue::Object(vec![("message".to_string(), JsonValue::String(msg))]))
+        },
+        other => Err(format!("Unknown method '{}'", other)),
+    };
+}
@@ -0,0 +1,151 @@
+use std::env::var;
+use std::process::{Command, exit};
+
+use crate::utils::args::{get_positional_args, has_flag};
+use crate::utils::config::get_config;
+use crate::utils::file_io::expand_path;
+use crate::utils::reminders::Reminder;
+
+const DAEMON_UNIT: &str = "punch-daemon.service";
+const REMINDER_UNIT_PREFIX: &str = "punch-reminder-";
+
+/// A unit file's name and contents.
+struct UnitFile {
+    name: String,
+    contents: String,
+}
+
+/// Writes user-level systemd units that run the daemon, and a timer for each reminder in the
+/// config, then enables and starts them. Run it again after changing the reminders.
+pub fn install_service(other_args: Vec<String>) {
+    let exe: String = std::env::current_exe()
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or_else(|err| {
+            eprintln!("Couldn't find the punch executable: {}", err);
+            exit(1);
+        });
+    let units: Vec<UnitFile> = get_unit_files(&exe, get_config().reminders()).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+    if has_flag(&other_args, "--dry-run") {
+        for unit in &units {
+            println!("# {}\n{}", unit.name, unit.contents);
+        }
+        return;
+    }
+
+    let unit_dir: String = get_unit_dir();
+    if let Err(err) = std::fs::create_dir_all(&unit_dir) {
+        eprintln!("Couldn't create '{}': {}", unit_dir, err);
+        exit(1);
+    }
+    // Reminders that are no longer in the config are stopped before their units go.
+    let stale_units: Vec<String> = get_stale_reminder_units(&unit_dir, &units);
+    let stale_timers: Vec<&str> = stale_units.iter()
+        .map(|x: &String| x.as_str())
+        .filter(|name: &&str| name.ends_with(".timer"))
+        .collect();
+    if !stale_timers.is_empty() {
+        let _ = run_systemctl(&[&["disable", "--now"], stale_timers.as_slice()].concat());
+    }
+    for name in &stale_units {
+        let _ = std::fs::remove_file(unit_dir.clone() + name);
+    }
+    for unit in &units {
+        let path: String = unit_dir.clone() + &unit.name;
+        if let Err(err) = std::fs::write(&path, &unit.contents) {
+            eprintln!("Couldn't write '{}': {}", path, err);
+            exit(1);
+        }
+        println!("Wrote '{}'.", path);
+    }
+    // The timers start their services, so only the daemon and the timers are enabled.
+    let to_enable: Vec<&str> = units.iter()
+        .map(|unit: &UnitFile| unit.name.as_str())
+        .filter(|name: &&str| *name == DAEMON_UNIT || name.ends_with(".timer"))
+        .collect();
+    let result: Result<(), String> = run_systemctl(&["daemon-reload"])
+        .and_then(|_| run_systemctl(&[&["enable", "--now"], to_enable.as_slice()].concat()));
+    match result {
+        Ok(()) => println!("Enabled and started {}. Check on them with `punch service status`.", to_enable.join(", ")),
+        Err(msg) => {
+            eprintln!("{}", msg);
+            exit(1);
+        },
+    }
+}
+
+/// `punch service status`: shows whether the daemon is running and when the reminders go off next.
+pub fn service(other_args: Vec<String>) {
+    let positional_args: Vec<String> = get_positional_args(&other_args, &[]);
+    match positional_args.first().map(|x: &String| x.as_str()) {
+        Some("status") => {
+            let _ = Command::new("systemctl").args(["--user", "status", "--no-pager", DAEMON_UNIT]).status();
+            let timer_pattern: String = format!("{}*", REMINDER_UNIT_PREFIX);
+            let _ = Command::new("systemctl").args(["--user", "list-timers", "--all", "--no-pager", &timer_pattern]).status();
+        },
+        _ => {
+            eprintln!("Usage: punch service status");
+            exit(1);
+        },
+    }
+}
+
+fn get_unit_dir() -> String {
+    let config_home: String = var("XDG_CONFIG_HOME").unwrap_or(expand_path("~/.config"));
+    return config_home.trim_end_matches('/').to_string() + "/systemd/user/";
+}
+
+fn get_unit_files(exe: &str, reminders: &Vec<Reminder>) -> Result<Vec<UnitFile>, String> {
+    let mut units: Vec<UnitFile> = vec![UnitFile {
+        name: DAEMON_UNIT.to_string(),
+        contents: format!(
+            concat!(
+                "[Unit]\nDescription=punch daemon\n\n",
+                "[Service]\nExecStart=\"{}\" daemon{}\nRestart=on-failure\n\n",
+                "[Install]\nWantedBy=default.target\n"),
+            exe, if reminders.is_empty() {""} else {" --no-reminders"}),
+    }];
+    for (ind, reminder) in reminders.iter().enumerate() {
+        let time: String = reminder.get_time()?.format("%H:%M:00").to_string();
+        let weekdays: String = match reminder.weekdays.is_empty() {
+            true => String::new(),
+            false => reminder.weekdays.join(",") + " ",
+        };
+        let name: String = format!("{}{}", REMINDER_UNIT_PREFIX, ind);
+        units.push(UnitFile {
+            name: name.clone() + ".service",
+            contents: format!(
+                "[Unit]\nDescription=punch reminder at {}\n\n[Service]\nType=oneshot\nExecStart=\"{}\" daemon --reminder {}\n",
+                reminder.at, exe, ind),
+        });
+        units.push(UnitFile {
+            name: name + ".timer",
+            contents: format!(
+                "[Unit]\nDescription=punch reminder at {}\n\n[Timer]\nOnCalendar={}*-*-* {}\nPersistent=false\n\n[Install]\nWantedBy=timers.target\n",
+                reminder.at, weekdays, time),
+        });
+    }
+    return Ok(units);
+}
+
+/// The reminder units in `unit_dir` for reminders that are no longer in the config.
+fn get_stale_reminder_units(unit_dir: &str, units: &Vec<UnitFile>) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(unit_dir) else {
+        return Vec::new();
+    };
+    return entries.flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name: &String| name.starts_with(REMINDER_UNIT_PREFIX) && !units.iter().any(|unit: &UnitFile| unit.name == *name))
+        .collect();
+}
+
+fn run_systemctl(args: &[&str]) -> Result<(), String> {
+    let status = Command::new("systemctl").arg("--user").args(args).status()
+        .map_err(|err| format!("Couldn't run systemctl: {}", err))?;
+    if !status.success() {
+        return Err(format!("'systemctl --user {}' failed", args.join(" ")));
+    }
+    return Ok(());
+}
@@ -0,0 +1,84 @@
+use chrono::prelude::Weekday;
+
+use crate::utils::config::{Config, DEFAULT_BREAK_TASK, DEFAULT_PUNCH_IN_TASK, create_config, get_config_path};
+use crate::utils::file_io::{BASE_DIR, get_editor, is_absolute_path};
+use crate::utils::prompt::ask;
+
+/// Runs on first launch, when there's no config yet: asks for the main settings and writes
+/// them to a new, commented config, rather than leaving defaults to be found out about later.
+pub fn setup_wizard() {
+    println!("Welcome to punch! There's no config yet, so let's set one up.");
+    println!("Press enter to keep the suggestion in brackets. You can change any of it later with `punch edit-config`.");
+    println!();
+    let day_in_minutes: i64 = ask_until_valid("How long is your working day, in hours (or H:MM)?", "8", parse_day_length);
+    let work_days: Vec<String> = ask_until_valid("Which days of the week do you work?", "Mon-Fri", parse_work_days);
+    let punch_in_task: String = ask("What should `punch in` call your first task of the day?", DEFAULT_PUNCH_IN_TASK);
+    let data_dir: String = ask_until_valid("Where should your days be kept?", BASE_DIR, parse_data_dir);
+    let default_editor: String = get_editor().join(" ");
+    let editor: String = ask("Which editor should `punch edit` open?", &default_editor);
+
+    let mut config: Config = Config::new(day_in_minutes, punch_in_task, DEFAULT_BREAK_TASK.to_owned());
+    config.set_work_days(Some(work_days));
+    config.set_data_dir(Some(data_dir).filter(|dir: &String| dir.trim_end_matches('/') != BASE_DIR.trim_end_matches('/')));
+    config.set_editor(Some(editor).filter(|editor: &String| *editor != default_editor));
+    create_config(&config);
+    println!();
+    println!("Saved your config to '{}'. Start your day with `punch in`.", get_config_path());
+}
+
+/// Keeps asking until the answer parses, saying what was wrong with it each time.
+fn ask_until_valid<T>(question: &str, default: &str, parse: fn(&str) -> Result<T, String>) -> T {
+    loop {
+        match parse(&ask(question, default)) {
+            Ok(value) => return value,
+            Err(msg) => println!("{}", msg),
+        }
+    }
+}
+
+/// A folder that doesn't depend on where punch is run from.
+fn parse_data_dir(text: &str) -> Result<String, String> {
+    if !is_absolute_path(text.trim()) {
+        return Err(format!("'{}' would depend on the folder punch is run from. Give a full path, or one starting with ~/.", text.trim()));
+    }
+    return Ok(text.trim().to_string());
+}
+
+/// Minutes in a day given as hours, like "7.5", or as "7:30".
+fn parse_day_length(text: &str) -> Result<i64, String> {
+    let minutes: Option<f64> = match text.trim().split_once(':') {
+        Some((hours, minutes)) => hours.parse::<u32>().ok().zip(minutes.parse::<u32>().ok().filter(|x: &u32| *x < 60))
+            .map(|(hours, minutes)| (hours * 60 + minutes) as f64),
+        None => text.trim().parse::<f64>().ok().map(|hours: f64| hours * 60.0),
+    };
+    return match minutes {
+        Some(minutes) if minutes > 0.0 && minutes <= 24.0 * 60.0 => Ok(minutes.round() as i64),
+        _ => Err(format!("'{}' isn't a length of day. Give hours like 8 or 7.5, or hours and minutes like 7:30.", text)),
+    };
+}
+
+/// Days of the week, like "Mon-Fri", "mon,tue,thu" or "Sun-Thu, Sat", in the order given.
+fn parse_work_days(text: &str) -> Result<Vec<String>, String> {
+    let invalid = || format!("'{}' isn't a list of days. Give days like Mon-Fri, or Mon,Tue,Thu.", text);
+    let mut days: Vec<Weekday> = Vec::new();
+    for part in text.split(',').map(|x: &str| x.trim()).filter(|x: &&str| !x.is_empty()) {
+        let (first, last): (&str, &str) = part.split_once('-').unwrap_or((part, part));
+        let (Ok(first), Ok(last)) = (first.trim().parse::<Weekday>(), last.trim().parse::<Weekday>()) else {
+            return Err(invalid());
+        };
+        let mut day: Weekday = first;
+        loop {
+            if !days.contains(&day) {
+                days.push(day);
+            }
+            if day == last {
+                break;
+            }
+            day = day.succ();
+        }
+    }
+    if days.is_empty() {
+        return Err(invalid());
+    }
+    return Ok(days.iter().map(|day: &Weekday| day.to_string()).collect());
+}
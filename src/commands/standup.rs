@@ -0,0 +1,141 @@
+use std::process::exit;
+use chrono::prelude::{DateTime, Local, NaiveDate, NaiveTime};
+
+use crate::units::components::{Note, PlannedBlock};
+use crate::units::day::{Day, get_current_day, list_recorded_dates, read_day_for_date, write_day};
+use crate::utils::aggregate::format_secs;
+use crate::utils::args::{get_flag_value, get_positional_args};
+use crate::utils::report::{Report, ReportSection, ReportValue};
+use crate::commands::report::print_report;
+
+/// Adds something you mean to get done today, e.g. `punch plan "review the API changes"`,
+/// or a block of time for a task with `punch plan --minutes 90 [--at 09:00] Writing`.
+pub fn plan(now: &DateTime<Local>, mut day: Day, other_args: Vec<String>) {
+    if let Some(minutes_str) = get_flag_value(&other_args, "--minutes") {
+        let minutes: u64 = minutes_str.trim().parse().unwrap_or_else(|_| {
+            eprintln!("'--minutes {}' should be a whole number of minutes.", minutes_str);
+            exit(1);
+        });
+        let start: Option<String> = get_flag_value(&other_args, "--at").map(|at: String| match NaiveTime::parse_from_str(at.trim(), "%H:%M") {
+            Ok(time) => time.format("%H:%M").to_string(),
+            Err(_) => {
+                eprintln!("'--at {}' should be a time like 09:30.", at);
+                exit(1);
+            },
+        });
+        let task: String = get_positional_args(&other_args, &["--minutes", "--at"]).join(" ");
+        if task.trim().is_empty() {
+            eprintln!("'punch plan --minutes' needs a task, e.g. 'punch plan --minutes 90 Writing'");
+            exit(1);
+        }
+        let planned: PlannedBlock = PlannedBlock::new(task, minutes, start);
+        println!("Planned a block: {}", planned.describe());
+        day.planned_blocks.push(planned);
+        write_day(&day);
+        return;
+    }
+    let text: String = get_entry_text("plan", &other_args);
+    day.plans.push(Note::new(now, &text));
+    write_day(&day);
+    println!("Planned '{}' for today.", text);
+}
+
+/// Adds something that's holding you up, e.g. `punch blocker "waiting on staging access"`.
+pub fn blocker(now: &DateTime<Local>, mut day: Day, other_args: Vec<String>) {
+    let text: String = get_entry_text("blocker", &other_args);
+    day.blockers.push(Note::new(now, &text));
+    write_day(&day);
+    println!("Noted blocker '{}'.", text);
+}
+
+/// Prints what was worked on the last day before today, today's plans and any blockers,
+/// ready to paste into a standup. With `--format`, it's given as a report.
+pub fn standup(now: &DateTime<Local>, other_args: Vec<String>) {
+    // Yesterday's day only counts as today's if it's still going past midnight.
+    let today: Option<Day> = get_current_day(now).ok()
+        .filter(|day: &Day| day.get_day_start().as_dt().date_naive() == now.date_naive() || !day.has_ended());
+    let today_date: NaiveDate = match &today {
+        Some(day) => day.get_day_start().as_dt().date_naive(),
+        None => now.date_naive(),
+    };
+    let previous: Option<(NaiveDate, Day)> = list_recorded_dates().into_iter().rev()
+        .find(|date: &NaiveDate| *date < today_date)
+        .and_then(|date: NaiveDate| read_day_for_date(&date).ok().map(|day: Day| (date, day)));
+    let plans: Vec<Note> = today.as_ref().map(|day: &Day| day.plans.clone()).unwrap_or_default();
+    let blockers: Vec<Note> = today.as_ref().map(|day: &Day| day.blockers.clone()).unwrap_or_default();
+    if get_flag_value(&other_args, "--format").is_some() {
+        print_report(build_standup_report(&today_date, previous.as_ref(), &plans, &blockers), &other_args, now);
+        return;
+    }
+
+    match &previous {
+        Some((date, day)) => {
+            println!("Yesterday ({}):", date);
+            for (task, secs) in get_task_times(day) {
+                println!("- {} ({})", task, format_secs(secs));
+            }
+        },
+        None => {
+            println!("Yesterday:");
+            println!("- Nothing recorded");
+        },
+    }
+
+    println!("Today:");
+    if plans.is_empty() {
+        println!("- Nothing planned yet. Add plans with 'punch plan \"...\"'");
+    }
+    for plan in plans {
+        println!("- {}", plan.get_msg());
+    }
+
+    println!("Blockers:");
+    if blockers.is_empty() {
+        println!("- None");
+    }
+    for blocker in blockers {
+        println!("- {}", blocker.get_msg());
+    }
+}
+
+/// The time spent on each of the day's tasks, in the order they were first worked on.
+fn get_task_times(day: &Day) -> Vec<(String, i64)> {
+    let task_times = day.get_task_times_secs_and_num_blocks();
+    return day.get_tasks_in_chronological_order().into_iter()
+        .map(|task: String| {
+            let secs: i64 = task_times.get(&task).map(|x| x.0).unwrap_or(0);
+            return (task, secs);
+        })
+        .collect();
+}
+
+fn build_standup_report(today_date: &NaiveDate, previous: Option<&(NaiveDate, Day)>, plans: &Vec<Note>, blockers: &Vec<Note>) -> Report {
+    let mut report: Report = Report::new(format!("Standup for {}", today_date));
+    let mut yesterday_section: ReportSection = ReportSection::new("yesterday", &["date", "task", "time"]);
+    if let Some((date, day)) = previous {
+        for (task, secs) in get_task_times(day) {
+            yesterday_section.add_row(vec![date.to_string().into(), task.into(), ReportValue::Secs(secs)]);
+        }
+    }
+    let mut plans_section: ReportSection = ReportSection::new("plans", &["plan"]);
+    for plan in plans {
+        plans_section.add_row(vec![plan.get_msg().into()]);
+    }
+    let mut blockers_section: ReportSection = ReportSection::new("blockers", &["blocker"]);
+    for blocker in blockers {
+        blockers_section.add_row(vec![blocker.get_msg().into()]);
+    }
+    report.add_section(yesterday_section);
+    report.add_section(plans_section);
+    report.add_section(blockers_section);
+    return report;
+}
+
+fn get_entry_text(command: &str, other_args: &Vec<String>) -> String {
+    let text: String = get_positional_args(other_args, &[]).join(" ");
+    if text.trim().is_empty() {
+        eprintln!("'punch {}' needs some text, e.g. 'punch {} \"...\"'", command, command);
+        exit(1);
+    }
+    return text;
+}
@@ -0,0 +1,307 @@
+use std::io::{Write, stdout};
+use std::process::exit;
+use std::thread::sleep;
+use std::time::Duration as StdDuration;
+use chrono::prelude::{DateTime, Local};
+use chrono::Duration;
+
+use crate::units::components::TimeBlock;
+use crate::units::day::{Day, get_current_day};
+use crate::utils::aggregate::{close_day_at, format_secs};
+use crate::utils::args::{get_flag_value, get_interval_secs, has_flag};
+use crate::utils::clock::Clock;
+use crate::utils::color::{GREEN, RED, RESET};
+use crate::utils::config::{Config, get_config};
+use crate::utils::state::get_state;
+use crate::utils::json::JsonValue;
+use crate::commands::timeline::format_annotations;
+
+const DEFAULT_WATCH_INTERVAL_SECS: u64 = 5;
+const CLEAR_SCREEN: &str = "\x1B[2J\x1B[H";
+const DEFAULT_STOPWATCH_INTERVAL_SECS: u64 = 1;
+/// Goes back to the start of the line and clears it, so each refresh overwrites the last.
+const CLEAR_LINE: &str = "\r\x1B[2K";
+
+pub fn status(clock: &dyn Clock, day: Day, other_args: Vec<String>) {
+    if has_flag(&other_args, "--compact") {
+        let color: Option<String> = get_flag_value(&other_args, "--color");
+        match get_compact_status(&clock.now(), &day, color.as_deref()) {
+            Ok(line) => println!("{}", line),
+            Err(msg) => {
+                eprintln!("{}", msg);
+                exit(1);
+            },
+        }
+        return;
+    }
+    if !has_flag(&other_args, "--watch") {
+        print_status(&clock.now(), &day);
+        return;
+    }
+
+    let interval_secs: u64 = get_interval_secs(&other_args).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    }).unwrap_or(DEFAULT_WATCH_INTERVAL_SECS);
+    loop {
+        let tick: DateTime<Local> = clock.now();
+        print!("{}", CLEAR_SCREEN);
+        match get_current_day(&tick) {
+            Ok(current_day) => print_status(&tick, &current_day),
+            Err(msg) => println!("{}", msg),
+        }
+        println!("\n(Refreshing every {} s. Press Ctrl-C to stop.)", interval_secs);
+        sleep(StdDuration::from_secs(interval_secs));
+    }
+}
+
+/// A single line showing how long the current block has gone on and the time left to the
+/// target, rewritten in place every `--interval` seconds, or `watch_interval_secs` from the config.
+pub fn watch(clock: &dyn Clock, other_args: Vec<String>) {
+    let interval_secs: u64 = get_interval_secs(&other_args).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    }).unwrap_or_else(|| get_config().watch_interval_secs().unwrap_or(DEFAULT_STOPWATCH_INTERVAL_SECS));
+    loop {
+        let tick: DateTime<Local> = clock.now();
+        let line: String = match get_current_day(&tick) {
+            Ok(day) => get_watch_line(&tick, &day),
+            Err(_) => "Not punched in".to_string(),
+        };
+        print!("{}{}", CLEAR_LINE, line);
+        stdout().flush().expect("Should be able to flush stdout");
+        sleep(StdDuration::from_secs(interval_secs.max(1)));
+    }
+}
+
+fn get_watch_line(now: &DateTime<Local>, day: &Day) -> String {
+    let closed_day: Day = close_day_at(day.clone(), now);
+    let left_secs: i64 = closed_day.get_time_left_secs().expect("Day is closed");
+    if day.has_ended() {
+        return format!("■ out | left {}", format_stopwatch(left_secs));
+    }
+    let current_block: &TimeBlock = day.timeblocks.last().expect("Expected there to be a block!");
+    let block_secs: i64 = (*now - current_block.get_start().as_dt()).num_seconds();
+    let symbol: &str = if day.on_break {"☕"} else {"▶"};
+    return format!("{} {} {} | left {}", symbol, current_block.get_task_name(), format_stopwatch(block_secs), format_stopwatch(left_secs));
+}
+
+/// Like "1:02:03", or "-0:05:00" once past the target.
+fn format_stopwatch(secs: i64) -> String {
+    let sign: &str = if secs < 0 {"-"} else {""};
+    let secs: i64 = secs.abs();
+    return format!("{}{}:{:02}:{:02}", sign, secs / 3600, secs % 3600 / 60, secs % 60);
+}
+
+/// The line for `punch status --compact`, like "▶ 3h12m (-0h18m)": whether you're working (▶),
+/// on break (☕) or out (■), the time done, and the time done over (+) or under (-) the target.
+/// Scripts may rely on this format. `color` is "ansi" for terminal colors, or "tmux" for tmux's
+/// `#[fg=...]` styles, which show the difference in green once the target is met and red before.
+fn get_compact_status(now: &DateTime<Local>, day: &Day, color: Option<&str>) -> Result<String, String> {
+    let closed_day: Day = close_day_at(day.clone(), now);
+    let done_secs: i64 = closed_day.get_time_done_secs().expect("Day is closed");
+    let diff_secs: i64 = -closed_day.get_time_left_secs().expect("Day is closed");
+    let symbol: &str = match (day.has_ended(), day.on_break) {
+        (true, _) => "■",
+        (false, true) => "☕",
+        (false, false) => "▶",
+    };
+    let sign: &str = if diff_secs < 0 {"-"} else {"+"};
+    let diff: String = format!("{}{}", sign, format_hours_and_mins(diff_secs.abs()));
+    let (color_start, color_end): (&str, &str) = match (color, diff_secs < 0) {
+        (None, _) => ("", ""),
+        (Some("ansi"), true) => (RED, RESET),
+        (Some("ansi"), false) => (GREEN, RESET),
+        (Some("tmux"), true) => ("#[fg=red]", "#[default]"),
+        (Some("tmux"), false) => ("#[fg=green]", "#[default]"),
+        (Some(other), _) => return Err(format!("'--color' should be 'ansi' or 'tmux'! Given: '{}'", other)),
+    };
+    return Ok(format!("{} {} ({}{}{})", symbol, format_hours_and_mins(done_secs), color_start, diff, color_end));
+}
+
+fn format_hours_and_mins(secs: i64) -> String {
+    return format!("{}h{:02}m", secs / 3600, secs % 3600 / 60);
+}
+
+fn print_status(now: &DateTime<Local>, day: &Day) {
+    let current_block: &TimeBlock = day.timeblocks.last().expect("Expected there to be a block!");
+    let closed_day: Day = close_day_at(day.clone(), now);
+    let block_secs: i64 = (*now - current_block.get_start().as_dt()).num_seconds();
+
+    if day.has_ended() {
+        println!("Punched out at {}.", day.get_day_end().expect("Day has ended").as_dt().format("%H:%M"));
+    }
+    else if day.on_break {
+        println!(
+            "On break '{}' since {} ({}).",
+            current_block.get_task_name(), current_block.get_start().as_dt().format("%H:%M"), format_secs(block_secs));
+    }
+    else {
+        println!(
+            "Working on '{}' since {} ({}).",
+            current_block.get_task_name(), current_block.get_start().as_dt().format("%H:%M"), format_secs(block_secs));
+    }
+    println!("Time done today: {}", format_secs(closed_day.get_time_done_secs().expect("Day is closed")));
+    println!("Time spent on break: {}", format_secs(closed_day.get_total_break_time_secs().expect("Day is closed")));
+    println!("Time left today: {}", format_secs(closed_day.get_time_left_secs().expect("Day is closed")));
+    let config: Config = get_config();
+    print_break_budget(&closed_day, &config);
+    if let Some(msg) = get_daily_cap_warning(&closed_day, &config) {
+        println!("{}", msg);
+    }
+    if !day.has_ended() {
+        print_projected_finish(now, &closed_day, &config);
+    }
+}
+
+/// Works out when the target will be hit if work carries on from `now`, leaving room for
+/// whatever is left of the expected break time.
+pub fn get_projected_finish(now: &DateTime<Local>, closed_day: &Day, config: &Config) -> Option<DateTime<Local>> {
+    let time_left: i64 = closed_day.get_time_left_secs().expect("Day is closed so time left is known");
+    if time_left <= 0 {
+        return None;
+    }
+    let break_taken: i64 = closed_day.get_total_break_time_secs().expect("Day is closed so break time is known");
+    let break_left: i64 = (config.expected_break_minutes() as i64 * 60 - break_taken).max(0);
+    return Some(*now + Duration::seconds(time_left + break_left));
+}
+
+pub fn print_projected_finish(now: &DateTime<Local>, closed_day: &Day, config: &Config) {
+    match get_projected_finish(now, closed_day, config) {
+        Some(finish) => println!("You'll hit your target at {}", finish.format("%H:%M")),
+        None => println!("You've already hit your target for today!"),
+    }
+}
+
+pub fn print_break_budget(closed_day: &Day, config: &Config) {
+    let break_secs: i64 = closed_day.get_total_break_time_secs().expect("Day is closed so break time is known");
+    match config.break_budget_left_secs(break_secs) {
+        Some(left) if left >= 0 => println!("Break budget left today: {}", format_secs(left)),
+        Some(left) => println!("Break budget exceeded by: {}", format_secs(-left)),
+        None => (),
+    }
+}
+
+pub fn get_daily_cap_warning(closed_day: &Day, config: &Config) -> Option<String> {
+    let done_secs: i64 = closed_day.get_time_done_secs().expect("Day is closed so time done is known");
+    let secs_over: i64 = config.secs_over_daily_cap(done_secs);
+    return match (config.max_daily_minutes(), secs_over > 0) {
+        (Some(cap), true) => Some(format!("You're {} over your daily cap of {} m!", format_secs(secs_over), cap)),
+        (_, _) => None,
+    };
+}
+
+/// A compact, single-screen view of the day: where it's at, its blocks, notes and summaries.
+pub fn today(now: &DateTime<Local>, day: Day) {
+    let closed_day: Day = close_day_at(day.clone(), now);
+    let config: Config = get_config();
+    let state: String = if day.has_ended() {
+        "punched out".to_string()
+    }
+    else if day.on_break {
+        format!("on break '{}'", day.get_latest_task_name())
+    }
+    else {
+        format!("working on '{}'", day.get_latest_task_name())
+    };
+    println!("{} - {}", day.get_day_start().as_dt().format("%a %Y-%m-%d"), state);
+
+    let mut totals_line: String = format!(
+        "Done {} | left {} | break {}",
+        format_secs(closed_day.get_time_done_secs().expect("Day is closed")),
+        format_secs(closed_day.get_time_left_secs().expect("Day is closed")),
+        format_secs(closed_day.get_total_break_time_secs().expect("Day is closed")));
+    if !day.has_ended() {
+        if let Some(finish) = get_projected_finish(now, &closed_day, &config) {
+            totals_line += &format!(" | finish ~{}", finish.format("%H:%M"));
+        }
+    }
+    println!("{}", totals_line);
+
+    println!("Blocks:");
+    for (ind, block) in closed_day.timeblocks.iter().enumerate() {
+        let end: String = match day.timeblocks[ind].get_end() {
+            Some(end) => end.as_dt().format("%H:%M").to_string(),
+            None => "now".to_string(),
+        };
+        let mut tags: Vec<String> = Vec::new();
+        if day.is_break(ind) {
+            tags.push("break".to_string());
+        }
+        else if block.is_billable() {
+            tags.push("billable".to_string());
+        }
+        if let Some(focus_minutes) = block.get_focus_minutes() {
+            tags.push(format!("focus, {} m planned", focus_minutes));
+        }
+        println!(
+            "\t{:>2}. {}-{:<5} {}{} ({}){}",
+            ind + 1, block.get_start().as_dt().format("%H:%M"), end, block.get_task_name(),
+            tags.iter().map(|tag: &String| format!(" [{}]", tag)).collect::<String>(),
+            format_secs(block.get_length_secs().unwrap_or(0)),
+            format_annotations(block, false));
+    }
+
+    let notes: Vec<(String, String, String)> = day.timeblocks.iter()
+        .flat_map(|block: &TimeBlock| block.get_notes().iter().map(|note| (
+            note.get_time().as_dt().format("%H:%M").to_string(), block.get_task_name(), note.get_msg())))
+        .collect();
+    if !notes.is_empty() {
+        println!("Notes:");
+        for (time, task, msg) in notes {
+            println!("\t{} [{}] {}", time, task, msg);
+        }
+    }
+    if !day.planned_blocks.is_empty() {
+        println!("Planned blocks:");
+        for planned in &day.planned_blocks {
+            println!("\t{}", planned.describe());
+        }
+    }
+    for (heading, entries) in [("Plans", &day.plans), ("Blockers", &day.blockers)] {
+        if !entries.is_empty() {
+            println!("{}:", heading);
+            for entry in entries {
+                println!("\t{} {}", entry.get_time().as_dt().format("%H:%M"), entry.get_msg());
+            }
+        }
+    }
+    if !day.moods.is_empty() {
+        println!("Mood:");
+        for mood in &day.moods {
+            let note: String = mood.get_note().map(|note: &String| format!(" {}", note)).unwrap_or_default();
+            println!("\t{} {}/5{}", mood.get_time().as_dt().format("%H:%M"), mood.get_score(), note);
+        }
+    }
+    if !day.summaries.is_empty() {
+        println!("Summaries:");
+        for summary in &day.summaries {
+            println!("\t{}", summary.as_short_string());
+        }
+    }
+}
+
+/// The same information as `punch status`, for machine consumers.
+pub fn get_status_json(now: &DateTime<Local>, day: &Day) -> JsonValue {
+    let closed_day: Day = close_day_at(day.clone(), now);
+    let config: Config = get_config();
+    let break_secs: i64 = closed_day.get_total_break_time_secs().expect("Day is closed");
+    let projected_finish: JsonValue = match day.has_ended() {
+        true => JsonValue::Null,
+        false => match get_projected_finish(now, &closed_day, &config) {
+            Some(finish) => JsonValue::String(finish.format("%H:%M").to_string()),
+            None => JsonValue::Null,
+        },
+    };
+    return JsonValue::Object(vec![
+        ("date".to_string(), JsonValue::String(day.get_day_start().as_dt().format("%Y-%m-%d").to_string())),
+        ("punched_out".to_string(), JsonValue::Bool(day.has_ended())),
+        ("on_break".to_string(), JsonValue::Bool(day.on_break)),
+        ("task".to_string(), JsonValue::String(day.get_latest_task_name())),
+        ("done_minutes".to_string(), JsonValue::Number(closed_day.get_time_done_secs().expect("Day is closed") / 60)),
+        ("left_minutes".to_string(), JsonValue::Number(closed_day.get_time_left_secs().expect("Day is closed") / 60)),
+        ("break_minutes".to_string(), JsonValue::Number(break_secs / 60)),
+        ("minutes_behind".to_string(), JsonValue::Number(get_state().minutes_behind())),
+        ("projected_finish".to_string(), projected_finish),
+    ]);
+}
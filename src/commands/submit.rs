@@ -0,0 +1,31 @@
+use std::process::exit;
+use chrono::prelude::{DateTime, Local, NaiveDate};
+
+use crate::units::day::{Day, list_recorded_dates, read_day_for_date, write_day};
+use crate::units::interval::Dt;
+use crate::utils::period::{DateRange, resolve_range_from_args};
+
+/// Hands in the days in a period, e.g. `punch submit --period last-week`, marking them
+/// submitted so they become read-only. Every day in the period has to be punched out.
+/// Days that were already submitted keep their first submission time.
+pub fn submit(now: &DateTime<Local>, other_args: Vec<String>) {
+    let range: DateRange = resolve_range_from_args(now, &other_args).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+    let days: Vec<Day> = list_recorded_dates().into_iter()
+        .filter(|date: &NaiveDate| range.contains(date))
+        .filter_map(|date: NaiveDate| read_day_for_date(&date).ok())
+        .collect();
+    if let Some(ongoing) = days.iter().find(|day: &&Day| !day.has_ended()) {
+        eprintln!("The day for {} hasn't been punched out yet, so {} can't be submitted.", ongoing.get_day_start().as_dt().date_naive(), range.as_string());
+        exit(1);
+    }
+    let mut num_submitted: usize = 0;
+    for mut day in days.into_iter().filter(|day: &Day| day.submitted.is_none()) {
+        day.submitted = Some(Dt(*now));
+        write_day(&day);
+        num_submitted += 1;
+    }
+    println!("Submitted {} days for {}. They can only be changed with --force now.", num_submitted, range.as_string());
+}
@@ -0,0 +1,68 @@
+use std::io::{IsTerminal, stdin};
+use chrono::prelude::{DateTime, Local};
+
+use crate::units::day::Day;
+use crate::units::interval::Dt;
+use crate::utils::aggregate::format_secs;
+use crate::utils::config::{Config, get_config};
+use crate::utils::prompt::{ask, choose};
+use crate::utils::samples::{SuggestedSpan, WindowSamplingConfig, read_samples, suggest_breakdown};
+
+/// Offers to break the day's unlabeled time (blocks still on the default punch-in task)
+/// into tasks, going by the windows the daemon sampled. Only asks on a terminal.
+pub fn suggest_tasks_for_unlabeled_time(day: &mut Day) {
+    let config: Config = get_config();
+    let Some(window_sampling) = config.window_sampling() else {
+        return;
+    };
+    if !stdin().is_terminal() {
+        return;
+    }
+    let suggestions: Vec<SuggestedSpan> = get_suggestions(day, config.get_default_punch_in_task(), window_sampling);
+    if suggestions.is_empty() {
+        return;
+    }
+    println!("Going by the windows you had open, the time you didn't label might break down like this:");
+    for span in &suggestions {
+        println!("\t{}-{} {} ({})", span.start.format("%H:%M"), span.end.format("%H:%M"), span.task, format_secs((span.end - span.start).num_seconds()));
+    }
+    let options: [(&str, &str); 3] = [("a", "Use these tasks"), ("e", "Go through them one by one"), ("s", "Leave the time unlabeled")];
+    let chosen: Vec<SuggestedSpan> = match choose("What would you like to do?", &options).as_str() {
+        "a" => suggestions,
+        "e" => {
+            println!("Enter a task for each, or '-' to leave it unlabeled.");
+            suggestions.into_iter()
+                .filter_map(|mut span: SuggestedSpan| {
+                    let times: String = format!("{}-{}", span.start.format("%H:%M"), span.end.format("%H:%M"));
+                    span.task = ask(&format!("Task for {}", times), &span.task);
+                    return if span.task == "-" {None} else {Some(span)};
+                })
+                .collect()
+        },
+        _ => Vec::new(),
+    };
+    let mut num_labeled: usize = 0;
+    for span in chosen {
+        match day.relabel_span(&span.start, &span.end, &span.task) {
+            Ok(_) => num_labeled += 1,
+            Err(msg) => eprintln!("Couldn't label {}-{}: {}", span.start.format("%H:%M"), span.end.format("%H:%M"), msg),
+        }
+    }
+    if num_labeled > 0 {
+        println!("Labeled {} of the suggested stretches.", num_labeled);
+    }
+}
+
+fn get_suggestions(day: &Day, unlabeled_task: &str, window_sampling: &WindowSamplingConfig) -> Vec<SuggestedSpan> {
+    let samples: Vec<(DateTime<Local>, String)> = read_samples(&day.get_day_start().as_dt().date_naive());
+    let mut suggestions: Vec<SuggestedSpan> = Vec::new();
+    for (ind, block) in day.timeblocks.iter().enumerate() {
+        let Some(end) = block.get_end().map(|end: Dt| end.as_dt()) else {
+            continue;
+        };
+        if block.get_task_name() == unlabeled_task && !day.is_break(ind) {
+            suggestions.extend(suggest_breakdown(&samples, &block.get_start().as_dt(), &end, window_sampling));
+        }
+    }
+    return suggestions;
+}
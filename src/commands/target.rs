@@ -0,0 +1,47 @@
+use std::process::exit;
+
+use crate::units::day::{Day, write_day};
+use crate::utils::config::get_config;
+use crate::utils::state::{State, get_state, update_state};
+
+/// `punch target 300`: sets how many minutes today needs, in place of the config's
+/// `day_in_minutes`, say for an appointment in the afternoon. `punch target reset` goes back
+/// to the config's, and `punch target` on its own shows today's.
+pub fn target(mut day: Day, other_args: Vec<String>) {
+    let config_minutes: u64 = get_config().day_in_minutes() as u64;
+    let minutes: u64 = match other_args.first().map(|x: &String| x.as_str()) {
+        None => {
+            println!("Today's target: {} minutes{}", day.get_time_to_do(), describe_override(day.get_time_to_do(), config_minutes));
+            return;
+        },
+        Some(_) if other_args.len() > 1 => {
+            eprintln!("'punch target' takes a single number of minutes, like 'punch target 300'.");
+            exit(1);
+        },
+        Some("reset") => config_minutes,
+        Some(minutes_str) => minutes_str.parse().unwrap_or_else(|_| {
+            eprintln!("Expected the minutes to work today, like '300'! Given: '{}'", minutes_str);
+            exit(1);
+        }),
+    };
+
+    let time_left_before: Option<i64> = day.get_time_left_secs();
+    let old_minutes: u64 = day.get_time_to_do();
+    day.time_to_do = minutes;
+    write_day(&day);
+    println!("Today's target is now {} minutes (was {}){}", minutes, old_minutes, describe_override(minutes, config_minutes));
+
+    // Finished days have already counted towards minutes behind, so only the change is applied.
+    if let (Some(before), Some(after)) = (time_left_before, day.get_time_left_secs()) {
+        let mut state: State = get_state();
+        state.update_minutes_behind(after / 60 - before / 60);
+        update_state(&state);
+    }
+}
+
+fn describe_override(minutes: u64, config_minutes: u64) -> String {
+    return match minutes == config_minutes {
+        true => String::new(),
+        false => format!(", in place of the usual {}", config_minutes),
+    };
+}
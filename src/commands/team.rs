@@ -0,0 +1,85 @@
+use std::collections::BTreeMap;
+use std::process::exit;
+use chrono::prelude::{DateTime, Local, NaiveDate};
+
+use crate::units::day::{DAILY_DIR, Day};
+use crate::units::interval::DATE_FMT;
+use crate::utils::aggregate::close_day_at;
+use crate::utils::args::get_positional_args;
+use crate::utils::config::get_config;
+use crate::utils::file_io::{FromString, read_file};
+use crate::utils::period::{DateRange, resolve_range_from_args};
+use crate::utils::report::{Report, ReportSection, ReportValue};
+use crate::utils::team::TeamConfig;
+use crate::commands::report::print_report;
+
+const NO_PROJECT: &str = "(no project)";
+
+/// Team commands, e.g. `punch team report --period last-month`, which adds up everyone's
+/// hours per project. Takes the same `--period`, `--from`, `--to` and `--format` flags as
+/// `punch report`.
+pub fn team(now: &DateTime<Local>, other_args: Vec<String>) {
+    let team: TeamConfig = match get_config().team() {
+        Some(team) => team.clone(),
+        None => {
+            eprintln!("Team mode is off. Add a 'team' section to the config, e.g. 'team: {{root: ~/Dropbox/studio-punch, user: ravi}}'.");
+            exit(1);
+        },
+    };
+    match get_positional_args(&other_args, &["--period", "--from", "--to", "--format", "--section", "--output"]).first().map(|x: &String| x.as_str()) {
+        Some("report") => {},
+        _ => {
+            eprintln!("Try 'punch team report'.");
+            exit(1);
+        },
+    }
+    let range: DateRange = resolve_range_from_args(now, &other_args).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+
+    let mut report: Report = Report::new(format!("Team report for {}", range.as_string()));
+    let mut people_section: ReportSection = ReportSection::new("people", &["person", "days", "done", "break"]);
+    let mut projects_section: ReportSection = ReportSection::new("projects", &["person", "project", "done"]);
+    for member in team.list_members() {
+        let days: Vec<Day> = read_member_days(&team, &member, &range, now);
+        if days.is_empty() {
+            continue;
+        }
+        let mut project_secs: BTreeMap<String, i64> = BTreeMap::new();
+        for day in &days {
+            let done_secs: i64 = day.get_time_done_secs().unwrap_or(0);
+            let mut in_projects_secs: i64 = 0;
+            for (project, secs) in day.get_project_times_secs() {
+                let project: String = if project.is_empty() {NO_PROJECT.to_string()} else {project};
+                *project_secs.entry(project).or_insert(0) += secs;
+                in_projects_secs += secs;
+            }
+            if done_secs > in_projects_secs {
+                *project_secs.entry(NO_PROJECT.to_string()).or_insert(0) += done_secs - in_projects_secs;
+            }
+        }
+        people_section.add_row(vec![
+            member.clone().into(),
+            ReportValue::Number(days.len() as i64),
+            ReportValue::Secs(days.iter().map(|day: &Day| day.get_time_done_secs().unwrap_or(0)).sum()),
+            ReportValue::Secs(days.iter().map(|day: &Day| day.get_total_break_time_secs().unwrap_or(0)).sum()),
+        ]);
+        for (project, secs) in project_secs {
+            projects_section.add_row(vec![member.clone().into(), project.into(), ReportValue::Secs(secs)]);
+        }
+    }
+    report.add_section(people_section);
+    report.add_section(projects_section);
+    print_report(report, &other_args, now);
+}
+
+/// The days someone on the team recorded in the range, with one still going ended at `now`.
+fn read_member_days(team: &TeamConfig, member: &str, range: &DateRange, now: &DateTime<Local>) -> Vec<Day> {
+    let daily_dir: String = team.get_root_dir() + member + "/" + DAILY_DIR;
+    return range.dates().iter()
+        .filter_map(|date: &NaiveDate| read_file(&(daily_dir.clone() + &date.format(DATE_FMT).to_string())).ok())
+        .filter_map(|yaml_str: String| Day::try_from_string(&yaml_str).ok())
+        .map(|day: Day| close_day_at(day, now))
+        .collect();
+}
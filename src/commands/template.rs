@@ -0,0 +1,77 @@
+use std::process::exit;
+use chrono::prelude::{DateTime, Local};
+
+use crate::units::components::PlannedBlock;
+use crate::units::day::{Day, get_current_day};
+use crate::utils::access::is_read_only;
+use crate::utils::aggregate::close_day_at;
+use crate::utils::args::get_positional_args;
+use crate::utils::templates::{DayTemplate, delete_template, list_templates, read_template, write_template};
+
+/// `punch template save <name>` keeps today's blocks and summaries as the plan for days
+/// like it, which `punch in --template <name>` starts from. `list`, `show <name>` and
+/// `delete <name>` look after the saved ones.
+pub fn template(now: &DateTime<Local>, other_args: Vec<String>) {
+    let positional_args: Vec<String> = get_positional_args(&other_args, &[]);
+    let args: Vec<&str> = positional_args.iter().map(|x: &String| x.as_str()).collect();
+    let result: Result<(), String> = match args.as_slice() {
+        [] | ["list"] => {
+            let names: Vec<String> = list_templates();
+            if names.is_empty() {
+                println!("No templates yet. Save today as one with 'punch template save <name>'.");
+            }
+            for name in names {
+                println!("{}", name);
+            }
+            Ok(())
+        },
+        ["save", name] => save_template(now, name),
+        ["show", name] => read_template(name).map(|template: DayTemplate| {
+            for planned in &template.planned_blocks {
+                println!("{}", planned.describe());
+            }
+            for summary in &template.summaries {
+                println!("Summary: {}", summary.as_short_string());
+            }
+        }),
+        ["delete", name] => check_not_read_only("delete").and_then(|_| delete_template(name)).map(|_| println!("Deleted the template '{}'.", name)),
+        _ => Err("Usage: punch template [list] | punch template save|show|delete <name>".to_string()),
+    };
+    if let Err(msg) = result {
+        eprintln!("{}", msg);
+        exit(1);
+    }
+}
+
+fn save_template(now: &DateTime<Local>, name: &str) -> Result<(), String> {
+    check_not_read_only("save")?;
+    let day: Day = close_day_at(get_current_day(now)?, now);
+    let template: DayTemplate = DayTemplate::from_day(&day);
+    write_template(name, &template)?;
+    println!("Saved today's {} blocks as the template '{}':", template.planned_blocks.len(), name);
+    for planned in &template.planned_blocks {
+        println!("\t{}", planned.describe());
+    }
+    return Ok(());
+}
+
+/// Fills a new day in from a template: its planned blocks, summaries and time to do.
+pub fn apply_template(day: &mut Day, template: DayTemplate) {
+    day.planned_blocks = template.planned_blocks;
+    day.summaries = template.summaries;
+    if let Some(time_to_do) = template.time_to_do {
+        day.time_to_do = time_to_do;
+    }
+}
+
+/// The task a day from the template starts with, if it plans any.
+pub fn get_first_planned_task(template: &DayTemplate) -> Option<String> {
+    return template.planned_blocks.first().map(|planned: &PlannedBlock| planned.task.clone());
+}
+
+fn check_not_read_only(action: &str) -> Result<(), String> {
+    if is_read_only() {
+        return Err(format!("'punch template {}' changes your templates, and punch is running with --read-only.", action));
+    }
+    return Ok(());
+}
@@ -0,0 +1,79 @@
+use std::process::exit;
+use chrono::prelude::{DateTime, Local, NaiveDate};
+
+use crate::units::components::TimeBlock;
+use crate::units::day::{Day, get_current_day, read_recorded_day};
+use crate::utils::aggregate::{close_day_at, format_secs};
+use crate::utils::args::get_positional_args;
+use crate::utils::color::{DIM, TASK_COLORS, colors_enabled, paint};
+use crate::utils::period::parse_date;
+
+const BAR_WIDTH: i64 = 60;
+const WORK_CHAR: &str = "█";
+const BREAK_CHAR: &str = "░";
+
+pub fn timeline(now: &DateTime<Local>, other_args: Vec<String>) {
+    let positional: Vec<String> = get_positional_args(&other_args, &[]);
+    let day_result: Result<Day, String> = match positional.first() {
+        Some(date_str) => parse_date(date_str).and_then(|date: NaiveDate| {
+            read_recorded_day(&date)
+        }),
+        None => get_current_day(now),
+    };
+    if let Err(msg) = day_result {
+        eprintln!("{}", msg);
+        exit(1);
+    }
+    let day: Day = close_day_at(day_result.expect("Error already handled!"), now);
+    let use_color: bool = colors_enabled(&other_args);
+    let tasks: Vec<String> = day.get_tasks_in_chronological_order();
+    let task_color = |task: &String| -> &str {
+        let ind: usize = tasks.iter().position(|x: &String| x == task).unwrap_or(0);
+        return TASK_COLORS[ind % TASK_COLORS.len()];
+    };
+
+    let start: DateTime<Local> = day.get_day_start().as_dt();
+    let end: DateTime<Local> = day.get_day_end().expect("Day is closed").as_dt();
+    let total_secs: i64 = (end - start).num_seconds().max(1);
+    let mut bar: String = String::new();
+    for col in 0..BAR_WIDTH {
+        let col_time: DateTime<Local> = start + chrono::Duration::seconds(total_secs * col / BAR_WIDTH);
+        let block_ind: usize = day.timeblocks.iter()
+            .rposition(|block| block.get_start().as_dt() <= col_time)
+            .unwrap_or(0);
+        let block_task: String = day.timeblocks[block_ind].get_task_name();
+        bar += &match day.is_break(block_ind) {
+            true => paint(BREAK_CHAR, DIM, use_color),
+            false => paint(WORK_CHAR, task_color(&block_task), use_color),
+        };
+    }
+
+    println!("{}", start.format("%a %Y-%m-%d"));
+    println!("{}", bar);
+    println!("{:<width$}{}", start.format("%H:%M"), end.format("%H:%M"), width = BAR_WIDTH as usize - 5);
+    for (ind, block) in day.timeblocks.iter().enumerate() {
+        let marker: String = match day.is_break(ind) {
+            true => paint(BREAK_CHAR, DIM, use_color),
+            false => paint(WORK_CHAR, task_color(&block.get_task_name()), use_color),
+        };
+        let paid_secs: i64 = if day.is_break(ind) {day.get_paid_secs_of_break(ind)} else {0};
+        let paid: String = if paid_secs > 0 {format!(", {} paid", format_secs(paid_secs))} else {String::new()};
+        println!(
+            "{} {}-{} {} ({}{}){}",
+            marker,
+            block.get_start().as_dt().format("%H:%M"),
+            block.get_end().expect("Day is closed").as_dt().format("%H:%M"),
+            block.get_task_name(),
+            format_secs(block.get_length_secs().unwrap_or(0)),
+            paid,
+            format_annotations(block, use_color));
+    }
+}
+
+/// The block's annotations, to go at the end of its line.
+pub fn format_annotations(block: &TimeBlock, use_color: bool) -> String {
+    return match block.get_annotations().is_empty() {
+        true => String::new(),
+        false => paint(&format!(" - {}", block.get_annotations().join("; ")), DIM, use_color),
+    };
+}
@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+use std::process::exit;
+use chrono::prelude::{DateTime, Local};
+
+use crate::units::interval::{DATE_FMT, Interval};
+use crate::utils::args::get_positional_args;
+use crate::utils::period::{DateRange, resolve_range_from_args};
+use crate::utils::report::{Report, ReportSection, ReportValue};
+use crate::utils::timers::{Timer, read_timers, write_timers};
+use crate::commands::report::print_report;
+
+/// Stopwatches for measuring things like build times or meetings, which don't change the
+/// day: `punch timer start <label>`, `punch timer stop [label]`, and `punch timer report`
+/// for the totals per label. With no args, lists the timers running now.
+pub fn timer(now: &DateTime<Local>, other_args: Vec<String>) {
+    let positional_args: Vec<String> = get_positional_args(&other_args, &["--period", "--from", "--to", "--format", "--output", "--section"]);
+    let mut timers: Vec<Timer> = read_timers();
+    let label: String = positional_args.get(1..).unwrap_or_default().join(" ");
+    match positional_args.first().map(|x: &String| x.as_str()) {
+        None => {
+            let running: Vec<&Timer> = timers.iter().filter(|timer: &&Timer| timer.is_running()).collect();
+            if running.is_empty() {
+                println!("No timers running. Start one with 'punch timer start <label>'.");
+            }
+            for timer in running {
+                let elapsed_secs: i64 = (*now - timer.interval.get_start().as_dt()).num_seconds();
+                println!("{}: {}", timer.label, format_elapsed(elapsed_secs));
+            }
+        },
+        Some("start") => {
+            if label.trim().is_empty() {
+                eprintln!("'punch timer start' needs a label, e.g. 'punch timer start build'.");
+                exit(1);
+            }
+            if timers.iter().any(|timer: &Timer| timer.is_running() && timer.label == label) {
+                eprintln!("The timer '{}' is already running.", label);
+                exit(1);
+            }
+            timers.push(Timer {label: label.clone(), interval: Interval::new(now)});
+            write_timers(&timers);
+            println!("Started the timer '{}' at {}.", label, now.format("%H:%M:%S"));
+        },
+        Some("stop") => {
+            // Without a label, the timer started last is stopped.
+            let Some(timer) = timers.iter_mut().rev()
+                .find(|timer: &&mut Timer| timer.is_running() && (label.is_empty() || timer.label == label)) else {
+                eprintln!("There's no timer running{}.", if label.is_empty() {String::new()} else {format!(" called '{}'", label)});
+                exit(1);
+            };
+            timer.interval.end_at(now);
+            println!("Stopped the timer '{}' after {}.", timer.label, format_elapsed(timer.interval.get_length_secs().unwrap_or(0)));
+            write_timers(&timers);
+        },
+        Some("report") => {
+            let range: DateRange = resolve_range_from_args(now, &other_args).unwrap_or_else(|msg: String| {
+                eprintln!("{}", msg);
+                exit(1);
+            });
+            print_report(build_timer_report(&range, &timers), &other_args, now);
+        },
+        Some(other) => {
+            eprintln!("'{}' isn't something 'punch timer' does. Use 'start', 'stop' or 'report'.", other);
+            exit(1);
+        },
+    }
+}
+
+/// Each stopped timer started in the range, and the count, total and average for each label.
+fn build_timer_report(range: &DateRange, timers: &Vec<Timer>) -> Report {
+    let mut report: Report = Report::new(format!("Timers for {}", range.as_string()));
+    let mut timers_section: ReportSection = ReportSection::new("timers", &["date", "start", "label", "time"]);
+    let mut label_totals: BTreeMap<String, (i64, i64)> = BTreeMap::new();
+    for timer in timers {
+        let start: DateTime<Local> = timer.interval.get_start().as_dt();
+        let Some(secs) = timer.interval.get_length_secs() else {
+            continue;
+        };
+        if !range.contains(&start.date_naive()) {
+            continue;
+        }
+        let totals: &mut (i64, i64) = label_totals.entry(timer.label.clone()).or_default();
+        totals.0 += 1;
+        totals.1 += secs;
+        timers_section.add_row(vec![
+            start.format(DATE_FMT).to_string().into(),
+            start.format("%H:%M:%S").to_string().into(),
+            timer.label.clone().into(),
+            ReportValue::Secs(secs),
+        ]);
+    }
+    report.add_section(timers_section);
+    let mut labels_section: ReportSection = ReportSection::new("labels", &["label", "count", "total", "average"]);
+    for (label, (count, total_secs)) in label_totals {
+        labels_section.add_row(vec![
+            label.into(),
+            ReportValue::Number(count),
+            ReportValue::Secs(total_secs),
+            ReportValue::Secs(total_secs / count),
+        ]);
+    }
+    report.add_section(labels_section);
+    return report;
+}
+
+/// Like "1 h 2 m 3 s", keeping the seconds that matter for short timers.
+fn format_elapsed(secs: i64) -> String {
+    return match secs {
+        secs if secs >= 3600 => format!("{} h {} m {} s", secs / 3600, secs % 3600 / 60, secs % 60),
+        secs if secs >= 60 => format!("{} m {} s", secs / 60, secs % 60),
+        secs => format!("{} s", secs),
+    };
+}
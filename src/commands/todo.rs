@@ -0,0 +1,72 @@
+use std::process::exit;
+use chrono::prelude::{DateTime, Local, NaiveDate};
+use chrono::Duration;
+
+use crate::units::day::{Day, DayIterator, read_day_for_date, write_day};
+use crate::units::interval::DATE_FMT;
+use crate::utils::args::{get_flag_value, get_positional_args};
+use crate::utils::period::{DateRange, parse_date};
+use crate::utils::work_summary::WorkSummary;
+
+const DEFAULT_TODO_DAYS: i64 = 14;
+
+/// Lists the summaries marked unfinished over the last two weeks, or `--days <n>`, each with
+/// an id like `2024-01-15:2` (the day and which of its summaries it is) for `punch done`.
+pub fn todo(now: &DateTime<Local>, other_args: Vec<String>) {
+    let days: i64 = match get_flag_value(&other_args, "--days") {
+        Some(days_str) => days_str.trim().parse().unwrap_or_else(|_| {
+            eprintln!("'--days {}' should be a whole number of days.", days_str);
+            exit(1);
+        }),
+        None => DEFAULT_TODO_DAYS,
+    };
+    let today: NaiveDate = now.date_naive();
+    let range: DateRange = DateRange::new(today - Duration::days(days.max(1) - 1), today).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+    let mut open_items: usize = 0;
+    for day in DayIterator::new(&range) {
+        let date: String = day.get_day_start().as_dt().format(DATE_FMT).to_string();
+        for (ind, summary) in day.summaries.iter().enumerate() {
+            if summary.is_unfinished() {
+                println!("{}:{}  {}: {}", date, ind + 1, summary.get_task(), summary.get_summary());
+                open_items += 1;
+            }
+        }
+    }
+    if open_items == 0 {
+        println!("Nothing left unfinished in the last {} days.", days);
+    }
+}
+
+/// `punch done <id>` marks a summary from `punch todo` finished, and `punch undone <id>`
+/// marks it unfinished again.
+pub fn set_done(other_args: Vec<String>, force: bool, done: bool) {
+    let command: &str = if done {"done"} else {"undone"};
+    let positional_args: Vec<String> = get_positional_args(&other_args, &[]);
+    let Some(id) = positional_args.first() else {
+        eprintln!("'punch {}' needs the id of a summary from 'punch todo', e.g. 'punch {} 2024-01-15:2'.", command, command);
+        exit(1);
+    };
+    if let Err(msg) = set_summary_done(id, force, done) {
+        eprintln!("{}", msg);
+        exit(1);
+    }
+}
+
+fn set_summary_done(id: &str, force: bool, done: bool) -> Result<(), String> {
+    let (date_str, number_str): (&str, &str) = id.split_once(':')
+        .ok_or(format!("'{}' isn't a summary id. They look like '2024-01-15:2'; see 'punch todo'.", id))?;
+    let date: NaiveDate = parse_date(date_str)?;
+    let mut day: Day = read_day_for_date(&date).map_err(|_| format!("There's no day recorded for {}.", date))?;
+    day.check_editable(force)?;
+    let summary: &mut WorkSummary = number_str.trim().parse::<usize>().ok()
+        .and_then(|number: usize| number.checked_sub(1))
+        .and_then(|ind: usize| day.summaries.get_mut(ind))
+        .ok_or(format!("{} has no summary '{}'.", date, number_str))?;
+    summary.set_unfinished(!done);
+    println!("Marked '{}: {}' {}.", summary.get_task(), summary.get_summary(), if done {"done"} else {"unfinished"});
+    write_day(&day);
+    return Ok(());
+}
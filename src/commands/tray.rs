@@ -0,0 +1,77 @@
+use std::process::exit;
+use chrono::prelude::{DateTime, Local};
+
+#[cfg(feature = "tray")]
+use crate::units::day::{Day, get_current_day};
+#[cfg(feature = "tray")]
+use crate::utils::aggregate::close_day_at;
+#[cfg(feature = "tray")]
+use crate::utils::args::get_flag_value;
+#[cfg(feature = "tray")]
+use crate::utils::json::JsonValue;
+#[cfg(feature = "tray")]
+use crate::commands::serve::handle_request;
+
+/// Prints a menu for a menu bar app like SwiftBar or xbar, which runs `punch tray` every so
+/// often and shows the first line in the menu bar. Clicking an action runs
+/// `punch tray --action <action>`, which goes through the same requests as `punch serve`.
+#[cfg(feature = "tray")]
+pub fn tray(now: &DateTime<Local>, other_args: Vec<String>) {
+    if let Some(action) = get_flag_value(&other_args, "--action") {
+        if let Err(msg) = handle_request(&action, &JsonValue::Null, now) {
+            eprintln!("{}", msg);
+            exit(1);
+        }
+        return;
+    }
+    print!("{}", get_tray_menu(now));
+}
+
+#[cfg(not(feature = "tray"))]
+pub fn tray(_now: &DateTime<Local>, _other_args: Vec<String>) {
+    eprintln!("'punch tray' needs punch to be built with the 'tray' feature: cargo build --release --features tray");
+    exit(1);
+}
+
+#[cfg(feature = "tray")]
+fn get_tray_menu(now: &DateTime<Local>) -> String {
+    let day: Day = match get_current_day(now) {
+        Ok(day) => day,
+        Err(_) => return format!("punch: not in\n---\n{}", get_action_line("Punch in", "in")),
+    };
+    let closed_day: Day = close_day_at(day.clone(), now);
+    let done_mins: i64 = closed_day.get_time_done_secs().expect("Day is closed") / 60;
+    let left_mins: i64 = closed_day.get_time_left_secs().expect("Day is closed") / 60;
+    let elapsed: String = format_hours_and_mins(done_mins);
+    let mut menu: String = match (day.has_ended(), day.on_break) {
+        (true, _) => format!("punch: out ({})\n---\n", elapsed),
+        (false, true) => format!("☕ {}\n---\nOn break: {}\n", elapsed, day.get_latest_task_name()),
+        (false, false) => format!("⏱ {}\n---\nWorking on: {}\n", elapsed, day.get_latest_task_name()),
+    };
+    menu += &format!("Left today: {}\n", format_hours_and_mins(left_mins));
+    if !day.has_ended() {
+        menu += &match day.on_break {
+            true => get_action_line("Resume", "resume"),
+            false => get_action_line("Pause", "pause"),
+        };
+        menu += &get_action_line("Punch out", "out");
+    }
+    return menu;
+}
+
+#[cfg(feature = "tray")]
+fn format_hours_and_mins(mins: i64) -> String {
+    let sign: &str = if mins < 0 {"-"} else {""};
+    return format!("{}{}:{:02}", sign, mins.abs() / 60, mins.abs() % 60);
+}
+
+/// A menu item that runs a `punch tray --action` and then refreshes the menu.
+#[cfg(feature = "tray")]
+fn get_action_line(label: &str, action: &str) -> String {
+    let exe: String = std::env::current_exe()
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or("punch".to_string());
+    return format!(
+        "{} | shell=\"{}\" param1=tray param2=--action param3={} terminal=false refresh=true\n",
+        label, exe, action);
+}
@@ -0,0 +1,40 @@
+use std::process::exit;
+
+use crate::utils::args::{get_flag_value, get_positional_args};
+use crate::utils::signing::{Signature, get_signature_path, verify_file};
+
+/// Checks a signed export against its signature, e.g. `punch verify timesheet.csv`. The
+/// signature is read from `<file>.sig` or `--signature`. Pass the signer's public key
+/// with `--public-key <pem>` to also check who signed it.
+pub fn verify(other_args: Vec<String>) {
+    let path: String = match get_positional_args(&other_args, &["--signature", "--public-key"]).as_slice() {
+        [path] => path.clone(),
+        _ => {
+            eprintln!("Usage: punch verify <file> [--signature <file.sig>] [--public-key <key.pem>]");
+            exit(1);
+        },
+    };
+    let signature_path: String = get_flag_value(&other_args, "--signature").unwrap_or(get_signature_path(&path));
+    let signature: Signature = std::fs::read_to_string(&signature_path)
+        .map_err(|err| format!("Couldn't read '{}': {}", signature_path, err))
+        .and_then(|text: String| Signature::from_string(&text))
+        .unwrap_or_else(|msg: String| {
+            eprintln!("{}", msg);
+            exit(1);
+        });
+    let public_key_pem: Option<String> = get_flag_value(&other_args, "--public-key").map(|key_path: String| {
+        return std::fs::read_to_string(&key_path).unwrap_or_else(|err| {
+            eprintln!("Couldn't read '{}': {}", key_path, err);
+            exit(1);
+        });
+    });
+    if let Err(msg) = verify_file(&path, &signature, public_key_pem.as_deref()) {
+        eprintln!("'{}' does NOT match its signature. It may have been changed after it was signed.", path);
+        eprintln!("{}", msg);
+        exit(1);
+    }
+    println!("'{}' matches its signature from {}.", path, signature.signed_at);
+    if public_key_pem.is_none() {
+        println!("It was signed with the key {}. Check that this is the key you were given, or pass it with --public-key.", signature.public_key);
+    }
+}
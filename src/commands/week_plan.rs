@@ -0,0 +1,101 @@
+use std::collections::BTreeMap;
+use std::process::exit;
+use chrono::prelude::{DateTime, Local, NaiveDate};
+use chrono::Duration;
+
+use crate::units::interval::DATE_FMT;
+use crate::utils::aggregate::{format_secs, get_project_secs};
+use crate::utils::args::{get_flag_value, get_flag_values};
+use crate::utils::file_io::{create_dir_if_not_exists, delete_file, edit_file, expand_path, read_file, write_file};
+use crate::utils::index::{DaySummary, get_summaries_in_range};
+use crate::utils::period::{DateRange, parse_date};
+use crate::utils::team::get_data_dir;
+use crate::utils::week_plans::{WEEK_PLANS_DIR, WeekPlan, get_week_start, read_week_plan, write_week_plan};
+
+/// `punch plan-week`: sets the hours to spend on each project in the coming week, in your
+/// editor, or with `--set project=hours`. `--week this`, or any date in the week, plans
+/// another week. A new plan starts from the hours done the week before.
+pub fn plan_week(now: &DateTime<Local>, other_args: Vec<String>) {
+    let week_start: NaiveDate = get_planned_week_start(now, &other_args).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+    let existing: Option<WeekPlan> = read_week_plan(&week_start).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+    let settings: Vec<String> = get_flag_values(&other_args, "--set");
+    let plan: WeekPlan = match settings.is_empty() {
+        true => edit_week_plan(&week_start, existing.unwrap_or_else(|| get_plan_from_week_before(now, &week_start))),
+        false => set_project_hours(existing.unwrap_or_default(), &settings),
+    }.unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+
+    write_week_plan(&week_start, &plan);
+    let total_hours: f64 = plan.projects.values().sum();
+    println!("Planned {} h for the week of {}:", total_hours, week_start.format(DATE_FMT));
+    for (project, hours) in &plan.projects {
+        println!("\t{}: {} h", project, hours);
+    }
+}
+
+fn get_planned_week_start(now: &DateTime<Local>, other_args: &Vec<String>) -> Result<NaiveDate, String> {
+    let today: NaiveDate = now.date_naive();
+    return match get_flag_value(other_args, "--week").as_deref() {
+        None | Some("next") => Ok(get_week_start(&today) + Duration::weeks(1)),
+        Some("this") => Ok(get_week_start(&today)),
+        Some(date_str) => parse_date(date_str).map(|date: NaiveDate| get_week_start(&date)),
+    };
+}
+
+/// `--set` values like "client-a=12.5". Setting a project to 0 takes it out of the plan.
+fn set_project_hours(mut plan: WeekPlan, settings: &Vec<String>) -> Result<WeekPlan, String> {
+    for setting in settings {
+        let (project, hours_str): (&str, &str) = setting.rsplit_once('=')
+            .ok_or(format!("Expected '--set project=hours', like '--set client-a=12.5'! Given: '{}'", setting))?;
+        let hours: f64 = hours_str.trim().parse().ok().filter(|hours: &f64| *hours >= 0.0)
+            .ok_or(format!("Expected a number of hours for '{}'! Given: '{}'", project, hours_str))?;
+        match hours == 0.0 {
+            true => plan.projects.remove(project.trim()),
+            false => plan.projects.insert(project.trim().to_string(), hours),
+        };
+    }
+    return Ok(plan);
+}
+
+/// A plan with the hours done on each project the week before, rounded to the half hour.
+fn get_plan_from_week_before(now: &DateTime<Local>, week_start: &NaiveDate) -> WeekPlan {
+    let week_before: DateRange = DateRange {from: *week_start - Duration::weeks(1), to: *week_start - Duration::days(1)};
+    let summaries: Vec<DaySummary> = get_summaries_in_range(&week_before, now);
+    let projects: BTreeMap<String, f64> = get_project_secs(&summaries).into_iter()
+        .map(|(project, (secs, _)): (String, (i64, String))| (project, (secs as f64 / 1800.0).round() / 2.0))
+        .filter(|(_, hours): &(String, f64)| *hours > 0.0)
+        .collect();
+    return WeekPlan {projects: projects};
+}
+
+fn edit_week_plan(week_start: &NaiveDate, plan: WeekPlan) -> Result<WeekPlan, String> {
+    create_dir_if_not_exists(&(get_data_dir() + WEEK_PLANS_DIR));
+    let temp_path: String = format!("{}{}{}-temp", get_data_dir(), WEEK_PLANS_DIR, week_start.format(DATE_FMT));
+    let header: String = format!(
+        "# Hours to spend on each project in the week of {}. Projects are the ones in task summaries.\n",
+        week_start.format(DATE_FMT));
+    write_file(&temp_path, header + &serde_yaml::to_string(&plan).expect("Week plans should be serializable"));
+    edit_file(&expand_path(&temp_path));
+    let edited: Result<WeekPlan, String> = read_file(&temp_path)
+        .map_err(|err| format!("Couldn't read the edited plan: {}", err))
+        .and_then(|yaml_str: String| serde_yaml::from_str(&yaml_str).map_err(|err| format!("The plan isn't valid, so it wasn't changed: {}", err)));
+    let _ = delete_file(&temp_path);
+    return edited;
+}
+
+/// Lines of each planned project's progress, like "client-a: 6 h 0 m / 12 h 0 m (50%)".
+pub fn format_week_plan_progress(plan: &WeekPlan, summaries: &Vec<DaySummary>) -> Vec<String> {
+    return plan.get_progress(summaries).into_iter()
+        .map(|(project, planned_secs, done_secs): (String, i64, i64)| format!(
+            "{}: {} / {} ({}%)", project, format_secs(done_secs), format_secs(planned_secs),
+            if planned_secs > 0 {done_secs * 100 / planned_secs} else {0}))
+        .collect();
+}
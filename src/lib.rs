@@ -1,3 +1,9 @@
 pub mod units;
 pub mod utils;
 pub mod commands;
+pub mod testing;
+pub mod builder;
+pub mod prelude;
+
+pub use units::day::{Day, DayIterator};
+pub use utils::period::DateRange;
@@ -11,8 +11,8 @@ use crate::commands::core::{
     punch_out, 
     take_break, 
     resume, 
-    view_day, 
-    edit_day,
+    view,
+    edit,
     switch_to_new_task,
     update_current_task_name,
     add_note_to_today,
@@ -21,8 +21,64 @@ use crate::commands::core::{
     edit_config,
     summary,
 };
-use crate::utils::file_io::{create_base_dir_if_not_exists};
-use crate::utils::config::{create_default_config_if_not_exists};
+use crate::commands::amend::amend;
+use crate::commands::delete::delete;
+use crate::commands::history::{add_day, backfill};
+use crate::commands::demo::demo_data;
+use crate::commands::adjust::adjust_balance;
+use crate::commands::compliance::compliance;
+use crate::commands::target::target;
+use crate::commands::week_plan::plan_week;
+use crate::commands::forecast::forecast;
+use crate::commands::self_update::self_update;
+use crate::commands::config::config;
+use crate::commands::setup::setup_wizard;
+use crate::commands::prune::prune;
+use crate::commands::compress::compress;
+use crate::commands::away::{away, review_away_time_if_any};
+use crate::commands::team::team;
+use crate::commands::submit::submit;
+use crate::commands::close_week::close_week;
+use crate::commands::verify::verify;
+use crate::commands::merge::merge;
+use crate::commands::event_log::{recalc, undo};
+use crate::commands::blocks::{split, annotate, mark, focus};
+use crate::commands::mood::mood;
+use crate::commands::meta::meta;
+use crate::commands::oncall::oncall;
+use crate::commands::expense::expense;
+use crate::commands::template::template;
+use crate::commands::carry::carry;
+use crate::commands::todo::{todo, set_done};
+use crate::commands::is::is;
+use crate::commands::reminders::reminders;
+use crate::commands::timer::timer;
+use crate::commands::export::export;
+use crate::commands::calendar::import;
+use crate::commands::standup::{plan, blocker, standup};
+use crate::commands::report::{report, compare, email_report, reindex};
+use crate::commands::budget::budget;
+use crate::commands::project::project;
+use crate::commands::rename::rename_task;
+use crate::commands::status::{status, today, watch};
+use crate::commands::doctor::doctor;
+use crate::commands::daemon::daemon;
+use crate::commands::timeline::timeline;
+use crate::commands::heatmap::heatmap;
+use crate::commands::batch::batch;
+use crate::commands::serve::serve;
+use crate::commands::tray::tray;
+use crate::commands::service::{install_service, service};
+use crate::utils::file_io::{DataLock, create_base_dir_if_not_exists, lock_data_dir};
+use crate::utils::config::{config_exists, create_default_config_if_not_exists, get_config};
+use crate::utils::state::create_state_if_not_exists;
+use crate::utils::picker::can_pick;
+use crate::utils::ledger::allow_reopening;
+use crate::utils::access::{is_read_only, set_read_only};
+use crate::utils::clock::{SharedClock, set_clock, take_clock_from_args};
+use crate::utils::args::{add_default_flags, take_flag};
+use crate::utils::i18n::tr;
+use crate::utils::suggestions::get_suggestion;
 
 const VERSION: &str = "2.2.4";
 
@@ -34,14 +90,76 @@ enum SubCommand {
     Pause(Vec<String>),
     Resume(Vec<String>),
     Summary(Vec<String>),
+    Status(Vec<String>),
+    Today(Vec<String>),
     View(Vec<String>),
     Edit(Vec<String>),
     Task(Vec<String>),
     Note(Vec<String>),
+    Config(Vec<String>),
     EditConfig(Vec<String>),
     ViewConfig(Vec<String>),
     AddSummary(Vec<String>),
     UpdateTask(Vec<String>),
+    Amend(Vec<String>),
+    Target(Vec<String>),
+    Delete(Vec<String>),
+    AddDay(Vec<String>),
+    Backfill(Vec<String>),
+    DemoData(Vec<String>),
+    AdjustBalance(Vec<String>),
+    Compliance(Vec<String>),
+    Forecast(Vec<String>),
+    PlanWeek(Vec<String>),
+    Prune(Vec<String>),
+    Compress(Vec<String>),
+    Away(Vec<String>),
+    Team(Vec<String>),
+    Submit(Vec<String>),
+    CloseWeek(Vec<String>),
+    Verify(Vec<String>),
+    Merge(Vec<String>),
+    Undo(Vec<String>),
+    Recalc(Vec<String>),
+    Split(Vec<String>),
+    Annotate(Vec<String>),
+    Mark(Vec<String>),
+    Focus(Vec<String>),
+    Mood(Vec<String>),
+    Meta(Vec<String>),
+    Oncall(Vec<String>),
+    Expense(Vec<String>),
+    Template(Vec<String>),
+    Plan(Vec<String>),
+    Carry(Vec<String>),
+    Todo(Vec<String>),
+    Done(Vec<String>),
+    Undone(Vec<String>),
+    Reminders(Vec<String>),
+    Timer(Vec<String>),
+    Watch(Vec<String>),
+    Is(Vec<String>),
+    Blocker(Vec<String>),
+    Standup(Vec<String>),
+    EmailReport(Vec<String>),
+    Export(Vec<String>),
+    Import(Vec<String>),
+    Budget(Vec<String>),
+    Project(Vec<String>),
+    RenameTask(Vec<String>),
+    Report(Vec<String>),
+    Compare(Vec<String>),
+    Doctor(Vec<String>),
+    Daemon(Vec<String>),
+    Timeline(Vec<String>),
+    Heatmap(Vec<String>),
+    Batch(Vec<String>),
+    Serve(Vec<String>),
+    InstallService(Vec<String>),
+    Service(Vec<String>),
+    Tray(Vec<String>),
+    Reindex(Vec<String>),
+    SelfUpdate(Vec<String>),
     Version(Vec<String>),
     Invalid(String),
 }
@@ -54,14 +172,76 @@ impl SubCommand {
             "pause" => Self::Pause(other_args),
             "resume" => Self::Resume(other_args),
             "summary" => Self::Summary(other_args),
+            "status" => Self::Status(other_args),
+            "today" => Self::Today(other_args),
             "view" => Self::View(other_args),
             "edit" => Self::Edit(other_args),
             "task" => Self::Task(other_args),
             "note" => Self::Note(other_args),
+            "config" => Self::Config(other_args),
             "edit-config" => Self::EditConfig(other_args),
             "view-config" => Self::ViewConfig(other_args),
             "add-summary" => Self::AddSummary(other_args),
             "update-task" => Self::UpdateTask(other_args),
+            "amend" => Self::Amend(other_args),
+            "target" => Self::Target(other_args),
+            "delete" => Self::Delete(other_args),
+            "add-day" => Self::AddDay(other_args),
+            "backfill" => Self::Backfill(other_args),
+            "demo-data" => Self::DemoData(other_args),
+            "adjust-balance" => Self::AdjustBalance(other_args),
+            "compliance" => Self::Compliance(other_args),
+            "forecast" => Self::Forecast(other_args),
+            "prune" => Self::Prune(other_args),
+            "compress" => Self::Compress(other_args),
+            "away" => Self::Away(other_args),
+            "team" => Self::Team(other_args),
+            "submit" => Self::Submit(other_args),
+            "close-week" => Self::CloseWeek(other_args),
+            "verify" => Self::Verify(other_args),
+            "merge" => Self::Merge(other_args),
+            "undo" => Self::Undo(other_args),
+            "recalc" => Self::Recalc(other_args),
+            "split" => Self::Split(other_args),
+            "annotate" => Self::Annotate(other_args),
+            "mark" => Self::Mark(other_args),
+            "focus" => Self::Focus(other_args),
+            "mood" => Self::Mood(other_args),
+            "meta" => Self::Meta(other_args),
+            "oncall" => Self::Oncall(other_args),
+            "expense" => Self::Expense(other_args),
+            "template" => Self::Template(other_args),
+            "plan" => Self::Plan(other_args),
+            "plan-week" => Self::PlanWeek(other_args),
+            "carry" => Self::Carry(other_args),
+            "todo" => Self::Todo(other_args),
+            "done" => Self::Done(other_args),
+            "undone" => Self::Undone(other_args),
+            "reminders" => Self::Reminders(other_args),
+            "timer" => Self::Timer(other_args),
+            "watch" => Self::Watch(other_args),
+            "is" => Self::Is(other_args),
+            "blocker" => Self::Blocker(other_args),
+            "standup" => Self::Standup(other_args),
+            "email-report" => Self::EmailReport(other_args),
+            "export" => Self::Export(other_args),
+            "import" => Self::Import(other_args),
+            "budget" => Self::Budget(other_args),
+            "project" => Self::Project(other_args),
+            "rename-task" => Self::RenameTask(other_args),
+            "report" => Self::Report(other_args),
+            "compare" => Self::Compare(other_args),
+            "doctor" => Self::Doctor(other_args),
+            "daemon" => Self::Daemon(other_args),
+            "timeline" => Self::Timeline(other_args),
+            "heatmap" => Self::Heatmap(other_args),
+            "batch" => Self::Batch(other_args),
+            "serve" => Self::Serve(other_args),
+            "install-service" => Self::InstallService(other_args),
+            "service" => Self::Service(other_args),
+            "tray" => Self::Tray(other_args),
+            "reindex" => Self::Reindex(other_args),
+            "self-update" => Self::SelfUpdate(other_args),
             "version" | "-v" | "--version" => Self::Version(other_args),
             other => Self::Invalid(other.to_string()),
         }
@@ -70,38 +250,253 @@ impl SubCommand {
     fn get_allowed_strings() -> Vec<String> {
         return Vec::from(
             [
-                "in", "out", "pause", "resume", "summary", "view", "edit", 
-                "task", "note", "edit-config", "add-summary", "update-task",
-                "version", "-v", "--version"
+                "in", "out", "pause", "resume", "summary", "status", "today", "view", "edit", 
+                "task", "note", "config", "edit-config", "add-summary", "update-task", "amend", "target", "delete", "add-day", "backfill", "demo-data", "adjust-balance", "compliance", "forecast", "prune", "compress", "away", "team", "submit", "close-week", "verify", "merge", "undo", "recalc", "split", "annotate", "mark", "focus", "mood", "meta", "oncall", "expense", "template", "plan", "plan-week", "carry", "todo", "done", "undone", "reminders", "timer", "watch", "is", "blocker", "standup", "email-report", "export", "import", "budget", "project", "rename-task",
+                "report", "compare", "doctor", "daemon", "timeline", "heatmap",
+                "batch", "serve", "install-service", "service", "tray", "reindex", "self-update", "version", "-v", "--version"
             ].map(|x: &str| x.to_string())
         );
     }
+
+    /// Whether the command can change your data, so can't run with --read-only. The daemon
+    /// and `demo-data` do without holding the lock for the whole run.
+    fn changes_data(&self) -> bool {
+        return self.needs_lock() || matches!(self, Self::Daemon(_) | Self::DemoData(_));
+    }
+
+    /// Commands that read, change and write back days or the config.
+    fn needs_lock(&self) -> bool {
+        return matches!(
+            self,
+            Self::In(_) | Self::Out(_) | Self::Pause(_) | Self::Resume(_) | Self::Edit(_) | Self::Task(_)
+            | Self::Note(_) | Self::Config(_) | Self::EditConfig(_) | Self::AddSummary(_) | Self::UpdateTask(_) | Self::Amend(_) | Self::Target(_) | Self::Delete(_) | Self::AddDay(_) | Self::Backfill(_) | Self::AdjustBalance(_) | Self::Prune(_) | Self::Compress(_) | Self::Away(_) | Self::Submit(_) | Self::CloseWeek(_) | Self::Merge(_) | Self::Undo(_) | Self::Recalc(_) | Self::Split(_) | Self::Annotate(_) | Self::Mark(_) | Self::Focus(_) | Self::Mood(_) | Self::Meta(_) | Self::Oncall(_) | Self::Expense(_) | Self::Plan(_) | Self::PlanWeek(_) | Self::Carry(_) | Self::Done(_) | Self::Undone(_) | Self::Timer(_) | Self::Blocker(_) | Self::Import(_) | Self::Project(_) | Self::RenameTask(_) | Self::Batch(_)
+            | Self::Reindex(_)
+        );
+    }
 }
 
 fn main() {
-    let env_args: Vec<String> = args().collect();
+    let mut env_args: Vec<String> = args().collect();
+    let clock: SharedClock = take_clock_from_args(&mut env_args).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+    set_clock(Some(clock.clone()));
+    // Any command that changes days can change submitted ones with --force.
+    let force: bool = take_flag(&mut env_args, "--force");
+    // Days of closed weeks can be changed with --reopen, each change being kept as a correction.
+    if take_flag(&mut env_args, "--reopen") {
+        allow_reopening();
+    }
+    // With --read-only, nothing can change your data, including through `punch serve`.
+    if take_flag(&mut env_args, "--read-only") {
+        set_read_only();
+    }
     let command_name: &String = &env_args[1];
-    let other_args: Vec<String> = env_args[2..].to_vec();
-    let command: SubCommand = SubCommand::from_string(command_name, other_args);
+    let mut other_args: Vec<String> = env_args[2..].to_vec();
 
     setup();
 
-    let now: DateTime<Local> = Local::now();
-    run_command(command, now);
+    let default_flags: Vec<String> = get_config().command_defaults(command_name).unwrap_or_else(|msg: String| {
+        eprintln!("{}", msg);
+        exit(1);
+    });
+    add_default_flags(&mut other_args, &default_flags);
+    let command: SubCommand = SubCommand::from_string(command_name, other_args);
+
+    if command.changes_data() && is_read_only() {
+        eprintln!("'punch {}' changes your data, and punch is running with --read-only.", command_name);
+        exit(1);
+    }
+    let _lock: Option<DataLock> = match command.needs_lock() {
+        true => Some(lock_data_dir().unwrap_or_else(|msg: String| {
+            eprintln!("{}", msg);
+            exit(1);
+        })),
+        false => None,
+    };
+    // Time the daemon counted as a break while you were away is asked about before changing the day.
+    if command.needs_lock() && !matches!(command, SubCommand::Config(_) | SubCommand::EditConfig(_) | SubCommand::Batch(_) | SubCommand::Away(_) | SubCommand::Oncall(_) | SubCommand::Timer(_)) {
+        review_away_time_if_any(&clock.now());
+    }
+    run_command(command, clock, force);
 }
 
 fn setup() {
+    if is_read_only() && !config_exists() {
+        eprintln!("There's no config yet, and punch is running with --read-only. Run punch once without it to set one up.");
+        exit(1);
+    }
     create_base_dir_if_not_exists();
-    create_daily_dir_if_not_exists();
+    let is_first_run: bool = !config_exists();
+    if is_first_run && can_pick() {
+        setup_wizard();
+    }
     create_default_config_if_not_exists();
+    create_daily_dir_if_not_exists();
+    if is_first_run {
+        create_state_if_not_exists();
+    }
 }
 
-fn run_command(command: SubCommand, now: DateTime<Local>) {
+/// `force` lets commands change submitted days.
+fn run_command(command: SubCommand, clock: SharedClock, force: bool) {
+    let now: DateTime<Local> = clock.now();
     if let SubCommand::In(other_args) = command {
         punch_in(&now, other_args);
     }
+    else if let SubCommand::Report(other_args) = command {
+        report(&now, other_args);
+    }
+    else if let SubCommand::Compare(other_args) = command {
+        compare(&now, other_args);
+    }
+    else if let SubCommand::EmailReport(other_args) = command {
+        email_report(&now, other_args);
+    }
+    else if let SubCommand::Export(other_args) = command {
+        export(&now, other_args);
+    }
+    else if let SubCommand::Budget(other_args) = command {
+        budget(&now, other_args);
+    }
+    else if let SubCommand::Project(other_args) = command {
+        project(&now, other_args, force);
+    }
+    else if let SubCommand::RenameTask(other_args) = command {
+        rename_task(&now, other_args, force);
+    }
+    else if let SubCommand::Doctor(other_args) = command {
+        doctor(&now, other_args);
+    }
+    else if let SubCommand::Daemon(other_args) = command {
+        daemon(other_args, clock);
+    }
+    else if let SubCommand::Timeline(other_args) = command {
+        timeline(&now, other_args);
+    }
+    else if let SubCommand::Heatmap(other_args) = command {
+        heatmap(&now, other_args);
+    }
+    else if let SubCommand::Batch(_other_args) = command {
+        batch(&now);
+    }
+    else if let SubCommand::Serve(other_args) = command {
+        serve(other_args, clock);
+    }
+    else if let SubCommand::InstallService(other_args) = command {
+        install_service(other_args);
+    }
+    else if let SubCommand::Service(other_args) = command {
+        service(other_args);
+    }
+    else if let SubCommand::Tray(other_args) = command {
+        tray(&now, other_args);
+    }
+    else if let SubCommand::View(other_args) = command {
+        view(&now, other_args);
+    }
+    else if let SubCommand::Edit(other_args) = command {
+        edit(&now, other_args, force);
+    }
+    else if let SubCommand::AddDay(other_args) = command {
+        add_day(&now, other_args);
+    }
+    else if let SubCommand::Backfill(other_args) = command {
+        backfill(&now, other_args);
+    }
+    else if let SubCommand::DemoData(other_args) = command {
+        demo_data(&now, other_args);
+    }
+    else if let SubCommand::AdjustBalance(other_args) = command {
+        adjust_balance(&now, other_args);
+    }
+    else if let SubCommand::Compliance(other_args) = command {
+        compliance(&now, other_args);
+    }
+    else if let SubCommand::Forecast(other_args) = command {
+        forecast(&now, other_args);
+    }
+    else if let SubCommand::PlanWeek(other_args) = command {
+        plan_week(&now, other_args);
+    }
+    else if let SubCommand::Delete(other_args) = command {
+        delete(other_args, force);
+    }
+    else if let SubCommand::Prune(other_args) = command {
+        prune(&now, other_args);
+    }
+    else if let SubCommand::Compress(other_args) = command {
+        compress(&now, other_args);
+    }
+    else if let SubCommand::Away(other_args) = command {
+        away(&now, other_args);
+    }
+    else if let SubCommand::Oncall(other_args) = command {
+        oncall(&now, other_args);
+    }
+    else if let SubCommand::Expense(other_args) = command {
+        expense(&now, other_args, force);
+    }
+    else if let SubCommand::Template(other_args) = command {
+        template(&now, other_args);
+    }
+    else if let SubCommand::Todo(other_args) = command {
+        todo(&now, other_args);
+    }
+    else if let SubCommand::Done(other_args) = command {
+        set_done(other_args, force, true);
+    }
+    else if let SubCommand::Undone(other_args) = command {
+        set_done(other_args, force, false);
+    }
+    else if let SubCommand::Reminders(_other_args) = command {
+        reminders(&now);
+    }
+    else if let SubCommand::Timer(other_args) = command {
+        timer(&now, other_args);
+    }
+    else if let SubCommand::Watch(other_args) = command {
+        watch(clock.as_ref(), other_args);
+    }
+    else if let SubCommand::Is(other_args) = command {
+        is(&now, other_args);
+    }
+    else if let SubCommand::Team(other_args) = command {
+        team(&now, other_args);
+    }
+    else if let SubCommand::Submit(other_args) = command {
+        submit(&now, other_args);
+    }
+    else if let SubCommand::CloseWeek(other_args) = command {
+        close_week(&now, other_args);
+    }
+    else if let SubCommand::Verify(other_args) = command {
+        verify(other_args);
+    }
+    else if let SubCommand::Merge(other_args) = command {
+        merge(other_args, force);
+    }
+    else if let SubCommand::Undo(other_args) = command {
+        undo(&now, other_args, force);
+    }
+    else if let SubCommand::Recalc(other_args) = command {
+        recalc(other_args);
+    }
+    else if let SubCommand::Standup(other_args) = command {
+        standup(&now, other_args);
+    }
+    else if let SubCommand::Reindex(_other_args) = command {
+        reindex();
+    }
+    else if let SubCommand::Config(other_args) = command {
+        config(other_args);
+    }
+    else if let SubCommand::SelfUpdate(other_args) = command {
+        self_update(VERSION, other_args);
+    }
     else if let SubCommand::Version(_other_args) = command {
-        println!("Current punch-card version: {}", VERSION);
+        println!("{}", tr("version", &[&VERSION]));
     }
     else if let SubCommand::Invalid(original) = command {
         handle_invalid_cmd(&original);
@@ -113,20 +508,88 @@ fn run_command(command: SubCommand, now: DateTime<Local>) {
             exit(1);
         }
         let day: Day = possible_day.unwrap();
+        if command.needs_lock() && !matches!(command, SubCommand::EditConfig(_)) {
+            if let Err(msg) = day.check_editable(force) {
+                eprintln!("{}", msg);
+                exit(1);
+            }
+        }
 
         match command {
             SubCommand::Out(_) => punch_out(&now, day),
             SubCommand::Pause(other_args) => take_break(&now, other_args, day),
             SubCommand::Resume(other_args) => resume(&now, other_args, day),
             SubCommand::Summary(_) => summary(&now, day),
-            SubCommand::View(_) => view_day(day),
-            SubCommand::Edit(_) => edit_day(day),
+            SubCommand::Status(other_args) => status(clock.as_ref(), day, other_args),
+            SubCommand::Today(_) => today(&now, day),
+            SubCommand::View(_) => unreachable!("'punch view' commands should already be processed."),
+            SubCommand::Edit(_) => unreachable!("'punch edit' commands should already be processed."),
             SubCommand::EditConfig(_) => edit_config(),
             SubCommand::ViewConfig(_) => view_config(),
             SubCommand::Task(other_args) => switch_to_new_task(&now, day, other_args),
             SubCommand::Note(other_args) => add_note_to_today(&now, day, other_args),
             SubCommand::AddSummary(other_args) => add_summary_to_today(day, other_args),
             SubCommand::UpdateTask(other_args) => update_current_task_name(&now, day, other_args),
+            SubCommand::Amend(other_args) => amend(&now, day, other_args),
+            SubCommand::Target(other_args) => target(day, other_args),
+            SubCommand::Split(other_args) => split(&now, day, other_args),
+            SubCommand::Annotate(other_args) => annotate(day, other_args),
+            SubCommand::Mark(other_args) => mark(day, other_args),
+            SubCommand::Focus(other_args) => focus(&now, day, other_args),
+            SubCommand::Mood(other_args) => mood(&now, day, other_args),
+            SubCommand::Meta(other_args) => meta(day, other_args),
+            SubCommand::Plan(other_args) => plan(&now, day, other_args),
+            SubCommand::Carry(other_args) => carry(&now, day, other_args),
+            SubCommand::Blocker(other_args) => blocker(&now, day, other_args),
+            SubCommand::Import(other_args) => import(&now, day, other_args),
+            SubCommand::Report(_) => unreachable!("'punch report' commands should already be processed."),
+            SubCommand::Compare(_) => unreachable!("'punch compare' commands should already be processed."),
+            SubCommand::EmailReport(_) => unreachable!("'punch email-report' commands should already be processed."),
+            SubCommand::Export(_) => unreachable!("'punch export' commands should already be processed."),
+            SubCommand::Oncall(_) => unreachable!("'punch oncall' commands should already be processed."),
+            SubCommand::Expense(_) => unreachable!("'punch expense' commands should already be processed."),
+            SubCommand::Template(_) => unreachable!("'punch template' commands should already be processed."),
+            SubCommand::Todo(_) => unreachable!("'punch todo' commands should already be processed."),
+            SubCommand::Done(_) => unreachable!("'punch done' commands should already be processed."),
+            SubCommand::Undone(_) => unreachable!("'punch undone' commands should already be processed."),
+            SubCommand::Reminders(_) => unreachable!("'punch reminders' commands should already be processed."),
+            SubCommand::Timer(_) => unreachable!("'punch timer' commands should already be processed."),
+            SubCommand::Watch(_) => unreachable!("'punch watch' commands should already be processed."),
+            SubCommand::Is(_) => unreachable!("'punch is' commands should already be processed."),
+            SubCommand::Budget(_) => unreachable!("'punch budget' commands should already be processed."),
+            SubCommand::Project(_) => unreachable!("'punch project' commands should already be processed."),
+            SubCommand::RenameTask(_) => unreachable!("'punch rename-task' commands should already be processed."),
+            SubCommand::Doctor(_) => unreachable!("'punch doctor' commands should already be processed."),
+            SubCommand::Daemon(_) => unreachable!("'punch daemon' commands should already be processed."),
+            SubCommand::Timeline(_) => unreachable!("'punch timeline' commands should already be processed."),
+            SubCommand::Heatmap(_) => unreachable!("'punch heatmap' commands should already be processed."),
+            SubCommand::Batch(_) => unreachable!("'punch batch' commands should already be processed."),
+            SubCommand::Serve(_) => unreachable!("'punch serve' commands should already be processed."),
+            SubCommand::InstallService(_) => unreachable!("'punch install-service' commands should already be processed."),
+            SubCommand::Service(_) => unreachable!("'punch service' commands should already be processed."),
+            SubCommand::Tray(_) => unreachable!("'punch tray' commands should already be processed."),
+            SubCommand::Delete(_) => unreachable!("'punch delete' commands should already be processed."),
+            SubCommand::AddDay(_) => unreachable!("'punch add-day' commands should already be processed."),
+            SubCommand::Backfill(_) => unreachable!("'punch backfill' commands should already be processed."),
+            SubCommand::DemoData(_) => unreachable!("'punch demo-data' commands should already be processed."),
+            SubCommand::AdjustBalance(_) => unreachable!("'punch adjust-balance' commands should already be processed."),
+            SubCommand::Compliance(_) => unreachable!("'punch compliance' commands should already be processed."),
+            SubCommand::Forecast(_) => unreachable!("'punch forecast' commands should already be processed."),
+            SubCommand::PlanWeek(_) => unreachable!("'punch plan-week' commands should already be processed."),
+            SubCommand::Away(_) => unreachable!("'punch away' commands should already be processed."),
+            SubCommand::Prune(_) => unreachable!("'punch prune' commands should already be processed."),
+            SubCommand::Compress(_) => unreachable!("'punch compress' commands should already be processed."),
+            SubCommand::Team(_) => unreachable!("'punch team' commands should already be processed."),
+            SubCommand::Submit(_) => unreachable!("'punch submit' commands should already be processed."),
+            SubCommand::CloseWeek(_) => unreachable!("'punch close-week' commands should already be processed."),
+            SubCommand::Verify(_) => unreachable!("'punch verify' commands should already be processed."),
+            SubCommand::Merge(_) => unreachable!("'punch merge' commands should already be processed."),
+            SubCommand::Undo(_) => unreachable!("'punch undo' commands should already be processed."),
+            SubCommand::Recalc(_) => unreachable!("'punch recalc' commands should already be processed."),
+            SubCommand::Standup(_) => unreachable!("'punch standup' commands should already be processed."),
+            SubCommand::Reindex(_) => unreachable!("'punch reindex' commands should already be processed."),
+            SubCommand::Config(_) => unreachable!("'punch config' commands should already be processed."),
+            SubCommand::SelfUpdate(_) => unreachable!("'punch self-update' commands should already be processed."),
             SubCommand::Version(_) => unreachable!("`punch version/--version/-v` commands should already be processed."),
             SubCommand::In(_) => unreachable!("'punch in' commands shouldn't be being processed"),
             SubCommand::Invalid(_) => unreachable!("Invalid commands shouldn't be being processed here"),
@@ -134,8 +597,24 @@ fn run_command(command: SubCommand, now: DateTime<Local>) {
     }
 }
 
+/// Words people reach for that aren't subcommands, with the subcommand they likely meant.
+const SUBCOMMAND_SYNONYMS: [(&str, &str); 9] = [
+    ("start", "in"), ("stop", "out"), ("break", "pause"), ("continue", "resume"), ("switch", "task"),
+    ("log", "view"), ("show", "view"), ("settings", "config"), ("rename", "rename-task"),
+];
+
+/// Suggests the subcommand `command` was likely meant to be, or else lists them all.
 fn handle_invalid_cmd(command: &String) {
-    eprintln!("'{}' is not a valid subcommand for punch. Try one of the following:", command);
+    let mut words: Vec<(String, String)> = SubCommand::get_allowed_strings().into_iter()
+        .filter(|x: &String| !x.starts_with('-'))
+        .map(|x: String| (x.clone(), x))
+        .collect();
+    words.extend(SUBCOMMAND_SYNONYMS.iter().map(|(word, subcommand)| (word.to_string(), subcommand.to_string())));
+    if let Some(suggestion) = get_suggestion(command, &words) {
+        eprintln!("{}", tr("did_you_mean_command", &[&command, suggestion]));
+        exit(1);
+    }
+    eprintln!("{}", tr("invalid_command", &[&command]));
     for str_subcommand in SubCommand::get_allowed_strings() {
         eprintln!("\t{}", str_subcommand);
     }
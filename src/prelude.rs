@@ -0,0 +1,8 @@
+//! The types for building and reading days from other crates: `use punch::prelude::*;`.
+
+pub use crate::builder::DayBuilder;
+pub use crate::units::components::TimeBlock;
+pub use crate::units::day::{Day, DayIterator};
+pub use crate::units::interval::{Dt, Interval, DATE_FMT, DATETIME_FMT};
+pub use crate::utils::period::DateRange;
+pub use crate::utils::work_summary::WorkSummary;
@@ -0,0 +1,154 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use chrono::prelude::{DateTime, Local, NaiveDate};
+
+use crate::commands::batch::{BatchState, apply_args};
+use crate::units::day::{Day, create_daily_dir_if_not_exists, read_day_for_date};
+use crate::utils::args::split_command_line;
+use crate::utils::clock::{SharedClock, SystemClock, set_clock};
+use crate::utils::config::{Config, create_default_config_if_not_exists, get_config};
+use crate::utils::file_io::create_base_dir_if_not_exists;
+use crate::utils::state::{State, create_state_if_not_exists, get_state};
+use crate::utils::storage::{MemoryStorage, set_storage};
+
+/// The storage backend is process-wide, so only one runner can be alive at a time.
+static RUNNER_LOCK: Mutex<()> = Mutex::new(());
+/// Numbers the home folders of `CliRunner`s, which can run side by side.
+static NUM_CLI_HOMES: AtomicUsize = AtomicUsize::new(0);
+
+/// Runs punch commands against in-memory storage, for simulating whole workflows in tests.
+/// Commands are written like `punch batch` lines, e.g. `pause --at "2024-01-01 12:00"`, and
+/// go through batch mode's handling of them. `CliRunner` runs the real commands instead.
+/// The filesystem is back in use once the runner is dropped.
+pub struct CommandRunner {
+    storage: Arc<MemoryStorage>,
+    clock: SharedClock,
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl CommandRunner {
+    /// Starts from an empty data directory with the default config, on the real time.
+    pub fn new() -> Self {
+        return Self::with_clock(Arc::new(SystemClock));
+    }
+
+    /// Like `new`, but commands without `--at` happen at `clock`'s time.
+    pub fn with_clock(clock: SharedClock) -> Self {
+        let guard: MutexGuard<'static, ()> = RUNNER_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+        let storage: Arc<MemoryStorage> = Arc::new(MemoryStorage::new());
+        set_storage(Some(storage.clone()));
+        set_clock(Some(clock.clone()));
+        create_base_dir_if_not_exists();
+        create_default_config_if_not_exists();
+        create_daily_dir_if_not_exists();
+        create_state_if_not_exists();
+        return Self {storage: storage, clock: clock, _guard: guard};
+    }
+
+    /// Runs one command, with `now` as the time for commands without `--at`.
+    pub fn run_at(&self, line: &str, now: &DateTime<Local>) -> Result<String, String> {
+        let config: Config = get_config();
+        let mut state: BatchState = BatchState::new();
+        let msg: String = apply_args(&mut state, split_command_line(line)?, now, &config)?;
+        state.commit()?;
+        return Ok(msg);
+    }
+
+    pub fn run(&self, line: &str) -> Result<String, String> {
+        return self.run_at(line, &self.clock.now());
+    }
+
+    /// Runs each command in turn, stopping at the first failure.
+    pub fn run_all(&self, lines: &[&str]) -> Result<Vec<String>, String> {
+        return lines.iter().map(|line: &&str| self.run(line)).collect();
+    }
+
+    pub fn day(&self, date: &NaiveDate) -> Option<Day> {
+        return read_day_for_date(date).ok();
+    }
+
+    pub fn config(&self) -> Config {
+        return get_config();
+    }
+
+    pub fn state(&self) -> State {
+        return get_state();
+    }
+
+    pub fn storage(&self) -> &MemoryStorage {
+        return &self.storage;
+    }
+}
+
+impl Default for CommandRunner {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl Drop for CommandRunner {
+    fn drop(&mut self) {
+        set_storage(None);
+        set_clock(None);
+    }
+}
+
+/// Runs a built punch binary the way someone would from a shell, in a home folder of its
+/// own, so tests go through the real command handlers, their output and their exit codes.
+/// Integration tests get the binary from `env!("CARGO_BIN_EXE_punch")`. The home folder is
+/// deleted once the runner is dropped.
+pub struct CliRunner {
+    bin: PathBuf,
+    home: PathBuf,
+    envs: Vec<(String, String)>,
+}
+
+impl CliRunner {
+    pub fn new(bin: &str) -> Self {
+        let home: PathBuf = std::env::temp_dir().join(format!("punch-cli-{}-{}", std::process::id(), NUM_CLI_HOMES.fetch_add(1, Ordering::SeqCst)));
+        let _ = fs::remove_dir_all(&home);
+        fs::create_dir_all(&home).expect("The temp folder should be writable");
+        return Self {bin: PathBuf::from(bin), home: home, envs: Vec::new()};
+    }
+
+    /// Runs every command with the environment variable set, like `PATH` to hide a program.
+    pub fn with_env(mut self, key: &str, value: &str) -> Self {
+        self.envs.push((key.to_string(), value.to_string()));
+        return self;
+    }
+
+    /// Runs `punch --now <now> <args>`, on UTC, on a machine named "desktop".
+    pub fn run(&self, now: &str, args: &[&str]) -> Output {
+        return Command::new(&self.bin)
+            .env("HOME", &self.home)
+            .env("HOSTNAME", "desktop")
+            .env("TZ", "UTC")
+            .envs(self.envs.iter().map(|(key, value): &(String, String)| (key, value)))
+            .args(["--now", now])
+            .args(args)
+            .stdin(Stdio::null())
+            .output()
+            .expect("The punch binary should run");
+    }
+
+    /// Like `run`, but the command has to succeed, and its output is returned.
+    pub fn ok(&self, now: &str, args: &[&str]) -> String {
+        let output: Output = self.run(now, args);
+        assert!(output.status.success(), "punch {:?} failed: {}", args, String::from_utf8_lossy(&output.stderr));
+        return String::from_utf8(output.stdout).expect("punch should print text");
+    }
+
+    /// Where a file in the default data folder is, like "days/2024-01-31".
+    pub fn data_path(&self, path: &str) -> PathBuf {
+        return self.home.join(".punch-card").join(path);
+    }
+}
+
+impl Drop for CliRunner {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.home);
+    }
+}
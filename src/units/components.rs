@@ -7,6 +7,9 @@ use crate::units::interval::{Dt,Interval};
 pub struct Note {
     time: Dt,
     msg: String,
+    /// When the daemon should remind you of the note, if it should.
+    #[serde(default)]
+    remind_at: Option<Dt>,
 }
 
 impl Note {
@@ -14,8 +17,129 @@ impl Note {
         return Note {
             time: Dt(*time),
             msg: msg.to_string(),
+            remind_at: None,
         };
     }
+
+    pub fn get_remind_at(&self) -> Option<Dt> {
+        return self.remind_at;
+    }
+
+    pub fn set_remind_at(&mut self, remind_at: &DateTime<Local>) {
+        self.remind_at = Some(Dt(*remind_at));
+    }
+
+    pub fn get_time(&self) -> Dt {
+        return self.time;
+    }
+
+    pub fn get_msg(&self) -> String {
+        return self.msg.clone();
+    }
+}
+
+/// A check-in of how you're feeling, from 1 (low) to 5 (high).
+#[derive(Debug,Serialize,Deserialize,Clone)]
+pub struct MoodEntry {
+    time: Dt,
+    score: u8,
+    #[serde(default)]
+    note: Option<String>,
+}
+
+impl MoodEntry {
+    pub fn new(time: &DateTime<Local>, score: u8, note: Option<String>) -> Self {
+        return MoodEntry {
+            time: Dt(*time),
+            score: score,
+            note: note,
+        };
+    }
+
+    pub fn get_time(&self) -> Dt {
+        return self.time;
+    }
+
+    pub fn get_score(&self) -> u8 {
+        return self.score;
+    }
+
+    pub fn get_note(&self) -> Option<&String> {
+        return self.note.as_ref();
+    }
+
+    pub fn clear_note(&mut self) {
+        self.note = None;
+    }
+}
+
+/// A stretch of time you were away from the computer, such as while it was asleep.
+#[derive(Debug,Serialize,Deserialize,Clone)]
+pub struct AwaySpan {
+    pub start: Dt,
+    pub end: Dt,
+    /// Why you were taken to be away, e.g. "asleep".
+    pub reason: String,
+}
+
+impl AwaySpan {
+    pub fn new(start: &DateTime<Local>, end: &DateTime<Local>, reason: &str) -> Self {
+        return Self {start: Dt(*start), end: Dt(*end), reason: reason.to_string()};
+    }
+}
+
+/// A reminder from the daemon to take a break, and how soon after it a break had to start
+/// to count as taking it.
+#[derive(Debug,Serialize,Deserialize,Clone)]
+pub struct BreakReminder {
+    pub sent: Dt,
+    pub followed_within_minutes: u64,
+}
+
+impl BreakReminder {
+    pub fn new(sent: &DateTime<Local>, followed_within_minutes: u64) -> Self {
+        return Self {sent: Dt(*sent), followed_within_minutes: followed_within_minutes};
+    }
+}
+
+/// A block you mean to work, from a template or the day's plan.
+#[derive(Debug,Serialize,Deserialize,Clone)]
+pub struct PlannedBlock {
+    pub task: String,
+    pub minutes: u64,
+    /// When it's meant to start, as "HH:MM", for blocks at a set time like a class or a clinic.
+    #[serde(default)]
+    pub start: Option<String>,
+}
+
+impl PlannedBlock {
+    pub fn new(task: String, minutes: u64, start: Option<String>) -> Self {
+        return Self {task: task, minutes: minutes, start: start};
+    }
+
+    pub fn describe(&self) -> String {
+        return match &self.start {
+            Some(start) => format!("{} {} ({} m)", start, self.task, self.minutes),
+            None => format!("{} ({} m)", self.task, self.minutes),
+        };
+    }
+}
+
+/// Money spent for work, or a distance driven for it that's paid back by the kilometre.
+#[derive(Debug,Serialize,Deserialize,Clone)]
+pub struct Expense {
+    pub time: Dt,
+    pub amount_cents: i64,
+    pub description: String,
+    /// The distance driven, when `amount_cents` was worked out from the mileage rate.
+    #[serde(default)]
+    pub km: Option<f64>,
+}
+
+impl Expense {
+    pub fn new(time: &DateTime<Local>, amount_cents: i64, description: String, km: Option<f64>) -> Self {
+        return Self {time: Dt(*time), amount_cents: amount_cents, description: description, km: km};
+    }
 }
 
 #[derive(Debug,Serialize,Deserialize,Clone)]
@@ -23,6 +147,14 @@ pub struct TimeBlock {
     task_name: String,
     interval: Interval,
     notes: Vec<Note>,
+    /// Remarks about the block as a whole, added after the fact.
+    #[serde(default)]
+    annotations: Vec<String>,
+    #[serde(default)]
+    billable: bool,
+    /// The planned length of the block if it was started as a focus session.
+    #[serde(default)]
+    focus_minutes: Option<u64>,
 }
 
 impl TimeBlock {
@@ -31,6 +163,9 @@ impl TimeBlock {
             task_name: task_name,
             interval: Interval::new(start),
             notes: Vec::new(),
+            annotations: Vec::new(),
+            billable: false,
+            focus_minutes: None,
         };
     }
 
@@ -44,6 +179,10 @@ impl TimeBlock {
         return Ok(());
     }
 
+    pub fn start_at(&mut self, start: &DateTime<Local>) {
+        self.interval.start_at(start);
+    }
+
     pub fn end_at(&mut self, end: &DateTime<Local>) {
         self.interval.end_at(end);
     }
@@ -91,8 +230,89 @@ impl TimeBlock {
         return self.interval.get_length_mins();
     }
 
-    pub fn add_note(&mut self, time: &DateTime<Local>, msg: &String) {
+    pub fn add_note(&mut self, time: &DateTime<Local>, msg: &String) -> &mut Note {
         let new_note: Note = Note::new(time, msg);
         self.notes.push(new_note);
+        return self.notes.last_mut().expect("A note was just added");
+    }
+
+    pub fn get_notes(&self) -> &Vec<Note> {
+        return &self.notes;
+    }
+
+    pub fn is_billable(&self) -> bool {
+        return self.billable;
+    }
+
+    pub fn set_billable(&mut self, billable: bool) {
+        self.billable = billable;
+    }
+
+    pub fn get_focus_minutes(&self) -> Option<u64> {
+        return self.focus_minutes;
+    }
+
+    pub fn set_focus_minutes(&mut self, focus_minutes: Option<u64>) {
+        self.focus_minutes = focus_minutes;
+    }
+
+    /// A one-line description, like "09:00-12:30 coding".
+    pub fn describe(&self) -> String {
+        let end: String = match self.get_end() {
+            Some(end) => end.as_dt().format("%H:%M").to_string(),
+            None => "now".to_string(),
+        };
+        return format!("{}-{} {}", self.get_start().as_dt().format("%H:%M"), end, self.task_name);
+    }
+
+    pub fn add_annotation(&mut self, text: String) {
+        self.annotations.push(text);
+    }
+
+    pub fn get_annotations(&self) -> &Vec<String> {
+        return &self.annotations;
+    }
+
+    /// Removes the block's notes and annotations.
+    pub fn clear_notes(&mut self) {
+        self.notes.clear();
+        self.annotations.clear();
+    }
+
+    /// Whether `later`, which starts when this block ends, could be joined onto it.
+    pub fn can_join(&self, later: &TimeBlock) -> bool {
+        return self.task_name == later.task_name && self.billable == later.billable && self.focus_minutes == later.focus_minutes;
+    }
+
+    /// Extends this block to the end of `later`, taking its notes and annotations.
+    pub fn join(&mut self, later: TimeBlock) {
+        self.interval = Interval::new(&self.get_start().as_dt());
+        if let Some(end) = later.get_end() {
+            self.end_at(&end.as_dt());
+        }
+        self.notes.extend(later.notes);
+        for annotation in later.annotations {
+            if !self.annotations.contains(&annotation) {
+                self.annotations.push(annotation);
+            }
+        }
+    }
+
+    /// Ends this block at `at` and returns the rest of it as a new block for `task_name`,
+    /// taking the notes made from `at` onwards with it.
+    pub fn split_at(&mut self, at: &DateTime<Local>, task_name: String) -> TimeBlock {
+        let mut rest: TimeBlock = TimeBlock::new(task_name, at);
+        if let Some(end) = self.get_end() {
+            rest.end_at(&end.as_dt());
+        }
+        let (later_notes, earlier_notes): (Vec<Note>, Vec<Note>) = self.notes.drain(..)
+            .partition(|note: &Note| note.get_time().as_dt() >= *at);
+        self.notes = earlier_notes;
+        rest.notes = later_notes;
+        rest.annotations = self.annotations.clone();
+        rest.billable = self.billable;
+        rest.focus_minutes = self.focus_minutes;
+        self.end_at(at);
+        return rest;
     }
 }
@@ -1,26 +1,37 @@
-use std::collections::{HashMap,HashSet};
-use chrono::prelude::{DateTime, Local};
+use std::collections::{BTreeMap,HashMap,HashSet};
+use std::process::exit;
+use chrono::prelude::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime};
 use chrono::Duration;
 use serde::{Serialize, Deserialize};
 
-use crate::units::components::TimeBlock;
+use crate::units::components::{AwaySpan, BreakReminder, Expense, MoodEntry, Note, PlannedBlock, TimeBlock};
 use crate::units::interval::{Dt,Interval, DATE_FMT, DATETIME_FMT};
 
+use crate::utils::access::is_read_only;
+use crate::utils::checksums::{Checksummed, check_checksum, delete_checksum, warn_if_corrupted, write_checksum};
+use crate::utils::event_log::{can_replay, is_same_day, list_logged_dates, read_events, record_day, record_deletion, replay};
 use crate::utils::file_io::{
     create_dir_if_not_exists,
-    expand_path, 
+    delete_file,
+    holds_data_lock,
+    list_dir,
     read_file,
     write_file,
     FromString,
     SafeFileEdit,
-    ToFile, 
-    BASE_DIR};
+    ToFile};
+use crate::utils::index::{remove_from_index, update_index_for_day};
+use crate::utils::ledger::{check_week_open, record_correction_if_closed};
+use crate::utils::period::DateRange;
+use crate::utils::team::get_data_dir;
 use crate::utils::work_summary::WorkSummary;
 
 pub const DAILY_DIR: &str = "days/";
 
 
+/// A day of work. It's stored in the format of [`DAY_FORMAT_VERSION`], and older files still read.
 #[derive(Debug,Serialize,Deserialize,Clone)]
+#[serde(try_from = "DayRecord", into = "DayRecord")]
 pub struct Day {
     pub overall_interval: Interval,
     pub timeblocks: Vec<TimeBlock>,
@@ -29,6 +40,140 @@ pub struct Day {
     pub on_break: bool,
     pub time_to_do: u64,
     pub summaries: Vec<WorkSummary>,
+    /// How long after the expected start the day was punched in, if one was set then.
+    /// Negative when early.
+    pub lateness_secs: Option<i64>,
+    pub moods: Vec<MoodEntry>,
+    /// What you mean to get done today, for `punch standup`.
+    pub plans: Vec<Note>,
+    /// What's holding you up, for `punch standup`.
+    pub blockers: Vec<Note>,
+    /// When the day was handed in with `punch submit`, after which it's read-only.
+    pub submitted: Option<Dt>,
+    /// Time the daemon counted as a break while you were away, still to be confirmed.
+    pub away_spans: Vec<AwaySpan>,
+    /// Free-form key-values set with `punch meta`, like a location or a ticket number.
+    pub meta: BTreeMap<String, String>,
+    /// Money spent and distances driven for work, added with `punch expense`.
+    pub expenses: Vec<Expense>,
+    /// The blocks you meant to work today, e.g. from `punch in --template`.
+    pub planned_blocks: Vec<PlannedBlock>,
+    /// How much of the day's breaks counts as time done, from the config's
+    /// `paid_break_minutes` when the day started.
+    pub paid_break_minutes: u64,
+    /// The daemon's reminders to take a break after working a while without one.
+    pub break_reminders: Vec<BreakReminder>,
+}
+
+/// The version of the day format written now. Files from before it was versioned have no
+/// `version` and also list each task's blocks under `tasks`, which is now worked out from
+/// the blocks when reading.
+pub const DAY_FORMAT_VERSION: u32 = 2;
+
+/// A day as it's stored, checked and turned into a [`Day`] when read.
+#[derive(Debug,Serialize,Deserialize)]
+struct DayRecord {
+    #[serde(default = "get_legacy_format_version")]
+    version: u32,
+    overall_interval: Interval,
+    timeblocks: Vec<TimeBlock>,
+    breaks: Vec<usize>,
+    on_break: bool,
+    time_to_do: u64,
+    summaries: Vec<WorkSummary>,
+    #[serde(default)]
+    lateness_secs: Option<i64>,
+    #[serde(default)]
+    moods: Vec<MoodEntry>,
+    #[serde(default)]
+    plans: Vec<Note>,
+    #[serde(default)]
+    blockers: Vec<Note>,
+    #[serde(default)]
+    submitted: Option<Dt>,
+    #[serde(default)]
+    away_spans: Vec<AwaySpan>,
+    #[serde(default)]
+    meta: BTreeMap<String, String>,
+    #[serde(default)]
+    expenses: Vec<Expense>,
+    #[serde(default)]
+    planned_blocks: Vec<PlannedBlock>,
+    #[serde(default)]
+    paid_break_minutes: u64,
+    #[serde(default)]
+    break_reminders: Vec<BreakReminder>,
+}
+
+fn get_legacy_format_version() -> u32 {
+    return 1;
+}
+
+impl TryFrom<DayRecord> for Day {
+    type Error = String;
+
+    fn try_from(record: DayRecord) -> Result<Self, Self::Error> {
+        if record.version > DAY_FORMAT_VERSION {
+            return Err(format!(
+                "This day is in format version {}, from a newer punch, which only reads up to {}. Update punch to read it.",
+                record.version, DAY_FORMAT_VERSION));
+        }
+        if record.timeblocks.is_empty() {
+            return Err("A day needs at least one block!".to_string());
+        }
+        if let Some(ind) = record.breaks.iter().find(|ind: &&usize| **ind >= record.timeblocks.len()) {
+            return Err(format!("Break {} isn't one of the day's {} blocks!", ind, record.timeblocks.len()));
+        }
+        let mut tasks: HashMap<String, Vec<usize>> = HashMap::new();
+        for (ind, block) in record.timeblocks.iter().enumerate() {
+            tasks.entry(block.get_task_name()).or_default().push(ind);
+        }
+        return Ok(Self {
+            overall_interval: record.overall_interval,
+            timeblocks: record.timeblocks,
+            tasks: tasks,
+            breaks: record.breaks,
+            on_break: record.on_break,
+            time_to_do: record.time_to_do,
+            summaries: record.summaries,
+            lateness_secs: record.lateness_secs,
+            moods: record.moods,
+            plans: record.plans,
+            blockers: record.blockers,
+            submitted: record.submitted,
+            away_spans: record.away_spans,
+            meta: record.meta,
+            expenses: record.expenses,
+            planned_blocks: record.planned_blocks,
+            paid_break_minutes: record.paid_break_minutes,
+            break_reminders: record.break_reminders,
+        });
+    }
+}
+
+impl From<Day> for DayRecord {
+    fn from(day: Day) -> Self {
+        return Self {
+            version: DAY_FORMAT_VERSION,
+            overall_interval: day.overall_interval,
+            timeblocks: day.timeblocks,
+            breaks: day.breaks,
+            on_break: day.on_break,
+            time_to_do: day.time_to_do,
+            summaries: day.summaries,
+            lateness_secs: day.lateness_secs,
+            moods: day.moods,
+            plans: day.plans,
+            blockers: day.blockers,
+            submitted: day.submitted,
+            away_spans: day.away_spans,
+            meta: day.meta,
+            expenses: day.expenses,
+            planned_blocks: day.planned_blocks,
+            paid_break_minutes: day.paid_break_minutes,
+            break_reminders: day.break_reminders,
+        };
+    }
 }
 
 impl Day {
@@ -42,6 +187,56 @@ impl Day {
             on_break: false, 
             time_to_do: time_to_do,
             summaries: Vec::new(),
+            lateness_secs: None,
+            moods: Vec::new(),
+            plans: Vec::new(),
+            blockers: Vec::new(),
+            submitted: None,
+            away_spans: Vec::new(),
+            meta: BTreeMap::new(),
+            expenses: Vec::new(),
+            planned_blocks: Vec::new(),
+            paid_break_minutes: 0,
+            break_reminders: Vec::new(),
+        };
+    }
+
+    /// Builds a day out of blocks in chronological order, each flagged with whether it's a
+    /// break. The day has ended if the last block has.
+    pub fn from_blocks(blocks: Vec<(TimeBlock, bool)>, time_to_do: u64, summaries: Vec<WorkSummary>) -> Self {
+        let first: &TimeBlock = &blocks.first().expect("A day needs at least one block").0;
+        let (last, last_is_break): &(TimeBlock, bool) = blocks.last().expect("A day needs at least one block");
+        let mut overall_interval: Interval = Interval::new(&first.get_start().as_dt());
+        if let Some(end) = last.get_end() {
+            overall_interval.end_at(&end.as_dt());
+        }
+        let mut tasks: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut breaks: Vec<usize> = Vec::new();
+        for (ind, (block, is_break)) in blocks.iter().enumerate() {
+            tasks.entry(block.get_task_name()).or_default().push(ind);
+            if *is_break {
+                breaks.push(ind);
+            }
+        }
+        return Self {
+            overall_interval: overall_interval,
+            on_break: *last_is_break && !last.has_end(),
+            timeblocks: blocks.into_iter().map(|(block, _)| block).collect(),
+            tasks: tasks,
+            breaks: breaks,
+            time_to_do: time_to_do,
+            summaries: summaries,
+            lateness_secs: None,
+            moods: Vec::new(),
+            plans: Vec::new(),
+            blockers: Vec::new(),
+            submitted: None,
+            away_spans: Vec::new(),
+            meta: BTreeMap::new(),
+            expenses: Vec::new(),
+            planned_blocks: Vec::new(),
+            paid_break_minutes: 0,
+            break_reminders: Vec::new(),
         };
     }
 
@@ -114,12 +309,7 @@ impl Day {
         let new_block: TimeBlock = TimeBlock::new(task_name.clone(), at);
         let new_ind: usize = self.timeblocks.len();
         self.timeblocks.push(new_block);
-        if self.tasks.contains_key(&task_name) {
-            self.tasks.get_mut(&task_name).expect("Key exists").push(new_ind);
-        }
-        else {
-            self.tasks.insert(task_name, vec![new_ind]);
-        }
+        self.tasks.entry(task_name).or_default().push(new_ind);
         return Ok(());
     }
 
@@ -139,6 +329,183 @@ impl Day {
         }
     }
 
+    /// The most recent event that can be amended ("in", "pause", "resume", "task" or "out")
+    /// and when it happened.
+    pub fn get_last_event(&self) -> (&'static str, DateTime<Local>) {
+        let last_ind: usize = self.timeblocks.len() - 1;
+        if let Some(end) = self.get_day_end() {
+            return ("out", end.as_dt());
+        }
+        let event: &str = if last_ind == 0 {"in"}
+            else if self.is_break(last_ind) {"pause"}
+            else if self.is_break(last_ind - 1) {"resume"}
+            else {"task"};
+        return (event, self.timeblocks[last_ind].get_start().as_dt());
+    }
+
+    /// Moves the most recent event to `at`. It has to stay after the event before it.
+    /// Returns the name of the event that was moved.
+    pub fn amend_last_event_at(&mut self, at: &DateTime<Local>) -> Result<&'static str, String> {
+        let (event, _): (&str, DateTime<Local>) = self.get_last_event();
+        let last_ind: usize = self.timeblocks.len() - 1;
+        let previous: Option<DateTime<Local>> = match event {
+            "in" => None,
+            "out" => Some(self.timeblocks[last_ind].get_start().as_dt()),
+            _ => Some(self.timeblocks[last_ind - 1].get_start().as_dt()),
+        };
+        if let Some(previous) = previous {
+            if *at <= previous {
+                return Err(format!("It has to come after the event before it, at {}", previous.format("%H:%M")));
+            }
+        }
+        match event {
+            "in" => {
+                let moved_by: i64 = (*at - self.get_day_start().as_dt()).num_seconds();
+                self.lateness_secs = self.lateness_secs.map(|secs: i64| secs + moved_by);
+                self.overall_interval.start_at(at);
+                self.timeblocks[0].start_at(at);
+            },
+            "out" => {
+                self.overall_interval.end_at(at);
+                self.timeblocks[last_ind].end_at(at);
+            },
+            _ => {
+                self.timeblocks[last_ind - 1].end_at(at);
+                self.timeblocks[last_ind].start_at(at);
+            },
+        }
+        return Ok(event);
+    }
+
+    /// Splits the block at `ind` in two at `at`. The second half is for `task_name`, or the
+    /// same task if not given.
+    pub fn split_block_at(&mut self, ind: usize, at: &DateTime<Local>, task_name: Option<String>) -> Result<(), String> {
+        let block: &TimeBlock = self.timeblocks.get(ind).ok_or(format!("There's no block {}", ind + 1))?;
+        let after_start: bool = *at > block.get_start().as_dt();
+        let before_end: bool = match block.get_end() {
+            Some(end) => *at < end.as_dt(),
+            None => true,
+        };
+        if !after_start || !before_end {
+            return Err(format!("{} isn't inside block {}", at.format("%H:%M"), ind + 1));
+        }
+
+        let mut blocks: Vec<(TimeBlock, bool)> = self.timeblocks.iter().cloned().enumerate()
+            .map(|(i, block): (usize, TimeBlock)| (block, self.is_break(i)))
+            .collect();
+        let rest_task: String = task_name.unwrap_or(blocks[ind].0.get_task_name());
+        let rest: TimeBlock = blocks[ind].0.split_at(at, rest_task);
+        blocks.insert(ind + 1, (rest, blocks[ind].1));
+        self.replace_blocks(blocks);
+        return Ok(());
+    }
+
+    /// Gives the time from `start` to `end` to `task_name`, splitting the blocks it starts
+    /// and ends in. Breaks in that time are left alone. Returns how many blocks changed.
+    pub fn relabel_span(&mut self, start: &DateTime<Local>, end: &DateTime<Local>, task_name: &String) -> Result<usize, String> {
+        self.split_blocks_at_span(start, end)?;
+        let mut blocks: Vec<(TimeBlock, bool)> = self.timeblocks.iter().cloned().enumerate()
+            .map(|(i, block): (usize, TimeBlock)| (block, self.is_break(i)))
+            .collect();
+        let mut num_changed: usize = 0;
+        for (block, is_break) in blocks.iter_mut() {
+            let within: bool = block.get_start().as_dt() >= *start && block.get_end().is_some_and(|block_end: Dt| block_end.as_dt() <= *end);
+            if within && !*is_break {
+                block.update_task_name(task_name.clone()).expect("Renaming a block can't fail");
+                num_changed += 1;
+            }
+        }
+        self.replace_blocks(blocks);
+        return Ok(num_changed);
+    }
+
+    /// Makes the time from `start` to `end` a block of `task_name`, as a break or not, splitting
+    /// the blocks it starts and ends in. Used to fix up time you were away after the fact.
+    pub fn set_span(&mut self, start: &DateTime<Local>, end: &DateTime<Local>, task_name: &String, is_break: bool) -> Result<(), String> {
+        self.split_blocks_at_span(start, end)?;
+        let mut blocks: Vec<(TimeBlock, bool)> = self.timeblocks.iter().cloned().enumerate()
+            .map(|(i, block): (usize, TimeBlock)| (block, self.is_break(i)))
+            .collect();
+        for (block, block_is_break) in blocks.iter_mut() {
+            let within: bool = block.get_start().as_dt() >= *start && block.get_end().is_some_and(|block_end: Dt| block_end.as_dt() <= *end);
+            if within {
+                block.update_task_name(task_name.clone()).expect("Renaming a block can't fail");
+                *block_is_break = is_break;
+            }
+        }
+        // Blocks split by the span that end up the same again are put back together.
+        let mut joined: Vec<(TimeBlock, bool)> = Vec::new();
+        for (block, block_is_break) in blocks {
+            let boundary: DateTime<Local> = block.get_start().as_dt();
+            match joined.last_mut() {
+                Some((last, last_is_break)) if *last_is_break == block_is_break && last.can_join(&block)
+                    && *start <= boundary && boundary <= *end => last.join(block),
+                _ => joined.push((block, block_is_break)),
+            }
+        }
+        self.replace_blocks(joined);
+        return Ok(());
+    }
+
+    /// Splits the blocks that `start` and `end` fall inside, so that the span starts and
+    /// ends on block boundaries.
+    fn split_blocks_at_span(&mut self, start: &DateTime<Local>, end: &DateTime<Local>) -> Result<(), String> {
+        let day_start: DateTime<Local> = self.get_day_start().as_dt();
+        let day_end: Option<DateTime<Local>> = self.get_day_end().map(|end: Dt| end.as_dt());
+        if start >= end || *start < day_start || day_end.is_some_and(|day_end: DateTime<Local>| *end > day_end) {
+            return Err(format!("{}-{} isn't within the day", start.format("%H:%M"), end.format("%H:%M")));
+        }
+        for at in [start, end] {
+            let inside: Option<usize> = self.timeblocks.iter().position(|block: &TimeBlock| {
+                return block.get_start().as_dt() < *at && block.get_end().is_none_or(|block_end: Dt| *at < block_end.as_dt());
+            });
+            if let Some(ind) = inside {
+                self.split_block_at(ind, at, None)?;
+            }
+        }
+        return Ok(());
+    }
+
+    /// Drops the blocks from `at` on, cutting short the block it falls in, so the day can be
+    /// ended then. Notes are kept.
+    pub fn drop_blocks_from(&mut self, at: &DateTime<Local>) -> Result<(), String> {
+        if self.has_ended() || *at <= self.get_day_start().as_dt() {
+            return Err(format!("The day can't be cut off at {}", at.format("%H:%M")));
+        }
+        if let Some(ind) = self.timeblocks.iter().position(|block: &TimeBlock| block.get_start().as_dt() < *at && block.get_end().is_none_or(|end: Dt| *at < end.as_dt())) {
+            self.split_block_at(ind, at, None)?;
+        }
+        let blocks: Vec<(TimeBlock, bool)> = self.timeblocks.iter().cloned().enumerate()
+            .filter(|(_, block): &(usize, TimeBlock)| block.get_start().as_dt() < *at)
+            .map(|(i, block): (usize, TimeBlock)| (block, self.is_break(i)))
+            .collect();
+        self.replace_blocks(blocks);
+        return Ok(());
+    }
+
+    /// The task being worked on just before `at`, going back past breaks.
+    pub fn get_work_task_before(&self, at: &DateTime<Local>) -> Option<String> {
+        return self.timeblocks.iter().enumerate()
+            .rfind(|(ind, block): &(usize, &TimeBlock)| block.get_start().as_dt() < *at && !self.is_break(*ind))
+            .map(|(_, block): (usize, &TimeBlock)| block.get_task_name());
+    }
+
+    /// Swaps in new blocks, keeping everything else about the day.
+    fn replace_blocks(&mut self, blocks: Vec<(TimeBlock, bool)>) {
+        let rebuilt: Day = Self::from_blocks(blocks, self.time_to_do, Vec::new());
+        self.timeblocks = rebuilt.timeblocks;
+        self.tasks = rebuilt.tasks;
+        self.breaks = rebuilt.breaks;
+        self.on_break = rebuilt.on_break;
+    }
+
+    /// Records how late the day started compared to `expected_start` on the same date.
+    pub fn record_lateness(&mut self, expected_start: &NaiveTime) {
+        let start: DateTime<Local> = self.get_day_start().as_dt();
+        let expected: NaiveDateTime = start.date_naive().and_time(*expected_start);
+        self.lateness_secs = Some((start.naive_local() - expected).num_seconds());
+    }
+
     pub fn get_day_start(&self) -> Dt {
         return self.overall_interval.get_start();
     }
@@ -152,14 +519,13 @@ impl Day {
     }
 
     pub fn get_task_name(&self, ind: isize) -> String {
-        let out_ind: usize;
-        if ind < 0 {
+        let out_ind: usize = if ind < 0 {
             let size: usize = self.timeblocks.len();
-            out_ind = ((size as isize) + ind) as usize;
+            ((size as isize) + ind) as usize
         }
         else {
-            out_ind = ind as usize;
-        }
+            ind as usize
+        };
         return self.timeblocks[out_ind].get_task_name();
     }
 
@@ -183,6 +549,7 @@ impl Day {
         return self.overall_interval.get_length_mins() 
     }
 
+    #[allow(dead_code)]
     pub fn get_task_times_secs(&self) -> HashMap<String, i64> {
         return HashMap::from_iter(
             self.tasks.clone().into_iter().map(
@@ -218,6 +585,38 @@ impl Day {
         );
     }
 
+    /// Time planned and time done for each task that was planned or worked on, planned
+    /// ones first. Breaks aren't counted as time done.
+    pub fn get_plan_vs_actual_secs(&self) -> Vec<(String, i64, i64)> {
+        let mut rows: Vec<(String, i64, i64)> = Vec::new();
+        for planned in &self.planned_blocks {
+            match rows.iter_mut().find(|row: &&mut (String, i64, i64)| row.0 == planned.task) {
+                Some(row) => row.1 += planned.minutes as i64 * 60,
+                None => rows.push((planned.task.clone(), planned.minutes as i64 * 60, 0)),
+            }
+        }
+        for (ind, block) in self.timeblocks.iter().enumerate() {
+            if self.is_break(ind) {
+                continue;
+            }
+            let secs: i64 = block.get_length_secs().unwrap_or(0);
+            match rows.iter_mut().find(|row: &&mut (String, i64, i64)| row.0 == block.get_task_name()) {
+                Some(row) => row.2 += secs,
+                None => rows.push((block.get_task_name(), 0, secs)),
+            }
+        }
+        return rows;
+    }
+
+    /// How much of the time planned went to the tasks it was planned for, capped at what was planned for each.
+    pub fn get_plan_matched_secs(&self) -> i64 {
+        return self.get_plan_vs_actual_secs().iter().map(|(_, planned, actual)| (*planned).min(*actual)).sum();
+    }
+
+    pub fn get_planned_secs(&self) -> i64 {
+        return self.planned_blocks.iter().map(|planned: &PlannedBlock| planned.minutes as i64 * 60).sum();
+    }
+
     pub fn get_total_break_time_secs(&self) -> Option<i64> {
         return match self.on_break {
             true => None,
@@ -225,13 +624,80 @@ impl Day {
         };
     }
 
+    /// Time worked, plus the paid part of the breaks.
     pub fn get_time_done_secs(&self) -> Option<i64> {
-        return match (self.get_day_length_secs(), self.get_total_break_time_secs()) {
-            (Some(day), Some(breaks)) => Some(day - breaks),
-            (_, _) => None,
+        return match (self.get_day_length_secs(), self.get_total_break_time_secs(), self.get_paid_break_secs()) {
+            (Some(day), Some(breaks), Some(paid_breaks)) => Some(day - breaks + paid_breaks),
+            (_, _, _) => None,
         };
     }
 
+    /// The part of the breaks that's paid: the first `paid_break_minutes` of them.
+    pub fn get_paid_break_secs(&self) -> Option<i64> {
+        return self.get_total_break_time_secs().map(|break_secs: i64| break_secs.min(self.paid_break_minutes as i64 * 60));
+    }
+
+    /// How much of the break block at `ind` is paid, going through the breaks in order.
+    /// Unfinished breaks aren't paid.
+    pub fn get_paid_secs_of_break(&self, ind: usize) -> i64 {
+        let mut paid_secs_left: i64 = self.paid_break_minutes as i64 * 60;
+        for break_ind in &self.breaks {
+            let break_secs: i64 = self.timeblocks[*break_ind].get_length_secs().unwrap_or(0);
+            let paid_secs: i64 = break_secs.min(paid_secs_left);
+            if *break_ind == ind {
+                return paid_secs;
+            }
+            paid_secs_left -= paid_secs;
+        }
+        return 0;
+    }
+
+    /// When the stretch of work going on now started: the end of the last break, or else the
+    /// start of the day. `None` on a break or once the day has ended.
+    pub fn get_continuous_work_start(&self) -> Option<Dt> {
+        if self.on_break || self.has_ended() {
+            return None;
+        }
+        return match self.breaks.last() {
+            Some(ind) => self.timeblocks[*ind].get_end(),
+            None => Some(self.get_day_start()),
+        };
+    }
+
+    /// How many of the day's break reminders were followed by a break soon enough.
+    pub fn get_followed_break_reminders(&self) -> u64 {
+        return self.break_reminders.iter()
+            .filter(|reminder: &&BreakReminder| self.breaks.iter().any(|ind: &usize| {
+                let start: DateTime<Local> = self.timeblocks[*ind].get_start().as_dt();
+                let sent: DateTime<Local> = reminder.sent.as_dt();
+                return start >= sent && start <= sent + Duration::minutes(reminder.followed_within_minutes as i64);
+            }))
+            .count() as u64;
+    }
+
+    /// Time spent on billable blocks, not counting breaks or unfinished blocks.
+    pub fn get_billable_secs(&self) -> i64 {
+        return self.timeblocks.iter().enumerate()
+            .filter(|(ind, block)| block.is_billable() && !self.is_break(*ind))
+            .map(|(_, block)| block.get_length_secs().unwrap_or(0))
+            .sum();
+    }
+
+    /// Time spent in focus sessions, counted like billable time.
+    pub fn get_focus_secs(&self) -> i64 {
+        return self.timeblocks.iter().enumerate()
+            .filter(|(ind, block)| block.get_focus_minutes().is_some() && !self.is_break(*ind))
+            .map(|(_, block)| block.get_length_secs().unwrap_or(0))
+            .sum();
+    }
+
+    /// Whether the latest block of a task was billable, for new blocks of it to follow.
+    pub fn was_task_billable(&self, task: &String) -> bool {
+        return self.timeblocks.iter().rev()
+            .find(|block: &&TimeBlock| &block.get_task_name() == task)
+            .is_some_and(|block: &TimeBlock| block.is_billable());
+    }
+
     pub fn get_time_to_do(&self) -> u64 {
         return self.time_to_do;
     }
@@ -243,17 +709,140 @@ impl Day {
         }
     }
 
-    pub fn add_note(&mut self, time: &DateTime<Local>, msg: &String) {
-        self.timeblocks.last_mut()
+    /// Submitted days can only be changed when forced, and days of closed weeks when reopened.
+    pub fn check_editable(&self, force: bool) -> Result<(), String> {
+        if let (Some(submitted), false) = (self.submitted, force) {
+            return Err(format!(
+                "The day for {} was submitted on {} and is read-only. Pass --force to change it anyway.",
+                self.get_day_start().as_dt().format(DATE_FMT), submitted.as_dt().format(DATE_FMT)));
+        }
+        return check_week_open(&self.get_day_start().as_dt().date_naive());
+    }
+
+    /// Removes everything written about the day in your own words: notes, annotations,
+    /// summary texts, mood notes, plans and blockers. Blocks, task names, categories and
+    /// projects are kept, so totals and reports over the day don't change.
+    pub fn anonymize(&mut self) {
+        for block in self.timeblocks.iter_mut() {
+            block.clear_notes();
+        }
+        for summary in self.summaries.iter_mut() {
+            summary.set_summary(String::new());
+        }
+        for mood in self.moods.iter_mut() {
+            mood.clear_note();
+        }
+        self.plans.clear();
+        self.blockers.clear();
+    }
+
+    /// Whether the day's metadata has every one of `conditions`, as pairs of key and value.
+    pub fn matches_meta(&self, conditions: &Vec<(String, String)>) -> bool {
+        return conditions.iter().all(|(key, value)| self.meta.get(key) == Some(value));
+    }
+
+    pub fn add_note(&mut self, time: &DateTime<Local>, msg: &String) -> &mut Note {
+        return self.timeblocks.last_mut()
             .expect("Expected there to be an ongoing block!")
             .add_note(time, msg);
     }
 
+    /// The notes with a reminder, as when to remind and the note, soonest first.
+    pub fn get_note_reminders(&self) -> Vec<(DateTime<Local>, String)> {
+        let mut reminders: Vec<(DateTime<Local>, String)> = self.timeblocks.iter()
+            .flat_map(|block: &TimeBlock| block.get_notes().iter())
+            .filter_map(|note: &Note| note.get_remind_at().map(|remind_at: Dt| (remind_at.as_dt(), note.get_msg())))
+            .collect();
+        reminders.sort_by_key(|(remind_at, _)| *remind_at);
+        return reminders;
+    }
+
+    pub fn add_mood(&mut self, time: &DateTime<Local>, score: u8, note: Option<String>) {
+        self.moods.push(MoodEntry::new(time, score, note));
+    }
+
+    pub fn get_average_mood(&self) -> Option<f64> {
+        return match self.moods.len() {
+            0 => None,
+            n => Some(self.moods.iter().map(|mood: &MoodEntry| mood.get_score() as f64).sum::<f64>() / n as f64),
+        };
+    }
+
+    pub fn annotate_block(&mut self, ind: usize, text: String) -> Result<(), String> {
+        let block: &mut TimeBlock = self.timeblocks.get_mut(ind).ok_or(format!("There's no block {}", ind + 1))?;
+        block.add_annotation(text);
+        return Ok(());
+    }
+
     pub fn add_summary(&mut self, category: String, project: String, task: String, summary: String) {
         let summary: WorkSummary = WorkSummary::new(category, project, task, summary);
         self.summaries.push(summary);
     }
 
+    /// Renames a task in the day's blocks and summaries, merging it into `new_task` if the
+    /// day already has that. Returns how many blocks and summaries were changed.
+    pub fn rename_task(&mut self, old_task: &String, new_task: &String) -> usize {
+        let Some(mut old_inds) = self.tasks.remove(old_task) else {
+            return self.rename_task_in_summaries(old_task, new_task);
+        };
+        for ind in &old_inds {
+            self.timeblocks[*ind].update_task_name(new_task.clone()).expect("Renaming a block can't fail");
+        }
+        let num_blocks: usize = old_inds.len();
+        let new_inds: &mut Vec<usize> = self.tasks.entry(new_task.clone()).or_default();
+        new_inds.append(&mut old_inds);
+        new_inds.sort();
+        return num_blocks + self.rename_task_in_summaries(old_task, new_task);
+    }
+
+    fn rename_task_in_summaries(&mut self, old_task: &String, new_task: &String) -> usize {
+        let mut num_renamed: usize = 0;
+        for summary in self.summaries.iter_mut().filter(|summary: &&mut WorkSummary| summary.get_task() == old_task) {
+            summary.set_task(new_task.clone());
+            num_renamed += 1;
+        }
+        return num_renamed;
+    }
+
+    /// The latest summary written for a task, which gives its category and project.
+    pub fn get_task_summary(&self, task: &String) -> Option<&WorkSummary> {
+        return self.summaries.iter().rev().find(|summary: &&WorkSummary| summary.get_task() == task);
+    }
+
+    /// Time worked per category, for tasks that have one, in the order the categories were
+    /// first worked on. Unfinished blocks aren't counted.
+    pub fn get_category_times_secs(&self) -> Vec<(String, i64)> {
+        return self.get_times_secs_by_summary(WorkSummary::get_category);
+    }
+
+    /// Time worked per project, like `get_category_times_secs`.
+    pub fn get_project_times_secs(&self) -> Vec<(String, i64)> {
+        return self.get_times_secs_by_summary(WorkSummary::get_project);
+    }
+
+    fn get_times_secs_by_summary(&self, get_label: fn(&WorkSummary) -> &String) -> Vec<(String, i64)> {
+        let mut label_times: Vec<(String, i64)> = Vec::new();
+        for (ind, block) in self.timeblocks.iter().enumerate() {
+            let Some(summary) = self.get_task_summary(&block.get_task_name()) else {
+                continue;
+            };
+            if self.is_break(ind) {
+                continue;
+            }
+            let label: &String = get_label(summary);
+            let secs: i64 = block.get_length_secs().unwrap_or(0);
+            match label_times.iter_mut().find(|(name, _)| name == label) {
+                Some((_, total)) => *total += secs,
+                None => label_times.push((label.clone(), secs)),
+            }
+        }
+        return label_times;
+    }
+
+    pub fn is_break(&self, ind: usize) -> bool {
+        return self.breaks.contains(&ind);
+    }
+
     pub fn get_total_timeblocks(&self) -> u64 {
         return self.timeblocks.len() as u64;
     }
@@ -286,8 +875,7 @@ impl ToFile for Day {
     }
 
     fn write(&self) {
-        let path: &String = &self.get_path();
-        write_file(path, self.as_string());
+        write_day(self);
     }
 }
 
@@ -296,31 +884,225 @@ impl SafeFileEdit<Day, serde_yaml::Error> for Day{}
 #[allow(dead_code)]
 pub fn string_as_time(time_str: &String) -> DateTime<Local> {
     let start_time: DateTime<Local> = DateTime::parse_from_str(&time_str, DATETIME_FMT)
-    .expect(&format!("Expected time in ISO format! Given: {}", time_str))
+    .unwrap_or_else(|_| panic!("Expected time in ISO format! Given: {}", time_str))
     .with_timezone(&Local);
     return start_time;
 }
 
 
 pub fn get_day_file_path(now: &DateTime<Local>) -> String {
-    let day_string: String = now.format(DATE_FMT).to_string();
-    return expand_path(BASE_DIR) + &(DAILY_DIR.to_string()) + &day_string;
+    return get_day_file_path_for_date(&now.date_naive());
+}
+
+
+pub fn get_day_file_path_for_date(date: &NaiveDate) -> String {
+    let day_string: String = date.format(DATE_FMT).to_string();
+    return get_daily_dir() + &day_string;
 }
 
 
+/// Writes the day, which has to be in a week that's still open, or `--reopen`ed. Every
+/// command that writes days goes through here, so none can change a closed week unnoticed.
 pub fn write_day(day: &Day) {
-    let path: &String = &get_day_file_path(&day.get_day_start().as_dt());
-    write_file(path, day.as_string());
+    if let Err(msg) = try_write_day(day) {
+        eprintln!("{}", msg);
+        exit(1);
+    }
+}
+
+/// Like `write_day`, but gives the reason the day couldn't be written instead of exiting.
+pub fn try_write_day(day: &Day) -> Result<(), String> {
+    check_week_open(&day.get_day_start().as_dt().date_naive())?;
+    write_day_unchecked(day);
+    return Ok(());
+}
+
+/// Writes the day even if its week is closed, for changes that leave the week's totals as
+/// they were, like anonymizing it.
+pub fn write_day_unchecked(day: &Day) {
+    record_day(day);
+    write_day_file(day);
+    record_correction_if_closed(&day.get_day_start().as_dt().date_naive());
+}
+
+/// Writes the day's file, its checksum and its index entry, which are kept as the day's
+/// event log has it, see `read_day_for_date`.
+fn write_day_file(day: &Day) {
+    let contents: String = day.as_string();
+    write_file(&get_day_file_path(&day.get_day_start().as_dt()), contents.clone());
+    write_checksum(&Checksummed::Day(day.get_day_start().as_dt().date_naive()), &contents);
+    update_index_for_day(day);
 }
 
 
 pub fn read_day(now: &DateTime<Local>) -> Result<Day, std::io::Error> {
-    let path: &String = &get_day_file_path(&now);
-    let read_result: Result<String, std::io::Error> = read_file(path);
-    return match read_result {
-        Ok(string) => Ok(Day::from_string(&string)),
-        Err(err) => Err(err),
+    return read_day_for_date(&now.date_naive());
+}
+
+/// The day as its event log has it, so changes synced from other devices are in it, or
+/// for a day from before there were logs, as its file has it. Every read of a day goes
+/// through here, and brings the day file up to date with the log if it's fallen behind and
+/// the data lock is held.
+pub fn read_day_for_date(date: &NaiveDate) -> Result<Day, std::io::Error> {
+    return read_day_and_refresh(date, true);
+}
+
+/// Reads the day as `read_day_for_date` does. Without `refresh`, the day file is left as it
+/// is, so days can be read on several threads at once.
+fn read_day_and_refresh(date: &NaiveDate, refresh: bool) -> Result<Day, std::io::Error> {
+    // While a log can't be read, the file has the latest this device wrote.
+    if !can_replay(date) {
+        return read_day_file_for_date(date);
+    }
+    let day: Option<Day> = replay(&read_events(date));
+    if refresh {
+        refresh_day_file(date, day.as_ref());
+    }
+    return day.ok_or(std::io::Error::new(
+        std::io::ErrorKind::NotFound, format!("The day for {} was deleted", date.format(DATE_FMT))));
+}
+
+/// Brings the files of days read without refreshing them up to date with their logs, one
+/// after the other.
+pub fn refresh_day_files(dates: &[NaiveDate]) {
+    if is_read_only() || !holds_data_lock() {
+        return;
+    }
+    for date in dates.iter().filter(|date: &&NaiveDate| can_replay(date)) {
+        refresh_day_file(date, replay(&read_events(date)).as_ref());
+    }
+}
+
+/// Like `read_day_for_date`, but with an error to show: that there's no day, or why it
+/// couldn't be read.
+pub fn read_recorded_day(date: &NaiveDate) -> Result<Day, String> {
+    return read_day_for_date(date).map_err(|err: std::io::Error| match err.kind() {
+        std::io::ErrorKind::NotFound => format!("No day recorded for {}", date),
+        _ => err.to_string(),
+    });
+}
+
+/// The day as its file has it, whatever its event log says.
+pub fn read_day_file_for_date(date: &NaiveDate) -> Result<Day, std::io::Error> {
+    let yaml_str: String = read_file(&get_day_file_path_for_date(date))?;
+    warn_if_corrupted(&Checksummed::Day(*date), &yaml_str);
+    return Day::try_from_string(&yaml_str).map_err(|err| std::io::Error::new(
+        std::io::ErrorKind::InvalidData, format!("Couldn't parse the day file for {}: {}", date.format(DATE_FMT), err)));
+}
+
+/// Rewrites the day file (and index entry) if it no longer has what the log does, like
+/// after a sync brought in another device's events. A file changed outside punch is left
+/// for `punch doctor` to report, rather than overwritten. Only commands holding the data
+/// lock refresh, as the file and its checksum are written one after the other, and another
+/// process writing the day in between would leave them not matching.
+fn refresh_day_file(date: &NaiveDate, day: Option<&Day>) {
+    if is_read_only() || !holds_data_lock() {
+        return;
+    }
+    let file_str: Option<String> = read_file(&get_day_file_path_for_date(date)).ok();
+    if let Some(yaml_str) = &file_str {
+        if check_checksum(&Checksummed::Day(*date), yaml_str).is_err() {
+            warn_if_corrupted(&Checksummed::Day(*date), yaml_str);
+            return;
+        }
+    }
+    let file_day: Option<Day> = file_str.and_then(|yaml_str: String| Day::try_from_string(&yaml_str).ok());
+    if is_same_day(day, file_day.as_ref()) {
+        return;
+    }
+    match day {
+        Some(day) => write_day_file(day),
+        None => {
+            let _ = delete_file(&get_day_file_path_for_date(date));
+            delete_checksum(&Checksummed::Day(*date));
+            remove_from_index(date);
+        },
+    }
+}
+
+pub fn delete_day_for_date(date: &NaiveDate) -> Result<(), std::io::Error> {
+    record_deletion(date);
+    delete_file(&get_day_file_path_for_date(date))?;
+    delete_checksum(&Checksummed::Day(*date));
+    remove_from_index(date);
+    record_correction_if_closed(date);
+    return Ok(());
+}
+
+/// Paths of every day file for the date: the day's own file, plus any copies whose name
+/// starts with the date, such as sync conflict copies.
+pub fn list_day_records_for_date(date: &NaiveDate) -> Vec<String> {
+    let daily_dir: String = get_daily_dir();
+    let date_str: String = date.format(DATE_FMT).to_string();
+    let mut names: Vec<String> = list_dir(&daily_dir).unwrap_or_default().into_iter()
+        .filter(|name: &String| name.starts_with(&date_str))
+        .collect();
+    names.sort_by_key(|name: &String| (name != &date_str, name.clone()));
+    return names.into_iter().map(|name: String| daily_dir.clone() + &name).collect();
+}
+
+/// All dates with a day file or an event log, oldest first. A date whose log has the day
+/// deleted is in here too, but doesn't read as a day.
+pub fn list_recorded_dates() -> Vec<NaiveDate> {
+    let daily_dir: String = get_daily_dir();
+    let mut dates: Vec<NaiveDate> = match list_dir(&daily_dir) {
+        Ok(names) => names.iter()
+            .filter_map(|name: &String| NaiveDate::parse_from_str(name, DATE_FMT).ok())
+            .collect(),
+        Err(_) => Vec::new(),
     };
+    dates.extend(list_logged_dates());
+    dates.sort();
+    dates.dedup();
+    return dates;
+}
+
+/// Streams recorded days over a range of dates, reading each day with `read_day_for_date`
+/// only when it's reached, so that long histories don't have to be held in memory at once.
+/// Dates without a day are skipped, and so are ones whose day can't be read, with a warning.
+pub struct DayIterator {
+    dates: std::vec::IntoIter<NaiveDate>,
+    refresh: bool,
+}
+
+impl DayIterator {
+    pub fn new(range: &DateRange) -> Self {
+        return Self::from_dates(list_recorded_dates().into_iter()
+            .filter(|date: &NaiveDate| range.contains(date))
+            .collect());
+    }
+
+    /// Every recorded day, oldest first.
+    #[allow(dead_code)]
+    pub fn all() -> Self {
+        return Self::from_dates(list_recorded_dates());
+    }
+
+    pub fn from_dates(dates: Vec<NaiveDate>) -> Self {
+        return Self {dates: dates.into_iter(), refresh: true};
+    }
+
+    /// Leaves the day files as they are, for iterating on several threads. Call
+    /// `refresh_day_files` with the dates once the threads are done.
+    pub fn without_refresh(mut self) -> Self {
+        self.refresh = false;
+        return self;
+    }
+}
+
+impl Iterator for DayIterator {
+    type Item = Day;
+
+    fn next(&mut self) -> Option<Day> {
+        for date in self.dates.by_ref() {
+            match read_day_and_refresh(&date, self.refresh) {
+                Ok(day) => return Some(day),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {},
+                Err(err) => eprintln!("Warning: {}, so {} is left out.", err, date.format(DATE_FMT)),
+            }
+        }
+        return None;
+    }
 }
 
 pub fn get_current_day(now: &DateTime<Local>) -> Result<Day, String> {
@@ -337,6 +1119,10 @@ pub fn get_current_day(now: &DateTime<Local>) -> Result<Day, String> {
 }
 
 pub fn create_daily_dir_if_not_exists() {
-    let daily_dir: String = BASE_DIR.to_string() + &(DAILY_DIR.to_string());
-    create_dir_if_not_exists(&daily_dir);
+    create_dir_if_not_exists(&get_daily_dir());
+}
+
+/// Where day files go, see `get_data_dir`.
+pub fn get_daily_dir() -> String {
+    return get_data_dir() + DAILY_DIR;
 }
@@ -86,6 +86,10 @@ impl Interval {
         return Self::new(&now);
     }
 
+    pub fn start_at(&mut self, start: &DateTime<Local>) {
+        self.start = Dt(*start);
+    }
+
     pub fn end_at(&mut self, end: &DateTime<Local>) {
         self.end = Some(Dt(*end));
     }
@@ -0,0 +1,91 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use serde::{Serialize, Deserialize};
+
+use crate::utils::config::get_config;
+
+pub const SCOPES: [&str; 2] = ["read", "write"];
+
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// What a request needs to be allowed to do.
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum Scope {
+    Read,
+    Write,
+}
+
+/// A token for `punch serve` from the config's `api_tokens`, with what it's allowed to do.
+/// `write` allows reading too.
+#[derive(Debug,Serialize,Deserialize,Clone,PartialEq)]
+pub struct ApiToken {
+    pub token: String,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+}
+
+impl ApiToken {
+    fn allows(&self, scope: Scope) -> bool {
+        let has_scope = |name: &str| self.scopes.iter().any(|x: &String| x == name);
+        return match scope {
+            Scope::Read => has_scope("read") || has_scope("write"),
+            Scope::Write => has_scope("write"),
+        };
+    }
+}
+
+/// The tokens of the config, including the older `api_token`, which can do anything.
+fn get_api_tokens() -> Vec<ApiToken> {
+    let mut tokens: Vec<ApiToken> = get_config().api_tokens().cloned().unwrap_or_default();
+    if let Some(token) = get_config().api_token() {
+        tokens.push(ApiToken {token: token.clone(), scopes: SCOPES.map(|x: &str| x.to_string()).to_vec()});
+    }
+    return tokens;
+}
+
+/// Checks the config's tokens before serving, so a typo in a scope doesn't quietly lock
+/// someone out.
+pub fn check_api_tokens() -> Result<(), String> {
+    for token in get_config().api_tokens().into_iter().flatten() {
+        if token.token.trim().is_empty() {
+            return Err("A token in 'api_tokens' is empty.".to_string());
+        }
+        if let Some(scope) = token.scopes.iter().find(|x: &&String| !SCOPES.contains(&x.as_str())) {
+            return Err(format!("'{}' isn't a scope a token can have. Use {}.", scope, SCOPES.join(" and/or ")));
+        }
+    }
+    return Ok(());
+}
+
+/// Turns off everything that changes your days for the rest of the run, for `--read-only`.
+pub fn set_read_only() {
+    READ_ONLY.store(true, Ordering::Relaxed);
+}
+
+pub fn is_read_only() -> bool {
+    return READ_ONLY.load(Ordering::Relaxed);
+}
+
+/// Whether a request with `token` can do what `scope` covers, or else the HTTP status and
+/// message to refuse it with. Writes need a token with the write scope. Reads are open until
+/// `api_tokens` are set, and then need a token with the read (or write) scope.
+pub fn check_access(token: Option<&String>, scope: Scope) -> Result<(), (u16, String)> {
+    if scope == Scope::Write && is_read_only() {
+        return Err((403, "punch serve is running with --read-only, so nothing can be changed through it.".to_string()));
+    }
+    let tokens: Vec<ApiToken> = get_api_tokens();
+    let is_locked: bool = match scope {
+        Scope::Read => get_config().api_tokens().is_some_and(|tokens: &Vec<ApiToken>| !tokens.is_empty()),
+        Scope::Write => true,
+    };
+    if !is_locked {
+        return Ok(());
+    }
+    if scope == Scope::Write && !tokens.iter().any(|x: &ApiToken| x.allows(Scope::Write)) {
+        return Err((403, "Write endpoints are disabled. Add a token with the write scope to 'api_tokens' in the config to enable them.".to_string()));
+    }
+    return match tokens.iter().find(|x: &&ApiToken| Some(&x.token) == token) {
+        Some(found) if found.allows(scope) => Ok(()),
+        Some(_) => Err((403, format!("This token doesn't have the {} scope.", if scope == Scope::Read {"read"} else {"write"}))),
+        None => Err((401, "Missing or incorrect bearer token".to_string())),
+    };
+}
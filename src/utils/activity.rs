@@ -0,0 +1,92 @@
+use std::process::Command;
+
+/// Names of the processes running now, from `/proc` on Linux or `ps` elsewhere.
+#[cfg(feature = "meeting-detection")]
+pub fn running_process_names() -> Vec<String> {
+    if let Ok(entries) = std::fs::read_dir("/proc") {
+        return entries.flatten()
+            .filter(|entry| entry.file_name().to_string_lossy().chars().all(|c: char| c.is_ascii_digit()))
+            .filter_map(|entry| std::fs::read_to_string(entry.path().join("comm")).ok())
+            .map(|name: String| name.trim().to_string())
+            .collect();
+    }
+    return run_for_output("ps", &["-axo", "comm="])
+        .map(|output: String| output.lines()
+            .map(|line: &str| line.trim().rsplit('/').next().unwrap_or_default().to_string())
+            .collect())
+        .unwrap_or_default();
+}
+
+/// The title of the focused window, using `osascript` on a Mac, PowerShell on Windows and
+/// `xdotool` elsewhere. `None` if there's no way to find it.
+pub fn active_window_title() -> Option<String> {
+    let title: Option<String> = if cfg!(target_os = "macos") {
+        run_for_output("osascript", &[
+            "-e", "tell application \"System Events\" to set frontApp to first application process whose frontmost is true",
+            "-e", "tell frontApp to if (count of windows) > 0 then get name of front window",
+        ])
+    }
+    else if cfg!(windows) {
+        run_for_output("powershell", &["-NoProfile", "-Command", concat!(
+            "Add-Type -Name W -Namespace P -MemberDefinition '",
+            "[DllImport(\"user32.dll\")] public static extern IntPtr GetForegroundWindow();",
+            "[DllImport(\"user32.dll\")] public static extern int GetWindowThreadProcessId(IntPtr h, out int p);'; ",
+            "$p = 0; [void][P.W]::GetWindowThreadProcessId([P.W]::GetForegroundWindow(), [ref]$p); ",
+            "(Get-Process -Id $p).MainWindowTitle")])
+    }
+    else {
+        run_for_output("xdotool", &["getactivewindow", "getwindowname"])
+    };
+    return title.map(|title: String| title.trim().to_string()).filter(|title: &String| !title.is_empty());
+}
+
+/// Whether the screen is locked, asking `ioreg` on a Mac, looking for the lock screen's
+/// process on Windows and asking logind (`loginctl`) elsewhere. `None` if there's no way to tell.
+pub fn is_screen_locked() -> Option<bool> {
+    if cfg!(target_os = "macos") {
+        let output: String = run_for_output("ioreg", &["-n", "Root", "-d1", "-a"])?;
+        return Some(output.contains("<key>CGSSessionScreenIsLocked</key>"));
+    }
+    if cfg!(windows) {
+        let output: String = run_for_output("tasklist", &["/FI", "IMAGENAME eq LogonUI.exe", "/NH"])?;
+        return Some(output.contains("LogonUI.exe"));
+    }
+    let session: String = std::env::var("XDG_SESSION_ID").unwrap_or("auto".to_string());
+    let output: String = run_for_output("loginctl", &["show-session", &session, "--property=LockedHint"])?;
+    return match output.trim() {
+        "LockedHint=yes" => Some(true),
+        "LockedHint=no" => Some(false),
+        _ => None,
+    };
+}
+
+/// How long it's been since the last keyboard or mouse input, from `ioreg` on a Mac,
+/// PowerShell on Windows and `xprintidle` elsewhere. `None` if there's no way to tell.
+pub fn idle_secs() -> Option<u64> {
+    if cfg!(target_os = "macos") {
+        let output: String = run_for_output("ioreg", &["-c", "IOHIDSystem", "-d", "4"])?;
+        let line: &str = output.lines().find(|line: &&str| line.contains("\"HIDIdleTime\""))?;
+        let nanos: u64 = line.rsplit('=').next()?.trim().parse().ok()?;
+        return Some(nanos / 1_000_000_000);
+    }
+    let millis: String = if cfg!(windows) {
+        run_for_output("powershell", &["-NoProfile", "-Command", concat!(
+            "Add-Type -Name I -Namespace P -MemberDefinition '",
+            "[StructLayout(LayoutKind.Sequential)] public struct L { public uint cbSize; public uint dwTime; } ",
+            "[DllImport(\"user32.dll\")] public static extern bool GetLastInputInfo(ref L l);'; ",
+            "$l = New-Object P.I+L; $l.cbSize = 8; [void][P.I]::GetLastInputInfo([ref]$l); ",
+            "[Environment]::TickCount - $l.dwTime")])?
+    }
+    else {
+        run_for_output("xprintidle", &[])?
+    };
+    return millis.trim().parse::<u64>().ok().map(|millis: u64| millis / 1000);
+}
+
+fn run_for_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    return match output.status.success() {
+        true => Some(String::from_utf8_lossy(&output.stdout).to_string()),
+        false => None,
+    };
+}
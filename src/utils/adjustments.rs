@@ -0,0 +1,39 @@
+use serde::{Serialize, Deserialize};
+
+use crate::units::interval::Dt;
+use crate::utils::file_io::{read_file, write_file};
+use crate::utils::team::get_data_dir;
+
+pub const ADJUSTMENTS_FILE: &str = "adjustments";
+
+/// A change to the balance made by hand with `punch adjust-balance`, kept so the balance can
+/// always be traced back to the days and these.
+#[derive(Debug,Serialize,Deserialize,Clone)]
+pub struct BalanceAdjustment {
+    pub time: Dt,
+    /// Positive when credited, which takes away from minutes behind.
+    pub minutes: i64,
+    pub reason: String,
+}
+
+pub fn get_adjustments_path() -> String {
+    return get_data_dir() + ADJUSTMENTS_FILE;
+}
+
+/// Every adjustment so far, oldest first.
+pub fn read_adjustments() -> Vec<BalanceAdjustment> {
+    return match read_file(&get_adjustments_path()) {
+        Ok(yaml_str) => serde_yaml::from_str(&yaml_str).unwrap_or_default(),
+        Err(_) => Vec::new(),
+    };
+}
+
+pub fn write_adjustments(adjustments: &Vec<BalanceAdjustment>) {
+    write_file(&get_adjustments_path(), serde_yaml::to_string(adjustments).expect("Adjustments should be serializable"));
+}
+
+/// Like "+2 h 0 m" or "-0 h 30 m".
+pub fn format_adjustment(minutes: i64) -> String {
+    let sign: &str = if minutes < 0 {"-"} else {"+"};
+    return format!("{}{} h {} m", sign, minutes.abs() / 60, minutes.abs() % 60);
+}
@@ -0,0 +1,196 @@
+use std::collections::{BTreeMap, HashMap};
+use chrono::Duration;
+use chrono::prelude::{DateTime, Datelike, Local, NaiveDate};
+
+use crate::units::day::Day;
+use crate::units::interval::DATE_FMT;
+use crate::utils::index::DaySummary;
+
+/// Totals over a set of days, used by the multi-day report commands.
+#[derive(Debug,Clone,Default)]
+pub struct PeriodTotals {
+    pub days_worked: u64,
+    pub done_secs: i64,
+    pub billable_secs: i64,
+    pub focus_secs: i64,
+    pub break_secs: i64,
+    pub paid_break_secs: i64,
+    pub to_do_secs: i64,
+    start_secs_sum: i64,
+    /// Days with a recorded lateness, how many of them started on time, and the total
+    /// lateness, where starting early counts as 0.
+    days_with_expected_start: u64,
+    days_on_time: u64,
+    lateness_secs_sum: i64,
+    summary_count: u64,
+    unfinished_count: u64,
+    task_secs: HashMap<String, i64>,
+    task_order: Vec<String>,
+}
+
+impl PeriodTotals {
+    pub fn from_summaries(summaries: &Vec<DaySummary>) -> Self {
+        let mut totals: PeriodTotals = PeriodTotals::default();
+        for summary in summaries {
+            totals.add_day(summary);
+        }
+        return totals;
+    }
+
+    fn add_day(&mut self, summary: &DaySummary) {
+        self.days_worked += 1;
+        self.done_secs += summary.done_secs;
+        self.billable_secs += summary.billable_secs;
+        self.focus_secs += summary.focus_secs;
+        self.break_secs += summary.break_secs;
+        self.paid_break_secs += summary.paid_break_secs;
+        self.to_do_secs += summary.to_do_secs;
+        self.start_secs_sum += summary.start_secs;
+        if let Some(lateness_secs) = summary.lateness_secs {
+            self.days_with_expected_start += 1;
+            self.lateness_secs_sum += lateness_secs.max(0);
+            if lateness_secs <= 0 {
+                self.days_on_time += 1;
+            }
+        }
+        self.summary_count += summary.summary_count;
+        self.unfinished_count += summary.unfinished_count;
+        for task_time in &summary.tasks {
+            *self.task_secs.entry(task_time.task.clone()).or_insert(0) += task_time.secs;
+            if !self.task_order.contains(&task_time.task) {
+                self.task_order.push(task_time.task.clone());
+            }
+        }
+    }
+
+    pub fn non_billable_secs(&self) -> i64 {
+        return self.done_secs - self.billable_secs;
+    }
+
+    pub fn secs_behind(&self) -> i64 {
+        return self.to_do_secs - self.done_secs;
+    }
+
+    /// Average punch-in time as seconds from midnight.
+    pub fn average_start_secs(&self) -> Option<i64> {
+        return match self.days_worked {
+            0 => None,
+            n => Some(self.start_secs_sum / n as i64),
+        };
+    }
+
+    /// Average lateness over the days with an expected start, counting early starts as on time.
+    pub fn average_lateness_secs(&self) -> Option<i64> {
+        return match self.days_with_expected_start {
+            0 => None,
+            n => Some(self.lateness_secs_sum / n as i64),
+        };
+    }
+
+    /// Percentage of the days with an expected start that started on time.
+    pub fn on_time_percent(&self) -> Option<i64> {
+        return match self.days_with_expected_start {
+            0 => None,
+            n => Some((self.days_on_time * 100 / n) as i64),
+        };
+    }
+
+    /// Percentage of the summaries that aren't marked unfinished.
+    pub fn completion_percent(&self) -> Option<i64> {
+        return match self.summary_count {
+            0 => None,
+            n => Some(((n - self.unfinished_count) * 100 / n) as i64),
+        };
+    }
+
+    pub fn get_task_secs(&self, task: &String) -> i64 {
+        return *self.task_secs.get(task).unwrap_or(&0);
+    }
+
+    /// Task names in the order they were first worked on.
+    pub fn get_tasks(&self) -> Vec<String> {
+        return self.task_order.clone();
+    }
+}
+
+/// Time done on each date.
+pub fn get_done_secs_by_date(summaries: &Vec<DaySummary>) -> HashMap<NaiveDate, i64> {
+    return summaries.iter()
+        .filter_map(|summary: &DaySummary| NaiveDate::parse_from_str(&summary.date, DATE_FMT).ok()
+            .map(|date: NaiveDate| (date, summary.done_secs)))
+        .collect();
+}
+
+/// Time per project over the days, with the date each was last worked on.
+pub fn get_project_secs(summaries: &Vec<DaySummary>) -> BTreeMap<String, (i64, String)> {
+    let mut project_secs: BTreeMap<String, (i64, String)> = BTreeMap::new();
+    for summary in summaries {
+        for project_time in &summary.projects {
+            let entry: &mut (i64, String) = project_secs.entry(project_time.project.clone()).or_default();
+            entry.0 += project_time.secs;
+            entry.1 = entry.1.clone().max(summary.date.clone());
+        }
+    }
+    return project_secs;
+}
+
+/// Focus time per week, keyed by the Monday each week starts on, with the number of days worked that week.
+pub fn get_focus_secs_by_week(summaries: &Vec<DaySummary>) -> BTreeMap<NaiveDate, (i64, u64)> {
+    let mut focus_by_week: BTreeMap<NaiveDate, (i64, u64)> = BTreeMap::new();
+    for summary in summaries {
+        if let Ok(date) = NaiveDate::parse_from_str(&summary.date, DATE_FMT) {
+            let week_start: NaiveDate = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+            let entry: &mut (i64, u64) = focus_by_week.entry(week_start).or_default();
+            entry.0 += summary.focus_secs;
+            entry.1 += 1;
+        }
+    }
+    return focus_by_week;
+}
+
+/// The current and longest runs of days worked in a row that met the daily focus goal.
+/// Days not worked, like weekends, don't break a streak.
+pub fn get_focus_streaks(summaries: &Vec<DaySummary>, goal_secs: i64) -> (u64, u64) {
+    let mut current: u64 = 0;
+    let mut longest: u64 = 0;
+    for summary in summaries {
+        current = match summary.focus_secs >= goal_secs {
+            true => current + 1,
+            false => 0,
+        };
+        longest = longest.max(current);
+    }
+    return (current, longest);
+}
+
+/// Pearson correlation between the two values of each pair, or `None` if either doesn't vary.
+pub fn correlation(pairs: &Vec<(f64, f64)>) -> Option<f64> {
+    let n: f64 = pairs.len() as f64;
+    let mean_x: f64 = pairs.iter().map(|pair: &(f64, f64)| pair.0).sum::<f64>() / n;
+    let mean_y: f64 = pairs.iter().map(|pair: &(f64, f64)| pair.1).sum::<f64>() / n;
+    let covariance: f64 = pairs.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let spread_x: f64 = pairs.iter().map(|(x, _)| (x - mean_x).powi(2)).sum::<f64>().sqrt();
+    let spread_y: f64 = pairs.iter().map(|(_, y)| (y - mean_y).powi(2)).sum::<f64>().sqrt();
+    if pairs.len() < 2 || spread_x == 0.0 || spread_y == 0.0 {
+        return None;
+    }
+    return Some(covariance / (spread_x * spread_y));
+}
+
+/// Ends a copy of a still-running day at `now` so that its totals can be calculated.
+pub fn close_day_at(mut day: Day, now: &DateTime<Local>) -> Day {
+    if !day.has_ended() {
+        day.end_day_at(now).expect("We should be able to end the day");
+    }
+    return day;
+}
+
+pub fn format_secs(secs: i64) -> String {
+    let sign: &str = if secs < 0 {"-"} else {""};
+    let mins: i64 = secs.abs() / 60;
+    return format!("{}{} h {} m", sign, mins / 60, mins % 60);
+}
+
+pub fn format_time_of_day(secs_from_midnight: i64) -> String {
+    return format!("{:02}:{:02}", secs_from_midnight / 3600, (secs_from_midnight % 3600) / 60);
+}
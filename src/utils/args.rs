@@ -0,0 +1,121 @@
+/// Returns the value following `flag` in the args, if the flag was given.
+pub fn get_flag_value(args: &Vec<String>, flag: &str) -> Option<String> {
+    let position: Option<usize> = args.iter().position(|x: &String| x == flag);
+    return match position {
+        Some(ind) => args.get(ind + 1).cloned(),
+        None => None,
+    };
+}
+
+/// The `--interval` given, in whole seconds, if any. Less than a second would have commands
+/// that repeat every interval spin as fast as they can, so it's refused.
+pub fn get_interval_secs(args: &Vec<String>) -> Result<Option<u64>, String> {
+    let Some(secs_str) = get_flag_value(args, "--interval") else {
+        return Ok(None);
+    };
+    return match secs_str.parse() {
+        Ok(secs) if secs >= 1 => Ok(Some(secs)),
+        _ => Err(format!("'--interval' should be a whole number of seconds, at least 1! Given: '{}'", secs_str)),
+    };
+}
+
+/// Returns the value following each time `flag` was given, for flags that can be repeated.
+pub fn get_flag_values(args: &Vec<String>, flag: &str) -> Vec<String> {
+    return args.windows(2)
+        .filter(|pair: &&[String]| pair[0] == flag)
+        .map(|pair: &[String]| pair[1].clone())
+        .collect();
+}
+
+/// Reads each `--where key=value`, for picking out days by their `punch meta`.
+pub fn get_where_conditions(args: &Vec<String>) -> Result<Vec<(String, String)>, String> {
+    return get_flag_values(args, "--where").iter()
+        .map(|condition: &String| match condition.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => Ok((key.trim().to_string(), value.trim().to_string())),
+            _ => Err(format!("'--where {}' should look like '--where key=value'.", condition)),
+        })
+        .collect();
+}
+
+pub fn has_flag(args: &Vec<String>, flag: &str) -> bool {
+    return args.iter().any(|x: &String| x == flag);
+}
+
+/// Removes every `flag` from the args, returning whether there were any.
+pub fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    let len_before: usize = args.len();
+    args.retain(|x: &String| x != flag);
+    return args.len() != len_before;
+}
+
+/// Adds each flag in `defaults`, along with the values following it, unless the flag is in
+/// `args` already. Anything in `defaults` before the first flag is left out.
+pub fn add_default_flags(args: &mut Vec<String>, defaults: &Vec<String>) {
+    let mut groups: Vec<Vec<String>> = Vec::new();
+    for arg in defaults {
+        match groups.last_mut() {
+            Some(group) if !arg.starts_with("--") => group.push(arg.clone()),
+            _ => groups.push(vec![arg.clone()]),
+        }
+    }
+    for group in groups {
+        if group[0].starts_with("--") && !has_flag(args, &group[0]) {
+            args.extend(group);
+        }
+    }
+}
+
+/// Returns the args which aren't flags or the values of `flags_with_values`.
+pub fn get_positional_args(args: &Vec<String>, flags_with_values: &[&str]) -> Vec<String> {
+    let mut positional: Vec<String> = Vec::new();
+    let mut skip_next: bool = false;
+    for arg in args {
+        if skip_next {
+            skip_next = false;
+        }
+        else if flags_with_values.contains(&arg.as_str()) {
+            skip_next = true;
+        }
+        else if !arg.starts_with("--") {
+            positional.push(arg.to_owned());
+        }
+    }
+    return positional;
+}
+
+/// Splits a line into args like a shell would for simple cases: on whitespace, with
+/// double or single quotes grouping words together.
+pub fn split_command_line(line: &str) -> Result<Vec<String>, String> {
+    let mut args: Vec<String> = Vec::new();
+    let mut current: String = String::new();
+    let mut in_arg: bool = false;
+    let mut quote: Option<char> = None;
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => current.push(c),
+            (None, '"') | (None, '\'') => {
+                quote = Some(c);
+                in_arg = true;
+            },
+            (None, c) if c.is_whitespace() => {
+                if in_arg {
+                    args.push(current.clone());
+                    current.clear();
+                    in_arg = false;
+                }
+            },
+            (None, c) => {
+                current.push(c);
+                in_arg = true;
+            },
+        }
+    }
+    if quote.is_some() {
+        return Err(format!("Unclosed quote in: {}", line));
+    }
+    if in_arg {
+        args.push(current);
+    }
+    return Ok(args);
+}
@@ -0,0 +1,40 @@
+use chrono::prelude::{DateTime, Local};
+use chrono::Duration;
+use serde::{Serialize, Deserialize};
+
+/// A gap this much longer than the daemon's interval between two of its checks means the
+/// computer was asleep in between.
+const SUSPEND_GAP_SECS: i64 = 120;
+const DEFAULT_LOCK_GRACE_MINUTES: u64 = 5;
+
+/// What the daemon does about time you're away from the computer. Time it counts as a break
+/// is kept on the day to be confirmed, which `punch away` (or the next command you run) asks about.
+#[derive(Debug,Serialize,Deserialize,Clone)]
+pub struct AwayConfig {
+    /// Count the time the computer was asleep as a break.
+    #[serde(default)]
+    pub pause_on_suspend: bool,
+    /// Pause while the screen is locked, and resume once it's unlocked.
+    #[serde(default)]
+    pub pause_on_lock: bool,
+    /// How long the screen has to stay locked before it counts as a break, so that locking it
+    /// to grab a coffee doesn't split the day into tiny blocks. Defaults to 5.
+    #[serde(default)]
+    pub lock_grace_minutes: Option<u64>,
+    /// After this many minutes without keyboard or mouse input, the time is counted as a break
+    /// once you're back. Off if not set.
+    #[serde(default)]
+    pub idle_minutes: Option<u64>,
+}
+
+impl AwayConfig {
+    pub fn get_lock_grace_minutes(&self) -> u64 {
+        return self.lock_grace_minutes.unwrap_or(DEFAULT_LOCK_GRACE_MINUTES);
+    }
+}
+
+/// Whether the daemon's checks at `last_check` and `now`, meant to be `interval_secs` apart,
+/// are far enough apart that the computer must have been asleep.
+pub fn was_suspended(last_check: &DateTime<Local>, now: &DateTime<Local>, interval_secs: u64) -> bool {
+    return *now - *last_check > Duration::seconds(interval_secs as i64 + SUSPEND_GAP_SECS);
+}
@@ -0,0 +1,38 @@
+use std::collections::BTreeMap;
+use serde::{Serialize, Deserialize};
+
+use crate::utils::index::DaySummary;
+
+/// A cap on the time spent on a category each month, like "meetings ≤ 20 h/month".
+#[derive(Debug,Serialize,Deserialize,Clone)]
+pub struct CategoryBudget {
+    pub category: String,
+    pub max_monthly_minutes: i64,
+}
+
+impl CategoryBudget {
+    pub fn get_max_secs(&self) -> i64 {
+        return self.max_monthly_minutes * 60;
+    }
+}
+
+/// Total time per category over the days.
+pub fn get_category_secs(summaries: &Vec<DaySummary>) -> BTreeMap<String, i64> {
+    let mut category_secs: BTreeMap<String, i64> = BTreeMap::new();
+    for category_time in summaries.iter().flat_map(|summary: &DaySummary| summary.categories.iter()) {
+        *category_secs.entry(category_time.category.clone()).or_insert(0) += category_time.secs;
+    }
+    return category_secs;
+}
+
+/// Each budget with the time used on its category, and how much of the budget is left
+/// (negative when it's been overrun).
+pub fn get_budget_usage(summaries: &Vec<DaySummary>, budgets: &Vec<CategoryBudget>) -> Vec<(CategoryBudget, i64, i64)> {
+    let category_secs: BTreeMap<String, i64> = get_category_secs(summaries);
+    return budgets.iter()
+        .map(|budget: &CategoryBudget| {
+            let used_secs: i64 = category_secs.get(&budget.category).copied().unwrap_or(0);
+            (budget.clone(), used_secs, budget.get_max_secs() - used_secs)
+        })
+        .collect();
+}
@@ -0,0 +1,119 @@
+use std::process::Command;
+use chrono::prelude::{DateTime, Local, NaiveDate, TimeZone, Utc};
+use serde::{Serialize, Deserialize};
+
+use crate::utils::file_io::expand_path;
+use crate::utils::ics::{CalendarEvent, get_events_on};
+use crate::utils::json::{JsonValue, parse_json};
+
+const GOOGLE_CALENDAR_API: &str = "https://www.googleapis.com/calendar/v3/calendars";
+
+/// Where to find your calendar: either an iCalendar (ICS) URL or file, such as Google
+/// Calendar's secret iCal address, or the Google Calendar API with an OAuth access token.
+/// Fetching uses `curl`, which needs to be installed.
+#[derive(Debug,Serialize,Deserialize,Clone)]
+pub struct CalendarConfig {
+    #[serde(default)]
+    pub ics_url: Option<String>,
+    #[serde(default)]
+    pub google: Option<GoogleCalendarConfig>,
+    /// The category for summaries of meetings logged from the calendar. Defaults to "meetings".
+    #[serde(default)]
+    pub meeting_category: Option<String>,
+}
+
+#[derive(Debug,Serialize,Deserialize,Clone)]
+pub struct GoogleCalendarConfig {
+    /// Defaults to "primary".
+    #[serde(default)]
+    pub calendar_id: Option<String>,
+    #[serde(default)]
+    pub access_token: Option<String>,
+    /// A command printing a fresh access token, like `gcloud auth print-access-token`,
+    /// since access tokens only last an hour.
+    #[serde(default)]
+    pub access_token_command: Option<String>,
+}
+
+/// The timed events on `date` from the configured calendar, sorted by start.
+pub fn fetch_events_on(calendar: &CalendarConfig, date: &NaiveDate) -> Result<Vec<CalendarEvent>, String> {
+    if let Some(google) = &calendar.google {
+        return fetch_google_events_on(google, date);
+    }
+    let ics_url: &String = calendar.ics_url.as_ref().ok_or("The 'calendar' config needs an 'ics_url' or a 'google' section.")?;
+    return Ok(get_events_on(&read_ics(ics_url)?, date));
+}
+
+/// An iCalendar file's contents, fetched with `curl` if it's a URL.
+pub fn read_ics(url_or_path: &str) -> Result<String, String> {
+    return match url_or_path.starts_with("http://") || url_or_path.starts_with("https://") {
+        true => run_curl(&[url_or_path]),
+        false => std::fs::read_to_string(expand_path(url_or_path)).map_err(|err| format!("Couldn't read '{}': {}", url_or_path, err)),
+    };
+}
+
+fn fetch_google_events_on(google: &GoogleCalendarConfig, date: &NaiveDate) -> Result<Vec<CalendarEvent>, String> {
+    let token: String = match (&google.access_token_command, &google.access_token) {
+        (Some(command), _) => run_shell(command)?,
+        (None, Some(token)) => token.clone(),
+        (None, None) => return Err("The 'google' calendar config needs an 'access_token' or 'access_token_command'.".to_string()),
+    };
+    let day_start: DateTime<Local> = Local.from_local_datetime(&date.and_hms_opt(0, 0, 0).expect("Midnight exists"))
+        .earliest().ok_or("Couldn't find the start of the day")?;
+    let url: String = format!(
+        "{}/{}/events?singleEvents=true&orderBy=startTime&timeMin={}&timeMax={}",
+        GOOGLE_CALENDAR_API, percent_encode(google.calendar_id.as_deref().unwrap_or("primary")),
+        percent_encode(&day_start.with_timezone(&Utc).to_rfc3339()),
+        percent_encode(&(day_start + chrono::Duration::days(1)).with_timezone(&Utc).to_rfc3339()));
+    let body: String = run_curl(&["-H", &format!("Authorization: Bearer {}", token.trim()), &url])?;
+    let response: JsonValue = parse_json(&body).map_err(|msg: String| format!("Couldn't read Google Calendar's response: {}", msg))?;
+    let Some(JsonValue::Array(items)) = response.get("items") else {
+        return Err("Google Calendar's response had no events list".to_string());
+    };
+    let mut events: Vec<CalendarEvent> = Vec::new();
+    for item in items {
+        // All-day events have a "date" instead of a "dateTime".
+        let start = item.get("start").and_then(|x: &JsonValue| x.get("dateTime")).and_then(|x: &JsonValue| x.as_str());
+        let end = item.get("end").and_then(|x: &JsonValue| x.get("dateTime")).and_then(|x: &JsonValue| x.as_str());
+        let cancelled: bool = item.get("status").and_then(|x: &JsonValue| x.as_str()) == Some("cancelled");
+        if let (Some(start), Some(end), false) = (start, end, cancelled) {
+            let (Ok(start), Ok(end)) = (DateTime::parse_from_rfc3339(start), DateTime::parse_from_rfc3339(end)) else {
+                continue;
+            };
+            events.push(CalendarEvent {
+                summary: item.get("summary").and_then(|x: &JsonValue| x.as_str()).unwrap_or("(no title)").to_string(),
+                start: start.with_timezone(&Local),
+                end: end.with_timezone(&Local),
+            });
+        }
+    }
+    return Ok(events);
+}
+
+fn run_curl(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("curl").args(["--silent", "--show-error", "--fail", "--location"]).args(args).output()
+        .map_err(|err| format!("Couldn't run curl to fetch the calendar: {}", err))?;
+    if !output.status.success() {
+        return Err(format!("Couldn't fetch the calendar: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    return Ok(String::from_utf8_lossy(&output.stdout).to_string());
+}
+
+fn run_shell(command: &str) -> Result<String, String> {
+    let (shell, flag): (&str, &str) = if cfg!(windows) {("cmd", "/C")} else {("sh", "-c")};
+    let output = Command::new(shell).args([flag, command]).output()
+        .map_err(|err| format!("Couldn't run '{}': {}", command, err))?;
+    if !output.status.success() {
+        return Err(format!("'{}' failed: {}", command, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    return Ok(String::from_utf8_lossy(&output.stdout).trim().to_string());
+}
+
+fn percent_encode(text: &str) -> String {
+    return text.bytes()
+        .map(|byte: u8| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect();
+}
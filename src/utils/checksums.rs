@@ -0,0 +1,107 @@
+use std::sync::{Mutex, MutexGuard};
+use chrono::prelude::NaiveDate;
+
+use crate::units::interval::DATE_FMT;
+use crate::utils::file_io::{BASE_DIR, create_dir_if_not_exists, delete_file, expand_path, read_file, write_file};
+use crate::utils::team::get_data_dir;
+use crate::utils::zip::crc32;
+
+pub const CHECKSUMS_DIR: &str = "checksums/";
+
+/// The files already warned about in this run, so a file read several times only warns once.
+static WARNED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// A file punch keeps the checksum of, to tell when it's been changed outside punch.
+pub enum Checksummed {
+    Day(NaiveDate),
+    /// An event log, by its file name, like "2024-03-04.laptop".
+    EventLog(String),
+    Ledger,
+    State,
+}
+
+impl Checksummed {
+    /// What its checksum is kept under. A day's is under its date, as it was before other
+    /// files had checksums.
+    fn get_name(&self) -> String {
+        return match self {
+            Self::Day(date) => date.format(DATE_FMT).to_string(),
+            Self::EventLog(file_name) => format!("events.{}", file_name),
+            Self::Ledger => "ledger".to_string(),
+            Self::State => "state".to_string(),
+        };
+    }
+
+    fn describe(&self) -> String {
+        return match self {
+            Self::Day(_) => "The day file".to_string(),
+            Self::EventLog(file_name) => format!("The event log '{}'", file_name),
+            Self::Ledger => "The ledger".to_string(),
+            Self::State => "The state file".to_string(),
+        };
+    }
+}
+
+pub fn get_checksum(contents: &str) -> String {
+    return format!("crc32:{:08x}", crc32(contents.as_bytes()));
+}
+
+/// The folder the file's checksum is kept under: the data folder's, except for the state
+/// file, which is kept beside the config.
+fn get_checksums_dir(file: &Checksummed) -> String {
+    return match file {
+        Checksummed::State => expand_path(BASE_DIR) + CHECKSUMS_DIR,
+        _ => get_data_dir() + CHECKSUMS_DIR,
+    };
+}
+
+fn get_checksum_path(file: &Checksummed) -> String {
+    return get_checksums_dir(file) + &file.get_name();
+}
+
+/// Keeps the checksum of what was just written to the file.
+pub fn write_checksum(file: &Checksummed, contents: &str) {
+    create_dir_if_not_exists(&get_checksums_dir(file));
+    write_file(&get_checksum_path(file), get_checksum(contents) + "\n");
+}
+
+pub fn delete_checksum(file: &Checksummed) {
+    let _ = delete_file(&get_checksum_path(file));
+}
+
+/// Deletes a checksum kept in another folder than `file`'s, like one left behind in `dir`
+/// when the file was moved.
+pub fn delete_checksum_at(dir: &str, file: &Checksummed) {
+    let _ = delete_file(&(dir.to_string() + CHECKSUMS_DIR + &file.get_name()));
+}
+
+/// Whether the file still has what punch last wrote to it. Files written before checksums
+/// were kept don't have one, and pass.
+pub fn check_checksum(file: &Checksummed, contents: &str) -> Result<(), String> {
+    let Ok(expected) = read_file(&get_checksum_path(file)) else {
+        return Ok(());
+    };
+    if expected.trim() == get_checksum(contents) {
+        return Ok(());
+    }
+    return Err(format!(
+        "{} doesn't match its checksum ({} instead of {}), so it's been changed outside punch, maybe corrupted by a sync.",
+        file.describe(), get_checksum(contents), expected.trim()));
+}
+
+/// Warns once per run about a file that doesn't match its checksum, since what's read from
+/// it may be wrong.
+pub fn warn_if_corrupted(file: &Checksummed, contents: &str) {
+    if check_checksum(file, contents).is_ok() {
+        return;
+    }
+    let mut warned: MutexGuard<Vec<String>> = WARNED.lock().unwrap();
+    if !warned.contains(&file.get_name()) {
+        warned.push(file.get_name());
+        let subject: String = match file {
+            Checksummed::Day(date) => format!("The day file for {}", date.format(DATE_FMT)),
+            _ => file.describe(),
+        };
+        eprintln!("Warning: {} doesn't match its checksum and may be corrupted. Run 'punch doctor' for details.", subject);
+    }
+}
@@ -0,0 +1,125 @@
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
+use chrono::prelude::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use chrono::Duration;
+
+use crate::utils::reminders::TIME_OF_DAY_FMT;
+
+pub const NOW_FLAG: &str = "--now";
+const TIMESTAMP_FMTS: [&str; 2] = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"];
+
+/// Where commands get the current time from.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Local>;
+}
+
+pub type SharedClock = Arc<dyn Clock>;
+
+static CLOCK: RwLock<Option<SharedClock>> = RwLock::new(None);
+
+/// The clock the whole process runs on, for times recorded where no `now` is passed down,
+/// like when a change went into the event log or the ledger. The real time unless another
+/// has been set, like the one `--now` gives.
+pub fn get_clock() -> SharedClock {
+    return match CLOCK.read().unwrap().as_ref() {
+        Some(clock) => clock.clone(),
+        None => Arc::new(SystemClock),
+    };
+}
+
+/// Swaps the clock for the whole process. `None` goes back to the real time.
+pub fn set_clock(clock: Option<SharedClock>) {
+    *CLOCK.write().unwrap() = clock;
+}
+
+/// The real time.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Local> {
+        return Local::now();
+    }
+}
+
+/// Stays at the same time until it's moved on.
+pub struct FixedClock {
+    at: Mutex<DateTime<Local>>,
+}
+
+#[allow(dead_code)]
+impl FixedClock {
+    pub fn new(at: DateTime<Local>) -> Self {
+        return Self {at: Mutex::new(at)};
+    }
+
+    pub fn set(&self, at: DateTime<Local>) {
+        *self.at.lock().unwrap() = at;
+    }
+
+    pub fn advance(&self, by: Duration) {
+        let mut at = self.at.lock().unwrap();
+        *at += by;
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Local> {
+        return *self.at.lock().unwrap();
+    }
+}
+
+/// Starts at a given time and then keeps ticking in whole seconds, so that a replayed
+/// command sees exactly the time it was given.
+pub struct OffsetClock {
+    start: DateTime<Local>,
+    started: Instant,
+}
+
+impl OffsetClock {
+    pub fn starting_at(start: DateTime<Local>) -> Self {
+        return Self {start: start, started: Instant::now()};
+    }
+}
+
+impl Clock for OffsetClock {
+    fn now(&self) -> DateTime<Local> {
+        return self.start + Duration::seconds(self.started.elapsed().as_secs() as i64);
+    }
+}
+
+/// Parses a local time like `2024-01-31 09:00` (seconds optional) or an RFC 3339 timestamp.
+pub fn parse_timestamp(timestamp: &str) -> Result<DateTime<Local>, String> {
+    if let Ok(at) = DateTime::parse_from_rfc3339(timestamp.trim()) {
+        return Ok(at.with_timezone(&Local));
+    }
+    for fmt in TIMESTAMP_FMTS {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(timestamp.trim(), fmt) {
+            return Local.from_local_datetime(&naive).single()
+                .ok_or(format!("'{}' isn't a valid local time", timestamp));
+        }
+    }
+    return Err(format!("Expected a time like '2024-01-31 09:00'! Given: '{}'", timestamp));
+}
+
+/// Parses a time of day (`HH:MM`) on the given date, or a full timestamp.
+pub fn parse_time_on(at_str: &str, date: &NaiveDate) -> Result<DateTime<Local>, String> {
+    let Ok(time) = NaiveTime::parse_from_str(at_str.trim(), TIME_OF_DAY_FMT) else {
+        return parse_timestamp(at_str);
+    };
+    return Local.from_local_datetime(&date.and_time(time)).single()
+        .ok_or(format!("'{}' isn't a valid local time", at_str));
+}
+
+/// Removes the hidden `--now <timestamp>` flag from the args, if it's there, and returns the
+/// clock to run with: one that starts at the given time and keeps ticking, or the real time.
+pub fn take_clock_from_args(args: &mut Vec<String>) -> Result<SharedClock, String> {
+    let Some(ind) = args.iter().position(|arg: &String| arg == NOW_FLAG) else {
+        return Ok(Arc::new(SystemClock));
+    };
+    if ind + 1 >= args.len() {
+        return Err(format!("'{}' needs a timestamp", NOW_FLAG));
+    }
+    let at: DateTime<Local> = parse_timestamp(&args[ind + 1])?;
+    args.drain(ind..ind + 2);
+    return Ok(Arc::new(OffsetClock::starting_at(at)));
+}
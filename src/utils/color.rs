@@ -0,0 +1,20 @@
+use std::env::var;
+use std::io::{IsTerminal, stdout};
+
+use crate::utils::args::has_flag;
+
+pub const RESET: &str = "\x1B[0m";
+pub const DIM: &str = "\x1B[2m";
+pub const GREEN: &str = "\x1B[32m";
+pub const RED: &str = "\x1B[31m";
+pub const TASK_COLORS: [&str; 6] = ["\x1B[34m", "\x1B[32m", "\x1B[35m", "\x1B[36m", "\x1B[33m", "\x1B[31m"];
+
+/// Colors are on when printing to a terminal, unless `--no-color` is given or `NO_COLOR` is
+/// set. Piped into a file or another program, the escape codes would only get in the way.
+pub fn colors_enabled(args: &Vec<String>) -> bool {
+    return !has_flag(args, "--no-color") && var("NO_COLOR").is_err() && stdout().is_terminal();
+}
+
+pub fn paint(text: &str, color: &str, enabled: bool) -> String {
+    return if enabled {format!("{}{}{}", color, text, RESET)} else {text.to_string()};
+}
@@ -0,0 +1,81 @@
+use std::collections::BTreeMap;
+use chrono::prelude::NaiveDate;
+use chrono::Duration;
+use serde::{Serialize, Deserialize};
+
+use crate::utils::index::DaySummary;
+
+const DEFAULT_MAX_WEEKLY_HOURS: f64 = 48.0;
+const DEFAULT_WINDOW_WEEKS: u64 = 17;
+
+/// A working-time rule like "after 6 hours of work, take at least 30 minutes of break".
+#[derive(Debug,Serialize,Deserialize,Clone)]
+pub struct BreakRule {
+    pub after_minutes: i64,
+    pub min_break_minutes: i64,
+}
+
+impl BreakRule {
+    pub fn is_violated_by(&self, worked_mins: i64, break_mins: i64) -> bool {
+        return worked_mins > self.after_minutes && break_mins < self.min_break_minutes;
+    }
+
+    pub fn as_string(&self) -> String {
+        return format!(
+            "after {} h {} m of work, at least {} m of break",
+            self.after_minutes / 60, self.after_minutes % 60, self.min_break_minutes);
+    }
+}
+
+/// Returns a message for each rule the day breaks.
+pub fn check_break_rules(summary: &DaySummary, rules: &Vec<BreakRule>) -> Vec<String> {
+    let worked_mins: i64 = summary.done_secs / 60;
+    let break_mins: i64 = summary.break_secs / 60;
+    return rules.iter()
+        .filter(|rule: &&BreakRule| rule.is_violated_by(worked_mins, break_mins))
+        .map(|rule: &BreakRule| format!(
+            "Worked {} m with only {} m of break (rule: {})", worked_mins, break_mins, rule.as_string()))
+        .collect();
+}
+
+/// A limit on the average hours a week over a rolling window, like the EU Working Time
+/// Directive's 48 hours over 17 weeks, which are the defaults.
+#[derive(Debug,Serialize,Deserialize,Clone,Default)]
+pub struct WorkingTimeLimit {
+    #[serde(default)]
+    pub max_weekly_hours: Option<f64>,
+    #[serde(default)]
+    pub window_weeks: Option<u64>,
+}
+
+impl WorkingTimeLimit {
+    pub fn get_max_weekly_hours(&self) -> f64 {
+        return self.max_weekly_hours.unwrap_or(DEFAULT_MAX_WEEKLY_HOURS);
+    }
+
+    pub fn get_window_days(&self) -> i64 {
+        return self.window_weeks.unwrap_or(DEFAULT_WINDOW_WEEKS) as i64 * 7;
+    }
+}
+
+/// The total time done in the `window_days` days up to and including each of `ends`, given
+/// the time done per day. Running totals are built once, so each window is a subtraction.
+pub fn get_rolling_totals(done_secs_by_date: &BTreeMap<NaiveDate, i64>, ends: &Vec<NaiveDate>, window_days: i64) -> Vec<i64> {
+    let (Some(first), Some(last)) = (done_secs_by_date.keys().next(), ends.iter().max()) else {
+        return vec![0; ends.len()];
+    };
+    let mut running_totals: BTreeMap<NaiveDate, i64> = BTreeMap::new();
+    let mut total: i64 = 0;
+    let mut date: NaiveDate = *first;
+    while date <= *last {
+        total += done_secs_by_date.get(&date).copied().unwrap_or(0);
+        running_totals.insert(date, total);
+        date += Duration::days(1);
+    }
+    let total_up_to = |date: NaiveDate| -> i64 {
+        return running_totals.range(..=date).next_back().map(|(_, total)| *total).unwrap_or(0);
+    };
+    return ends.iter()
+        .map(|end: &NaiveDate| total_up_to(*end) - total_up_to(*end - Duration::days(window_days)))
+        .collect();
+}
@@ -0,0 +1,32 @@
+use chrono::prelude::NaiveDate;
+use serde::{Serialize, Deserialize};
+
+use crate::units::day::{get_day_file_path_for_date, list_recorded_dates};
+use crate::utils::event_log::list_log_paths;
+use crate::utils::file_io::{compress_file, is_compressed};
+
+/// How old days have to be before their files are compressed, e.g.
+/// `compression: {older_than: 90d}`. The daemon compresses them once a day.
+#[derive(Debug,Serialize,Deserialize,Clone)]
+pub struct CompressionConfig {
+    /// An age like "90d", "6w", "18m" or "2y".
+    pub older_than: String,
+}
+
+/// The day files and event logs of days before `oldest_kept` that aren't compressed yet.
+pub fn get_files_to_compress(oldest_kept: &NaiveDate) -> Vec<String> {
+    return list_recorded_dates().into_iter()
+        .filter(|date: &NaiveDate| date < oldest_kept)
+        .flat_map(|date: NaiveDate| [vec![get_day_file_path_for_date(&date)], list_log_paths(&date)].concat())
+        .filter(|path: &String| !is_compressed(path))
+        .collect();
+}
+
+/// Compresses the files of days before `oldest_kept`, giving how many were compressed.
+pub fn compress_days_before(oldest_kept: &NaiveDate) -> Result<usize, String> {
+    let paths: Vec<String> = get_files_to_compress(oldest_kept);
+    for path in &paths {
+        compress_file(path)?;
+    }
+    return Ok(paths.len());
+}
@@ -1,34 +1,217 @@
+use std::collections::BTreeMap;
+use chrono::prelude::{Datelike, NaiveDate, NaiveTime, Weekday};
 use serde::{Serialize,Deserialize};
-use std::path::Path;
-use crate::utils::file_io::{expand_path,write_file,read_file,BASE_DIR, FromString, ToFile, SafeFileEdit};
+use serde_yaml::{Mapping, Value};
+use crate::utils::budget::CategoryBudget;
+use crate::utils::compliance::{BreakRule, WorkingTimeLimit};
+use crate::utils::calendar::CalendarConfig;
+use crate::utils::holidays::HolidayCalendar;
+use crate::utils::taxonomy::Taxonomy;
+use crate::utils::updates::UpdateConfig;
+use crate::utils::access::ApiToken;
+use crate::utils::suggestions::get_nearest_match;
+use crate::utils::yaml_text::update_yaml_text;
+use crate::utils::email::EmailConfig;
+use crate::utils::meetings::MeetingDetectionConfig;
+use crate::utils::retention::RetentionConfig;
+use crate::utils::compression::CompressionConfig;
+use crate::utils::samples::WindowSamplingConfig;
+use crate::utils::signing::SigningConfig;
+use crate::utils::team::TeamConfig;
+use crate::utils::away::AwayConfig;
+use crate::utils::oncall::OnCallConfig;
+use crate::utils::expenses::ExpenseConfig;
+use crate::utils::mqtt::MqttConfig;
+use crate::utils::shifts::Shift;
+use crate::utils::period::parse_date;
+use crate::utils::args::split_command_line;
+use crate::utils::reminders::{BreakReminderConfig, Reminder, TIME_OF_DAY_FMT};
+use crate::utils::file_io::{expand_path,path_exists,write_file,read_file,BASE_DIR, FromString, ToFile, SafeFileEdit};
 
 pub const CONFIG_FILE: &str = "punch.cfg";
-const DEFAULT_TIME_MINS: i64 = 480;
-const DEFAULT_PUNCH_IN_TASK: &str = "Starting-up";
-const DEFAULT_BREAK_TASK: &str = "Break";
+pub const DEFAULT_TIME_MINS: i64 = 480;
+pub const DEFAULT_PUNCH_IN_TASK: &str = "Starting-up";
+pub const DEFAULT_BREAK_TASK: &str = "Break";
+
+/// What the main settings are for, written above them in a new config.
+const SETTING_COMMENTS: [(&str, &str); 6] = [
+    ("day_in_minutes", "How many minutes of work a day needs."),
+    ("default_punch_in_task", "The task `punch in` starts when you don't name one."),
+    ("default_break_task", "What `punch pause` calls a break when you don't name it."),
+    ("work_days", "The days of the week you work. On other days, `punch in` gives the day a target of 0."),
+    ("data_dir", "Where days, the index and other records are kept, if not in ~/.punch-card/."),
+    ("editor", "The editor for `punch edit` and `punch edit-config`, ahead of $VISUAL and $EDITOR."),
+];
 
 #[derive(Debug,Serialize,Deserialize)]
 pub struct Config {
     day_in_minutes: i64,
     default_punch_in_task: String,
     default_break_task: String,
-    minutes_behind: i64,
-    minutes_behind_non_neg: u64,
+    /// Where older versions kept the minutes behind, now in the state file. Only read, to
+    /// move them there.
+    #[serde(default, skip_serializing)]
+    minutes_behind: Option<i64>,
+    #[serde(default, skip_serializing)]
+    minutes_behind_non_neg: Option<u64>,
+    #[serde(default)]
+    expected_break_minutes: u64,
+    #[serde(default)]
+    break_budget_minutes: Option<u64>,
+    #[serde(default)]
+    break_rules: Vec<BreakRule>,
+    #[serde(default)]
+    max_daily_minutes: Option<u64>,
+    #[serde(default)]
+    reminders: Vec<Reminder>,
+    #[serde(default)]
+    api_token: Option<String>,
+    #[serde(default)]
+    api_tokens: Option<Vec<ApiToken>>,
+    #[serde(default)]
+    mqtt: Option<MqttConfig>,
+    #[serde(default)]
+    email: Option<EmailConfig>,
+    #[serde(default)]
+    calendar: Option<CalendarConfig>,
+    #[serde(default)]
+    meeting_detection: Option<MeetingDetectionConfig>,
+    #[serde(default)]
+    window_sampling: Option<WindowSamplingConfig>,
+    #[serde(default)]
+    retention: Option<RetentionConfig>,
+    #[serde(default)]
+    compression: Option<CompressionConfig>,
+    #[serde(default)]
+    team: Option<TeamConfig>,
+    #[serde(default)]
+    signing: Option<SigningConfig>,
+    #[serde(default)]
+    away: Option<AwayConfig>,
+    #[serde(default)]
+    oncall: Option<OnCallConfig>,
+    #[serde(default)]
+    expenses: Option<ExpenseConfig>,
+    /// "en", "de" or "fr". Falls back to the language in `LANG`.
+    #[serde(default)]
+    language: Option<String>,
+    #[serde(default)]
+    budgets: Vec<CategoryBudget>,
+    /// Projects to list even before any time has been spent on them.
+    #[serde(default)]
+    projects: Vec<String>,
+    #[serde(default)]
+    archived_projects: Vec<String>,
+    /// When you mean to start work, as "HH:MM".
+    #[serde(default)]
+    expected_start: Option<String>,
+    /// How many minutes of focus sessions to aim for each day.
+    #[serde(default)]
+    daily_focus_goal_minutes: Option<u64>,
+    /// Where `punch in` says you're working when it isn't given `--location`: "home",
+    /// "office" or "client".
+    #[serde(default)]
+    default_location: Option<String>,
+    /// How often `punch watch` refreshes, in seconds. Defaults to 1.
+    #[serde(default)]
+    watch_interval_secs: Option<u64>,
+    /// Days off like public holidays, as "YYYY-MM-DD".
+    #[serde(default)]
+    holidays: Option<Vec<String>>,
+    /// Public holidays by region or from a calendar, and how long to work on them.
+    #[serde(default)]
+    holiday_calendar: Option<HolidayCalendar>,
+    /// How many characters wide tables can be, instead of the terminal's width.
+    #[serde(default)]
+    output_width: Option<usize>,
+    /// The pager for long output, instead of `PAGER`, or "off" for none.
+    #[serde(default)]
+    pager: Option<String>,
+    /// The fuzzy finder for picking days and tasks, instead of `fzf`, or "builtin".
+    #[serde(default)]
+    picker: Option<String>,
+    /// Flags to add to a command when they're not given, like `report: --period this-week`.
+    #[serde(default)]
+    command_defaults: Option<BTreeMap<String, String>>,
+    /// How many minutes of breaks a day count as time done, for employers that pay for them.
+    #[serde(default)]
+    paid_break_minutes: Option<u64>,
+    #[serde(default)]
+    shifts: Option<Vec<Shift>>,
+    #[serde(default)]
+    working_time_limit: Option<WorkingTimeLimit>,
+    #[serde(default)]
+    break_reminder: Option<BreakReminderConfig>,
+    #[serde(default)]
+    taxonomy: Option<Taxonomy>,
+    #[serde(default)]
+    self_update: Option<UpdateConfig>,
+    #[serde(default)]
+    work_days: Option<Vec<String>>,
+    #[serde(default)]
+    data_dir: Option<String>,
+    #[serde(default)]
+    editor: Option<String>,
+    /// What this machine's changes are filed under in the event logs, instead of its hostname.
+    #[serde(default)]
+    device_name: Option<String>,
 }
 
 impl Config {
     pub fn new(
         day_length: i64, 
         default_punch_in_task: String, 
-        default_break_task: String, 
-        minutes_behind: i64) 
+        default_break_task: String) 
     -> Self {
         return Self {
             day_in_minutes: day_length, 
             default_punch_in_task: default_punch_in_task,
             default_break_task: default_break_task,
-            minutes_behind: minutes_behind,
-            minutes_behind_non_neg: if minutes_behind < 0 {0} else {minutes_behind} as u64,
+            minutes_behind: None,
+            minutes_behind_non_neg: None,
+            expected_break_minutes: 0,
+            break_budget_minutes: None,
+            break_rules: Vec::new(),
+            max_daily_minutes: None,
+            reminders: Vec::new(),
+            api_token: None,
+            api_tokens: None,
+            mqtt: None,
+            email: None,
+            calendar: None,
+            meeting_detection: None,
+            window_sampling: None,
+            retention: None,
+            compression: None,
+            team: None,
+            signing: None,
+            away: None,
+            oncall: None,
+            expenses: None,
+            language: None,
+            budgets: Vec::new(),
+            projects: Vec::new(),
+            archived_projects: Vec::new(),
+            expected_start: None,
+            daily_focus_goal_minutes: None,
+            default_location: None,
+            watch_interval_secs: None,
+            holidays: None,
+            holiday_calendar: None,
+            output_width: None,
+            pager: None,
+            picker: None,
+            command_defaults: None,
+            paid_break_minutes: None,
+            shifts: None,
+            working_time_limit: None,
+            break_reminder: None,
+            taxonomy: None,
+            self_update: None,
+            work_days: None,
+            data_dir: None,
+            editor: None,
+            device_name: None,
         }
     }
 
@@ -36,6 +219,22 @@ impl Config {
         return serde_yaml::to_string(&self).unwrap();
     }
 
+    /// Like `as_string`, but with what each of the main settings is for above it.
+    pub fn as_commented_string(&self) -> String {
+        let mut lines: Vec<String> = vec![
+            "# punch's config. Run `punch edit-config` to change it; the README lists every setting.".to_string()];
+        for line in self.as_string().lines() {
+            let comment: Option<&str> = SETTING_COMMENTS.iter()
+                .find(|(key, _)| line.starts_with(&format!("{}:", key)))
+                .map(|(_, comment)| *comment);
+            if let Some(comment) = comment {
+                lines.push(format!("# {}", comment));
+            }
+            lines.push(line.to_string());
+        }
+        return lines.join("\n") + "\n";
+    }
+
     pub fn day_in_minutes(&self) -> i64 {
         return self.day_in_minutes;
     }
@@ -48,20 +247,290 @@ impl Config {
         return &self.default_break_task;
     }
 
-    pub fn minutes_behind(&self) -> i64 {
-        return self.minutes_behind;
+    pub fn expected_break_minutes(&self) -> u64 {
+        return self.expected_break_minutes;
+    }
+
+    pub fn break_budget_minutes(&self) -> Option<u64> {
+        return self.break_budget_minutes;
+    }
+
+    /// How much of the break budget is left given `break_secs` of break so far.
+    /// Negative once the budget has been exceeded.
+    pub fn break_budget_left_secs(&self, break_secs: i64) -> Option<i64> {
+        return self.break_budget_minutes.map(|mins: u64| mins as i64 * 60 - break_secs);
+    }
+
+    pub fn break_rules(&self) -> &Vec<BreakRule> {
+        return &self.break_rules;
+    }
+
+    pub fn max_daily_minutes(&self) -> Option<u64> {
+        return self.max_daily_minutes;
+    }
+
+    /// How far `done_secs` goes past the daily cap. Zero when there's no cap or it hasn't been hit.
+    pub fn secs_over_daily_cap(&self, done_secs: i64) -> i64 {
+        return match self.max_daily_minutes {
+            Some(mins) => (done_secs - mins as i64 * 60).max(0),
+            None => 0,
+        };
+    }
+
+    pub fn reminders(&self) -> &Vec<Reminder> {
+        return &self.reminders;
+    }
+
+    pub fn api_token(&self) -> Option<&String> {
+        return self.api_token.as_ref();
+    }
+
+    pub fn api_tokens(&self) -> Option<&Vec<ApiToken>> {
+        return self.api_tokens.as_ref();
+    }
+
+    pub fn mqtt(&self) -> Option<&MqttConfig> {
+        return self.mqtt.as_ref();
+    }
+
+    pub fn email(&self) -> Option<&EmailConfig> {
+        return self.email.as_ref();
+    }
+
+    pub fn calendar(&self) -> Option<&CalendarConfig> {
+        return self.calendar.as_ref();
+    }
+
+    #[allow(dead_code)]
+    pub fn meeting_detection(&self) -> Option<&MeetingDetectionConfig> {
+        return self.meeting_detection.as_ref();
+    }
+
+    pub fn window_sampling(&self) -> Option<&WindowSamplingConfig> {
+        return self.window_sampling.as_ref();
+    }
+
+    pub fn retention(&self) -> Option<&RetentionConfig> {
+        return self.retention.as_ref();
+    }
+
+    pub fn compression(&self) -> Option<&CompressionConfig> {
+        return self.compression.as_ref();
+    }
+
+    pub fn team(&self) -> Option<&TeamConfig> {
+        return self.team.as_ref();
+    }
+
+    pub fn signing(&self) -> Option<&SigningConfig> {
+        return self.signing.as_ref();
+    }
+
+    pub fn away(&self) -> Option<&AwayConfig> {
+        return self.away.as_ref();
+    }
+
+    pub fn oncall(&self) -> Option<&OnCallConfig> {
+        return self.oncall.as_ref();
+    }
+
+    pub fn expenses(&self) -> Option<&ExpenseConfig> {
+        return self.expenses.as_ref();
+    }
+
+    pub fn language(&self) -> Option<&str> {
+        return self.language.as_deref();
+    }
+
+    pub fn budgets(&self) -> &Vec<CategoryBudget> {
+        return &self.budgets;
+    }
+
+    pub fn expected_start(&self) -> Result<Option<NaiveTime>, String> {
+        return match &self.expected_start {
+            Some(time_str) => NaiveTime::parse_from_str(time_str.trim(), TIME_OF_DAY_FMT)
+                .map(Some)
+                .map_err(|_| format!("'expected_start' should be a time like \"09:00\"! Given: '{}'", time_str)),
+            None => Ok(None),
+        };
+    }
+
+    pub fn output_width(&self) -> Option<usize> {
+        return self.output_width;
+    }
+
+    pub fn pager(&self) -> Option<&String> {
+        return self.pager.as_ref();
+    }
+
+    /// The default flags for the command with the given name, split up like on the command line.
+    pub fn command_defaults(&self, command: &str) -> Result<Vec<String>, String> {
+        return match self.command_defaults.as_ref().and_then(|defaults: &BTreeMap<String, String>| defaults.get(command)) {
+            Some(flags) => split_command_line(flags).map_err(|msg: String| format!("In 'command_defaults' for '{}': {}", command, msg)),
+            None => Ok(Vec::new()),
+        };
+    }
+
+    pub fn paid_break_minutes(&self) -> Option<u64> {
+        return self.paid_break_minutes;
+    }
+
+    pub fn shifts(&self) -> Option<&Vec<Shift>> {
+        return self.shifts.as_ref();
+    }
+
+    pub fn taxonomy(&self) -> Option<&Taxonomy> {
+        return self.taxonomy.as_ref();
+    }
+
+    pub fn self_update(&self) -> Option<&UpdateConfig> {
+        return self.self_update.as_ref();
+    }
+
+    /// Whether `date` falls on one of the `work_days`, or always if they aren't set.
+    pub fn is_work_day(&self, date: &NaiveDate) -> bool {
+        return match &self.work_days {
+            Some(days) => days.iter().any(|day: &String| day.parse::<Weekday>().is_ok_and(|day: Weekday| day == date.weekday())),
+            None => true,
+        };
+    }
+
+    pub fn set_work_days(&mut self, work_days: Option<Vec<String>>) {
+        self.work_days = work_days;
+    }
+
+    pub fn data_dir(&self) -> Option<&String> {
+        return self.data_dir.as_ref();
+    }
+
+    pub fn set_data_dir(&mut self, data_dir: Option<String>) {
+        self.data_dir = data_dir;
+    }
+
+    pub fn editor(&self) -> Option<&String> {
+        return self.editor.as_ref();
+    }
+
+    pub fn set_editor(&mut self, editor: Option<String>) {
+        self.editor = editor;
+    }
+
+    pub fn device_name(&self) -> Option<&String> {
+        return self.device_name.as_ref();
+    }
+
+    /// The value of a setting, given as a dotted path like `self_update.enabled`.
+    pub fn get_setting(&self, path: &str) -> Option<Value> {
+        let mut value: Value = serde_yaml::to_value(self).ok()?;
+        for key in path.split('.') {
+            value = value.get(key)?.clone();
+        }
+        return Some(value);
+    }
+
+    /// A copy of the config with a setting changed, if that's a setting and the value suits it.
+    pub fn with_setting(&self, path: &str, new_value: Value) -> Result<Config, String> {
+        let mut root: Value = serde_yaml::to_value(self).map_err(|err| err.to_string())?;
+        let keys: Vec<&str> = path.split('.').collect();
+        let mut value: &mut Value = &mut root;
+        for (i, key) in keys.iter().enumerate() {
+            if i == 0 && value.get(*key).is_none() {
+                let settings: Vec<String> = value.as_mapping().into_iter().flatten()
+                    .filter_map(|(key, _)| key.as_str().map(|x: &str| x.to_string()))
+                    .collect();
+                return Err(match get_nearest_match(key, &settings) {
+                    Some(nearest) => format!("'{}' isn't a setting. Did you mean '{}'?", key, nearest),
+                    None => format!("'{}' isn't a setting.", key),
+                });
+            }
+            if value.is_null() {
+                *value = Value::Mapping(Mapping::new());
+            }
+            let Some(mapping) = value.as_mapping_mut() else {
+                return Err(format!("'{}' can't be set inside '{}'.", path, keys[..i].join(".")));
+            };
+            value = mapping.entry(Value::String(key.to_string())).or_insert(Value::Null);
+        }
+        *value = new_value.clone();
+        let config: Config = serde_yaml::from_value(root).map_err(|err| format!("Couldn't set '{}': {}", path, err))?;
+        // serde leaves out settings it doesn't know, so one that didn't stick isn't a setting.
+        if config.get_setting(path).as_ref() != Some(&new_value) && !new_value.is_null() {
+            return Err(format!("'{}' isn't a setting.", path));
+        }
+        return Ok(config);
+    }
+
+    pub fn break_reminder(&self) -> Option<&BreakReminderConfig> {
+        return self.break_reminder.as_ref();
+    }
+
+    pub fn working_time_limit(&self) -> Option<&WorkingTimeLimit> {
+        return self.working_time_limit.as_ref();
     }
 
-    pub fn minutes_behind_non_neg(&self) -> u64 {
-        return self.minutes_behind_non_neg;
+    pub fn picker(&self) -> Option<&String> {
+        return self.picker.as_ref();
     }
 
-    pub fn update_minutes_behind(&mut self, delta: i64) {
-        let true_time_behind: i64 = self.minutes_behind() + delta;
-        let non_neg_time_behind: i64 = self.minutes_behind_non_neg() as i64 + delta;
-        let new_non_neg_time_behind: u64 = if true_time_behind < 0 {0} else {non_neg_time_behind} as u64;
-        self.minutes_behind = true_time_behind;
-        self.minutes_behind_non_neg = new_non_neg_time_behind;
+    pub fn holidays(&self) -> Result<Vec<NaiveDate>, String> {
+        return self.holidays.iter().flatten()
+            .map(|date_str: &String| parse_date(date_str).map_err(|msg: String| format!("In 'holidays': {}", msg)))
+            .collect();
+    }
+
+    pub fn holiday_calendar(&self) -> Option<&HolidayCalendar> {
+        return self.holiday_calendar.as_ref();
+    }
+
+    pub fn daily_focus_goal_minutes(&self) -> Option<u64> {
+        return self.daily_focus_goal_minutes;
+    }
+
+    pub fn default_location(&self) -> Option<&String> {
+        return self.default_location.as_ref();
+    }
+
+    pub fn watch_interval_secs(&self) -> Option<u64> {
+        return self.watch_interval_secs;
+    }
+
+    pub fn projects(&self) -> &Vec<String> {
+        return &self.projects;
+    }
+
+    pub fn is_project_archived(&self, project: &String) -> bool {
+        return self.archived_projects.contains(project);
+    }
+
+    /// Returns false if the project was already archived.
+    pub fn archive_project(&mut self, project: String) -> bool {
+        if self.is_project_archived(&project) {
+            return false;
+        }
+        self.archived_projects.push(project);
+        return true;
+    }
+
+    /// Returns false if the project wasn't archived.
+    pub fn unarchive_project(&mut self, project: &String) -> bool {
+        let num_archived: usize = self.archived_projects.len();
+        self.archived_projects.retain(|x: &String| x != project);
+        return self.archived_projects.len() != num_archived;
+    }
+
+    pub fn rename_project(&mut self, old: &String, new: &String) {
+        for project in self.projects.iter_mut().chain(self.archived_projects.iter_mut()) {
+            if project == old {
+                *project = new.clone();
+            }
+        }
+    }
+
+    /// The minutes behind from a config written by an older version, taken out of it.
+    pub fn take_legacy_minutes_behind(&mut self) -> Option<(i64, u64)> {
+        let minutes_behind: i64 = self.minutes_behind.take()?;
+        let minutes_behind_non_neg: u64 = self.minutes_behind_non_neg.take().unwrap_or(minutes_behind.max(0) as u64);
+        return Some((minutes_behind, minutes_behind_non_neg));
     }
 }
 
@@ -86,10 +555,21 @@ impl ToFile for Config {
     }
 }
 
-impl SafeFileEdit<Config, serde_yaml::Error> for Config{}
+impl SafeFileEdit<Config, serde_yaml::Error> for Config {
+    /// Keeps the file as it was edited, comments and all.
+    fn write_edited(_new_value: Config, edited: String) {
+        write_file(&get_config_path(), edited);
+    }
+}
 
+/// Writes the config, keeping the comments and order of the file that's there and only
+/// rewriting the settings that changed.
 pub fn write_config(path: &String, config: &Config) {
-    write_file(path, config.as_string());
+    let text: String = match (read_file(path), serde_yaml::to_value(config)) {
+        (Ok(existing), Ok(Value::Mapping(values))) => update_yaml_text(&existing, &values),
+        _ => config.as_string(),
+    };
+    write_file(path, text);
 }
 
 pub fn read_config(path: &String) -> Config {
@@ -98,17 +578,24 @@ pub fn read_config(path: &String) -> Config {
 }
 
 pub fn create_default_config_if_not_exists() {
-    let config_path: String = expand_path(&(BASE_DIR.to_owned() + &(CONFIG_FILE.to_owned())));
-    if !Path::new(&config_path).exists() {
+    if !config_exists() {
         let default_config: Config = Config::new(
             DEFAULT_TIME_MINS, 
             DEFAULT_PUNCH_IN_TASK.to_owned(),
-            DEFAULT_BREAK_TASK.to_owned(),
-            0);
-        write_config(&config_path, &default_config);
+            DEFAULT_BREAK_TASK.to_owned());
+        create_config(&default_config);
     }
 }
 
+pub fn config_exists() -> bool {
+    return path_exists(&get_config_path());
+}
+
+/// Writes a new config, with comments on the main settings.
+pub fn create_config(config: &Config) {
+    write_file(&get_config_path(), config.as_commented_string());
+}
+
 pub fn get_config() -> Config {
     let config_path: String = get_config_path();
     let config: Config = read_config(&config_path);
@@ -116,11 +603,11 @@ pub fn get_config() -> Config {
 }
 
 pub fn get_config_path() -> String {
-    return expand_path(&(BASE_DIR.to_owned() + &(CONFIG_FILE.to_owned())));
+    return expand_path(&(BASE_DIR.to_owned() + CONFIG_FILE));
 }
 
 
 pub fn update_config(config: Config) {
-    let config_path: String = expand_path(&(BASE_DIR.to_owned() + &(CONFIG_FILE.to_owned())));
+    let config_path: String = get_config_path();
     write_config(&config_path, &config)
 }
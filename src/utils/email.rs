@@ -0,0 +1,74 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use chrono::prelude::{DateTime, Local};
+use serde::{Serialize, Deserialize};
+
+const DEFAULT_SENDMAIL: &str = "sendmail";
+
+/// Where to send emailed reports. Sending goes through `curl` when `smtp` is set, and
+/// through `sendmail` otherwise, so one of them needs to be installed.
+#[derive(Debug,Serialize,Deserialize,Clone)]
+pub struct EmailConfig {
+    pub to: String,
+    /// Defaults to `to`.
+    #[serde(default)]
+    pub from: Option<String>,
+    #[serde(default)]
+    pub smtp: Option<SmtpConfig>,
+    /// The sendmail binary to pipe to when there's no `smtp`.
+    #[serde(default)]
+    pub sendmail: Option<String>,
+}
+
+#[derive(Debug,Serialize,Deserialize,Clone)]
+pub struct SmtpConfig {
+    /// Like "smtps://smtp.example.com:465". With "smtp://", STARTTLS is used if the server offers it.
+    pub url: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+impl EmailConfig {
+    fn get_from(&self) -> &String {
+        return self.from.as_ref().unwrap_or(&self.to);
+    }
+}
+
+/// Sends a plain text email using the configured SMTP server or sendmail.
+pub fn send_email(email: &EmailConfig, subject: &str, body: &str, now: &DateTime<Local>) -> Result<(), String> {
+    let mut message: String = format!(
+        "From: {}\nTo: {}\nSubject: {}\nDate: {}\nContent-Type: text/plain; charset=utf-8\n\n{}",
+        email.get_from(), email.to, subject, now.to_rfc2822(), body);
+    let mut command: Command = match &email.smtp {
+        Some(smtp) => {
+            let mut command: Command = Command::new("curl");
+            // SMTP wants CRLF line endings, where sendmail takes the local ones.
+            message = message.replace('\n', "\r\n");
+            command.args(["--silent", "--show-error", "--ssl"])
+                .args(["--url", &smtp.url])
+                .args(["--mail-from", email.get_from()])
+                .args(["--mail-rcpt", &email.to])
+                .args(["--upload-file", "-"]);
+            if let Some(username) = &smtp.username {
+                command.args(["--user", &format!("{}:{}", username, smtp.password.clone().unwrap_or_default())]);
+            }
+            command
+        },
+        None => {
+            let mut command: Command = Command::new(email.sendmail.as_deref().unwrap_or(DEFAULT_SENDMAIL));
+            command.arg("-t");
+            command
+        },
+    };
+    let program: String = command.get_program().to_string_lossy().to_string();
+    let mut child = command.stdin(Stdio::piped()).spawn()
+        .map_err(|err| format!("Couldn't run '{}' to send the email: {}", program, err))?;
+    child.stdin.take().expect("stdin was piped").write_all(message.as_bytes())
+        .map_err(|err| format!("Couldn't pass the email to '{}': {}", program, err))?;
+    return match child.wait() {
+        Ok(status) if status.success() => Ok(()),
+        _ => Err(format!("'{}' failed to send the email to {}", program, email.to)),
+    };
+}
@@ -0,0 +1,378 @@
+use std::collections::HashSet;
+use std::env::var;
+use std::io::ErrorKind;
+use std::process::Command;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use chrono::prelude::{DateTime, Local, NaiveDate};
+use serde::{Serialize, Deserialize};
+
+use crate::units::components::Note;
+use crate::units::day::{Day, read_day_file_for_date};
+use crate::units::interval::{DATE_FMT, Dt};
+use crate::utils::checksums::{Checksummed, check_checksum, delete_checksum, write_checksum};
+use crate::utils::clock::get_clock;
+use crate::utils::config::{Config, get_config_path};
+use crate::utils::file_io::{FromString, create_dir_if_not_exists, delete_file, list_dir, read_file, write_file};
+use crate::utils::team::get_data_dir;
+
+pub const EVENTS_DIR: &str = "events/";
+const DEFAULT_DEVICE_NAME: &str = "local";
+
+static DEVICE_NAME: OnceLock<String> = OnceLock::new();
+/// The unreadable logs already warned about, so each is only mentioned once per run.
+static WARNED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// A change to a day, as it's kept in the event log.
+#[derive(Debug,Serialize,Deserialize,Clone)]
+#[serde(tag = "event", rename_all = "lowercase")]
+pub enum DayChange {
+    In {at: Dt, task: String, time_to_do: u64},
+    Pause {at: Dt, name: String},
+    /// A new block of work, from `resume` or `task`.
+    Switch {at: Dt, task: String},
+    /// The ongoing block given another task, from `update-task`.
+    Rename {task: String},
+    Note {at: Dt, msg: String, #[serde(default)] remind_at: Option<Dt>},
+    Out {at: Dt},
+    /// The whole day, for changes that aren't one of the above, like edits.
+    Snapshot {day: Day},
+    /// The day as its file had it before the day had a log, which the log starts from.
+    Existing {day: Day},
+    Delete,
+    /// Takes back the event with the id.
+    Undo {id: String},
+}
+
+impl DayChange {
+    pub fn describe(&self) -> String {
+        let at_str = |at: &Dt| at.as_dt().format("%H:%M").to_string();
+        return match self {
+            Self::In {at, task, ..} => format!("punch in at {} on '{}'", at_str(at), task),
+            Self::Pause {at, name} => format!("pause at {} for '{}'", at_str(at), name),
+            Self::Switch {at, task} => format!("switch at {} to '{}'", at_str(at), task),
+            Self::Rename {task} => format!("rename of the current task to '{}'", task),
+            Self::Note {at, msg, ..} => format!("note at {}: '{}'", at_str(at), msg),
+            Self::Out {at} => format!("punch out at {}", at_str(at)),
+            Self::Snapshot {..} => "change to the day".to_string(),
+            Self::Existing {..} => "day as it was before it had a log".to_string(),
+            Self::Delete => "deletion of the day".to_string(),
+            Self::Undo {id} => format!("undo of {}", id),
+        };
+    }
+}
+
+/// An entry of a day's event log. Each device only ever adds to its own log, so synced
+/// logs never conflict, and a day is what you get from replaying every device's events.
+#[derive(Debug,Serialize,Deserialize,Clone)]
+pub struct DayEvent {
+    /// The device that recorded it and where it is in that device's log, like "laptop-3".
+    pub id: String,
+    /// When it was recorded. Events of different devices are replayed in this order.
+    pub recorded: Dt,
+    pub change: DayChange,
+}
+
+impl DayEvent {
+    /// What events are replayed in: when they were recorded, then by device and place in
+    /// its log, so every device replays them the same way.
+    fn get_order_key(&self) -> (DateTime<Local>, String, usize) {
+        let (device, seq): (&str, &str) = self.id.rsplit_once('-').unwrap_or((&self.id, "0"));
+        return (self.recorded.as_dt(), device.to_string(), seq.parse().unwrap_or(0));
+    }
+}
+
+pub fn get_events_dir() -> String {
+    return get_data_dir() + EVENTS_DIR;
+}
+
+/// What this device's events are filed under: the config's `device_name`, or else the
+/// hostname, kept to characters that are safe in file names.
+pub fn get_device_name() -> String {
+    return DEVICE_NAME.get_or_init(find_device_name).clone();
+}
+
+fn find_device_name() -> String {
+    let configured: Option<String> = read_file(&get_config_path()).ok()
+        .and_then(|yaml_str: String| Config::try_from_string(&yaml_str).ok())
+        .and_then(|config: Config| config.device_name().cloned());
+    let name: String = configured
+        .or(var("HOSTNAME").ok())
+        .or(var("COMPUTERNAME").ok())
+        .or(Command::new("hostname").output().ok()
+            .and_then(|output| String::from_utf8(output.stdout).ok()))
+        .unwrap_or_default()
+        .trim()
+        .chars()
+        .map(|c: char| if c.is_ascii_alphanumeric() || c == '_' {c} else {'-'})
+        .collect();
+    return if name.trim_matches('-').is_empty() {DEFAULT_DEVICE_NAME.to_string()} else {name};
+}
+
+fn get_log_path(date: &NaiveDate, device: &str) -> String {
+    return get_events_dir() + &date.format(DATE_FMT).to_string() + "." + device;
+}
+
+/// The paths of every device's log for the date.
+pub fn list_log_paths(date: &NaiveDate) -> Vec<String> {
+    let prefix: String = date.format(DATE_FMT).to_string() + ".";
+    let mut names: Vec<String> = list_dir(&get_events_dir()).unwrap_or_default().into_iter()
+        .filter(|name: &String| name.starts_with(&prefix))
+        .collect();
+    names.sort();
+    return names.into_iter().map(|name: String| get_events_dir() + &name).collect();
+}
+
+/// The events in the log at `path`, which has none if it doesn't exist yet.
+/// A log that doesn't match its checksum isn't trusted either, since replaying a mangled
+/// log could quietly change the day.
+pub fn read_log(path: &str) -> Result<Vec<DayEvent>, String> {
+    let yaml_str: String = match read_file(path) {
+        Ok(yaml_str) => yaml_str,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(format!("Couldn't read the event log '{}': {}", path, err)),
+    };
+    let events: Vec<DayEvent> = serde_yaml::from_str(&yaml_str).map_err(|err| format!("Couldn't read the event log '{}': {}", path, err))?;
+    check_checksum(&get_log_checksummed(path), &yaml_str)?;
+    return Ok(events);
+}
+
+/// What the log at `path` has its checksum kept as.
+pub fn get_log_checksummed(path: &str) -> Checksummed {
+    return Checksummed::EventLog(path.rsplit('/').next().unwrap_or(path).to_string());
+}
+
+fn warn_unreadable(msg: &str) {
+    let mut warned: MutexGuard<Vec<String>> = WARNED.lock().unwrap();
+    if !warned.iter().any(|warned_msg: &String| warned_msg == msg) {
+        warned.push(msg.to_string());
+        eprintln!("Warning: {}. It's left out until it's fixed. Run 'punch doctor' for details.", msg);
+    }
+}
+
+/// Why each of the date's logs that can't be read can't be.
+pub fn find_unreadable_logs(date: &NaiveDate) -> Vec<String> {
+    return list_log_paths(date).iter()
+        .filter_map(|path: &String| read_log(path).err())
+        .collect();
+}
+
+/// Whether the day can be replayed from its logs: there are some, and every one of them
+/// reads. Warns about the ones that don't.
+pub fn can_replay(date: &NaiveDate) -> bool {
+    let unreadable: Vec<String> = find_unreadable_logs(date);
+    unreadable.iter().for_each(|msg: &String| warn_unreadable(msg));
+    return has_event_log(date) && unreadable.is_empty();
+}
+
+pub fn has_event_log(date: &NaiveDate) -> bool {
+    return !list_log_paths(date).is_empty();
+}
+
+/// Every device's events for the date, in the order they're replayed in.
+pub fn read_events(date: &NaiveDate) -> Vec<DayEvent> {
+    let mut events: Vec<DayEvent> = list_log_paths(date).iter()
+        .flat_map(|path: &String| read_log(path).unwrap_or_else(|msg: String| {
+            warn_unreadable(&msg);
+            return Vec::new();
+        }))
+        .collect();
+    events.sort_by_key(DayEvent::get_order_key);
+    return events;
+}
+
+/// The dates with events from a device other than this one, whose day files and index
+/// entries may not have caught up with them yet.
+pub fn list_dates_logged_elsewhere() -> HashSet<NaiveDate> {
+    let device: String = get_device_name();
+    return list_dir(&get_events_dir()).unwrap_or_default().iter()
+        .filter_map(|name: &String| name.split_once('.'))
+        .filter(|(_, log_device): &(&str, &str)| *log_device != device)
+        .filter_map(|(date_str, _)| NaiveDate::parse_from_str(date_str, DATE_FMT).ok())
+        .collect();
+}
+
+/// The dates with an event log, oldest first.
+pub fn list_logged_dates() -> Vec<NaiveDate> {
+    let mut dates: Vec<NaiveDate> = list_dir(&get_events_dir()).unwrap_or_default().iter()
+        .filter_map(|name: &String| name.split_once('.'))
+        .filter_map(|(date_str, _)| NaiveDate::parse_from_str(date_str, DATE_FMT).ok())
+        .collect();
+    dates.sort();
+    dates.dedup();
+    return dates;
+}
+
+/// The day the events add up to, or None if they don't make one (or end with it deleted).
+/// Undone events are skipped, and so are events that don't fit the day as it is by then,
+/// like a pause from one device after another punched out.
+pub fn replay(events: &Vec<DayEvent>) -> Option<Day> {
+    let undone: HashSet<&String> = events.iter()
+        .filter_map(|event: &DayEvent| match &event.change {
+            DayChange::Undo {id} => Some(id),
+            _ => None,
+        })
+        .collect();
+    let mut day: Option<Day> = None;
+    for event in events.iter().filter(|event: &&DayEvent| !undone.contains(&event.id)) {
+        if let Ok(changed) = apply_change(day.clone(), &event.change) {
+            day = changed;
+        }
+    }
+    return day;
+}
+
+fn apply_change(day: Option<Day>, change: &DayChange) -> Result<Option<Day>, String> {
+    return match (day, change) {
+        (None, DayChange::In {at, task, time_to_do}) => Ok(Some(Day::new(&at.as_dt(), task.clone(), *time_to_do))),
+        (_, DayChange::Snapshot {day} | DayChange::Existing {day}) => Ok(Some(day.clone())),
+        (_, DayChange::Delete) => Ok(None),
+        (day, DayChange::Undo {..}) => Ok(day),
+        (Some(day), _) if day.has_ended() && !matches!(change, DayChange::Note {..}) => Err("The day has ended".to_string()),
+        (Some(mut day), DayChange::Pause {at, name}) => {
+            day.start_break_at(name.clone(), &at.as_dt())?;
+            Ok(Some(day))
+        },
+        (Some(mut day), DayChange::Switch {at, task}) => {
+            day.start_new_block(task.clone(), &at.as_dt())?;
+            Ok(Some(day))
+        },
+        (Some(mut day), DayChange::Rename {task}) => {
+            day.update_current_task_name(task.clone())?;
+            Ok(Some(day))
+        },
+        (Some(mut day), DayChange::Note {at, msg, remind_at}) => {
+            let note: &mut Note = day.add_note(&at.as_dt(), msg);
+            if let Some(remind_at) = remind_at {
+                note.set_remind_at(&remind_at.as_dt());
+            }
+            Ok(Some(day))
+        },
+        (Some(mut day), DayChange::Out {at}) => {
+            day.end_day_at(&at.as_dt())?;
+            Ok(Some(day))
+        },
+        _ => Err("The change doesn't fit the day".to_string()),
+    };
+}
+
+/// Days are compared as they're stored, so times that only differ below what's kept count
+/// as the same.
+pub fn is_same_day(day: Option<&Day>, other: Option<&Day>) -> bool {
+    return serde_yaml::to_value(day).ok() == serde_yaml::to_value(other).ok();
+}
+
+/// The changes that could have turned `before` into `after`, for `record_day` to try.
+fn get_candidate_changes(before: Option<&Day>, after: &Day) -> Vec<DayChange> {
+    let Some(before) = before else {
+        let first_start: Dt = after.timeblocks[0].get_start();
+        return vec![DayChange::In {at: first_start, task: after.get_task_name(0), time_to_do: after.time_to_do}];
+    };
+    let mut changes: Vec<DayChange> = Vec::new();
+    let last_ind: usize = after.timeblocks.len() - 1;
+    if after.timeblocks.len() == before.timeblocks.len() + 1 {
+        let at: Dt = after.timeblocks[last_ind].get_start();
+        changes.push(match after.is_break(last_ind) {
+            true => DayChange::Pause {at, name: after.get_latest_task_name()},
+            false => DayChange::Switch {at, task: after.get_latest_task_name()},
+        });
+    }
+    if let (Some(end), None) = (after.get_day_end(), before.get_day_end()) {
+        changes.push(DayChange::Out {at: end});
+    }
+    if after.timeblocks.len() == before.timeblocks.len() && after.get_latest_task_name() != before.get_latest_task_name() {
+        changes.push(DayChange::Rename {task: after.get_latest_task_name()});
+    }
+    if let Some(note) = after.timeblocks[last_ind].get_notes().last() {
+        changes.push(DayChange::Note {at: note.get_time(), msg: note.get_msg(), remind_at: note.get_remind_at()});
+    }
+    return changes;
+}
+
+/// Adds a change to this device's log for the date. A log that can't be read is left as it
+/// is for `punch doctor` to report, rather than overwritten, so the change isn't logged.
+fn add_event(date: &NaiveDate, change: DayChange) {
+    create_dir_if_not_exists(&get_events_dir());
+    let device: String = get_device_name();
+    let path: String = get_log_path(date, &device);
+    let mut log: Vec<DayEvent> = match read_log(&path) {
+        Ok(log) => log,
+        Err(msg) => {
+            warn_unreadable(&msg);
+            return;
+        },
+    };
+    // Kept in order even if the clock goes back, so the device's own events replay as recorded.
+    let now: DateTime<Local> = get_clock().now();
+    let recorded: DateTime<Local> = log.last()
+        .map(|event: &DayEvent| event.recorded.as_dt().max(now))
+        .unwrap_or(now);
+    let event: DayEvent = DayEvent {id: format!("{}-{}", device, log.len() + 1), recorded: Dt(recorded), change};
+    log.push(event);
+    let yaml_str: String = serde_yaml::to_string(&log).expect("Events should be serializable");
+    write_file(&path, yaml_str.clone());
+    write_checksum(&get_log_checksummed(&path), &yaml_str);
+}
+
+/// The day as the log has it. A day from before there were logs has its file taken as
+/// where the log starts.
+fn get_logged_day(date: &NaiveDate) -> Option<Day> {
+    if !has_event_log(date) {
+        let day: Day = read_day_file_for_date(date).ok()?;
+        add_event(date, DayChange::Existing {day: day.clone()});
+        return Some(day);
+    }
+    return replay(&read_events(date));
+}
+
+/// Logs how the day is being changed, before it's written. A change that one of the usual
+/// commands makes is logged as just that, when replaying it gives the same day, and
+/// anything else as a snapshot of the whole day.
+pub fn record_day(day: &Day) {
+    let date: NaiveDate = day.get_day_start().as_dt().date_naive();
+    let before: Option<Day> = get_logged_day(&date);
+    if is_same_day(before.as_ref(), Some(day)) {
+        return;
+    }
+    let change: DayChange = get_candidate_changes(before.as_ref(), day).into_iter()
+        .find(|change: &DayChange| apply_change(before.clone(), change)
+            .is_ok_and(|changed: Option<Day>| is_same_day(changed.as_ref(), Some(day))))
+        .unwrap_or(DayChange::Snapshot {day: day.clone()});
+    add_event(&date, change);
+}
+
+/// Logs that the day is being deleted, unless the log already has it gone.
+pub fn record_deletion(date: &NaiveDate) {
+    if get_logged_day(date).is_some() {
+        add_event(date, DayChange::Delete);
+    }
+}
+
+/// Takes back the latest change to the day that hasn't been undone yet, whichever device
+/// made it. Gives the change and the day as it is now.
+pub fn undo_last_change(date: &NaiveDate) -> Result<(DayEvent, Option<Day>), String> {
+    if let Some(msg) = find_unreadable_logs(date).first() {
+        return Err(format!("{}, so what the latest change was isn't known. Run 'punch doctor' for details.", msg));
+    }
+    let events: Vec<DayEvent> = read_events(date);
+    let undone: HashSet<&String> = events.iter()
+        .filter_map(|event: &DayEvent| match &event.change {
+            DayChange::Undo {id} => Some(id),
+            _ => None,
+        })
+        .collect();
+    let last: DayEvent = events.iter().rev()
+        .find(|event: &&DayEvent| !matches!(event.change, DayChange::Undo {..} | DayChange::Existing {..}) && !undone.contains(&event.id))
+        .cloned()
+        .ok_or(format!("There's nothing in the event log for {} to undo.", date.format(DATE_FMT)))?;
+    add_event(date, DayChange::Undo {id: last.id.clone()});
+    return Ok((last, replay(&read_events(date))));
+}
+
+/// Deletes the date's logs from every device, for when the day itself is meant to be gone,
+/// like when it's pruned.
+pub fn forget_events(date: &NaiveDate) {
+    for path in list_log_paths(date) {
+        let _ = delete_file(&path);
+        delete_checksum(&get_log_checksummed(&path));
+    }
+}
@@ -0,0 +1,35 @@
+use serde::{Serialize, Deserialize};
+
+/// How `punch expense` reads and shows amounts.
+#[derive(Debug,Serialize,Deserialize,Clone,Default)]
+pub struct ExpenseConfig {
+    /// Shown next to totals, e.g. "EUR".
+    #[serde(default)]
+    pub currency: Option<String>,
+    /// What a kilometre driven is paid back at, e.g. 0.30, for `punch expense --km`.
+    #[serde(default)]
+    pub mileage_rate: Option<f64>,
+}
+
+/// Reads an amount like "12", "12.5" or "12,50" into cents. Refunds can be negative.
+pub fn parse_amount_cents(amount: &str) -> Result<i64, String> {
+    let error: String = format!("'{}' isn't an amount. Give it like '12.50'.", amount);
+    let amount: String = amount.trim().replace(',', ".");
+    let (sign, unsigned): (i64, &str) = match amount.strip_prefix('-') {
+        Some(unsigned) => (-1, unsigned),
+        None => (1, amount.as_str()),
+    };
+    let (whole, fraction): (&str, &str) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    let is_digits = |part: &str| !part.is_empty() && part.chars().all(|c: char| c.is_ascii_digit());
+    if !is_digits(whole) || (!fraction.is_empty() && (fraction.len() > 2 || !is_digits(fraction))) {
+        return Err(error);
+    }
+    let whole: i64 = whole.parse().map_err(|_| error.clone())?;
+    let cents: i64 = format!("{:0<2}", fraction).parse().map_err(|_| error)?;
+    return Ok(sign * (whole * 100 + cents));
+}
+
+pub fn format_cents(cents: i64) -> String {
+    let sign: &str = if cents < 0 {"-"} else {""};
+    return format!("{}{}.{:02}", sign, cents.abs() / 100, cents.abs() % 100);
+}
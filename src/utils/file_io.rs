@@ -1,37 +1,136 @@
-use std::fs::{File, OpenOptions, create_dir_all, read_to_string, remove_file};
-use std::io::Write;
-use std::path::Path;
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions, TryLockError};
 use std::env::var;
+use std::io::{Error, ErrorKind, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, Command, Output, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread::{JoinHandle, sleep, spawn};
+use std::time::{Duration, Instant};
+
+use crate::utils::access::is_read_only;
+use crate::utils::config::{Config, get_config_path};
+use crate::utils::storage::get_storage;
 
 pub const BASE_DIR: &str = "~/.punch-card/";
+const LOCK_FILE: &str = ".lock";
+/// Added to the name of a file compressed with `compress_file`.
+pub const COMPRESSED_EXTENSION: &str = ".zst";
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(100);
 
-pub fn write_file(path: &str, contents: String) {
-    let path_str_to_write: String = expand_path(path);
-    let path_to_write: &Path = Path::new(&path_str_to_write); 
-    if path_to_write.exists() {
-        remove_file(path_str_to_write.clone()).expect("Should be able to delete");
+/// How many `DataLock`s this process holds right now.
+static LOCKS_HELD: AtomicUsize = AtomicUsize::new(0);
+
+/// With `--read-only`, nothing's written, whichever command or thread asks. Commands that
+/// change data refuse to start then, so this is only a backstop.
+fn check_writable(path: &str) -> Result<(), Error> {
+    if is_read_only() {
+        return Err(Error::new(ErrorKind::PermissionDenied, format!("Not changing '{}', as punch is running with --read-only.", path)));
     }
-    let file_result: Result<File, std::io::Error> = OpenOptions::new()
-        .create(true).write(true)
-        .open(path_str_to_write);
-    if let Ok(mut file) = file_result {
-        file.write_all(contents.as_bytes()).expect("Couldn't write to file!");
+    return Ok(());
+}
+
+pub fn write_file(path: &str, contents: String) {
+    let expanded: String = expand_path(path);
+    if let Err(err) = check_writable(&expanded) {
+        eprintln!("Warning: {}", err);
+        return;
     }
-    else {
-        panic!("Couldn't create file {path}");
+    get_storage().write(&expanded, &contents);
+    // What's just been written replaces a compressed copy, rather than sitting next to it.
+    let compressed: String = expanded + COMPRESSED_EXTENSION;
+    if get_storage().exists(&compressed) {
+        let _ = get_storage().remove(&compressed);
     }
 }
 
+/// Reads a text file, or its compressed copy if it's been compressed. Windows line endings,
+/// which editors like Notepad may leave in files edited by hand, are read as plain newlines.
 pub fn read_file(path: &str) -> Result<String,std::io::Error> {
     let path_to_read = expand_path(path);
-    return read_to_string(path_to_read);
+    let contents: Result<String, std::io::Error> = match get_storage().read(&path_to_read) {
+        Err(err) if err.kind() == ErrorKind::NotFound && is_compressed(&path_to_read) => read_compressed(&path_to_read),
+        result => result,
+    };
+    return contents.map(|contents: String| contents.replace("\r\n", "\n"));
+}
+
+pub fn delete_file(path: &str) -> Result<(), std::io::Error> {
+    let expanded: String = expand_path(path);
+    check_writable(&expanded)?;
+    if is_compressed(&expanded) {
+        return get_storage().remove(&(expanded + COMPRESSED_EXTENSION));
+    }
+    return get_storage().remove(&expanded);
+}
+
+pub fn path_exists(path: &str) -> bool {
+    let expanded: String = expand_path(path);
+    return get_storage().exists(&expanded) || get_storage().exists(&(expanded + COMPRESSED_EXTENSION));
+}
+
+/// Names of the entries directly inside `dir`. Compressed files are listed under the name
+/// they had before.
+pub fn list_dir(dir: &str) -> Result<Vec<String>, std::io::Error> {
+    let mut seen: HashSet<String> = HashSet::new();
+    return Ok(get_storage().list_dir(&expand_path(dir))?.into_iter()
+        .map(|name: String| name.strip_suffix(COMPRESSED_EXTENSION).map(|x: &str| x.to_string()).unwrap_or(name))
+        .filter(|name: &String| seen.insert(name.clone()))
+        .collect());
+}
+
+/// Whether the file has been compressed with `compress_file`, and not written since.
+pub fn is_compressed(path: &str) -> bool {
+    let expanded: String = expand_path(path);
+    return !get_storage().exists(&expanded) && get_storage().exists(&(expanded + COMPRESSED_EXTENSION));
+}
+
+/// Compresses a file with zstd, in place of the original. Everything here that reads,
+/// lists or deletes files sees through it, and writing the file again stores it plain.
+pub fn compress_file(path: &str) -> Result<(), String> {
+    let expanded: String = expand_path(path);
+    check_writable(&expanded).map_err(|err: Error| err.to_string())?;
+    let plain: Vec<u8> = get_storage().read_bytes(&expanded).map_err(|err: Error| format!("Couldn't read '{}': {}", expanded, err))?;
+    let compressed: Vec<u8> = run_zstd(&["-q", "-19", "-c"], plain)
+        .map_err(|msg: String| format!("zstd is needed to compress '{}': {}", expanded, msg))?;
+    get_storage().write_bytes(&(expanded.clone() + COMPRESSED_EXTENSION), &compressed);
+    return get_storage().remove(&expanded).map_err(|err: Error| format!("Couldn't delete '{}': {}", expanded, err));
+}
+
+/// Reads the compressed copy of a file. Without zstd to read it with, the error says so,
+/// rather than the file seeming not to exist.
+fn read_compressed(path: &str) -> Result<String, Error> {
+    let compressed: String = path.to_string() + COMPRESSED_EXTENSION;
+    let plain: Vec<u8> = run_zstd(&["-d", "-q", "-c"], get_storage().read_bytes(&compressed)?)
+        .map_err(|msg: String| Error::other(format!("zstd is needed to read '{}': {}", compressed, msg)))?;
+    return String::from_utf8(plain).map_err(|_| Error::new(ErrorKind::InvalidData, format!("'{}' isn't text", compressed)));
+}
+
+/// Runs zstd on `input` through its standard input and output, so files go through the
+/// storage like any other.
+fn run_zstd(args: &[&str], input: Vec<u8>) -> Result<Vec<u8>, String> {
+    let mut child: Child = Command::new("zstd").args(args)
+        .stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err: Error| format!("couldn't run it ({})", err))?;
+    let mut stdin: ChildStdin = child.stdin.take().expect("zstd's input is piped");
+    // Written from another thread, so that a large file can't fill both pipes at once.
+    let writer: JoinHandle<()> = spawn(move || {
+        let _ = stdin.write_all(&input);
+    });
+    let output: Output = child.wait_with_output().map_err(|err: Error| format!("it didn't finish ({})", err))?;
+    let _ = writer.join();
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    return Ok(output.stdout);
 }
 
 pub fn create_dir_if_not_exists(path: &str)  {
     let dir_expanded: String = expand_path(path);
-    if !Path::new(&dir_expanded).exists() {
-        let expect_msg: String = format!("Unable to create directory: '{path}'");
-        create_dir_all(dir_expanded).expect(&expect_msg);
+    if !path_exists(&dir_expanded) && check_writable(&dir_expanded).is_ok() {
+        get_storage().create_dir(&dir_expanded);
     }
 } 
 
@@ -39,15 +138,107 @@ pub fn create_base_dir_if_not_exists() {
     create_dir_if_not_exists(&BASE_DIR)
 }
 
+/// Holds the data directory's advisory lock until dropped.
+pub struct DataLock {
+    _file: File,
+}
+
+impl Drop for DataLock {
+    fn drop(&mut self) {
+        LOCKS_HELD.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Whether this process holds the data directory's lock, so can write files that aren't
+/// its own to change, like a day file another device's events have left behind.
+pub fn holds_data_lock() -> bool {
+    return LOCKS_HELD.load(Ordering::SeqCst) > 0;
+}
+
+/// Takes the lock on the data directory so that read-modify-write cycles on days and
+/// the config from different punch processes can't interleave. Waits a few seconds for
+/// another process to finish before giving up.
+pub fn lock_data_dir() -> Result<DataLock, String> {
+    let lock_path: String = expand_path(BASE_DIR) + LOCK_FILE;
+    let file: File = OpenOptions::new().create(true).truncate(false).write(true).open(&lock_path)
+        .map_err(|err| format!("Couldn't open the lock file '{}': {}", lock_path, err))?;
+    let started: Instant = Instant::now();
+    loop {
+        match file.try_lock() {
+            Ok(()) => {
+                LOCKS_HELD.fetch_add(1, Ordering::SeqCst);
+                return Ok(DataLock {_file: file});
+            },
+            Err(TryLockError::WouldBlock) if started.elapsed() < LOCK_TIMEOUT => sleep(LOCK_RETRY_INTERVAL),
+            Err(TryLockError::WouldBlock) => return Err(format!(
+                "Another punch process holds the lock ('{}'). Try again once it's finished.", lock_path)),
+            Err(TryLockError::Error(err)) => return Err(format!("Couldn't lock '{}': {}", lock_path, err)),
+        }
+    }
+}
+
 pub fn expand_path(path: &str) -> String {
-    return if path.starts_with("~/") {
-        var("HOME").unwrap() + &path[1..]
+    return if path.starts_with("~/") || path.starts_with("~\\") {
+        get_home_dir() + &path[1..]
     }else {path.to_string()};
 }
 
+/// Expands a folder given in the config. One that's relative is taken as relative to the
+/// config's own folder, `~/.punch-card/`, not to wherever punch happens to be run from.
+pub fn expand_config_dir(path: &str) -> String {
+    let expanded: String = expand_path(path.trim_end_matches(['/', '\\']));
+    if is_absolute_path(&expanded) {
+        return expanded + "/";
+    }
+    return expand_path(BASE_DIR) + &expanded + "/";
+}
+
+/// Whether the path, once `~/` is expanded, doesn't depend on the working directory.
+pub fn is_absolute_path(path: &str) -> bool {
+    return Path::new(&expand_path(path)).is_absolute();
+}
+
+/// The user's home folder: `HOME`, or on Windows, where that's usually unset, the profile
+/// folder in `USERPROFILE` (or `HOMEDRIVE` and `HOMEPATH`).
+pub fn get_home_dir() -> String {
+    if let Ok(home) = var("HOME") {
+        return home;
+    }
+    if let Ok(profile) = var("USERPROFILE") {
+        return profile;
+    }
+    return match (var("HOMEDRIVE"), var("HOMEPATH")) {
+        (Ok(drive), Ok(path)) => drive + &path,
+        _ => panic!("Couldn't find your home folder: none of HOME, USERPROFILE or HOMEDRIVE/HOMEPATH are set"),
+    };
+}
+
+/// The command to edit files with: the config's `editor`, `VISUAL` or `EDITOR` (which can
+/// include arguments, like "code --wait"), or else Notepad on Windows and Vim everywhere else.
+pub fn get_editor() -> Vec<String> {
+    let configured: Option<String> = read_file(&get_config_path()).ok()
+        .and_then(|yaml_str: String| Config::try_from_string(&yaml_str).ok())
+        .and_then(|config: Config| config.editor().cloned());
+    let editor: String = configured
+        .or(var("VISUAL").ok())
+        .or(var("EDITOR").ok())
+        .filter(|editor: &String| !editor.trim().is_empty())
+        .unwrap_or(if cfg!(windows) {"notepad".to_string()} else {"vim".to_string()});
+    let mut command: Vec<String> = editor.split_whitespace().map(|x: &str| x.to_string()).collect();
+    // VS Code returns straight away unless told to wait for the file to be closed.
+    let program: String = command[0].to_lowercase();
+    let is_vs_code: bool = ["code", "code.cmd", "code.exe"].iter()
+        .any(|name: &&str| program == *name || program.ends_with(&format!("/{}", name)) || program.ends_with(&format!("\\{}", name)));
+    if is_vs_code && !command.iter().any(|arg: &String| arg == "--wait" || arg == "-w") {
+        command.push("--wait".to_string());
+    }
+    return command;
+}
 
-pub fn edit_file_in_vim(path: &String) {
-    std::process::Command::new("vim")
+pub fn edit_file(path: &String) {
+    let editor: Vec<String> = get_editor();
+    std::process::Command::new(&editor[0])
+    .args(&editor[1..])
     .arg(path)
     .spawn()
     .expect("Error: Failed to run editor")
@@ -72,20 +263,26 @@ pub trait SafeFileEdit<T:FromString<T,E> + ToFile, E>: ToFile + FromString<T, E>
     fn safe_edit_from_file(&self) {
         let std_path: String = self.get_path();
         let temp_path: String = (&std_path).to_string() + "-temp";
-        std::process::Command::new("cp").args([&std_path, &temp_path]).output().expect("Failed to create temporary data!");
+        write_file(&temp_path, read_file(&std_path).expect("Failed to create temporary data!"));
     
-        println!("Opening config in vim...");
-        edit_file_in_vim(&temp_path);
-        println!("Vim closed.");
+        let editor: String = get_editor().join(" ");
+        println!("Opening in {}...", editor);
+        edit_file(&expand_path(&temp_path));
+        println!("{} closed.", editor);
         let yaml_str: String = read_file(&temp_path).unwrap();
         let new_result: Result<T, E> = T::try_from_string(&yaml_str);
         match new_result {
             Ok(new_value) => {
-                std::process::Command::new("rm").arg(&std_path).output().expect("Failed to clean up the temporary data!");
-                new_value.write();
+                delete_file(&std_path).expect("Failed to clean up the temporary data!");
+                Self::write_edited(new_value, yaml_str);
             },
             Err(_) => println!("Invalid Config created. Please try again"),
         };
-        std::process::Command::new("rm").arg(&temp_path).output().expect("Failed to clean up the temporary data!");
+        delete_file(&temp_path).expect("Failed to clean up the temporary data!");
+    }
+
+    /// Saves what was edited. By default it's written out afresh from the parsed value.
+    fn write_edited(new_value: T, _edited: String) {
+        new_value.write();
     }
 }
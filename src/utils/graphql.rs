@@ -0,0 +1,264 @@
+use crate::utils::json::JsonValue;
+
+/// A field asked for in a GraphQL query, with the fields asked for on its value.
+#[derive(Debug,Clone,PartialEq)]
+pub struct GraphqlField {
+    pub name: String,
+    /// What the field is called in the response: its alias, or else its name.
+    pub key: String,
+    pub args: Vec<(String, JsonValue)>,
+    pub selections: Vec<GraphqlField>,
+}
+
+impl GraphqlField {
+    pub fn get_arg(&self, name: &str) -> Option<&JsonValue> {
+        return self.args.iter()
+            .find(|(arg_name, _): &&(String, JsonValue)| arg_name == name)
+            .map(|(_, value): &(String, JsonValue)| value)
+            .filter(|value: &&JsonValue| **value != JsonValue::Null);
+    }
+
+    pub fn get_str_arg(&self, name: &str) -> Result<Option<String>, String> {
+        return match self.get_arg(name) {
+            None => Ok(None),
+            Some(JsonValue::String(value)) => Ok(Some(value.clone())),
+            Some(other) => Err(format!("Argument '{}' of '{}' should be a string, not {}", name, self.name, other.as_string())),
+        };
+    }
+
+    /// A list of strings, where a single string counts as a list of one.
+    pub fn get_str_list_arg(&self, name: &str) -> Result<Vec<String>, String> {
+        return match self.get_arg(name) {
+            None => Ok(Vec::new()),
+            Some(JsonValue::String(value)) => Ok(vec![value.clone()]),
+            Some(JsonValue::Array(values)) => values.iter()
+                .map(|value: &JsonValue| value.as_str().map(|x: &str| x.to_string())
+                    .ok_or(format!("Argument '{}' of '{}' should be a list of strings", name, self.name)))
+                .collect(),
+            Some(other) => Err(format!("Argument '{}' of '{}' should be a list of strings, not {}", name, self.name, other.as_string())),
+        };
+    }
+}
+
+/// Parses a GraphQL query into the fields it asks for at the top. Only queries are supported,
+/// written out in full: `$variables` are filled in from `variables`, but fragments and
+/// directives aren't.
+pub fn parse_graphql(query: &str, variables: &JsonValue) -> Result<Vec<GraphqlField>, String> {
+    let mut parser: GraphqlParser = GraphqlParser {chars: query.chars().collect(), pos: 0, variables: variables.clone()};
+    parser.skip_ignored();
+    if parser.peek() != Some('{') {
+        let operation: String = parser.parse_name()?;
+        if operation != "query" {
+            return Err(format!("Only queries are supported, not '{}'", operation));
+        }
+        parser.skip_ignored();
+        if parser.peek().is_some_and(is_name_start) {
+            parser.parse_name()?;
+        }
+        parser.skip_ignored();
+        if parser.peek() == Some('(') {
+            parser.skip_variable_definitions()?;
+        }
+    }
+    let fields: Vec<GraphqlField> = parser.parse_selection_set()?;
+    parser.skip_ignored();
+    if parser.pos != parser.chars.len() {
+        return Err(format!("Unexpected text at position {}. Only one operation can be sent at a time.", parser.pos));
+    }
+    return Ok(fields);
+}
+
+fn is_name_start(c: char) -> bool {
+    return c == '_' || c.is_ascii_alphabetic();
+}
+
+struct GraphqlParser {
+    chars: Vec<char>,
+    pos: usize,
+    variables: JsonValue,
+}
+
+impl GraphqlParser {
+    /// Skips whitespace, commas (which GraphQL ignores) and comments.
+    fn skip_ignored(&mut self) {
+        while let Some(c) = self.peek() {
+            if c == '#' {
+                while self.peek().is_some_and(|c: char| c != '\n') {
+                    self.pos += 1;
+                }
+            }
+            else if c.is_whitespace() || c == ',' {
+                self.pos += 1;
+            }
+            else {
+                break;
+            }
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        return self.chars.get(self.pos).copied();
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_ignored();
+        if self.peek() != Some(expected) {
+            return Err(format!("Expected '{}' at position {}", expected, self.pos));
+        }
+        self.pos += 1;
+        return Ok(());
+    }
+
+    fn parse_name(&mut self) -> Result<String, String> {
+        self.skip_ignored();
+        if !self.peek().is_some_and(is_name_start) {
+            return Err(format!("Expected a name at position {}", self.pos));
+        }
+        let start: usize = self.pos;
+        while self.peek().is_some_and(|c: char| c == '_' || c.is_ascii_alphanumeric()) {
+            self.pos += 1;
+        }
+        return Ok(self.chars[start..self.pos].iter().collect());
+    }
+
+    /// Variable types and defaults aren't checked, so `($from: String = "2024-01-01")` only
+    /// needs skipping. Variables missing from the request are null.
+    fn skip_variable_definitions(&mut self) -> Result<(), String> {
+        self.expect('(')?;
+        while self.peek().is_some_and(|c: char| c != ')') {
+            if self.peek() == Some('"') {
+                self.parse_string()?;
+            }
+            else {
+                self.pos += 1;
+            }
+        }
+        return self.expect(')');
+    }
+
+    fn parse_selection_set(&mut self) -> Result<Vec<GraphqlField>, String> {
+        self.expect('{')?;
+        let mut fields: Vec<GraphqlField> = Vec::new();
+        loop {
+            self.skip_ignored();
+            match self.peek() {
+                Some('}') => {
+                    self.pos += 1;
+                    return match fields.is_empty() {
+                        true => Err(format!("Empty selection at position {}", self.pos)),
+                        false => Ok(fields),
+                    };
+                },
+                Some('.') => return Err("Fragments aren't supported. Write the fields out instead.".to_string()),
+                Some('@') => return Err("Directives aren't supported.".to_string()),
+                Some(_) => fields.push(self.parse_field()?),
+                None => return Err("Unexpected end of query. Is a '}' missing?".to_string()),
+            }
+        }
+    }
+
+    fn parse_field(&mut self) -> Result<GraphqlField, String> {
+        let key: String = self.parse_name()?;
+        self.skip_ignored();
+        let name: String = match self.peek() {
+            Some(':') => {
+                self.pos += 1;
+                self.parse_name()?
+            },
+            _ => key.clone(),
+        };
+        self.skip_ignored();
+        let mut args: Vec<(String, JsonValue)> = Vec::new();
+        if self.peek() == Some('(') {
+            self.pos += 1;
+            loop {
+                self.skip_ignored();
+                if self.peek() == Some(')') {
+                    self.pos += 1;
+                    break;
+                }
+                let arg_name: String = self.parse_name()?;
+                self.expect(':')?;
+                args.push((arg_name, self.parse_value()?));
+            }
+        }
+        self.skip_ignored();
+        let selections: Vec<GraphqlField> = match self.peek() {
+            Some('{') => self.parse_selection_set()?,
+            _ => Vec::new(),
+        };
+        return Ok(GraphqlField {name, key, args, selections});
+    }
+
+    fn parse_value(&mut self) -> Result<JsonValue, String> {
+        self.skip_ignored();
+        return match self.peek() {
+            Some('$') => {
+                self.pos += 1;
+                let name: String = self.parse_name()?;
+                Ok(self.variables.get(&name).cloned().unwrap_or(JsonValue::Null))
+            },
+            Some('"') => Ok(JsonValue::String(self.parse_string()?)),
+            Some('[') => {
+                self.pos += 1;
+                let mut values: Vec<JsonValue> = Vec::new();
+                loop {
+                    self.skip_ignored();
+                    if self.peek() == Some(']') {
+                        self.pos += 1;
+                        return Ok(JsonValue::Array(values));
+                    }
+                    values.push(self.parse_value()?);
+                }
+            },
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                let start: usize = self.pos;
+                while self.peek().is_some_and(|c: char| c.is_ascii_digit() || ['-', '+', '.', 'e', 'E'].contains(&c)) {
+                    self.pos += 1;
+                }
+                let text: String = self.chars[start..self.pos].iter().collect();
+                match text.parse::<i64>() {
+                    Ok(value) => Ok(JsonValue::Number(value)),
+                    Err(_) => text.parse::<f64>().map(JsonValue::Float).map_err(|_| format!("Invalid number '{}'", text)),
+                }
+            },
+            Some(c) if is_name_start(c) => Ok(match self.parse_name()?.as_str() {
+                "true" => JsonValue::Bool(true),
+                "false" => JsonValue::Bool(false),
+                "null" => JsonValue::Null,
+                // Enum values are passed on as their names.
+                other => JsonValue::String(other.to_string()),
+            }),
+            Some(c) => Err(format!("Unexpected '{}' at position {}", c, self.pos)),
+            None => Err("Unexpected end of query".to_string()),
+        };
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut value: String = String::new();
+        loop {
+            match self.peek() {
+                Some('"') => {
+                    self.pos += 1;
+                    return Ok(value);
+                },
+                Some('\\') => {
+                    self.pos += 1;
+                    match self.peek() {
+                        Some('n') => value.push('\n'),
+                        Some('t') => value.push('\t'),
+                        Some(c) => value.push(c),
+                        None => return Err("Unterminated string".to_string()),
+                    }
+                    self.pos += 1;
+                },
+                Some(c) => {
+                    value.push(c);
+                    self.pos += 1;
+                },
+                None => return Err("Unterminated string".to_string()),
+            }
+        }
+    }
+}
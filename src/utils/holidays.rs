@@ -0,0 +1,147 @@
+use chrono::prelude::{Datelike, NaiveDate, Weekday};
+use chrono::Duration;
+use serde::{Serialize, Deserialize};
+
+use crate::utils::calendar::read_ics;
+use crate::utils::config::Config;
+use crate::utils::ics::get_all_day_events_on;
+
+/// Where public holidays come from: rules built in for a `region`, or the all-day events of an
+/// iCalendar file or URL in `ics`, or both. Days listed under `holidays` count too.
+#[derive(Debug,Serialize,Deserialize,Clone,Default)]
+pub struct HolidayCalendar {
+    /// One of "DE", "FR", "GB-ENG" or "US". Holidays moved off a weekend aren't included.
+    #[serde(default)]
+    pub region: Option<String>,
+    #[serde(default)]
+    pub ics: Option<String>,
+    /// The minutes to work on a holiday. Defaults to 0.
+    #[serde(default)]
+    pub target_minutes: Option<u64>,
+}
+
+/// How a region's holiday falls in a year.
+enum HolidayRule {
+    Fixed(u32, u32),
+    /// Days after Easter Sunday, so Good Friday is -2.
+    Easter(i64),
+    /// The nth of a weekday in a month, or the last one for -1.
+    NthWeekday(u32, Weekday, i32),
+}
+
+use HolidayRule::{Easter, Fixed, NthWeekday};
+
+const REGIONS: &[(&str, &[(&str, HolidayRule)])] = &[
+    ("DE", &[
+        ("Neujahr", Fixed(1, 1)), ("Karfreitag", Easter(-2)), ("Ostermontag", Easter(1)),
+        ("Tag der Arbeit", Fixed(5, 1)), ("Christi Himmelfahrt", Easter(39)), ("Pfingstmontag", Easter(50)),
+        ("Tag der Deutschen Einheit", Fixed(10, 3)), ("1. Weihnachtstag", Fixed(12, 25)), ("2. Weihnachtstag", Fixed(12, 26)),
+    ]),
+    ("FR", &[
+        ("Jour de l'an", Fixed(1, 1)), ("Lundi de Pâques", Easter(1)), ("Fête du Travail", Fixed(5, 1)),
+        ("Victoire 1945", Fixed(5, 8)), ("Ascension", Easter(39)), ("Lundi de Pentecôte", Easter(50)),
+        ("Fête nationale", Fixed(7, 14)), ("Assomption", Fixed(8, 15)), ("Toussaint", Fixed(11, 1)),
+        ("Armistice 1918", Fixed(11, 11)), ("Noël", Fixed(12, 25)),
+    ]),
+    ("GB-ENG", &[
+        ("New Year's Day", Fixed(1, 1)), ("Good Friday", Easter(-2)), ("Easter Monday", Easter(1)),
+        ("Early May bank holiday", NthWeekday(5, Weekday::Mon, 1)), ("Spring bank holiday", NthWeekday(5, Weekday::Mon, -1)),
+        ("Summer bank holiday", NthWeekday(8, Weekday::Mon, -1)), ("Christmas Day", Fixed(12, 25)), ("Boxing Day", Fixed(12, 26)),
+    ]),
+    ("US", &[
+        ("New Year's Day", Fixed(1, 1)), ("Martin Luther King Jr. Day", NthWeekday(1, Weekday::Mon, 3)),
+        ("Washington's Birthday", NthWeekday(2, Weekday::Mon, 3)), ("Memorial Day", NthWeekday(5, Weekday::Mon, -1)),
+        ("Juneteenth", Fixed(6, 19)), ("Independence Day", Fixed(7, 4)), ("Labor Day", NthWeekday(9, Weekday::Mon, 1)),
+        ("Columbus Day", NthWeekday(10, Weekday::Mon, 2)), ("Veterans Day", Fixed(11, 11)),
+        ("Thanksgiving Day", NthWeekday(11, Weekday::Thu, 4)), ("Christmas Day", Fixed(12, 25)),
+    ]),
+];
+
+/// The holidays from the config: its `holidays` and its `holiday_calendar`, with any
+/// calendar file already read.
+pub struct Holidays {
+    dates: Vec<NaiveDate>,
+    region: Option<String>,
+    ics: Option<String>,
+    target_minutes: u64,
+}
+
+impl Holidays {
+    pub fn load(config: &Config) -> Result<Self, String> {
+        let calendar: HolidayCalendar = config.holiday_calendar().cloned().unwrap_or_default();
+        if let Some(region) = &calendar.region {
+            if !REGIONS.iter().any(|(name, _)| name.eq_ignore_ascii_case(region)) {
+                let names: Vec<&str> = REGIONS.iter().map(|(name, _)| *name).collect();
+                return Err(format!("There are no holidays for the region '{}'. Try one of: {}", region, names.join(", ")));
+            }
+        }
+        let ics: Option<String> = match &calendar.ics {
+            Some(url_or_path) => Some(read_ics(url_or_path).map_err(|msg: String| format!("Couldn't read the holiday calendar: {}", msg))?),
+            None => None,
+        };
+        return Ok(Self {
+            dates: config.holidays()?,
+            region: calendar.region,
+            ics: ics,
+            target_minutes: calendar.target_minutes.unwrap_or(0),
+        });
+    }
+
+    /// The holiday's name if `date` is one, or "Holiday" for days only listed under `holidays`.
+    pub fn get_holiday(&self, date: &NaiveDate) -> Option<String> {
+        if let Some(region) = &self.region {
+            if let Some(name) = get_region_holiday(region, date) {
+                return Some(name.to_string());
+            }
+        }
+        if let Some(ics) = &self.ics {
+            if let Some(name) = get_all_day_events_on(ics, date).into_iter().next() {
+                return Some(name);
+            }
+        }
+        return match self.dates.contains(date) {
+            true => Some("Holiday".to_string()),
+            false => None,
+        };
+    }
+
+    pub fn is_holiday(&self, date: &NaiveDate) -> bool {
+        return self.get_holiday(date).is_some();
+    }
+
+    /// The minutes to work on a holiday.
+    pub fn target_minutes(&self) -> u64 {
+        return self.target_minutes;
+    }
+}
+
+fn get_region_holiday(region: &str, date: &NaiveDate) -> Option<&'static str> {
+    let (_, rules) = REGIONS.iter().find(|(name, _)| name.eq_ignore_ascii_case(region))?;
+    return rules.iter()
+        .find(|(_, rule)| get_date_in_year(rule, date.year()) == Some(*date))
+        .map(|(name, _)| *name);
+}
+
+fn get_date_in_year(rule: &HolidayRule, year: i32) -> Option<NaiveDate> {
+    return match rule {
+        Fixed(month, day) => NaiveDate::from_ymd_opt(year, *month, *day),
+        Easter(offset) => Some(get_easter_sunday(year)? + Duration::days(*offset)),
+        NthWeekday(month, weekday, n) if *n > 0 => NaiveDate::from_weekday_of_month_opt(year, *month, *weekday, *n as u8),
+        NthWeekday(month, weekday, _) => (1..=5).rev()
+            .find_map(|n: u8| NaiveDate::from_weekday_of_month_opt(year, *month, *weekday, n)),
+    };
+}
+
+/// Easter Sunday in the Gregorian calendar, by the Meeus/Jones/Butcher algorithm.
+fn get_easter_sunday(year: i32) -> Option<NaiveDate> {
+    let a: i32 = year % 19;
+    let (b, c): (i32, i32) = (year / 100, year % 100);
+    let (d, e): (i32, i32) = (b / 4, b % 4);
+    let g: i32 = (b - (b + 8) / 25 + 1) / 3;
+    let h: i32 = (19 * a + b - d - g + 15) % 30;
+    let (i, k): (i32, i32) = (c / 4, c % 4);
+    let l: i32 = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m: i32 = (a + 11 * h + 22 * l) / 451;
+    let n: i32 = h + l - 7 * m + 114;
+    return NaiveDate::from_ymd_opt(year, (n / 31) as u32, (n % 31 + 1) as u32);
+}
@@ -0,0 +1,316 @@
+use std::collections::{HashMap, VecDeque};
+
+/// The headers every HPACK decoder starts out knowing, from RFC 7541 appendix A.
+const STATIC_TABLE: [(&str, &str); 61] = [
+    (":authority", ""), (":method", "GET"), (":method", "POST"), (":path", "/"), (":path", "/index.html"),
+    (":scheme", "http"), (":scheme", "https"), (":status", "200"), (":status", "204"), (":status", "206"),
+    (":status", "304"), (":status", "400"), (":status", "404"), (":status", "500"), ("accept-charset", ""),
+    ("accept-encoding", "gzip, deflate"), ("accept-language", ""), ("accept-ranges", ""), ("accept", ""),
+    ("access-control-allow-origin", ""), ("age", ""), ("allow", ""), ("authorization", ""), ("cache-control", ""),
+    ("content-disposition", ""), ("content-encoding", ""), ("content-language", ""), ("content-length", ""),
+    ("content-location", ""), ("content-range", ""), ("content-type", ""), ("cookie", ""), ("date", ""), ("etag", ""),
+    ("expect", ""), ("expires", ""), ("from", ""), ("host", ""), ("if-match", ""), ("if-modified-since", ""),
+    ("if-none-match", ""), ("if-range", ""), ("if-unmodified-since", ""), ("last-modified", ""), ("link", ""),
+    ("location", ""), ("max-forwards", ""), ("proxy-authenticate", ""), ("proxy-authorization", ""), ("range", ""),
+    ("referer", ""), ("refresh", ""), ("retry-after", ""), ("server", ""), ("set-cookie", ""),
+    ("strict-transport-security", ""), ("transfer-encoding", ""), ("user-agent", ""), ("vary", ""), ("via", ""),
+    ("www-authenticate", ""),
+];
+
+/// The symbols of the HPACK Huffman code up to 19 bits long, shortest first, which covers
+/// every printable ASCII character: `\` is the only one past 15 bits. The code is canonical,
+/// so the codes follow from the lengths. Longer codes are for other bytes, which headers
+/// don't use in practice.
+const HUFFMAN_SYMBOLS_BY_LENGTH: [(u32, &[u8]); 11] = [
+    (5, b"012aceiost"),
+    (6, b" %-./3456789=A_bdfghlmnpru"),
+    (7, b":BCDEFGHIJKLMNOPQRSTUVWYjkqvwxyz"),
+    (8, b"&*,;XZ"),
+    (10, b"!\"()?"),
+    (11, b"'+|"),
+    (12, b"#>"),
+    (13, b"\0$@[]~"),
+    (14, b"^}"),
+    (15, b"<`{"),
+    (19, b"\\\xc3\xd0"),
+];
+/// The longest code in `HUFFMAN_SYMBOLS_BY_LENGTH`.
+const MAX_HUFFMAN_CODE_LEN: u32 = 19;
+
+/// The entry size RFC 7541 counts for a header: its name and value plus 32 bytes of overhead.
+pub fn get_entry_size(name: &str, value: &str) -> usize {
+    return name.len() + value.len() + 32;
+}
+
+/// Decodes HPACK header blocks. A connection's header blocks share one decoder, since each
+/// can add headers to the table the next ones refer to.
+pub struct HpackDecoder {
+    dynamic_table: VecDeque<(String, String)>,
+    table_size: usize,
+    max_table_size: usize,
+    huffman_codes: HashMap<(u32, u32), u8>,
+}
+
+impl HpackDecoder {
+    pub fn new(max_table_size: usize) -> Self {
+        let mut huffman_codes: HashMap<(u32, u32), u8> = HashMap::new();
+        let mut code: u32 = 0;
+        let mut prev_len: u32 = HUFFMAN_SYMBOLS_BY_LENGTH[0].0;
+        for (len, symbols) in HUFFMAN_SYMBOLS_BY_LENGTH {
+            code <<= len - prev_len;
+            for symbol in symbols {
+                huffman_codes.insert((len, code), *symbol);
+                code += 1;
+            }
+            prev_len = len;
+        }
+        return Self {dynamic_table: VecDeque::new(), table_size: 0, max_table_size: max_table_size, huffman_codes: huffman_codes};
+    }
+
+    pub fn decode(&mut self, block: &[u8]) -> Result<Vec<(String, String)>, String> {
+        let mut headers: Vec<(String, String)> = Vec::new();
+        let mut pos: usize = 0;
+        while pos < block.len() {
+            let first: u8 = block[pos];
+            if first & 0x80 != 0 {
+                let index: usize = decode_int(block, &mut pos, 7)?;
+                headers.push(self.get_indexed(index)?);
+            }
+            else if first & 0xe0 == 0x20 {
+                let size: usize = decode_int(block, &mut pos, 5)?;
+                if size > self.max_table_size {
+                    return Err(format!("HPACK table size {} is over the {} allowed", size, self.max_table_size));
+                }
+                self.max_table_size = size;
+                self.evict(0);
+            }
+            else {
+                let (prefix_bits, add_to_table): (u8, bool) = match first & 0x40 != 0 {
+                    true => (6, true),
+                    false => (4, false),
+                };
+                let name_index: usize = decode_int(block, &mut pos, prefix_bits)?;
+                let name: String = match name_index {
+                    0 => self.decode_string(block, &mut pos)?,
+                    index => self.get_indexed(index)?.0,
+                };
+                let value: String = self.decode_string(block, &mut pos)?;
+                if add_to_table {
+                    self.add(name.clone(), value.clone());
+                }
+                headers.push((name, value));
+            }
+        }
+        return Ok(headers);
+    }
+
+    fn get_indexed(&self, index: usize) -> Result<(String, String), String> {
+        return match index {
+            0 => Err("HPACK index 0 isn't valid".to_string()),
+            index if index <= STATIC_TABLE.len() => {
+                let (name, value): (&str, &str) = STATIC_TABLE[index - 1];
+                Ok((name.to_string(), value.to_string()))
+            },
+            index => self.dynamic_table.get(index - STATIC_TABLE.len() - 1).cloned()
+                .ok_or(format!("HPACK index {} isn't in the table", index)),
+        };
+    }
+
+    fn add(&mut self, name: String, value: String) {
+        let size: usize = get_entry_size(&name, &value);
+        self.evict(size);
+        if size <= self.max_table_size {
+            self.table_size += size;
+            self.dynamic_table.push_front((name, value));
+        }
+    }
+
+    /// Drops the oldest entries until `room` more bytes fit.
+    fn evict(&mut self, room: usize) {
+        while self.table_size + room > self.max_table_size {
+            let Some((name, value)) = self.dynamic_table.pop_back() else {
+                break;
+            };
+            self.table_size -= get_entry_size(&name, &value);
+        }
+    }
+
+    fn decode_string(&self, block: &[u8], pos: &mut usize) -> Result<String, String> {
+        let is_huffman: bool = block.get(*pos).ok_or("HPACK block ends early".to_string())? & 0x80 != 0;
+        let len: usize = decode_int(block, pos, 7)?;
+        let bytes: &[u8] = block.get(*pos..*pos + len).ok_or("HPACK string runs past the block".to_string())?;
+        *pos += len;
+        let decoded: Vec<u8> = match is_huffman {
+            true => self.decode_huffman(bytes)?,
+            false => bytes.to_vec(),
+        };
+        return String::from_utf8(decoded).map_err(|_| "HPACK string isn't UTF-8".to_string());
+    }
+
+    fn decode_huffman(&self, bytes: &[u8]) -> Result<Vec<u8>, String> {
+        let mut decoded: Vec<u8> = Vec::new();
+        let (mut code, mut len): (u32, u32) = (0, 0);
+        for byte in bytes {
+            for shift in (0..8).rev() {
+                code = (code << 1) | ((*byte >> shift) & 1) as u32;
+                len += 1;
+                if let Some(symbol) = self.huffman_codes.get(&(len, code)) {
+                    decoded.push(*symbol);
+                    (code, len) = (0, 0);
+                }
+                else if len >= MAX_HUFFMAN_CODE_LEN {
+                    return Err("HPACK string has a character punch doesn't read".to_string());
+                }
+            }
+        }
+        // What's left over has to be padding: the first bits of the end-of-string code, all ones.
+        if len >= 8 || code != (1 << len) - 1 {
+            return Err("HPACK string has invalid padding".to_string());
+        }
+        return Ok(decoded);
+    }
+}
+
+/// Reads an HPACK integer whose first byte keeps `prefix_bits` bits for it.
+fn decode_int(block: &[u8], pos: &mut usize, prefix_bits: u8) -> Result<usize, String> {
+    let max_prefix: usize = (1 << prefix_bits) - 1;
+    let first: usize = *block.get(*pos).ok_or("HPACK block ends early".to_string())? as usize & max_prefix;
+    *pos += 1;
+    if first < max_prefix {
+        return Ok(first);
+    }
+    let mut value: usize = max_prefix;
+    let mut shift: u32 = 0;
+    loop {
+        let byte: u8 = *block.get(*pos).ok_or("HPACK block ends early".to_string())?;
+        *pos += 1;
+        value += ((byte & 0x7f) as usize) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+        if shift > 28 {
+            return Err("HPACK integer is too large".to_string());
+        }
+    }
+}
+
+/// Encodes headers as literals that aren't added to the table, which any decoder reads
+/// without keeping state in step with ours.
+pub fn encode_headers(headers: &Vec<(String, String)>) -> Vec<u8> {
+    let mut block: Vec<u8> = Vec::new();
+    for (name, value) in headers {
+        block.push(0);
+        encode_string(&mut block, name);
+        encode_string(&mut block, value);
+    }
+    return block;
+}
+
+fn encode_string(block: &mut Vec<u8>, text: &str) {
+    encode_int(block, text.len(), 7);
+    block.extend_from_slice(text.as_bytes());
+}
+
+fn encode_int(block: &mut Vec<u8>, value: usize, prefix_bits: u8) {
+    let max_prefix: usize = (1 << prefix_bits) - 1;
+    if value < max_prefix {
+        block.push(value as u8);
+        return;
+    }
+    block.push(max_prefix as u8);
+    let mut rest: usize = value - max_prefix;
+    while rest >= 0x80 {
+        block.push((rest & 0x7f) as u8 | 0x80);
+        rest >>= 7;
+    }
+    block.push(rest as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_headers(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        return pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect();
+    }
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        let digits: Vec<char> = hex.chars().filter(|c: &char| !c.is_whitespace()).collect();
+        return digits.chunks(2).map(|pair: &[char]| u8::from_str_radix(&pair.iter().collect::<String>(), 16).unwrap()).collect();
+    }
+
+    #[test]
+    fn decodes_the_rfc_7541_requests_without_huffman() {
+        let mut decoder: HpackDecoder = HpackDecoder::new(4096);
+        let first: Vec<(String, String)> = decoder.decode(&from_hex("8286 8441 0f77 7777 2e65 7861 6d70 6c65 2e63 6f6d")).unwrap();
+        assert_eq!(first, to_headers(&[(":method", "GET"), (":scheme", "http"), (":path", "/"), (":authority", "www.example.com")]));
+        assert_eq!(decoder.table_size, 57);
+
+        // Refers back to the authority the first request added to the table.
+        let second: Vec<(String, String)> = decoder.decode(&from_hex("8286 84be 5808 6e6f 2d63 6163 6865")).unwrap();
+        assert_eq!(second, to_headers(&[(":method", "GET"), (":scheme", "http"), (":path", "/"), (":authority", "www.example.com"), ("cache-control", "no-cache")]));
+        assert_eq!(decoder.table_size, 110);
+    }
+
+    #[test]
+    fn decodes_the_rfc_7541_requests_with_huffman() {
+        let mut decoder: HpackDecoder = HpackDecoder::new(4096);
+        let first: Vec<(String, String)> = decoder.decode(&from_hex("8286 8441 8cf1 e3c2 e5f2 3a6b a0ab 90f4 ff")).unwrap();
+        assert_eq!(first, to_headers(&[(":method", "GET"), (":scheme", "http"), (":path", "/"), (":authority", "www.example.com")]));
+        let second: Vec<(String, String)> = decoder.decode(&from_hex("8286 84be 5886 a8eb 1064 9cbf")).unwrap();
+        assert_eq!(second.last().unwrap(), &("cache-control".to_string(), "no-cache".to_string()));
+    }
+
+    #[test]
+    fn evicts_the_oldest_entries_to_stay_within_the_table_size() {
+        let mut decoder: HpackDecoder = HpackDecoder::new(100);
+        decoder.decode(&from_hex("400a 6375 7374 6f6d 2d6b 6579 0d63 7573 746f 6d2d 6865 6164 6572")).unwrap();
+        decoder.decode(&from_hex("400a 6375 7374 6f6d 2d6b 6579 0d63 7573 746f 6d2d 6865 6164 6572")).unwrap();
+        assert_eq!((decoder.dynamic_table.len(), decoder.table_size), (1, 55));
+        // A table size update down to nothing empties it.
+        decoder.decode(&[0x20]).unwrap();
+        assert_eq!((decoder.dynamic_table.len(), decoder.table_size), (0, 0));
+    }
+
+    #[test]
+    fn integers_roundtrip_across_the_prefix() {
+        let mut block: Vec<u8> = Vec::new();
+        encode_int(&mut block, 1337, 5);
+        assert_eq!(block, vec![0x1f, 0x9a, 0x0a]);
+        for value in [0, 30, 31, 127, 128, 1337, 1 << 20] {
+            let mut block: Vec<u8> = Vec::new();
+            encode_int(&mut block, value, 7);
+            assert_eq!(decode_int(&block, &mut 0, 7), Ok(value));
+        }
+    }
+
+    #[test]
+    fn encoded_headers_decode_without_being_added_to_the_table() {
+        let headers: Vec<(String, String)> = to_headers(&[(":status", "200"), ("content-type", "application/grpc"), ("grpc-message", &"x".repeat(300))]);
+        let mut decoder: HpackDecoder = HpackDecoder::new(4096);
+        assert_eq!(decoder.decode(&encode_headers(&headers)).unwrap(), headers);
+        assert_eq!(decoder.table_size, 0);
+    }
+
+    #[test]
+    fn decodes_every_printable_ascii_character() {
+        let mut decoder: HpackDecoder = HpackDecoder::new(4096);
+        // "a\b" in Huffman code: 'a' is 5 bits, the backslash 19 and 'b' 6, then 2 bits of padding.
+        let block: Vec<u8> = vec![0x00, 0x01, b'x', 0x84, 0x1f, 0xff, 0xf0, 0x8f];
+        assert_eq!(decoder.decode(&block).unwrap(), to_headers(&[("x", "a\\b")]));
+        let printable: Vec<u8> = (b' '..=b'~').collect();
+        assert!(printable.iter().all(|c: &u8| decoder.huffman_codes.values().any(|symbol: &u8| symbol == c)));
+    }
+
+    #[test]
+    fn rejects_malformed_blocks() {
+        let mut decoder: HpackDecoder = HpackDecoder::new(4096);
+        assert!(decoder.decode(&[0x80]).is_err());
+        assert!(decoder.decode(&[0xc0 | 0x3f, 0x80]).is_err());
+        assert!(decoder.decode(&[0x41, 0x05, b'a']).is_err());
+        assert!(decoder.decode(&[0x3f, 0xe1, 0x7f]).is_err());
+        // Padding has to be all ones and shorter than a byte.
+        assert!(decoder.decode(&[0x00, 0x81, 0x00, 0x00]).is_err());
+    }
+}
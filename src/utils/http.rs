@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Error, ErrorKind, Read, Take, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+/// The most a request body can be, like for gRPC. Bigger requests get a 413.
+pub const MAX_BODY_SIZE: usize = 1024 * 1024;
+/// The most the request line and headers can be together.
+const MAX_HEAD_SIZE: u64 = 64 * 1024;
+/// How long a connection can go without sending or taking anything before it's dropped.
+pub const CONNECTION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The parts of an HTTP/1.1 request punch cares about.
+#[derive(Debug,Clone)]
+pub struct HttpRequest {
+    pub method: String,
+    pub path: String,
+    pub query: HashMap<String, String>,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+impl HttpRequest {
+    /// Reads a request, or gives the status and message to refuse it with. The head and
+    /// body are limited in size, so a client can't make punch hold more than that.
+    pub fn read_from(stream: &TcpStream) -> Result<Self, (u16, String)> {
+        let mut reader: BufReader<&TcpStream> = BufReader::new(stream);
+        let mut head: Take<&mut BufReader<&TcpStream>> = (&mut reader).take(MAX_HEAD_SIZE);
+        let request_line: String = read_head_line(&mut head)?;
+        let mut parts = request_line.split_whitespace();
+        let method: String = parts.next().ok_or((400, "Empty request".to_string()))?.to_string();
+        let target: String = parts.next().ok_or((400, "Request has no path".to_string()))?.to_string();
+
+        let mut headers: HashMap<String, String> = HashMap::new();
+        loop {
+            let line: String = read_head_line(&mut head)?;
+            if line.is_empty() {
+                break;
+            }
+            if let Some((key, value)) = line.split_once(':') {
+                headers.insert(key.trim().to_lowercase(), value.trim().to_string());
+            }
+        }
+
+        let content_length: usize = match headers.get("content-length") {
+            Some(x) => x.parse().map_err(|_| (400, format!("Invalid Content-Length '{}'", x)))?,
+            None => 0,
+        };
+        if content_length > MAX_BODY_SIZE {
+            return Err((413, format!("Request bodies can be at most {} bytes", MAX_BODY_SIZE)));
+        }
+        let mut body_bytes: Vec<u8> = vec![0; content_length];
+        reader.read_exact(&mut body_bytes).map_err(get_read_error)?;
+
+        let (path, query_str): (&str, &str) = target.split_once('?').unwrap_or((&target, ""));
+        return Ok(Self {
+            method: method,
+            path: percent_decode(path),
+            query: parse_query(query_str),
+            headers: headers,
+            body: String::from_utf8_lossy(&body_bytes).to_string(),
+        });
+    }
+
+    /// The token from an `Authorization: Bearer <token>` header.
+    pub fn get_bearer_token(&self) -> Option<String> {
+        return self.headers.get("authorization")
+            .and_then(|x: &String| x.strip_prefix("Bearer "))
+            .map(|x: &str| x.trim().to_string());
+    }
+}
+
+/// A line of the request head without its line ending. A line that runs past the limit on
+/// the head's size never ends.
+fn read_head_line(head: &mut Take<&mut BufReader<&TcpStream>>) -> Result<String, (u16, String)> {
+    let mut line: String = String::new();
+    head.read_line(&mut line).map_err(get_read_error)?;
+    if !line.ends_with('\n') && head.limit() == 0 {
+        return Err((431, "Request headers are too large".to_string()));
+    }
+    return Ok(line.trim_end().to_string());
+}
+
+fn get_read_error(err: Error) -> (u16, String) {
+    return match err.kind() {
+        ErrorKind::WouldBlock | ErrorKind::TimedOut => (408, "Timed out waiting for the request".to_string()),
+        _ => (400, err.to_string()),
+    };
+}
+
+/// Keeps a slow or stalled client from holding a connection, and the thread serving it, open.
+pub fn set_connection_timeouts(stream: &TcpStream) {
+    let _ = stream.set_read_timeout(Some(CONNECTION_TIMEOUT));
+    let _ = stream.set_write_timeout(Some(CONNECTION_TIMEOUT));
+}
+
+pub fn parse_query(query_str: &str) -> HashMap<String, String> {
+    return query_str.split('&')
+        .filter(|x: &&str| !x.is_empty())
+        .map(|pair: &str| match pair.split_once('=') {
+            Some((key, value)) => (percent_decode(key), percent_decode(value)),
+            None => (percent_decode(pair), String::new()),
+        })
+        .collect();
+}
+
+pub fn percent_decode(text: &str) -> String {
+    let bytes: &[u8] = text.as_bytes();
+    let mut decoded: Vec<u8> = Vec::new();
+    let mut ind: usize = 0;
+    while ind < bytes.len() {
+        match bytes[ind] {
+            b'+' => decoded.push(b' '),
+            b'%' if ind + 2 < bytes.len() => {
+                let hex: Option<u8> = std::str::from_utf8(&bytes[ind + 1..ind + 3]).ok()
+                    .and_then(|x: &str| u8::from_str_radix(x, 16).ok());
+                match hex {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        ind += 2;
+                    },
+                    None => decoded.push(b'%'),
+                }
+            },
+            byte => decoded.push(byte),
+        }
+        ind += 1;
+    }
+    return String::from_utf8_lossy(&decoded).to_string();
+}
+
+pub fn write_response(mut stream: &TcpStream, status: u16, content_type: &str, body: &str) {
+    let reason: &str = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        413 => "Content Too Large",
+        431 => "Request Header Fields Too Large",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    };
+    let response: String = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status, reason, content_type, body.len(), body);
+    let _ = stream.write_all(response.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Shutdown, TcpListener};
+    use std::thread;
+    use super::*;
+
+    /// Reads what a client sent as `raw` before closing its side of the connection.
+    fn read_raw(raw: Vec<u8>) -> Result<HttpRequest, (u16, String)> {
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client: TcpStream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        let writer = thread::spawn(move || {
+            let _ = client.write_all(&raw);
+            let _ = client.shutdown(Shutdown::Write);
+        });
+        let request: Result<HttpRequest, (u16, String)> = HttpRequest::read_from(&server);
+        drop(server);
+        writer.join().unwrap();
+        return request;
+    }
+
+    #[test]
+    fn reads_the_parts_of_a_request() {
+        let raw: &str = "POST /api/in%20x?task=code+review&at=09%3A00&flag HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer 6f1c \r\nContent-Length: 5\r\n\r\nhello";
+        let request: HttpRequest = read_raw(raw.as_bytes().to_vec()).unwrap();
+        assert_eq!((request.method.as_str(), request.path.as_str(), request.body.as_str()), ("POST", "/api/in x", "hello"));
+        assert_eq!(request.query.get("task").map(String::as_str), Some("code review"));
+        assert_eq!(request.query.get("at").map(String::as_str), Some("09:00"));
+        assert_eq!(request.query.get("flag").map(String::as_str), Some(""));
+        assert_eq!(request.headers.get("host").map(String::as_str), Some("localhost"));
+        assert_eq!(request.get_bearer_token(), Some("6f1c".to_string()));
+    }
+
+    #[test]
+    fn refuses_requests_that_are_too_big_or_malformed() {
+        let too_long: String = format!("POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n", MAX_BODY_SIZE + 1);
+        assert_eq!(read_raw(too_long.into_bytes()).unwrap_err().0, 413);
+        let huge_header: String = format!("GET / HTTP/1.1\r\nX-Filler: {}\r\n\r\n", "a".repeat(MAX_HEAD_SIZE as usize));
+        assert_eq!(read_raw(huge_header.into_bytes()).unwrap_err().0, 431);
+        assert_eq!(read_raw(b"POST / HTTP/1.1\r\nContent-Length: lots\r\n\r\n".to_vec()).unwrap_err().0, 400);
+        assert_eq!(read_raw(b"POST / HTTP/1.1\r\nContent-Length: 10\r\n\r\nshort".to_vec()).unwrap_err().0, 400);
+        assert_eq!(read_raw(b"\r\n".to_vec()).unwrap_err().0, 400);
+    }
+
+    #[test]
+    fn percent_decoding_leaves_what_isnt_an_escape() {
+        assert_eq!(percent_decode("a%41+b"), "aA b");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz%4"), "%zz%4");
+        assert_eq!(percent_decode("caf%C3%A9"), "café");
+    }
+}
@@ -0,0 +1,422 @@
+use std::collections::{HashMap, VecDeque};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+
+use crate::utils::hpack::{HpackDecoder, encode_headers, get_entry_size};
+
+const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+const DATA: u8 = 0x0;
+const HEADERS: u8 = 0x1;
+const RST_STREAM: u8 = 0x3;
+const SETTINGS: u8 = 0x4;
+const PING: u8 = 0x6;
+const GOAWAY: u8 = 0x7;
+const WINDOW_UPDATE: u8 = 0x8;
+const CONTINUATION: u8 = 0x9;
+const END_STREAM: u8 = 0x1;
+const ACK: u8 = 0x1;
+const END_HEADERS: u8 = 0x4;
+const PADDED: u8 = 0x8;
+const PRIORITY: u8 = 0x20;
+const SETTINGS_MAX_CONCURRENT_STREAMS: u16 = 0x3;
+const SETTINGS_INITIAL_WINDOW_SIZE: u16 = 0x4;
+const SETTINGS_MAX_FRAME_SIZE: u16 = 0x5;
+const SETTINGS_MAX_HEADER_LIST_SIZE: u16 = 0x6;
+const REFUSED_STREAM: u32 = 0x7;
+const DEFAULT_WINDOW_SIZE: i64 = 65535;
+const DEFAULT_MAX_FRAME_SIZE: usize = 16384;
+const HEADER_TABLE_SIZE: usize = 4096;
+/// Requests bigger than this are refused, as nothing punch serves takes more than a query.
+const MAX_REQUEST_SIZE: usize = 1024 * 1024;
+/// How many requests a client can have coming in at once. Streams opened past it are refused.
+const MAX_CONCURRENT_STREAMS: usize = 100;
+/// The most a request's headers can take, counted the way RFC 7541 counts table entries.
+/// Their encoded block can't be bigger either.
+const MAX_HEADER_LIST_SIZE: usize = 16 * 1024;
+/// How many frames can wait while a response waits for the client to make room for it.
+const MAX_PENDING_FRAMES: usize = 1000;
+
+/// A request that came in on a stream of an HTTP/2 connection.
+#[derive(Debug,Clone)]
+pub struct Http2Request {
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Http2Request {
+    pub fn get_header(&self, name: &str) -> Option<&String> {
+        return self.headers.iter()
+            .find(|(header_name, _): &&(String, String)| header_name == name)
+            .map(|(_, value): &(String, String)| value);
+    }
+}
+
+/// A response, with trailers sent after the body, like gRPC's status.
+#[derive(Debug,Clone)]
+pub struct Http2Response {
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    pub trailers: Vec<(String, String)>,
+}
+
+struct Frame {
+    kind: u8,
+    flags: u8,
+    stream_id: u32,
+    payload: Vec<u8>,
+}
+
+#[derive(Default)]
+struct Stream {
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+/// One cleartext HTTP/2 connection (h2c with prior knowledge, as gRPC clients use without
+/// TLS). Requests are answered one at a time, in the order they finish arriving.
+struct Connection {
+    stream: TcpStream,
+    decoder: HpackDecoder,
+    streams: HashMap<u32, Stream>,
+    /// Frames for other streams that came in while waiting to send more of a response.
+    pending: VecDeque<Frame>,
+    send_window: i64,
+    stream_send_windows: HashMap<u32, i64>,
+    initial_window_size: i64,
+    max_frame_size: usize,
+}
+
+/// Serves the connection until the client goes away, answering each request with `handle`.
+pub fn serve_http2_connection<F>(stream: TcpStream, handle: F) -> Result<(), String>
+where F: Fn(Http2Request) -> Http2Response {
+    let mut connection: Connection = Connection {
+        stream: stream,
+        decoder: HpackDecoder::new(HEADER_TABLE_SIZE),
+        streams: HashMap::new(),
+        pending: VecDeque::new(),
+        send_window: DEFAULT_WINDOW_SIZE,
+        stream_send_windows: HashMap::new(),
+        initial_window_size: DEFAULT_WINDOW_SIZE,
+        max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+    };
+    let mut preface: [u8; 24] = [0; 24];
+    connection.stream.read_exact(&mut preface).map_err(|err| err.to_string())?;
+    if preface != PREFACE {
+        return Err("Not an HTTP/2 connection. Clients have to use HTTP/2 without TLS (h2c) from the start.".to_string());
+    }
+    let mut settings: Vec<u8> = Vec::new();
+    for (id, value) in [(SETTINGS_MAX_CONCURRENT_STREAMS, MAX_CONCURRENT_STREAMS), (SETTINGS_MAX_HEADER_LIST_SIZE, MAX_HEADER_LIST_SIZE)] {
+        settings.extend_from_slice(&id.to_be_bytes());
+        settings.extend_from_slice(&(value as u32).to_be_bytes());
+    }
+    connection.write_frame(SETTINGS, 0, 0, &settings)?;
+    loop {
+        let Some(frame) = connection.next_frame()?.filter(|frame: &Frame| frame.kind != GOAWAY) else {
+            return Ok(());
+        };
+        if let Some(request) = connection.handle_frame(frame)? {
+            let stream_id: u32 = request.0;
+            connection.send_response(stream_id, handle(request.1))?;
+        }
+    }
+}
+
+impl Connection {
+    fn next_frame(&mut self) -> Result<Option<Frame>, String> {
+        return match self.pending.pop_front() {
+            Some(frame) => Ok(Some(frame)),
+            None => self.read_frame(),
+        };
+    }
+
+    fn read_frame(&mut self) -> Result<Option<Frame>, String> {
+        let mut header: [u8; 9] = [0; 9];
+        if self.stream.read_exact(&mut header).is_err() {
+            return Ok(None);
+        }
+        let len: usize = (header[0] as usize) << 16 | (header[1] as usize) << 8 | header[2] as usize;
+        if len > MAX_REQUEST_SIZE {
+            return Err(format!("Frame of {} bytes is too large", len));
+        }
+        let mut payload: Vec<u8> = vec![0; len];
+        self.stream.read_exact(&mut payload).map_err(|err| err.to_string())?;
+        let stream_id: u32 = u32::from_be_bytes([header[5], header[6], header[7], header[8]]) & 0x7fff_ffff;
+        return Ok(Some(Frame {kind: header[3], flags: header[4], stream_id, payload}));
+    }
+
+    fn write_frame(&mut self, kind: u8, flags: u8, stream_id: u32, payload: &[u8]) -> Result<(), String> {
+        let len: usize = payload.len();
+        let mut bytes: Vec<u8> = vec![(len >> 16) as u8, (len >> 8) as u8, len as u8, kind, flags];
+        bytes.extend_from_slice(&stream_id.to_be_bytes());
+        bytes.extend_from_slice(payload);
+        return self.stream.write_all(&bytes).map_err(|err| err.to_string());
+    }
+
+    /// Handles a frame, giving back a stream's request once all of it has come in.
+    fn handle_frame(&mut self, frame: Frame) -> Result<Option<(u32, Http2Request)>, String> {
+        match frame.kind {
+            SETTINGS if frame.flags & ACK == 0 => {
+                self.apply_settings(&frame.payload);
+                self.write_frame(SETTINGS, ACK, 0, &[])?;
+            },
+            PING if frame.flags & ACK == 0 => self.write_frame(PING, ACK, 0, &frame.payload)?,
+            WINDOW_UPDATE => self.apply_window_update(&frame),
+            HEADERS => {
+                let mut flags: u8 = frame.flags;
+                let mut block: Vec<u8> = strip_padding(&frame)?;
+                if flags & PRIORITY != 0 {
+                    block = block.get(5..).ok_or("HEADERS frame is too short".to_string())?.to_vec();
+                }
+                while flags & END_HEADERS == 0 {
+                    let next: Frame = self.next_frame()?.ok_or("Connection closed in the middle of headers".to_string())?;
+                    if next.kind != CONTINUATION || next.stream_id != frame.stream_id {
+                        return Err("Expected the rest of the headers in a CONTINUATION frame".to_string());
+                    }
+                    if block.len() + next.payload.len() > MAX_HEADER_LIST_SIZE {
+                        return Err("Request headers are too large".to_string());
+                    }
+                    block.extend_from_slice(&next.payload);
+                    flags |= next.flags & END_HEADERS;
+                }
+                // Decoded even for a stream that's refused, as it can change the HPACK table.
+                let headers: Vec<(String, String)> = self.decoder.decode(&block)?;
+                if headers.iter().map(|(name, value): &(String, String)| get_entry_size(name, value)).sum::<usize>() > MAX_HEADER_LIST_SIZE {
+                    return Err("Request headers are too large".to_string());
+                }
+                if !self.streams.contains_key(&frame.stream_id) && self.streams.len() >= MAX_CONCURRENT_STREAMS {
+                    self.write_frame(RST_STREAM, 0, frame.stream_id, &REFUSED_STREAM.to_be_bytes())?;
+                    return Ok(None);
+                }
+                // Headers after the body are trailers, which requests don't need.
+                let stream: &mut Stream = self.streams.entry(frame.stream_id).or_default();
+                if stream.headers.is_empty() {
+                    stream.headers = headers;
+                }
+                if frame.flags & END_STREAM != 0 {
+                    return Ok(self.finish_stream(frame.stream_id));
+                }
+            },
+            DATA => {
+                let data: Vec<u8> = strip_padding(&frame)?;
+                // Gives back what the data took from the client's window, so it can keep sending.
+                let increment: [u8; 4] = (frame.payload.len() as u32).to_be_bytes();
+                if !frame.payload.is_empty() {
+                    self.write_frame(WINDOW_UPDATE, 0, 0, &increment)?;
+                }
+                // Data for a stream that was refused or reset is dropped.
+                let Some(stream) = self.streams.get_mut(&frame.stream_id) else {
+                    return Ok(None);
+                };
+                if stream.body.len() + data.len() > MAX_REQUEST_SIZE {
+                    return Err("Request is too large".to_string());
+                }
+                stream.body.extend_from_slice(&data);
+                if !frame.payload.is_empty() && frame.flags & END_STREAM == 0 {
+                    self.write_frame(WINDOW_UPDATE, 0, frame.stream_id, &increment)?;
+                }
+                if frame.flags & END_STREAM != 0 {
+                    return Ok(self.finish_stream(frame.stream_id));
+                }
+            },
+            RST_STREAM => {
+                self.streams.remove(&frame.stream_id);
+            },
+            _ => (),
+        }
+        return Ok(None);
+    }
+
+    fn finish_stream(&mut self, stream_id: u32) -> Option<(u32, Http2Request)> {
+        let stream: Stream = self.streams.remove(&stream_id)?;
+        let path: String = stream.headers.iter()
+            .find(|(name, _): &&(String, String)| name == ":path")
+            .map(|(_, value): &(String, String)| value.clone())
+            .unwrap_or_default();
+        return Some((stream_id, Http2Request {path, headers: stream.headers, body: stream.body}));
+    }
+
+    fn apply_settings(&mut self, payload: &[u8]) {
+        for setting in payload.chunks_exact(6) {
+            let id: u16 = u16::from_be_bytes([setting[0], setting[1]]);
+            let value: u32 = u32::from_be_bytes([setting[2], setting[3], setting[4], setting[5]]);
+            match id {
+                SETTINGS_INITIAL_WINDOW_SIZE => {
+                    let change: i64 = value as i64 - self.initial_window_size;
+                    for window in self.stream_send_windows.values_mut() {
+                        *window += change;
+                    }
+                    self.initial_window_size = value as i64;
+                },
+                SETTINGS_MAX_FRAME_SIZE => self.max_frame_size = value as usize,
+                _ => (),
+            }
+        }
+    }
+
+    fn apply_window_update(&mut self, frame: &Frame) {
+        let Some(bytes) = frame.payload.get(0..4) else {
+            return;
+        };
+        let increment: i64 = (u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) & 0x7fff_ffff) as i64;
+        match frame.stream_id {
+            0 => self.send_window += increment,
+            // Only streams that are open or being answered have a window to keep.
+            stream_id if self.streams.contains_key(&stream_id) || self.stream_send_windows.contains_key(&stream_id) => {
+                *self.stream_send_windows.entry(stream_id).or_insert(self.initial_window_size) += increment;
+            },
+            _ => (),
+        }
+    }
+
+    fn send_response(&mut self, stream_id: u32, response: Http2Response) -> Result<(), String> {
+        self.send_headers(stream_id, &response.headers, false)?;
+        let mut sent: usize = 0;
+        while sent < response.body.len() {
+            let window: i64 = self.send_window.min(*self.stream_send_windows.entry(stream_id).or_insert(self.initial_window_size));
+            if window <= 0 {
+                self.wait_for_window()?;
+                continue;
+            }
+            let len: usize = (response.body.len() - sent).min(window as usize).min(self.max_frame_size);
+            self.write_frame(DATA, 0, stream_id, &response.body[sent..sent + len])?;
+            self.send_window -= len as i64;
+            *self.stream_send_windows.entry(stream_id).or_insert(self.initial_window_size) -= len as i64;
+            sent += len;
+        }
+        self.send_headers(stream_id, &response.trailers, true)?;
+        self.stream_send_windows.remove(&stream_id);
+        return Ok(());
+    }
+
+    fn send_headers(&mut self, stream_id: u32, headers: &Vec<(String, String)>, end_stream: bool) -> Result<(), String> {
+        let block: Vec<u8> = encode_headers(headers);
+        let end_stream_flag: u8 = if end_stream {END_STREAM} else {0};
+        let mut chunks = block.chunks(self.max_frame_size).peekable();
+        let first: &[u8] = chunks.next().unwrap_or(&[]);
+        let first_flags: u8 = end_stream_flag | if chunks.peek().is_none() {END_HEADERS} else {0};
+        self.write_frame(HEADERS, first_flags, stream_id, first)?;
+        while let Some(chunk) = chunks.next() {
+            let flags: u8 = if chunks.peek().is_none() {END_HEADERS} else {0};
+            self.write_frame(CONTINUATION, flags, stream_id, chunk)?;
+        }
+        return Ok(());
+    }
+
+    /// Reads frames until the client makes room to send more, keeping requests that come in
+    /// meanwhile for later.
+    fn wait_for_window(&mut self) -> Result<(), String> {
+        let frame: Frame = self.read_frame()?.ok_or("Connection closed while sending a response".to_string())?;
+        match frame.kind {
+            WINDOW_UPDATE | SETTINGS | PING => {
+                self.handle_frame(frame)?;
+            },
+            GOAWAY => return Err("The client went away".to_string()),
+            _ if self.pending.len() >= MAX_PENDING_FRAMES => return Err("Too many frames came in while sending a response".to_string()),
+            _ => self.pending.push_back(frame),
+        }
+        return Ok(());
+    }
+}
+
+/// The payload of a DATA or HEADERS frame without its padding.
+fn strip_padding(frame: &Frame) -> Result<Vec<u8>, String> {
+    if frame.flags & PADDED == 0 {
+        return Ok(frame.payload.clone());
+    }
+    let pad_len: usize = *frame.payload.first().ok_or("Padded frame is empty".to_string())? as usize;
+    return frame.payload.get(1..frame.payload.len().saturating_sub(pad_len))
+        .filter(|_| pad_len < frame.payload.len())
+        .map(|x: &[u8]| x.to_vec())
+        .ok_or("Frame has more padding than payload".to_string());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Shutdown, TcpListener};
+    use std::thread;
+    use super::*;
+
+    /// A frame the server sent, as its kind, stream and payload.
+    type SentFrame = (u8, u32, Vec<u8>);
+
+    fn frame(kind: u8, flags: u8, stream_id: u32, payload: &[u8]) -> Vec<u8> {
+        let len: usize = payload.len();
+        let mut bytes: Vec<u8> = vec![(len >> 16) as u8, (len >> 8) as u8, len as u8, kind, flags];
+        bytes.extend_from_slice(&stream_id.to_be_bytes());
+        bytes.extend_from_slice(payload);
+        return bytes;
+    }
+
+    /// Serves a connection on which the client sent `frames` after the preface, giving how
+    /// serving ended and the frames the server sent back.
+    fn serve(frames: Vec<Vec<u8>>) -> (Result<(), String>, Vec<SentFrame>) {
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client: TcpStream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        let mut reader: TcpStream = client.try_clone().unwrap();
+        let writer = thread::spawn(move || {
+            let _ = client.write_all(&[PREFACE.to_vec(), frames.concat()].concat());
+            let _ = client.shutdown(Shutdown::Write);
+        });
+        let received = thread::spawn(move || {
+            let mut bytes: Vec<u8> = Vec::new();
+            let _ = reader.read_to_end(&mut bytes);
+            return bytes;
+        });
+        let result: Result<(), String> = serve_http2_connection(server, |request: Http2Request| Http2Response {
+            headers: vec![(":status".to_string(), "200".to_string())],
+            body: request.path.into_bytes(),
+            trailers: Vec::new(),
+        });
+        writer.join().unwrap();
+        let bytes: Vec<u8> = received.join().unwrap();
+
+        let mut sent: Vec<SentFrame> = Vec::new();
+        let mut pos: usize = 0;
+        while pos + 9 <= bytes.len() {
+            let len: usize = (bytes[pos] as usize) << 16 | (bytes[pos + 1] as usize) << 8 | bytes[pos + 2] as usize;
+            let stream_id: u32 = u32::from_be_bytes(bytes[pos + 5..pos + 9].try_into().unwrap());
+            sent.push((bytes[pos + 3], stream_id, bytes[pos + 9..pos + 9 + len].to_vec()));
+            pos += 9 + len;
+        }
+        return (result, sent);
+    }
+
+    /// `:method GET` and `:path /` from the static table.
+    const GET_ROOT: [u8; 2] = [0x82, 0x84];
+
+    #[test]
+    fn advertises_its_limits_and_answers_requests() {
+        let (result, sent) = serve(vec![frame(HEADERS, END_HEADERS | END_STREAM, 1, &GET_ROOT)]);
+        assert!(result.is_ok());
+        assert_eq!(sent[0], (SETTINGS, 0, vec![0, 3, 0, 0, 0, 100, 0, 6, 0, 0, 0x40, 0]));
+        assert!(sent.contains(&(DATA, 1, b"/".to_vec())));
+    }
+
+    #[test]
+    fn refuses_streams_past_the_limit() {
+        let mut frames: Vec<Vec<u8>> = (0..=MAX_CONCURRENT_STREAMS as u32)
+            .map(|i: u32| frame(HEADERS, END_HEADERS, 2 * i + 1, &GET_ROOT))
+            .collect();
+        let refused: u32 = 2 * MAX_CONCURRENT_STREAMS as u32 + 1;
+        frames.push(frame(DATA, END_STREAM, refused, b"body"));
+        frames.push(frame(DATA, END_STREAM, 1, b""));
+        let (result, sent) = serve(frames);
+        assert!(result.is_ok());
+        assert_eq!(sent.iter().filter(|(kind, _, _)| *kind == RST_STREAM).collect::<Vec<&SentFrame>>(), vec![&(RST_STREAM, refused, vec![0, 0, 0, 7])]);
+        assert!(!sent.iter().any(|(kind, stream_id, _)| *kind == HEADERS && *stream_id == refused));
+        assert!(sent.contains(&(DATA, 1, b"/".to_vec())));
+    }
+
+    #[test]
+    fn refuses_headers_that_are_too_large() {
+        let mut frames: Vec<Vec<u8>> = vec![frame(HEADERS, 0, 1, &GET_ROOT)];
+        frames.extend((0..MAX_HEADER_LIST_SIZE / 1024).map(|_| frame(CONTINUATION, 0, 1, &[0x82; 1024])));
+        assert_eq!(serve(frames).0, Err("Request headers are too large".to_string()));
+
+        // Small when encoded, but each indexed header counts in full once decoded.
+        let block: Vec<u8> = vec![0x82; MAX_HEADER_LIST_SIZE / 40];
+        assert_eq!(serve(vec![frame(HEADERS, END_HEADERS | END_STREAM, 1, &block)]).0, Err("Request headers are too large".to_string()));
+    }
+}
@@ -0,0 +1,268 @@
+use std::env::var;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+use crate::utils::config::{Config, get_config_path};
+use crate::utils::file_io::{FromString, read_file};
+
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub enum Language {
+    English,
+    German,
+    French,
+}
+
+impl Language {
+    /// Reads a language code like "de", or a locale like "fr_FR.UTF-8".
+    pub fn from_string(name: &str) -> Option<Self> {
+        return match name.trim().to_lowercase().get(..2) {
+            Some("en") => Some(Self::English),
+            Some("de") => Some(Self::German),
+            Some("fr") => Some(Self::French),
+            _ => None,
+        };
+    }
+}
+
+/// Messages by key, in English, German and French. "{}" marks where arguments go, in order.
+const MESSAGES: &[(&str, &str, &str, &str)] = &[
+    ("version",
+        "Current punch-card version: {}",
+        "Aktuelle punch-card-Version: {}",
+        "Version actuelle de punch-card : {}"),
+    ("invalid_command",
+        "'{}' is not a valid subcommand for punch. Try one of the following:",
+        "'{}' ist kein gültiger Befehl für punch. Versuche einen der folgenden:",
+        "'{}' n'est pas une sous-commande valide de punch. Essayez l'une des suivantes :"),
+    ("did_you_mean_command",
+        "'{}' is not a valid subcommand for punch. Did you mean 'punch {}'?",
+        "'{}' ist kein gültiger Befehl für punch. Meintest du 'punch {}'?",
+        "'{}' n'est pas une sous-commande valide de punch. Vouliez-vous dire 'punch {}' ?"),
+    ("did_you_mean_task",
+        "'{}' is a new task. Did you mean '{}'? `punch update-task \"{}\"` fixes it.",
+        "'{}' ist eine neue Aufgabe. Meintest du '{}'? `punch update-task \"{}\"` behebt das.",
+        "'{}' est une nouvelle tâche. Vouliez-vous dire '{}' ? `punch update-task \"{}\"` le corrige."),
+    ("warning",
+        "Warning: {}",
+        "Warnung: {}",
+        "Attention : {}"),
+    ("already_clocked_in",
+        "You've already clocked in for the day!",
+        "Du hast dich heute schon eingestempelt!",
+        "Vous avez déjà pointé pour aujourd'hui !"),
+    ("clocking_in",
+        "Clocking in for the day at '{}'",
+        "Einstempeln für heute um '{}'",
+        "Début de la journée à '{}'"),
+    ("working_from",
+        "Working from: {}",
+        "Arbeitsort: {}",
+        "Lieu de travail : {}"),
+    ("using_template",
+        "Planning the day from the template, with {} blocks. See them with `punch today`.",
+        "Der Tag wird nach der Vorlage geplant, mit {} Blöcken. Zeig sie mit `punch today` an.",
+        "Journée planifiée d'après le modèle, avec {} blocs. Voir avec `punch today`."),
+    ("late_for_expected_start",
+        "That's {} after your expected start of {}.",
+        "Das ist {} nach deinem geplanten Beginn um {}.",
+        "C'est {} après votre début prévu à {}."),
+    ("on_time_for_expected_start",
+        "You're on time for your expected start of {}.",
+        "Du bist pünktlich für deinen geplanten Beginn um {}.",
+        "Vous êtes à l'heure pour votre début prévu à {}."),
+    ("default_time_to_do",
+        "Using the default time to do for the day: {} minutes",
+        "Standard-Arbeitszeit für heute: {} Minuten",
+        "Temps de travail par défaut pour la journée : {} minutes"),
+    ("holiday_time_to_do",
+        "Today is a holiday ({}), so the time to do is {} minutes",
+        "Heute ist ein Feiertag ({}), also beträgt die Arbeitszeit {} Minuten",
+        "Aujourd'hui est férié ({}), le temps de travail est donc de {} minutes"),
+    ("day_off_time_to_do",
+        "{} isn't one of your work days, so the time to do is 0 minutes",
+        "{} ist keiner deiner Arbeitstage, also beträgt die Arbeitszeit 0 Minuten",
+        "{} n'est pas l'un de vos jours travaillés, le temps de travail est donc de 0 minute"),
+    ("default_punch_in_task",
+        "No start task for the day provided. Using the default value: '{}'",
+        "Keine Start-Aufgabe angegeben. Standardwert: '{}'",
+        "Aucune tâche de départ indiquée. Valeur par défaut : '{}'"),
+    ("remember_edit",
+        "Remember: You can use `punch edit` to change anything about the day.",
+        "Tipp: Mit `punch edit` kannst du alles am Tag ändern.",
+        "Rappel : `punch edit` permet de modifier n'importe quoi dans la journée."),
+    ("punching_out",
+        "Punching out for the day at '{}'",
+        "Ausstempeln für heute um '{}'",
+        "Fin de la journée à '{}'"),
+    ("already_punched_out",
+        "Can't punch out: Already punched out for the day!",
+        "Ausstempeln nicht möglich: Du hast dich heute schon ausgestempelt!",
+        "Impossible de terminer : la journée est déjà terminée !"),
+    ("taking_break",
+        "Taking a break at '{}'",
+        "Pause ab '{}'",
+        "Pause à '{}'"),
+    ("back_to_work",
+        "Back to work at '{}'",
+        "Zurück an die Arbeit um '{}'",
+        "Retour au travail à '{}'"),
+    ("over_break_budget",
+        "Warning: You've gone over your break budget for today by {} m!",
+        "Warnung: Du hast dein Pausenbudget für heute um {} min überschritten!",
+        "Attention : vous avez dépassé votre budget de pause du jour de {} min !"),
+    ("resume_args",
+        "'punch resume' should have at most one argument!",
+        "'punch resume' nimmt höchstens ein Argument!",
+        "'punch resume' prend au plus un argument !"),
+    ("billable_and_non_billable",
+        "A block can't be both '--billable' and '--non-billable'!",
+        "Ein Block kann nicht zugleich '--billable' und '--non-billable' sein!",
+        "Un bloc ne peut pas être à la fois '--billable' et '--non-billable' !"),
+    ("now_working_on",
+        "Now working on '{}' from '{}'",
+        "Jetzt an '{}' ab '{}'",
+        "Travail sur '{}' depuis '{}'"),
+    ("task_needs_name",
+        "'punch task' needs a new task name!",
+        "'punch task' braucht einen neuen Aufgabennamen!",
+        "'punch task' a besoin d'un nouveau nom de tâche !"),
+    ("task_args",
+        "'punch task' should have at most one argument!",
+        "'punch task' nimmt höchstens ein Argument!",
+        "'punch task' prend au plus un argument !"),
+    ("day_so_far",
+        "Here's the day so far: \n",
+        "Der bisherige Tag: \n",
+        "Voici la journée jusqu'ici : \n"),
+    ("add_summary_args",
+        "'punch add-summary' takes exactly 4 arguments: category, project, task and summary.",
+        "'punch add-summary' nimmt genau 4 Argumente: Kategorie, Projekt, Aufgabe und Zusammenfassung.",
+        "'punch add-summary' prend exactement 4 arguments : catégorie, projet, tâche et résumé."),
+    ("time_done_today",
+        "Time done today: {} m {} s",
+        "Heute gearbeitet: {} min {} s",
+        "Temps travaillé aujourd'hui : {} min {} s"),
+    ("break_time_today",
+        "Total time spent on break: {} m {} s",
+        "Pausen insgesamt: {} min {} s",
+        "Temps total de pause : {} min {} s"),
+    ("time_left_today",
+        "Time left today: {} m {} s",
+        "Heute noch übrig: {} min {} s",
+        "Temps restant aujourd'hui : {} min {} s"),
+    ("blocks_with_breaks",
+        "Total task blocks (including breaks): {}",
+        "Blöcke insgesamt (mit Pausen): {}",
+        "Nombre total de blocs (pauses comprises) : {}"),
+    ("blocks_without_breaks",
+        "Total task blocks (excluding breaks): {}",
+        "Blöcke insgesamt (ohne Pausen): {}",
+        "Nombre total de blocs (hors pauses) : {}"),
+    ("latest_task",
+        "Latest task: '{}'",
+        "Letzte Aufgabe: '{}'",
+        "Dernière tâche : '{}'"),
+    ("task_times_header",
+        "Task times, blocks:",
+        "Zeit je Aufgabe, Blöcke:",
+        "Temps par tâche, blocs :"),
+    ("task_time_line",
+        "\t{}: {} m {} s, {} blocks",
+        "\t{}: {} min {} s, {} Blöcke",
+        "\t{} : {} min {} s, {} blocs"),
+    ("minutes_behind_overall",
+        "Minutes behind overall: {}",
+        "Minuten im Rückstand insgesamt: {}",
+        "Minutes de retard au total : {}"),
+    ("minutes_behind_since",
+        "Minutes behind since last fall behind: {}",
+        "Minuten im Rückstand seit dem letzten Zurückfallen: {}",
+        "Minutes de retard depuis le dernier retard : {}"),
+    ("plan_vs_actual_header",
+        "Planned / actual per task:",
+        "Geplant / tatsächlich je Aufgabe:",
+        "Prévu / réel par tâche :"),
+    ("week_plan_header",
+        "This week's plan, done / planned per project:",
+        "Wochenplan, erledigt / geplant je Projekt:",
+        "Plan de la semaine, fait / prévu par projet :"),
+    ("plan_accuracy",
+        "Time that went as planned: {}%",
+        "Zeit wie geplant verbracht: {} %",
+        "Temps passé comme prévu : {} %"),
+    ("current_config",
+        "Here's the current config: \n",
+        "Die aktuelle Konfiguration: \n",
+        "Voici la configuration actuelle : \n"),
+    ("note_needs_msg",
+        "'punch note' requires a msg argument!",
+        "'punch note' braucht eine Nachricht!",
+        "'punch note' a besoin d'un message !"),
+    ("note_args",
+        "'punch note' takes a single argument. Consider wrapping your message in quotes.",
+        "'punch note' nimmt ein einzelnes Argument. Setze die Nachricht am besten in Anführungszeichen.",
+        "'punch note' prend un seul argument. Mettez votre message entre guillemets."),
+    ("note_added",
+        "New note '{}' added to today at '{}'.",
+        "Neue Notiz '{}' für heute um '{}' hinzugefügt.",
+        "Nouvelle note '{}' ajoutée à la journée à '{}'."),
+    ("note_reminder_set",
+        "You'll be reminded at {}, as long as `punch daemon` is running.",
+        "Du wirst um {} erinnert, solange `punch daemon` läuft.",
+        "Un rappel sera envoyé à {}, tant que `punch daemon` tourne."),
+    ("task_updated",
+        "Updated the current task to '{}'",
+        "Aktuelle Aufgabe in '{}' umbenannt",
+        "Tâche en cours renommée en '{}'"),
+    ("update_task_needs_name",
+        "'punch update-task' needs a new task name!",
+        "'punch update-task' braucht einen neuen Aufgabennamen!",
+        "'punch update-task' a besoin d'un nouveau nom de tâche !"),
+    ("update_task_args",
+        "'punch update-task' should have at most one argument!",
+        "'punch update-task' nimmt höchstens ein Argument!",
+        "'punch update-task' prend au plus un argument !"),
+];
+
+static LANGUAGE: OnceLock<Language> = OnceLock::new();
+
+/// The language to talk in: the config's `language`, or else the one in `LC_ALL`,
+/// `LC_MESSAGES` or `LANG`, or else English.
+pub fn get_language() -> Language {
+    return *LANGUAGE.get_or_init(|| {
+        let configured: Option<String> = read_file(&get_config_path()).ok()
+            .and_then(|yaml_str: String| Config::try_from_string(&yaml_str).ok())
+            .and_then(|config: Config| config.language().map(|language: &str| language.to_string()));
+        return configured.into_iter()
+            .chain(["LC_ALL", "LC_MESSAGES", "LANG"].iter().filter_map(|name: &&str| var(name).ok()))
+            .find_map(|name: String| Language::from_string(&name))
+            .unwrap_or(Language::English);
+    });
+}
+
+/// The message for `key` in the current language, with `args` filled in. Unknown keys
+/// come back as they are, so a missing message shows up without breaking anything.
+pub fn tr(key: &str, args: &[&dyn Display]) -> String {
+    return tr_in(get_language(), key, args);
+}
+
+pub fn tr_in(language: Language, key: &str, args: &[&dyn Display]) -> String {
+    let Some((_, english, german, french)) = MESSAGES.iter().find(|(message_key, _, _, _)| *message_key == key) else {
+        return key.to_string();
+    };
+    let template: &str = match language {
+        Language::English => english,
+        Language::German => german,
+        Language::French => french,
+    };
+    let mut message: String = String::new();
+    let mut rest: &str = template;
+    let mut args_iter = args.iter();
+    while let Some(ind) = rest.find("{}") {
+        message += &rest[..ind];
+        message += &args_iter.next().map(|arg: &&dyn Display| arg.to_string()).unwrap_or_default();
+        rest = &rest[ind + 2..];
+    }
+    message += rest;
+    return message;
+}
@@ -0,0 +1,293 @@
+use chrono::prelude::{DateTime, Datelike, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use chrono::Duration;
+
+/// A calendar event on a particular day, with recurring events already expanded.
+#[derive(Debug,Clone,PartialEq)]
+pub struct CalendarEvent {
+    pub summary: String,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+}
+
+/// An event of a calendar punch publishes, rather than reads.
+#[derive(Debug,Clone,PartialEq)]
+pub struct FeedEvent {
+    /// Stays the same when the event changes, so calendar apps update it rather than add another.
+    pub uid: String,
+    pub summary: String,
+    pub description: String,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+}
+
+/// The parts of a VEVENT needed to place it on a day.
+#[derive(Debug,Clone,Default)]
+struct IcsEvent {
+    uid: String,
+    summary: String,
+    start: Option<NaiveDateTime>,
+    end: Option<NaiveDateTime>,
+    all_day_start: Option<NaiveDate>,
+    all_day_end: Option<NaiveDate>,
+    rrule: Option<String>,
+    exdates: Vec<NaiveDate>,
+    recurrence_id: Option<NaiveDate>,
+    cancelled: bool,
+}
+
+/// The timed events of an iCalendar file that happen on `date`, sorted by start.
+/// All-day events are left out. Times with a TZID are taken to be in the local time zone,
+/// and recurring events support daily, weekly and monthly rules.
+pub fn get_events_on(ics: &str, date: &NaiveDate) -> Vec<CalendarEvent> {
+    let events: Vec<IcsEvent> = parse_events(ics);
+    let mut found: Vec<CalendarEvent> = Vec::new();
+    for event in &events {
+        let (Some(start), Some(end)) = (event.start, event.end) else {
+            continue;
+        };
+        if event.cancelled {
+            continue;
+        }
+        // An edited occurrence of a recurring event replaces the one the rule would make.
+        let is_overridden: bool = events.iter()
+            .any(|other: &IcsEvent| other.uid == event.uid && other.recurrence_id == Some(*date));
+        let happens: bool = match &event.rrule {
+            Some(rrule) if event.recurrence_id.is_none() => !is_overridden && !event.exdates.contains(date)
+                && recurs_on(rrule, &start.date(), date),
+            _ => start.date() == *date,
+        };
+        if happens {
+            let occurrence_start: NaiveDateTime = date.and_time(start.time());
+            found.push(CalendarEvent {
+                summary: event.summary.clone(),
+                start: to_local(&occurrence_start),
+                end: to_local(&(occurrence_start + (end - start))),
+            });
+        }
+    }
+    found.sort_by_key(|event: &CalendarEvent| event.start);
+    return found;
+}
+
+/// The names of the all-day events of an iCalendar file on `date`, like the holidays in a
+/// holiday calendar. Events lasting several days count on each of them.
+pub fn get_all_day_events_on(ics: &str, date: &NaiveDate) -> Vec<String> {
+    return parse_events(ics).into_iter()
+        .filter(|event: &IcsEvent| !event.cancelled)
+        .filter(|event: &IcsEvent| {
+            let Some(start) = event.all_day_start else {
+                return false;
+            };
+            let num_days: i64 = event.all_day_end.map(|end: NaiveDate| (end - start).num_days()).unwrap_or(1).max(1);
+            return match &event.rrule {
+                Some(rrule) => (0..num_days).any(|offset: i64| recurs_on(rrule, &start, &(*date - Duration::days(offset)))),
+                None => *date >= start && *date < start + Duration::days(num_days),
+            };
+        })
+        .map(|event: IcsEvent| event.summary)
+        .collect();
+}
+
+/// An iCalendar file named `name` with `events` in it, for calendar apps to subscribe to.
+/// Times are written in UTC, so they show up right whatever the app's time zone.
+pub fn build_ics(name: &str, events: &Vec<FeedEvent>, now: &DateTime<Local>) -> String {
+    let mut lines: Vec<String> = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//punch-card//punch//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", escape_text(name)),
+        "REFRESH-INTERVAL;VALUE=DURATION:PT15M".to_string(),
+        "X-PUBLISHED-TTL:PT15M".to_string(),
+    ];
+    for event in events {
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", event.uid));
+        lines.push(format!("DTSTAMP:{}", format_utc(now)));
+        lines.push(format!("DTSTART:{}", format_utc(&event.start)));
+        lines.push(format!("DTEND:{}", format_utc(&event.end)));
+        lines.push(format!("SUMMARY:{}", escape_text(&event.summary)));
+        if !event.description.is_empty() {
+            lines.push(format!("DESCRIPTION:{}", escape_text(&event.description)));
+        }
+        lines.push("TRANSP:TRANSPARENT".to_string());
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+    return lines.iter().map(|line: &String| fold_line(line) + "\r\n").collect();
+}
+
+fn format_utc(time: &DateTime<Local>) -> String {
+    return time.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ").to_string();
+}
+
+fn escape_text(value: &str) -> String {
+    return value.replace('\\', "\\\\").replace(';', "\\;").replace(',', "\\,").replace('\n', "\\n");
+}
+
+/// Breaks lines longer than 75 bytes onto lines starting with a space, without splitting a character.
+fn fold_line(line: &str) -> String {
+    let mut folded: String = String::new();
+    let mut line_len: usize = 0;
+    for c in line.chars() {
+        if line_len + c.len_utf8() > 75 {
+            folded += "\r\n ";
+            line_len = 1;
+        }
+        folded.push(c);
+        line_len += c.len_utf8();
+    }
+    return folded;
+}
+
+fn parse_events(ics: &str) -> Vec<IcsEvent> {
+    let mut events: Vec<IcsEvent> = Vec::new();
+    let mut current: Option<IcsEvent> = None;
+    for line in unfold_lines(ics) {
+        let Some((name_and_params, value)) = line.split_once(':') else {
+            continue;
+        };
+        let mut parts = name_and_params.split(';');
+        let name: String = parts.next().unwrap_or_default().to_uppercase();
+        let params: Vec<&str> = parts.collect();
+        if name == "BEGIN" && value == "VEVENT" {
+            current = Some(IcsEvent::default());
+            continue;
+        }
+        if name == "END" && value == "VEVENT" {
+            events.extend(current.take());
+            continue;
+        }
+        let Some(event) = current.as_mut() else {
+            continue;
+        };
+        match name.as_str() {
+            "UID" => event.uid = value.to_string(),
+            "SUMMARY" => event.summary = unescape_text(value),
+            "DTSTART" => {
+                event.start = parse_date_time(value, &params);
+                event.all_day_start = parse_all_day_date(value, &params);
+            },
+            "DTEND" => {
+                event.end = parse_date_time(value, &params);
+                event.all_day_end = parse_all_day_date(value, &params);
+            },
+            "RRULE" => event.rrule = Some(value.to_string()),
+            "EXDATE" => event.exdates.extend(value.split(',').filter_map(|x: &str| parse_date_only(x))),
+            "RECURRENCE-ID" => event.recurrence_id = parse_date_only(value),
+            "STATUS" => event.cancelled = value.eq_ignore_ascii_case("CANCELLED"),
+            _ => {},
+        }
+    }
+    return events;
+}
+
+/// Joins lines that were folded onto the next line, which starts with a space or tab.
+fn unfold_lines(ics: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw_line in ics.lines() {
+        match raw_line.strip_prefix([' ', '\t']) {
+            Some(rest) if !lines.is_empty() => *lines.last_mut().expect("Checked not empty") += rest,
+            _ => lines.push(raw_line.to_string()),
+        }
+    }
+    return lines;
+}
+
+/// Reads times like "20240131T090000" or "20240131T090000Z", as local time. Dates without
+/// a time (all-day events) give `None`.
+fn parse_date_time(value: &str, params: &Vec<&str>) -> Option<NaiveDateTime> {
+    if params.iter().any(|param: &&str| param.eq_ignore_ascii_case("VALUE=DATE")) {
+        return None;
+    }
+    return match value.strip_suffix('Z') {
+        Some(utc_value) => NaiveDateTime::parse_from_str(utc_value, "%Y%m%dT%H%M%S").ok()
+            .map(|utc: NaiveDateTime| Utc.from_utc_datetime(&utc).with_timezone(&Local).naive_local()),
+        None => NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok(),
+    };
+}
+
+/// Reads dates without a time, like "20240101", as all-day events have.
+fn parse_all_day_date(value: &str, params: &Vec<&str>) -> Option<NaiveDate> {
+    let is_date: bool = params.iter().any(|param: &&str| param.eq_ignore_ascii_case("VALUE=DATE")) || value.len() == 8;
+    return match is_date {
+        true => parse_date_only(value),
+        false => None,
+    };
+}
+
+fn parse_date_only(value: &str) -> Option<NaiveDate> {
+    return NaiveDate::parse_from_str(value.get(..8)?, "%Y%m%d").ok();
+}
+
+fn to_local(naive: &NaiveDateTime) -> DateTime<Local> {
+    return Local.from_local_datetime(naive).earliest().unwrap_or_else(|| Local.from_utc_datetime(naive));
+}
+
+fn unescape_text(value: &str) -> String {
+    return value.replace("\\n", " ").replace("\\N", " ").replace("\\,", ",").replace("\\;", ";").replace("\\\\", "\\");
+}
+
+/// Whether an event first on `first` and repeating by `rrule` happens on `date`. Monthly and
+/// yearly rules on a weekday of the month, like the second Tuesday, aren't supported.
+fn recurs_on(rrule: &str, first: &NaiveDate, date: &NaiveDate) -> bool {
+    if date < first {
+        return false;
+    }
+    let parts: Vec<(&str, &str)> = rrule.split(';').filter_map(|part: &str| part.split_once('=')).collect();
+    let get = |key: &str| -> Option<&str> {
+        return parts.iter().find(|(part_key, _)| part_key.eq_ignore_ascii_case(key)).map(|(_, value)| *value);
+    };
+    let interval: i64 = get("INTERVAL").and_then(|x: &str| x.parse::<i64>().ok()).unwrap_or(1).max(1);
+    let weekdays: Vec<Weekday> = match get("BYDAY") {
+        Some(days) => days.split(',').filter_map(parse_weekday).collect(),
+        None => vec![first.weekday()],
+    };
+    if get("UNTIL").and_then(parse_date_only).is_some_and(|until: NaiveDate| *date > until) {
+        return false;
+    }
+    let matches = |day: &NaiveDate| -> bool {
+        return match get("FREQ").unwrap_or_default().to_uppercase().as_str() {
+            "DAILY" => (*day - *first).num_days() % interval == 0,
+            "WEEKLY" => {
+                let weeks: i64 = (week_start(day) - week_start(first)).num_days() / 7;
+                weeks % interval == 0 && weekdays.contains(&day.weekday())
+            },
+            "MONTHLY" => {
+                let months: i64 = (day.year() as i64 - first.year() as i64) * 12 + day.month() as i64 - first.month() as i64;
+                get("BYDAY").is_none() && months % interval == 0 && day.day() == first.day()
+            },
+            "YEARLY" => {
+                let years: i64 = day.year() as i64 - first.year() as i64;
+                get("BYDAY").is_none() && years % interval == 0 && day.month() == first.month() && day.day() == first.day()
+            },
+            _ => false,
+        };
+    };
+    if !matches(date) {
+        return false;
+    }
+    return match get("COUNT").and_then(|x: &str| x.parse::<usize>().ok()) {
+        Some(count) => first.iter_days().take_while(|day: &NaiveDate| day <= date).filter(|day| matches(day)).count() <= count,
+        None => true,
+    };
+}
+
+fn parse_weekday(day: &str) -> Option<Weekday> {
+    // Monthly rules can give positions like "2TU", which aren't supported.
+    return match day.trim() {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    };
+}
+
+fn week_start(date: &NaiveDate) -> NaiveDate {
+    return *date - Duration::days(date.weekday().num_days_from_monday() as i64);
+}
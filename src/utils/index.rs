@@ -0,0 +1,204 @@
+use std::collections::{BTreeMap, HashSet};
+use chrono::prelude::{DateTime, Local, NaiveDate, Timelike};
+use serde::{Serialize, Deserialize};
+
+use crate::units::day::{Day, DayIterator, list_recorded_dates, refresh_day_files};
+use crate::units::interval::DATE_FMT;
+use crate::utils::aggregate::close_day_at;
+use crate::utils::clock::get_clock;
+use crate::utils::event_log::list_dates_logged_elsewhere;
+use crate::utils::parallel::process_chunks_in_parallel;
+use crate::utils::file_io::{path_exists, read_file, write_file};
+use crate::utils::team::get_data_dir;
+use crate::utils::period::DateRange;
+use crate::utils::work_summary::WorkSummary;
+
+pub const INDEX_FILE: &str = "index";
+
+#[derive(Debug,Serialize,Deserialize,Clone,PartialEq)]
+pub struct TaskTime {
+    pub task: String,
+    pub secs: i64,
+}
+
+#[derive(Debug,Serialize,Deserialize,Clone,PartialEq)]
+pub struct CategoryTime {
+    pub category: String,
+    pub secs: i64,
+}
+
+#[derive(Debug,Serialize,Deserialize,Clone,PartialEq)]
+pub struct ProjectTime {
+    pub project: String,
+    pub secs: i64,
+}
+
+/// The totals of a day that multi-day reports need, so they don't have to reparse it.
+#[derive(Debug,Serialize,Deserialize,Clone,PartialEq)]
+pub struct DaySummary {
+    pub date: String,
+    pub done_secs: i64,
+    /// The part of `done_secs` spent on billable blocks.
+    pub billable_secs: i64,
+    /// The part of `done_secs` spent in focus sessions.
+    pub focus_secs: i64,
+    pub break_secs: i64,
+    /// The part of `break_secs` that's paid, and so counted in `done_secs` too.
+    pub paid_break_secs: i64,
+    pub to_do_secs: i64,
+    /// Punch-in time as seconds from midnight.
+    pub start_secs: i64,
+    /// How late the day started, if an expected start was set. Negative when early.
+    pub lateness_secs: Option<i64>,
+    /// The average of the day's mood check-ins, if there were any.
+    pub mood: Option<f64>,
+    /// Time per task, in the order the tasks were first worked on.
+    pub tasks: Vec<TaskTime>,
+    /// Time per category, for tasks with a summary giving their category. An index written
+    /// before this, `projects` or `billable_secs` was added won't parse, so it's ignored until it's rewritten.
+    pub categories: Vec<CategoryTime>,
+    /// Time per project, found the same way as categories.
+    pub projects: Vec<ProjectTime>,
+    /// Whether the day has been handed in with `punch submit`.
+    pub submitted: bool,
+    /// The day's `punch meta` key-values.
+    pub meta: BTreeMap<String, String>,
+    /// The time planned for the day's blocks, and how much of it went as planned.
+    pub planned_secs: i64,
+    pub plan_matched_secs: i64,
+    /// How many of the day's summaries there are, and how many of them are marked unfinished.
+    pub summary_count: u64,
+    pub unfinished_count: u64,
+    /// How many times the daemon reminded you to take a break, and how many times you did.
+    pub break_reminders: u64,
+    pub break_reminders_followed: u64,
+}
+
+impl DaySummary {
+    /// Days that haven't ended yet are counted up until `now`.
+    pub fn from_day(day: &Day, now: &DateTime<Local>) -> Self {
+        let closed_day: Day = close_day_at(day.clone(), now);
+        let task_times = closed_day.get_task_times_secs_and_num_blocks();
+        return Self {
+            date: day.get_day_start().as_dt().format(DATE_FMT).to_string(),
+            done_secs: closed_day.get_time_done_secs().expect("Day is closed so time done is known"),
+            billable_secs: closed_day.get_billable_secs(),
+            focus_secs: closed_day.get_focus_secs(),
+            break_secs: closed_day.get_total_break_time_secs().expect("Day is closed so break time is known"),
+            paid_break_secs: closed_day.get_paid_break_secs().expect("Day is closed so break time is known"),
+            to_do_secs: (closed_day.get_time_to_do() * 60) as i64,
+            start_secs: day.get_day_start().as_dt().num_seconds_from_midnight() as i64,
+            lateness_secs: day.lateness_secs,
+            mood: day.get_average_mood(),
+            tasks: closed_day.get_tasks_in_chronological_order().into_iter()
+                .map(|task: String| TaskTime {secs: task_times.get(&task).map(|x| x.0).unwrap_or(0), task: task})
+                .collect(),
+            categories: closed_day.get_category_times_secs().into_iter()
+                .map(|(category, secs): (String, i64)| CategoryTime {category: category, secs: secs})
+                .collect(),
+            projects: closed_day.get_project_times_secs().into_iter()
+                .map(|(project, secs): (String, i64)| ProjectTime {project: project, secs: secs})
+                .collect(),
+            submitted: day.submitted.is_some(),
+            meta: day.meta.clone(),
+            planned_secs: day.get_planned_secs(),
+            plan_matched_secs: closed_day.get_plan_matched_secs(),
+            summary_count: day.summaries.len() as u64,
+            unfinished_count: day.summaries.iter().filter(|summary: &&WorkSummary| summary.is_unfinished()).count() as u64,
+            break_reminders: day.break_reminders.len() as u64,
+            break_reminders_followed: day.get_followed_break_reminders(),
+        };
+    }
+
+    /// Whether the day's metadata has every one of `conditions`, as pairs of key and value.
+    pub fn matches_meta(&self, conditions: &Vec<(String, String)>) -> bool {
+        return conditions.iter().all(|(key, value)| self.meta.get(key) == Some(value));
+    }
+}
+
+/// Summaries of every finished day, keyed by date. Days still in progress aren't indexed
+/// because their totals keep changing.
+type DayIndex = BTreeMap<String, DaySummary>;
+
+pub fn get_index_path() -> String {
+    return get_data_dir() + INDEX_FILE;
+}
+
+fn read_index() -> DayIndex {
+    return match read_file(&get_index_path()) {
+        Ok(yaml_str) => serde_yaml::from_str(&yaml_str).unwrap_or_default(),
+        Err(_) => DayIndex::new(),
+    };
+}
+
+fn write_index(index: &DayIndex) {
+    write_file(&get_index_path(), serde_yaml::to_string(index).expect("Index should be serializable"));
+}
+
+/// Keeps the index in step with a day that has just been written.
+pub fn update_index_for_day(day: &Day) {
+    if !path_exists(&get_data_dir()) {
+        return;
+    }
+    let mut index: DayIndex = read_index();
+    let date: String = day.get_day_start().as_dt().format(DATE_FMT).to_string();
+    if day.has_ended() {
+        index.insert(date, DaySummary::from_day(day, &get_clock().now()));
+    }
+    else if index.remove(&date).is_none() {
+        return;
+    }
+    write_index(&index);
+}
+
+/// Drops a deleted day from the index.
+pub fn remove_from_index(date: &NaiveDate) {
+    let mut index: DayIndex = read_index();
+    if index.remove(&date.format(DATE_FMT).to_string()).is_some() {
+        write_index(&index);
+    }
+}
+
+/// Rebuilds the index from every day file. Returns the number of days indexed.
+pub fn rebuild_index() -> usize {
+    let now: DateTime<Local> = get_clock().now();
+    let dates: Vec<NaiveDate> = list_recorded_dates();
+    // The threads only read, as writes to day files and the index can't interleave.
+    let summaries: Vec<DaySummary> = process_chunks_in_parallel(
+        dates.clone(),
+        |dates: Vec<NaiveDate>| DayIterator::from_dates(dates).without_refresh()
+            .filter(|day: &Day| day.has_ended())
+            .map(|day: Day| DaySummary::from_day(&day, &now))
+            .collect(),
+    );
+    refresh_day_files(&dates);
+    let index: DayIndex = summaries.into_iter()
+        .map(|summary: DaySummary| (summary.date.clone(), summary))
+        .collect();
+    write_index(&index);
+    return index.len();
+}
+
+/// Summaries for every recorded day in the range, in date order. Finished days come from
+/// the index where possible and everything else is read as a day. Days with events from
+/// other devices are always read, since a sync may have brought in events the index
+/// hasn't seen.
+pub fn get_summaries_in_range(range: &DateRange, now: &DateTime<Local>) -> Vec<DaySummary> {
+    let index: DayIndex = read_index();
+    let logged_elsewhere: HashSet<NaiveDate> = list_dates_logged_elsewhere();
+    let mut summaries: Vec<DaySummary> = Vec::new();
+    let mut unindexed_dates: Vec<NaiveDate> = Vec::new();
+    for date in list_recorded_dates().into_iter().filter(|date: &NaiveDate| range.contains(date)) {
+        match index.get(&date.format(DATE_FMT).to_string()).filter(|_| !logged_elsewhere.contains(&date)) {
+            Some(summary) => summaries.push(summary.clone()),
+            None => unindexed_dates.push(date),
+        }
+    }
+    summaries.extend(process_chunks_in_parallel(
+        unindexed_dates.clone(),
+        |dates: Vec<NaiveDate>| DayIterator::from_dates(dates).without_refresh().map(|day: Day| DaySummary::from_day(&day, now)).collect(),
+    ));
+    refresh_day_files(&unindexed_dates);
+    summaries.sort_by(|a: &DaySummary, b: &DaySummary| a.date.cmp(&b.date));
+    return summaries;
+}
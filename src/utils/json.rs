@@ -0,0 +1,248 @@
+use serde::Serialize;
+
+/// A minimal JSON value, enough for punch's machine-readable output and IPC protocol.
+#[derive(Debug,Clone,PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(i64),
+    Float(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    pub fn as_string(&self) -> String {
+        return match self {
+            Self::Null => "null".to_string(),
+            Self::Bool(value) => value.to_string(),
+            Self::Number(value) => value.to_string(),
+            Self::Float(value) => value.to_string(),
+            Self::String(value) => escape_json_string(value),
+            Self::Array(values) => format!(
+                "[{}]", values.iter().map(|x: &JsonValue| x.as_string()).collect::<Vec<String>>().join(",")),
+            Self::Object(fields) => format!(
+                "{{{}}}",
+                fields.iter()
+                    .map(|(key, value): &(String, JsonValue)| format!("{}:{}", escape_json_string(key), value.as_string()))
+                    .collect::<Vec<String>>()
+                    .join(",")),
+        };
+    }
+
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        return match self {
+            Self::Object(fields) => fields.iter()
+                .find(|(field_key, _): &&(String, JsonValue)| field_key == key)
+                .map(|(_, value): &(String, JsonValue)| value),
+            _ => None,
+        };
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        return match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        };
+    }
+
+    /// Converts anything serde can serialize, going via serde_yaml's value type.
+    pub fn from_serializable<T: Serialize>(value: &T) -> JsonValue {
+        let yaml_value: serde_yaml::Value = serde_yaml::to_value(value).expect("Value should be serializable");
+        return Self::from_yaml(&yaml_value);
+    }
+
+    fn from_yaml(value: &serde_yaml::Value) -> JsonValue {
+        return match value {
+            serde_yaml::Value::Null => Self::Null,
+            serde_yaml::Value::Bool(b) => Self::Bool(*b),
+            serde_yaml::Value::Number(n) => match n.as_i64() {
+                Some(i) => Self::Number(i),
+                None => Self::Float(n.as_f64().unwrap_or(0.0)),
+            },
+            serde_yaml::Value::String(s) => Self::String(s.clone()),
+            serde_yaml::Value::Sequence(values) => Self::Array(values.iter().map(Self::from_yaml).collect()),
+            serde_yaml::Value::Mapping(mapping) => Self::Object(
+                mapping.iter()
+                    .map(|(key, value)| (
+                        key.as_str().map(|x: &str| x.to_string()).unwrap_or(format!("{:?}", key)),
+                        Self::from_yaml(value),
+                    ))
+                    .collect()),
+            serde_yaml::Value::Tagged(tagged) => Self::from_yaml(&tagged.value),
+        };
+    }
+}
+
+pub fn escape_json_string(value: &str) -> String {
+    let mut escaped: String = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => escaped += "\\\"",
+            '\\' => escaped += "\\\\",
+            '\n' => escaped += "\\n",
+            '\r' => escaped += "\\r",
+            '\t' => escaped += "\\t",
+            c if (c as u32) < 0x20 => escaped += &format!("\\u{:04x}", c as u32),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    return escaped;
+}
+
+
+pub fn parse_json(text: &str) -> Result<JsonValue, String> {
+    let mut parser: JsonParser = JsonParser {chars: text.chars().collect(), pos: 0};
+    let value: JsonValue = parser.parse_value()?;
+    parser.skip_whitespace();
+    if parser.pos != parser.chars.len() {
+        return Err(format!("Unexpected trailing characters at position {}", parser.pos));
+    }
+    return Ok(value);
+}
+
+struct JsonParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl JsonParser {
+    fn skip_whitespace(&mut self) {
+        while self.pos < self.chars.len() && self.chars[self.pos].is_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        return self.chars.get(self.pos).copied();
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.peek() != Some(expected) {
+            return Err(format!("Expected '{}' at position {}", expected, self.pos));
+        }
+        self.pos += 1;
+        return Ok(());
+    }
+
+    fn parse_literal(&mut self, literal: &str, value: JsonValue) -> Result<JsonValue, String> {
+        let end: usize = self.pos + literal.len();
+        if end <= self.chars.len() && self.chars[self.pos..end].iter().collect::<String>() == literal {
+            self.pos = end;
+            return Ok(value);
+        }
+        return Err(format!("Unexpected value at position {}", self.pos));
+    }
+
+    fn parse_value(&mut self) -> Result<JsonValue, String> {
+        self.skip_whitespace();
+        return match self.peek() {
+            Some('{') => self.parse_object(),
+            Some('[') => self.parse_array(),
+            Some('"') => Ok(JsonValue::String(self.parse_string()?)),
+            Some('t') => self.parse_literal("true", JsonValue::Bool(true)),
+            Some('f') => self.parse_literal("false", JsonValue::Bool(false)),
+            Some('n') => self.parse_literal("null", JsonValue::Null),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.parse_number(),
+            Some(c) => Err(format!("Unexpected '{}' at position {}", c, self.pos)),
+            None => Err("Unexpected end of input".to_string()),
+        };
+    }
+
+    fn parse_object(&mut self) -> Result<JsonValue, String> {
+        self.expect('{')?;
+        let mut fields: Vec<(String, JsonValue)> = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(JsonValue::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key: String = self.parse_string()?;
+            self.expect(':')?;
+            fields.push((key, self.parse_value()?));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some('}') => {
+                    self.pos += 1;
+                    return Ok(JsonValue::Object(fields));
+                },
+                _ => return Err(format!("Expected ',' or '}}' at position {}", self.pos)),
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<JsonValue, String> {
+        self.expect('[')?;
+        let mut values: Vec<JsonValue> = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.pos += 1;
+            return Ok(JsonValue::Array(values));
+        }
+        loop {
+            values.push(self.parse_value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(']') => {
+                    self.pos += 1;
+                    return Ok(JsonValue::Array(values));
+                },
+                _ => return Err(format!("Expected ',' or ']' at position {}", self.pos)),
+            }
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut value: String = String::new();
+        loop {
+            let c: char = self.peek().ok_or("Unterminated string".to_string())?;
+            self.pos += 1;
+            match c {
+                '"' => return Ok(value),
+                '\\' => {
+                    let escaped: char = self.peek().ok_or("Unterminated string".to_string())?;
+                    self.pos += 1;
+                    match escaped {
+                        'n' => value.push('\n'),
+                        'r' => value.push('\r'),
+                        't' => value.push('\t'),
+                        'b' => value.push('\u{8}'),
+                        'f' => value.push('\u{c}'),
+                        'u' => {
+                            let hex: String = self.chars.get(self.pos..self.pos + 4)
+                                .ok_or("Incomplete unicode escape".to_string())?
+                                .iter().collect();
+                            self.pos += 4;
+                            let code: u32 = u32::from_str_radix(&hex, 16).map_err(|_| "Invalid unicode escape".to_string())?;
+                            value.push(char::from_u32(code).unwrap_or('\u{fffd}'));
+                        },
+                        other => value.push(other),
+                    }
+                },
+                c => value.push(c),
+            }
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<JsonValue, String> {
+        let start: usize = self.pos;
+        while self.peek().is_some_and(|c: char| c == '-' || c == '+' || c == '.' || c == 'e' || c == 'E' || c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        if let Ok(number) = text.parse::<i64>() {
+            return Ok(JsonValue::Number(number));
+        }
+        return text.parse::<f64>()
+            .map(JsonValue::Float)
+            .map_err(|_| format!("Invalid number '{}' at position {}", text, start));
+    }
+}
@@ -0,0 +1,183 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use chrono::Duration;
+use chrono::prelude::{DateTime, Local, NaiveDate};
+use serde::{Serialize, Deserialize};
+
+use crate::units::interval::{DATE_FMT, Dt};
+use crate::utils::checksums::{Checksummed, warn_if_corrupted, write_checksum};
+use crate::utils::clock::get_clock;
+use crate::utils::file_io::{path_exists, read_file, write_file};
+use crate::utils::index::{DaySummary, get_summaries_in_range};
+use crate::utils::period::DateRange;
+use crate::utils::team::get_data_dir;
+use crate::utils::week_plans::get_week_start;
+
+pub const LEDGER_FILE: &str = "ledger";
+
+static REOPENING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug,Serialize,Deserialize,Clone,Copy,PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LedgerEntryKind {
+    /// The week's totals as they were when it was closed with `punch close-week`.
+    Close,
+    /// How much a change to a day of a closed week moved its totals.
+    Correction,
+}
+
+/// An entry of the ledger of closed weeks. Entries are only ever added, so what was closed,
+/// and every change made to it since, can be traced for payroll.
+#[derive(Debug,Serialize,Deserialize,Clone)]
+pub struct LedgerEntry {
+    pub kind: LedgerEntryKind,
+    /// The Monday of the week, like "2024-03-04".
+    pub week_start: String,
+    pub time: Dt,
+    /// Absolute for a close, and the change for a correction.
+    pub done_minutes: i64,
+    pub to_do_minutes: i64,
+    /// The day whose change made a correction.
+    #[serde(default)]
+    pub date: Option<String>,
+}
+
+/// The totals of a closed week, with its corrections added in.
+#[derive(Debug,Clone)]
+pub struct ClosedWeek {
+    pub week_start: NaiveDate,
+    pub closed: Dt,
+    pub done_minutes: i64,
+    pub to_do_minutes: i64,
+    pub num_corrections: usize,
+}
+
+impl ClosedWeek {
+    /// Minutes behind over the week. Negative when ahead.
+    pub fn get_balance_minutes(&self) -> i64 {
+        return self.to_do_minutes - self.done_minutes;
+    }
+}
+
+pub fn get_ledger_path() -> String {
+    return get_data_dir() + LEDGER_FILE;
+}
+
+/// Every entry so far, oldest first.
+pub fn read_ledger() -> Vec<LedgerEntry> {
+    return match read_file(&get_ledger_path()) {
+        Ok(yaml_str) => {
+            warn_if_corrupted(&Checksummed::Ledger, &yaml_str);
+            serde_yaml::from_str(&yaml_str).unwrap_or_else(|err| panic!("Couldn't read '{}': {}", get_ledger_path(), err))
+        },
+        Err(_) => Vec::new(),
+    };
+}
+
+fn add_ledger_entry(entry: LedgerEntry) {
+    let mut entries: Vec<LedgerEntry> = read_ledger();
+    entries.push(entry);
+    let yaml_str: String = serde_yaml::to_string(&entries).expect("Ledger entries should be serializable");
+    write_file(&get_ledger_path(), yaml_str.clone());
+    write_checksum(&Checksummed::Ledger, &yaml_str);
+}
+
+/// The closed weeks in order, each with its corrections added in.
+pub fn get_closed_weeks() -> Vec<ClosedWeek> {
+    let mut weeks: Vec<ClosedWeek> = Vec::new();
+    for entry in read_ledger() {
+        let Ok(week_start) = NaiveDate::parse_from_str(&entry.week_start, DATE_FMT) else {
+            continue;
+        };
+        match (entry.kind, weeks.iter_mut().find(|week: &&mut ClosedWeek| week.week_start == week_start)) {
+            (LedgerEntryKind::Close, None) => weeks.push(ClosedWeek {
+                week_start,
+                closed: entry.time,
+                done_minutes: entry.done_minutes,
+                to_do_minutes: entry.to_do_minutes,
+                num_corrections: 0,
+            }),
+            (LedgerEntryKind::Correction, Some(week)) => {
+                week.done_minutes += entry.done_minutes;
+                week.to_do_minutes += entry.to_do_minutes;
+                week.num_corrections += 1;
+            },
+            _ => (),
+        }
+    }
+    weeks.sort_by_key(|week: &ClosedWeek| week.week_start);
+    return weeks;
+}
+
+/// The closed week `date` falls in, if it's been closed.
+pub fn get_closed_week(date: &NaiveDate) -> Option<ClosedWeek> {
+    let week_start: NaiveDate = get_week_start(date);
+    return get_closed_weeks().into_iter().find(|week: &ClosedWeek| week.week_start == week_start);
+}
+
+fn get_week_range(week_start: &NaiveDate) -> DateRange {
+    return DateRange::new(*week_start, *week_start + Duration::days(6)).expect("A week ends after it starts");
+}
+
+/// Minutes done and to do over the recorded days of the week, as they are now.
+pub fn get_week_totals(week_start: &NaiveDate, now: &DateTime<Local>) -> (i64, i64) {
+    let summaries: Vec<DaySummary> = get_summaries_in_range(&get_week_range(week_start), now);
+    let done_secs: i64 = summaries.iter().map(|summary: &DaySummary| summary.done_secs).sum();
+    let to_do_secs: i64 = summaries.iter().map(|summary: &DaySummary| summary.to_do_secs).sum();
+    return (done_secs / 60, to_do_secs / 60);
+}
+
+/// Adds the week's totals to the ledger, closing it.
+pub fn close_week(week_start: &NaiveDate, now: &DateTime<Local>) -> ClosedWeek {
+    let (done_minutes, to_do_minutes): (i64, i64) = get_week_totals(week_start, now);
+    add_ledger_entry(LedgerEntry {
+        kind: LedgerEntryKind::Close,
+        week_start: week_start.format(DATE_FMT).to_string(),
+        time: Dt(*now),
+        done_minutes,
+        to_do_minutes,
+        date: None,
+    });
+    return get_closed_week(week_start).expect("The week was just closed");
+}
+
+/// Lets days of closed weeks be changed for the rest of the run, for `--reopen`.
+pub fn allow_reopening() {
+    REOPENING.store(true, Ordering::Relaxed);
+}
+
+/// Days of closed weeks can only be changed with `--reopen`.
+pub fn check_week_open(date: &NaiveDate) -> Result<(), String> {
+    if REOPENING.load(Ordering::Relaxed) {
+        return Ok(());
+    }
+    return match get_closed_week(date) {
+        Some(week) => Err(format!(
+            "The week of {} was closed on {}, so the day for {} is read-only. Pass --reopen to change it; the change is kept in the ledger as a correction.",
+            week.week_start.format(DATE_FMT), week.closed.as_dt().format(DATE_FMT), date.format(DATE_FMT))),
+        None => Ok(()),
+    };
+}
+
+/// After a day of a closed week has been written or deleted, adds a correction for however
+/// much that moved the week's totals.
+pub fn record_correction_if_closed(date: &NaiveDate) {
+    if !path_exists(&get_ledger_path()) {
+        return;
+    }
+    let Some(week) = get_closed_week(date) else {
+        return;
+    };
+    let now: DateTime<Local> = get_clock().now();
+    let (done_minutes, to_do_minutes): (i64, i64) = get_week_totals(&week.week_start, &now);
+    if (done_minutes, to_do_minutes) == (week.done_minutes, week.to_do_minutes) {
+        return;
+    }
+    add_ledger_entry(LedgerEntry {
+        kind: LedgerEntryKind::Correction,
+        week_start: week.week_start.format(DATE_FMT).to_string(),
+        time: Dt(now),
+        done_minutes: done_minutes - week.done_minutes,
+        to_do_minutes: to_do_minutes - week.to_do_minutes,
+        date: Some(date.format(DATE_FMT).to_string()),
+    });
+}
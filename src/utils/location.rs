@@ -0,0 +1,17 @@
+/// The day's meta key that `punch in --location` sets.
+pub const LOCATION_KEY: &str = "location";
+pub const LOCATIONS: [&str; 3] = ["home", "office", "client"];
+
+/// Checks that `location` is one `punch in --location` knows, e.g. "home" or "Office".
+pub fn parse_location(location: &str) -> Result<String, String> {
+    let location: String = location.trim().to_lowercase();
+    if !LOCATIONS.contains(&location.as_str()) {
+        return Err(format!("'{}' isn't a location punch knows. Try one of: {}", location, LOCATIONS.join(", ")));
+    }
+    return Ok(location);
+}
+
+/// Working from home counts as remote, while the office and a client's site count as on site.
+pub fn is_remote(location: &str) -> bool {
+    return location == "home";
+}
@@ -0,0 +1,54 @@
+use serde::{Serialize, Deserialize};
+
+#[cfg(feature = "meeting-detection")]
+use crate::utils::activity::{active_window_title, running_process_names};
+
+#[cfg(feature = "meeting-detection")]
+const DEFAULT_MEETING_TASK: &str = "meeting";
+
+/// How the daemon spots meetings, when built with the `meeting-detection` feature.
+#[derive(Debug,Serialize,Deserialize,Clone)]
+pub struct MeetingDetectionConfig {
+    /// The task to switch to during meetings. Defaults to "meeting".
+    #[serde(default)]
+    pub task: Option<String>,
+    pub rules: Vec<MeetingRule>,
+}
+
+/// A meeting is on when a process with `process` in its name is running, or the focused
+/// window's title contains `window_title`. Both ignore case.
+#[derive(Debug,Serialize,Deserialize,Clone)]
+pub struct MeetingRule {
+    #[serde(default)]
+    pub process: Option<String>,
+    #[serde(default)]
+    pub window_title: Option<String>,
+}
+
+impl MeetingDetectionConfig {
+    #[cfg(feature = "meeting-detection")]
+    pub fn get_task(&self) -> String {
+        return self.task.clone().unwrap_or(DEFAULT_MEETING_TASK.to_string());
+    }
+
+    /// What gave away that a meeting is on, if one is.
+    #[cfg(feature = "meeting-detection")]
+    pub fn detect(&self) -> Option<String> {
+        let processes: Vec<String> = running_process_names().into_iter().map(|name: String| name.to_lowercase()).collect();
+        let needs_title: bool = self.rules.iter().any(|rule: &MeetingRule| rule.window_title.is_some());
+        let title: Option<String> = if needs_title {active_window_title().map(|title: String| title.to_lowercase())} else {None};
+        for rule in &self.rules {
+            if let Some(process) = &rule.process {
+                if processes.iter().any(|name: &String| name.contains(&process.to_lowercase())) {
+                    return Some(format!("'{}' is running", process));
+                }
+            }
+            if let (Some(window_title), Some(title)) = (&rule.window_title, &title) {
+                if title.contains(&window_title.to_lowercase()) {
+                    return Some(format!("the focused window's title has '{}' in it", window_title));
+                }
+            }
+        }
+        return None;
+    }
+}
@@ -1,3 +1,62 @@
 pub mod file_io;
 pub mod config;
 pub mod work_summary;
+pub mod args;
+pub mod period;
+pub mod aggregate;
+pub mod compliance;
+pub mod notify;
+pub mod reminders;
+pub mod color;
+pub mod json;
+pub mod report;
+pub mod http;
+pub mod mqtt;
+pub mod index;
+pub mod parallel;
+pub mod storage;
+pub mod clock;
+pub mod prompt;
+pub mod budget;
+pub mod email;
+pub mod pdf;
+pub mod zip;
+pub mod xlsx;
+pub mod ics;
+pub mod calendar;
+pub mod activity;
+pub mod meetings;
+pub mod samples;
+pub mod retention;
+pub mod compression;
+pub mod checksums;
+pub mod team;
+pub mod signing;
+pub mod i18n;
+pub mod away;
+pub mod location;
+pub mod oncall;
+pub mod expenses;
+pub mod templates;
+pub mod timers;
+pub mod terminal;
+pub mod picker;
+pub mod adjustments;
+pub mod shifts;
+pub mod holidays;
+pub mod week_plans;
+pub mod taxonomy;
+pub mod suggestions;
+pub mod updates;
+pub mod yaml_text;
+pub mod state;
+pub mod ledger;
+pub mod graphql;
+#[cfg(feature = "grpc")]
+pub mod hpack;
+#[cfg(feature = "grpc")]
+pub mod http2;
+#[cfg(feature = "grpc")]
+pub mod protobuf;
+pub mod access;
+pub mod event_log;
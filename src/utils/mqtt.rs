@@ -0,0 +1,53 @@
+use std::process::Command;
+use chrono::prelude::{DateTime, Local};
+use serde::{Serialize, Deserialize};
+
+use crate::units::day::Day;
+use crate::utils::config::get_config;
+use crate::utils::json::JsonValue;
+
+const DEFAULT_MQTT_PORT: u16 = 1883;
+
+/// Where to publish state changes. Publishing uses `mosquitto_pub`, which needs to be installed.
+#[derive(Debug,Serialize,Deserialize,Clone)]
+pub struct MqttConfig {
+    pub broker: String,
+    #[serde(default)]
+    pub port: Option<u16>,
+    pub topic: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+/// Publishes a retained message describing the day after `event` (e.g. "pause") happened.
+/// Does nothing if MQTT isn't configured, and only warns if publishing fails.
+pub fn publish_event(event: &str, day: &Day, at: &DateTime<Local>) {
+    let mqtt: MqttConfig = match get_config().mqtt() {
+        Some(mqtt) => mqtt.clone(),
+        None => return,
+    };
+    let payload: JsonValue = JsonValue::Object(vec![
+        ("event".to_string(), JsonValue::String(event.to_string())),
+        ("time".to_string(), JsonValue::String(at.to_rfc3339())),
+        ("task".to_string(), JsonValue::String(day.get_latest_task_name())),
+        ("punched_in".to_string(), JsonValue::Bool(!day.has_ended())),
+        ("on_break".to_string(), JsonValue::Bool(!day.has_ended() && day.on_break)),
+    ]);
+    let mut command: Command = Command::new("mosquitto_pub");
+    command.args(["-h", &mqtt.broker])
+        .args(["-p", &mqtt.port.unwrap_or(DEFAULT_MQTT_PORT).to_string()])
+        .args(["-t", &mqtt.topic])
+        .args(["-m", &payload.as_string()])
+        .arg("-r");
+    if let Some(username) = &mqtt.username {
+        command.args(["-u", username]);
+    }
+    if let Some(password) = &mqtt.password {
+        command.args(["-P", password]);
+    }
+    if !command.status().is_ok_and(|status| status.success()) {
+        eprintln!("Warning: Couldn't publish the '{}' event to MQTT broker '{}'", event, mqtt.broker);
+    }
+}
@@ -0,0 +1,16 @@
+use std::process::Command;
+
+/// Shows a desktop notification, falling back to printing it if no notifier is available.
+pub fn send_notification(title: &str, msg: &str) {
+    let result = if cfg!(target_os = "macos") {
+        let script: String = format!(
+            "display notification \"{}\" with title \"{}\"", msg.replace('"', "'"), title.replace('"', "'"));
+        Command::new("osascript").args(["-e", &script]).status()
+    }
+    else {
+        Command::new("notify-send").args([title, msg]).status()
+    };
+    if !result.is_ok_and(|status| status.success()) {
+        println!("[{}] {}", title, msg);
+    }
+}
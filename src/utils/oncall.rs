@@ -0,0 +1,67 @@
+use chrono::prelude::{DateTime, Datelike, Local, NaiveDate, Weekday};
+use chrono::Duration;
+use serde::{Serialize, Deserialize};
+
+use crate::units::interval::Interval;
+use crate::utils::file_io::{read_file, write_file};
+use crate::utils::team::get_data_dir;
+
+pub const ONCALL_FILE: &str = "oncall";
+const DEFAULT_RATE_PERCENT: u64 = 25;
+
+/// How much of the time on call counts towards compensation. Time worked while on call
+/// is paid as work, so it isn't credited again.
+#[derive(Debug,Serialize,Deserialize,Clone,Default)]
+pub struct OnCallConfig {
+    /// The share of on-call time that counts, in percent. Defaults to 25.
+    #[serde(default)]
+    pub rate_percent: Option<u64>,
+    /// The share on Saturdays and Sundays. Defaults to `rate_percent`.
+    #[serde(default)]
+    pub weekend_rate_percent: Option<u64>,
+}
+
+impl OnCallConfig {
+    pub fn get_rate_percent(&self, date: &NaiveDate) -> u64 {
+        let rate_percent: u64 = self.rate_percent.unwrap_or(DEFAULT_RATE_PERCENT);
+        return match date.weekday() {
+            Weekday::Sat | Weekday::Sun => self.weekend_rate_percent.unwrap_or(rate_percent),
+            _ => rate_percent,
+        };
+    }
+}
+
+pub fn get_oncall_path() -> String {
+    return get_data_dir() + ONCALL_FILE;
+}
+
+/// Every on-call period so far, oldest first. The last one has no end while you're on call.
+pub fn read_oncall_periods() -> Vec<Interval> {
+    return match read_file(&get_oncall_path()) {
+        Ok(yaml_str) => serde_yaml::from_str(&yaml_str).unwrap_or_default(),
+        Err(_) => Vec::new(),
+    };
+}
+
+pub fn write_oncall_periods(periods: &Vec<Interval>) {
+    write_file(&get_oncall_path(), serde_yaml::to_string(periods).expect("On-call periods should be serializable"));
+}
+
+/// How much of `periods` falls between `start` and `end`, counting a period that hasn't ended up until `now`.
+pub fn get_oncall_secs_between(periods: &Vec<Interval>, start: &DateTime<Local>, end: &DateTime<Local>, now: &DateTime<Local>) -> i64 {
+    return periods.iter()
+        .map(|period: &Interval| {
+            let period_end: DateTime<Local> = period.get_end().map(|end| end.as_dt()).unwrap_or(*now);
+            let overlap_start: DateTime<Local> = period.get_start().as_dt().max(*start);
+            let overlap_end: DateTime<Local> = period_end.min(*end);
+            return (overlap_end - overlap_start).num_seconds().max(0);
+        })
+        .sum();
+}
+
+/// The midnights that start and end `date`.
+pub fn get_day_bounds(date: &NaiveDate) -> (DateTime<Local>, DateTime<Local>) {
+    let start_of = |date: NaiveDate| date.and_hms_opt(0, 0, 0).expect("Midnight exists")
+        .and_local_timezone(Local).earliest().expect("Midnight should exist locally");
+    return (start_of(*date), start_of(*date + Duration::days(1)));
+}
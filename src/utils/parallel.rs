@@ -0,0 +1,35 @@
+use std::thread::{available_parallelism, scope};
+
+/// Below this many items, threads cost more than they save.
+const MIN_ITEMS_PER_THREAD: usize = 16;
+
+/// Splits `items` into contiguous chunks, runs `process_chunk` on each chunk in its own
+/// thread and joins the results back together in the original order.
+pub fn process_chunks_in_parallel<T, R, F>(items: Vec<T>, process_chunk: F) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+    F: Fn(Vec<T>) -> Vec<R> + Sync,
+{
+    let num_threads: usize = available_parallelism().map(|x| x.get()).unwrap_or(1)
+        .min(items.len() / MIN_ITEMS_PER_THREAD)
+        .max(1);
+    if num_threads == 1 {
+        return process_chunk(items);
+    }
+
+    let chunk_size: usize = items.len().div_ceil(num_threads);
+    let mut chunks: Vec<Vec<T>> = Vec::new();
+    let mut items_iter = items.into_iter().peekable();
+    while items_iter.peek().is_some() {
+        chunks.push(items_iter.by_ref().take(chunk_size).collect());
+    }
+    return scope(|s| {
+        let handles: Vec<_> = chunks.into_iter()
+            .map(|chunk: Vec<T>| s.spawn(|| process_chunk(chunk)))
+            .collect();
+        return handles.into_iter()
+            .flat_map(|handle| handle.join().expect("Worker thread panicked"))
+            .collect();
+    });
+}
@@ -0,0 +1,156 @@
+/// A4 in points.
+const PAGE_WIDTH: f64 = 595.0;
+const PAGE_HEIGHT: f64 = 842.0;
+const MARGIN: f64 = 50.0;
+
+/// A minimal PDF writer for plain text documents, laid out top to bottom in Courier so
+/// that columns line up. Pages are added as the text reaches the bottom margin. Text is
+/// limited to ASCII, since the built-in fonts are used instead of embedding one.
+pub struct PdfDocument {
+    pages: Vec<String>,
+    y: f64,
+}
+
+impl PdfDocument {
+    pub fn new() -> Self {
+        return Self {pages: vec![String::new()], y: PAGE_HEIGHT - MARGIN};
+    }
+
+    /// Adds a line of text in the given font size, in bold if `bold`.
+    pub fn add_line(&mut self, text: &str, size: f64, bold: bool) {
+        let line_height: f64 = size * 1.4;
+        if self.y - line_height < MARGIN {
+            self.new_page();
+        }
+        self.y -= line_height;
+        let font: &str = if bold {"F2"} else {"F1"};
+        let page: &mut String = self.pages.last_mut().expect("There's always a page");
+        *page += &format!("BT /{} {} Tf {} {:.1} Td ({}) Tj ET\n", font, size, MARGIN, self.y, escape_pdf_text(text));
+    }
+
+    pub fn add_space(&mut self, height: f64) {
+        self.y -= height;
+    }
+
+    /// Makes sure the next `height` points fit on this page, starting a new one if not.
+    pub fn keep_together(&mut self, height: f64) {
+        if self.y - height < MARGIN {
+            self.new_page();
+        }
+    }
+
+    pub fn new_page(&mut self) {
+        self.pages.push(String::new());
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+
+    /// The finished document, with page numbers added to the foot of each page.
+    pub fn render(&self) -> String {
+        let num_pages: usize = self.pages.len();
+        // Objects 1-4 are the catalog, page tree and fonts, then each page has a page and a contents object.
+        let mut objects: Vec<String> = vec![
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                (0..num_pages).map(|i: usize| format!("{} 0 R", 5 + 2 * i)).collect::<Vec<String>>().join(" "),
+                num_pages),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Courier /Encoding /WinAnsiEncoding >>".to_string(),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Courier-Bold /Encoding /WinAnsiEncoding >>".to_string(),
+        ];
+        for (i, page) in self.pages.iter().enumerate() {
+            let contents: String = format!(
+                "{}BT /F1 8 Tf {} {} Td (Page {} of {}) Tj ET\n",
+                page, PAGE_WIDTH - MARGIN - 60.0, MARGIN / 2.0, i + 1, num_pages);
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                PAGE_WIDTH, PAGE_HEIGHT, 6 + 2 * i));
+            objects.push(format!("<< /Length {} >>\nstream\n{}endstream", contents.len(), contents));
+        }
+
+        let mut out: String = "%PDF-1.4\n".to_string();
+        let mut offsets: Vec<usize> = Vec::new();
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out += &format!("{} 0 obj\n{}\nendobj\n", i + 1, object);
+        }
+        let xref_offset: usize = out.len();
+        out += &format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for offset in offsets {
+            out += &format!("{:010} 00000 n \n", offset);
+        }
+        out += &format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, xref_offset);
+        return out;
+    }
+}
+
+impl Default for PdfDocument {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+fn escape_pdf_text(text: &str) -> String {
+    return text.chars()
+        .map(|c: char| match c {
+            '\\' | '(' | ')' => format!("\\{}", c),
+            c if c.is_ascii() && !c.is_ascii_control() => c.to_string(),
+            _ => "?".to_string(),
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_pdf_string_delimiters_and_replaces_non_ascii() {
+        assert_eq!(escape_pdf_text(r"a (b) \c"), r"a \(b\) \\c");
+        assert_eq!(escape_pdf_text("café\t"), "caf??");
+    }
+
+    #[test]
+    fn the_cross_reference_table_points_at_each_object() {
+        let mut doc: PdfDocument = PdfDocument::new();
+        doc.add_line("Report", 14.0, true);
+        let pdf: String = doc.render();
+        assert!(pdf.starts_with("%PDF-1.4\n") && pdf.ends_with("%%EOF\n"));
+
+        let xref_offset: usize = pdf.rsplit("startxref\n").next().unwrap().lines().next().unwrap().parse().unwrap();
+        assert!(pdf[xref_offset..].starts_with("xref\n0 7\n"));
+        let entries: Vec<&str> = pdf[xref_offset..].lines().skip(3).take(6).collect();
+        for (i, entry) in entries.iter().enumerate() {
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(pdf[offset..].starts_with(&format!("{} 0 obj\n", i + 1)), "object {} isn't at {}", i + 1, offset);
+        }
+        assert!(pdf.contains("BT /F2 14 Tf 50 772.4 Td (Report) Tj ET\n"));
+    }
+
+    #[test]
+    fn stream_lengths_match_their_contents() {
+        let mut doc: PdfDocument = PdfDocument::new();
+        doc.add_line("(x)", 10.0, false);
+        let pdf: String = doc.render();
+        let object: &str = pdf.split("6 0 obj\n").nth(1).unwrap();
+        let length: usize = object["<< /Length ".len()..].split(' ').next().unwrap().parse().unwrap();
+        let stream: &str = object.split_once(">>\nstream\n").unwrap().1.split("endstream").next().unwrap();
+        assert_eq!(stream.len(), length);
+        assert!(stream.contains(r"(\(x\)) Tj"));
+    }
+
+    #[test]
+    fn starts_a_new_page_at_the_bottom_margin() {
+        let mut doc: PdfDocument = PdfDocument::new();
+        // Each 10 point line takes 14 points, and 742 points fit between the margins.
+        for _ in 0..53 {
+            doc.add_line("line", 10.0, false);
+        }
+        assert_eq!(doc.pages.len(), 1);
+        doc.add_line("line", 10.0, false);
+        assert_eq!(doc.pages.len(), 2);
+        assert!(doc.render().contains("(Page 2 of 2)"));
+
+        doc.keep_together(PAGE_HEIGHT);
+        assert_eq!(doc.pages.len(), 3);
+    }
+}
@@ -0,0 +1,191 @@
+use chrono::prelude::{DateTime, Datelike, Local, NaiveDate};
+use chrono::{Duration, Months};
+
+use crate::units::interval::DATE_FMT;
+use crate::utils::args::get_flag_value;
+
+const DEFAULT_PERIOD: &str = "this-week";
+
+
+/// An inclusive range of calendar dates.
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub struct DateRange {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl DateRange {
+    pub fn new(from: NaiveDate, to: NaiveDate) -> Result<Self, String> {
+        if from > to {
+            return Err(format!("Invalid range: '{}' is after '{}'", from, to));
+        }
+        return Ok(Self {from: from, to: to});
+    }
+
+    pub fn contains(&self, date: &NaiveDate) -> bool {
+        return self.from <= *date && *date <= self.to;
+    }
+
+    pub fn num_days(&self) -> i64 {
+        return (self.to - self.from).num_days() + 1;
+    }
+
+    pub fn dates(&self) -> Vec<NaiveDate> {
+        return (0..self.num_days()).map(|i: i64| self.from + Duration::days(i)).collect();
+    }
+
+    pub fn as_string(&self) -> String {
+        return format!("{} to {}", self.from.format(DATE_FMT), self.to.format(DATE_FMT));
+    }
+}
+
+
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub enum Period {
+    Today,
+    Yesterday,
+    ThisWeek,
+    LastWeek,
+    ThisMonth,
+    LastMonth,
+    ThisQuarter,
+    LastQuarter,
+    Ytd,
+}
+
+impl Period {
+    pub fn from_string(name: &str) -> Result<Self, String> {
+        return match name.trim() {
+            "today" => Ok(Self::Today),
+            "yesterday" => Ok(Self::Yesterday),
+            "this-week" => Ok(Self::ThisWeek),
+            "last-week" => Ok(Self::LastWeek),
+            "this-month" => Ok(Self::ThisMonth),
+            "last-month" => Ok(Self::LastMonth),
+            "this-quarter" => Ok(Self::ThisQuarter),
+            "last-quarter" => Ok(Self::LastQuarter),
+            "ytd" => Ok(Self::Ytd),
+            other => Err(format!(
+                "'{}' is not a valid period. Try one of: {}", other, Self::get_allowed_strings().join(", ")
+            )),
+        };
+    }
+
+    pub fn get_allowed_strings() -> Vec<String> {
+        return Vec::from(
+            [
+                "today", "yesterday", "this-week", "last-week", "this-month",
+                "last-month", "this-quarter", "last-quarter", "ytd"
+            ].map(|x: &str| x.to_string())
+        );
+    }
+
+    pub fn resolve(&self, today: NaiveDate) -> DateRange {
+        let week_start: NaiveDate = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+        let month_start: NaiveDate = first_of_month(today.year(), today.month());
+        let quarter_start: NaiveDate = first_of_month(today.year(), ((today.month() - 1) / 3) * 3 + 1);
+        let (from, to): (NaiveDate, NaiveDate) = match self {
+            Self::Today => (today, today),
+            Self::Yesterday => (today - Duration::days(1), today - Duration::days(1)),
+            Self::ThisWeek => (week_start, week_start + Duration::days(6)),
+            Self::LastWeek => (week_start - Duration::days(7), week_start - Duration::days(1)),
+            Self::ThisMonth => (month_start, end_of_month(month_start)),
+            Self::LastMonth => {
+                let last_month_end: NaiveDate = month_start - Duration::days(1);
+                (first_of_month(last_month_end.year(), last_month_end.month()), last_month_end)
+            },
+            Self::ThisQuarter => (quarter_start, end_of_month(shift_months(quarter_start, 2))),
+            Self::LastQuarter => {
+                let last_quarter_start: NaiveDate = shift_months(quarter_start, -3);
+                (last_quarter_start, quarter_start - Duration::days(1))
+            },
+            Self::Ytd => (first_of_month(today.year(), 1), today),
+        };
+        return DateRange {from: from, to: to};
+    }
+}
+
+
+fn first_of_month(year: i32, month: u32) -> NaiveDate {
+    return NaiveDate::from_ymd_opt(year, month, 1).expect("The first of a month should always exist");
+}
+
+fn shift_months(date: NaiveDate, months: i32) -> NaiveDate {
+    let total: i32 = date.year() * 12 + (date.month() as i32 - 1) + months;
+    return first_of_month(total.div_euclid(12), (total.rem_euclid(12) + 1) as u32);
+}
+
+fn end_of_month(date: NaiveDate) -> NaiveDate {
+    return shift_months(date, 1) - Duration::days(1);
+}
+
+/// The calendar month containing `date`.
+pub fn month_of(date: NaiveDate) -> DateRange {
+    let month_start: NaiveDate = first_of_month(date.year(), date.month());
+    return DateRange {from: month_start, to: end_of_month(month_start)};
+}
+
+/// Parses a month like `2024-01` into its range of dates.
+pub fn parse_month(month_str: &str) -> Result<DateRange, String> {
+    let first: NaiveDate = parse_date(&format!("{}-01", month_str.trim()))
+        .map_err(|_| format!("Expected a month in the format YYYY-MM! Given: '{}'", month_str))?;
+    return Ok(month_of(first));
+}
+
+pub fn parse_date(date_str: &str) -> Result<NaiveDate, String> {
+    return NaiveDate::parse_from_str(date_str.trim(), DATE_FMT)
+        .map_err(|_| format!("Expected a date in the format YYYY-MM-DD! Given: '{}'", date_str));
+}
+
+/// Resolves `--period`, `--from` and `--to` into a single range. This is the one place
+/// commands working over several days should turn their args into dates.
+///
+/// `--from`/`--to` take precedence over the matching end of `--period`, and a missing
+/// `--to` means "up to today".
+pub fn resolve_range_from_args(now: &DateTime<Local>, args: &Vec<String>) -> Result<DateRange, String> {
+    let today: NaiveDate = now.date_naive();
+    let from_arg: Option<String> = get_flag_value(args, "--from");
+    let to_arg: Option<String> = get_flag_value(args, "--to");
+    let period_arg: Option<String> = get_flag_value(args, "--period");
+
+    let base: DateRange = match (&period_arg, &from_arg) {
+        (Some(name), _) => Period::from_string(name)?.resolve(today),
+        (None, Some(_)) => DateRange {from: today, to: today},
+        (None, None) => Period::from_string(DEFAULT_PERIOD)?.resolve(today),
+    };
+    let from: NaiveDate = match from_arg {
+        Some(date_str) => parse_date(&date_str)?,
+        None => base.from,
+    };
+    let to: NaiveDate = match to_arg {
+        Some(date_str) => parse_date(&date_str)?,
+        None => base.to,
+    };
+    return DateRange::new(from, to);
+}
+
+/// The first date that's younger than an age like "90d", "6w", "18m" or "2y", counting
+/// back from `today`. Days before it are older than the age.
+pub fn parse_age(age: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    let age: &str = age.trim();
+    let invalid = || format!("Ages look like '90d', '6w', '18m' or '2y'. Given: '{}'", age);
+    let unit_start: usize = age.char_indices().last().map(|(ind, _)| ind).ok_or_else(invalid)?;
+    let (num_str, unit) = age.split_at(unit_start);
+    let num: u32 = num_str.parse::<u32>().ok().filter(|num: &u32| *num > 0).ok_or_else(invalid)?;
+    let cutoff: Option<NaiveDate> = match unit {
+        "d" => today.checked_sub_signed(Duration::days(num as i64)),
+        "w" => today.checked_sub_signed(Duration::weeks(num as i64)),
+        "m" => today.checked_sub_months(Months::new(num)),
+        "y" => today.checked_sub_months(Months::new(num * 12)),
+        _ => return Err(invalid()),
+    };
+    return cutoff.ok_or_else(invalid);
+}
+
+/// Parses either a period name or an explicit `YYYY-MM-DD..YYYY-MM-DD` range.
+pub fn parse_range(spec: &str, now: &DateTime<Local>) -> Result<DateRange, String> {
+    return match spec.split_once("..") {
+        Some((from, to)) => DateRange::new(parse_date(from)?, parse_date(to)?),
+        None => Ok(Period::from_string(spec)?.resolve(now.date_naive())),
+    };
+}
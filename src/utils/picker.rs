@@ -0,0 +1,97 @@
+use std::io::{ErrorKind, IsTerminal, Write, stdin, stdout};
+use std::process::{Command, Stdio};
+
+use crate::utils::config::get_config;
+
+const DEFAULT_PICKER: &str = "fzf";
+/// Set as the config's `picker` to always use the built-in one.
+const BUILTIN_PICKER: &str = "builtin";
+const MAX_LISTED: usize = 20;
+
+/// Whether there's someone at a terminal to pick from a list.
+pub fn can_pick() -> bool {
+    return stdin().is_terminal() && stdout().is_terminal();
+}
+
+/// Lets the user pick one of `options` with a fuzzy finder: the config's `picker` (like "sk"),
+/// or else `fzf`, or else a simple built-in one if that isn't installed. `None` if nothing
+/// was picked.
+pub fn pick(prompt: &str, options: &Vec<String>) -> Option<String> {
+    if options.is_empty() {
+        return None;
+    }
+    let picker: Vec<String> = get_config().picker().cloned()
+        .unwrap_or(DEFAULT_PICKER.to_string())
+        .split_whitespace()
+        .map(|x: &str| x.to_string())
+        .collect();
+    if picker.first().is_some_and(|program: &String| program != BUILTIN_PICKER) {
+        match pick_with(&picker, prompt, options) {
+            Err(ErrorKind::NotFound) => {},
+            result => return result.ok().flatten(),
+        }
+    }
+    return pick_builtin(prompt, options);
+}
+
+/// Runs a finder that reads options on stdin and writes the picked one to stdout, like `fzf`.
+fn pick_with(picker: &Vec<String>, prompt: &str, options: &Vec<String>) -> Result<Option<String>, ErrorKind> {
+    let mut child = Command::new(&picker[0]).args(&picker[1..])
+        .arg(format!("--prompt={}> ", prompt))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|err| err.kind())?;
+    if let Some(mut picker_stdin) = child.stdin.take() {
+        let _ = picker_stdin.write_all((options.join("\n") + "\n").as_bytes());
+    }
+    let output = child.wait_with_output().map_err(|err| err.kind())?;
+    let picked: String = String::from_utf8_lossy(&output.stdout).trim_end().to_string();
+    return Ok(Some(picked).filter(|picked: &String| output.status.success() && !picked.is_empty()));
+}
+
+/// Lists the options with numbers, and narrows them down to the ones matching whatever's typed
+/// until a number is picked. An empty answer picks the first one listed.
+fn pick_builtin(prompt: &str, options: &Vec<String>) -> Option<String> {
+    let mut matching: Vec<&String> = options.iter().collect();
+    loop {
+        for (ind, option) in matching.iter().take(MAX_LISTED).enumerate() {
+            println!("  [{}] {}", ind + 1, option);
+        }
+        if matching.len() > MAX_LISTED {
+            println!("  ... and {} more", matching.len() - MAX_LISTED);
+        }
+        print!("{} (a number, or letters to narrow the list): ", prompt);
+        stdout().flush().expect("Should be able to flush stdout");
+        let mut answer: String = String::new();
+        if stdin().read_line(&mut answer).unwrap_or(0) == 0 {
+            return None;
+        }
+        let answer: &str = answer.trim();
+        if answer.is_empty() {
+            return matching.first().map(|x: &&String| x.to_string());
+        }
+        if let Ok(number) = answer.parse::<usize>() {
+            if (1..=matching.len().min(MAX_LISTED)).contains(&number) {
+                return Some(matching[number - 1].clone());
+            }
+        }
+        let narrowed: Vec<&String> = matching.iter().copied().filter(|option: &&String| is_fuzzy_match(answer, option)).collect();
+        if narrowed.is_empty() {
+            println!("Nothing matches '{}'.", answer);
+            matching = options.iter().collect();
+        }
+        else {
+            matching = narrowed;
+        }
+    }
+}
+
+/// Whether the letters of `query` all appear in `text` in order, ignoring case.
+pub fn is_fuzzy_match(query: &str, text: &str) -> bool {
+    let mut text_chars = text.chars().flat_map(|c: char| c.to_lowercase());
+    return query.chars()
+        .flat_map(|c: char| c.to_lowercase())
+        .filter(|c: &char| !c.is_whitespace())
+        .all(|c: char| text_chars.any(|text_char: char| text_char == c));
+}
@@ -0,0 +1,48 @@
+use std::io::{Write, stdin, stdout};
+
+/// Asks a yes/no question on the terminal. Anything but "y" or "yes" counts as no.
+pub fn confirm(question: &str) -> bool {
+    print!("{} [y/N] ", question);
+    stdout().flush().expect("Should be able to flush stdout");
+    let mut answer: String = String::new();
+    if stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+    return matches!(answer.trim().to_lowercase().as_str(), "y" | "yes");
+}
+
+/// Asks the user to pick one of `options`, given as (key, description) pairs, and keeps
+/// asking until they do. An empty answer picks the first option.
+pub fn choose(question: &str, options: &[(&str, &str)]) -> String {
+    println!("{}", question);
+    for (key, description) in options {
+        println!("  [{}] {}", key, description);
+    }
+    loop {
+        print!("Choice [{}]: ", options[0].0);
+        stdout().flush().expect("Should be able to flush stdout");
+        let mut answer: String = String::new();
+        if stdin().read_line(&mut answer).unwrap_or(0) == 0 {
+            return options[0].0.to_string();
+        }
+        let answer: &str = answer.trim();
+        if answer.is_empty() {
+            return options[0].0.to_string();
+        }
+        if let Some((key, _)) = options.iter().find(|(key, _)| *key == answer) {
+            return key.to_string();
+        }
+        println!("Please answer one of: {}", options.iter().map(|(key, _)| *key).collect::<Vec<&str>>().join(", "));
+    }
+}
+
+/// Asks for a line of text, giving `default` if the answer is empty.
+pub fn ask(question: &str, default: &str) -> String {
+    print!("{} [{}]: ", question, default);
+    stdout().flush().expect("Should be able to flush stdout");
+    let mut answer: String = String::new();
+    if stdin().read_line(&mut answer).is_err() || answer.trim().is_empty() {
+        return default.to_string();
+    }
+    return answer.trim().to_string();
+}
@@ -0,0 +1,160 @@
+/// A field of a protobuf message as it's sent, before it's known what type it's meant to be.
+#[derive(Debug,Clone,PartialEq)]
+pub enum ProtoValue {
+    Varint(u64),
+    Fixed64(u64),
+    Bytes(Vec<u8>),
+    Fixed32(u32),
+}
+
+/// The fields of a protobuf message by number, in the order they were sent. Repeated fields
+/// come up once for each value.
+#[derive(Debug,Clone,Default)]
+pub struct ProtoMessage {
+    pub fields: Vec<(u32, ProtoValue)>,
+}
+
+impl ProtoMessage {
+    pub fn decode(bytes: &[u8]) -> Result<Self, String> {
+        let mut fields: Vec<(u32, ProtoValue)> = Vec::new();
+        let mut pos: usize = 0;
+        while pos < bytes.len() {
+            let key: u64 = decode_varint(bytes, &mut pos)?;
+            let field_number: u32 = (key >> 3) as u32;
+            let value: ProtoValue = match key & 0x7 {
+                0 => ProtoValue::Varint(decode_varint(bytes, &mut pos)?),
+                1 => ProtoValue::Fixed64(u64::from_le_bytes(take(bytes, &mut pos, 8)?.try_into().expect("Took 8 bytes"))),
+                2 => {
+                    let len: usize = decode_varint(bytes, &mut pos)? as usize;
+                    ProtoValue::Bytes(take(bytes, &mut pos, len)?.to_vec())
+                },
+                5 => ProtoValue::Fixed32(u32::from_le_bytes(take(bytes, &mut pos, 4)?.try_into().expect("Took 4 bytes"))),
+                wire_type => return Err(format!("Unsupported protobuf wire type {}", wire_type)),
+            };
+            fields.push((field_number, value));
+        }
+        return Ok(Self {fields});
+    }
+
+    /// A string field, which is empty when it wasn't sent, as in proto3.
+    pub fn get_string(&self, field_number: u32) -> Result<String, String> {
+        return Ok(self.get_strings(field_number)?.pop().unwrap_or_default());
+    }
+
+    pub fn get_strings(&self, field_number: u32) -> Result<Vec<String>, String> {
+        return self.fields.iter()
+            .filter(|(number, _): &&(u32, ProtoValue)| *number == field_number)
+            .map(|(_, value): &(u32, ProtoValue)| match value {
+                ProtoValue::Bytes(bytes) => String::from_utf8(bytes.clone())
+                    .map_err(|_| format!("Field {} isn't UTF-8", field_number)),
+                _ => Err(format!("Field {} should be a string", field_number)),
+            })
+            .collect();
+    }
+
+    pub fn add_string(&mut self, field_number: u32, value: &str) {
+        self.fields.push((field_number, ProtoValue::Bytes(value.as_bytes().to_vec())));
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::new();
+        for (field_number, value) in &self.fields {
+            let (wire_type, payload): (u64, Vec<u8>) = match value {
+                ProtoValue::Varint(value) => (0, encode_varint(*value)),
+                ProtoValue::Fixed64(value) => (1, value.to_le_bytes().to_vec()),
+                ProtoValue::Bytes(value) => (2, [encode_varint(value.len() as u64), value.clone()].concat()),
+                ProtoValue::Fixed32(value) => (5, value.to_le_bytes().to_vec()),
+            };
+            bytes.extend(encode_varint((*field_number as u64) << 3 | wire_type));
+            bytes.extend(payload);
+        }
+        return bytes;
+    }
+}
+
+fn take<'a>(bytes: &'a [u8], pos: &mut usize, len: usize) -> Result<&'a [u8], String> {
+    let taken: &[u8] = pos.checked_add(len).and_then(|end: usize| bytes.get(*pos..end)).ok_or("Protobuf message ends early".to_string())?;
+    *pos += len;
+    return Ok(taken);
+}
+
+fn decode_varint(bytes: &[u8], pos: &mut usize) -> Result<u64, String> {
+    let mut value: u64 = 0;
+    for shift in (0..64).step_by(7) {
+        let byte: u8 = *bytes.get(*pos).ok_or("Protobuf message ends early".to_string())?;
+        *pos += 1;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    return Err("Protobuf varint is too long".to_string());
+}
+
+fn encode_varint(mut value: u64) -> Vec<u8> {
+    let mut bytes: Vec<u8> = Vec::new();
+    while value >= 0x80 {
+        bytes.push((value & 0x7f) as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+    return bytes;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn varints_match_the_protobuf_encoding() {
+        assert_eq!(encode_varint(1), vec![0x01]);
+        assert_eq!(encode_varint(150), vec![0x96, 0x01]);
+        assert_eq!(encode_varint(u64::MAX).len(), 10);
+        for value in [0, 127, 128, 300, 1 << 35, u64::MAX] {
+            assert_eq!(decode_varint(&encode_varint(value), &mut 0), Ok(value));
+        }
+    }
+
+    #[test]
+    fn decodes_every_wire_type() {
+        // Field 1 = 150, field 2 = "testing", field 3 (fixed64) = 1, field 4 (fixed32) = 2.
+        let bytes: Vec<u8> = vec![
+            0x08, 0x96, 0x01,
+            0x12, 0x07, b't', b'e', b's', b't', b'i', b'n', b'g',
+            0x19, 1, 0, 0, 0, 0, 0, 0, 0,
+            0x25, 2, 0, 0, 0,
+        ];
+        let message: ProtoMessage = ProtoMessage::decode(&bytes).unwrap();
+        assert_eq!(message.fields, vec![
+            (1, ProtoValue::Varint(150)),
+            (2, ProtoValue::Bytes(b"testing".to_vec())),
+            (3, ProtoValue::Fixed64(1)),
+            (4, ProtoValue::Fixed32(2)),
+        ]);
+        assert_eq!(message.encode(), bytes);
+    }
+
+    #[test]
+    fn strings_are_empty_when_missing_and_repeat_in_order() {
+        let mut message: ProtoMessage = ProtoMessage::default();
+        message.add_string(2, "coding");
+        message.add_string(2, "review");
+        let decoded: ProtoMessage = ProtoMessage::decode(&message.encode()).unwrap();
+        assert_eq!(decoded.get_strings(2), Ok(vec!["coding".to_string(), "review".to_string()]));
+        assert_eq!(decoded.get_string(2), Ok("review".to_string()));
+        assert_eq!(decoded.get_string(1), Ok(String::new()));
+    }
+
+    #[test]
+    fn rejects_malformed_messages() {
+        assert!(ProtoMessage::decode(&[0x08]).is_err());
+        assert!(ProtoMessage::decode(&[0x12, 0x05, b'a']).is_err());
+        assert!(ProtoMessage::decode(&[0x12, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]).is_err());
+        assert!(ProtoMessage::decode(&[0x0b]).is_err());
+        assert!(ProtoMessage::decode(&[0x80; 11]).is_err());
+        let wrong_type: ProtoMessage = ProtoMessage::decode(&[0x08, 0x01]).unwrap();
+        assert!(wrong_type.get_string(1).is_err());
+        let not_utf8: ProtoMessage = ProtoMessage::decode(&[0x0a, 0x01, 0xff]).unwrap();
+        assert!(not_utf8.get_string(1).is_err());
+    }
+}
@@ -0,0 +1,72 @@
+use chrono::prelude::{DateTime, Datelike, Local, NaiveTime};
+use serde::{Serialize, Deserialize};
+
+use crate::units::day::Day;
+
+pub const TIME_OF_DAY_FMT: &str = "%H:%M";
+const DEFAULT_FOLLOWED_WITHIN_MINUTES: u64 = 15;
+
+#[derive(Debug,Serialize,Deserialize,Clone,PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReminderKind {
+    /// Only fires if you haven't punched in yet today.
+    PunchIn,
+    /// Only fires if you're still punched in.
+    PunchOut,
+    /// Always fires.
+    Message,
+}
+
+/// A reminder the daemon sends to take a break once you've worked `after_minutes` without one.
+#[derive(Debug,Serialize,Deserialize,Clone)]
+pub struct BreakReminderConfig {
+    pub after_minutes: u64,
+    /// A break started this long after the reminder counts as taking it. Defaults to 15.
+    #[serde(default)]
+    pub followed_within_minutes: Option<u64>,
+}
+
+impl BreakReminderConfig {
+    pub fn get_followed_within_minutes(&self) -> u64 {
+        return self.followed_within_minutes.unwrap_or(DEFAULT_FOLLOWED_WITHIN_MINUTES);
+    }
+}
+
+/// A reminder the daemon sends once a day at (or after) a time of day.
+#[derive(Debug,Serialize,Deserialize,Clone)]
+pub struct Reminder {
+    pub at: String,
+    pub kind: ReminderKind,
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Three-letter weekday names, e.g. `[Mon, Tue]`. Empty means every day.
+    #[serde(default)]
+    pub weekdays: Vec<String>,
+}
+
+impl Reminder {
+    pub fn get_time(&self) -> Result<NaiveTime, String> {
+        return NaiveTime::parse_from_str(self.at.trim(), TIME_OF_DAY_FMT)
+            .map_err(|_| format!("Expected a reminder time like '09:05'! Given: '{}'", self.at));
+    }
+
+    fn applies_on_weekday(&self, now: &DateTime<Local>) -> bool {
+        let weekday: String = now.weekday().to_string();
+        return self.weekdays.is_empty() || self.weekdays.iter().any(|x: &String| x.eq_ignore_ascii_case(&weekday));
+    }
+
+    /// Returns the message to send if the reminder should fire at `now`, given today's day (if any).
+    pub fn get_due_message(&self, now: &DateTime<Local>, today: Option<&Day>) -> Option<String> {
+        let time: NaiveTime = self.get_time().ok()?;
+        if now.time() < time || !self.applies_on_weekday(now) {
+            return None;
+        }
+        let default_msg: &str = match (&self.kind, today) {
+            (ReminderKind::PunchIn, None) => "You haven't punched in yet today!",
+            (ReminderKind::PunchOut, Some(day)) if !day.has_ended() => "Don't forget to punch out!",
+            (ReminderKind::Message, _) => "Reminder",
+            (_, _) => return None,
+        };
+        return Some(self.message.clone().unwrap_or(default_msg.to_string()));
+    }
+}
@@ -0,0 +1,289 @@
+use crate::utils::aggregate::format_secs;
+use crate::utils::args::get_flag_value;
+use crate::utils::json::JsonValue;
+use crate::utils::terminal::get_output_width;
+
+const TAB_WIDTH: usize = 8;
+const COLUMN_GAP: usize = 2;
+const MIN_COLUMN_WIDTH: usize = 8;
+
+/// A single value in a report. Durations are kept as seconds so each renderer can
+/// pick its own representation.
+#[derive(Debug,Clone)]
+pub enum ReportValue {
+    Text(String),
+    Number(i64),
+    Secs(i64),
+}
+
+impl ReportValue {
+    pub fn as_display_string(&self) -> String {
+        return match self {
+            Self::Text(text) => text.clone(),
+            Self::Number(number) => number.to_string(),
+            Self::Secs(secs) => format_secs(*secs),
+        };
+    }
+
+    /// Durations become whole minutes in machine-readable formats.
+    pub fn as_machine_string(&self) -> String {
+        return match self {
+            Self::Text(text) => text.clone(),
+            Self::Number(number) => number.to_string(),
+            Self::Secs(secs) => (secs / 60).to_string(),
+        };
+    }
+
+    pub fn as_json(&self) -> JsonValue {
+        return match self {
+            Self::Text(text) => JsonValue::String(text.clone()),
+            Self::Number(number) => JsonValue::Number(*number),
+            Self::Secs(secs) => JsonValue::Number(secs / 60),
+        };
+    }
+}
+
+impl From<&str> for ReportValue {
+    fn from(text: &str) -> Self {
+        return Self::Text(text.to_string());
+    }
+}
+
+impl From<String> for ReportValue {
+    fn from(text: String) -> Self {
+        return Self::Text(text);
+    }
+}
+
+#[derive(Debug,Clone)]
+pub struct ReportSection {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<ReportValue>>,
+}
+
+impl ReportSection {
+    pub fn new(name: &str, columns: &[&str]) -> Self {
+        return Self {
+            name: name.to_string(),
+            columns: columns.iter().map(|x: &&str| x.to_string()).collect(),
+            rows: Vec::new(),
+        };
+    }
+
+    pub fn add_row(&mut self, row: Vec<ReportValue>) {
+        assert_eq!(row.len(), self.columns.len(), "Report rows should have a value for each column");
+        self.rows.push(row);
+    }
+
+    fn get_title(&self) -> String {
+        let title: String = self.name.replace('_', " ");
+        let mut chars = title.chars();
+        return match chars.next() {
+            Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+            None => title,
+        };
+    }
+}
+
+/// The output of a report command, made of named sections of rows.
+#[derive(Debug,Clone)]
+pub struct Report {
+    pub title: String,
+    pub sections: Vec<ReportSection>,
+}
+
+impl Report {
+    pub fn new(title: String) -> Self {
+        return Self {title: title, sections: Vec::new()};
+    }
+
+    pub fn add_section(&mut self, section: ReportSection) {
+        self.sections.push(section);
+    }
+
+    pub fn as_json(&self) -> JsonValue {
+        let sections: Vec<(String, JsonValue)> = self.sections.iter()
+            .map(|section: &ReportSection| (
+                section.name.clone(),
+                JsonValue::Array(section.rows.iter()
+                    .map(|row: &Vec<ReportValue>| JsonValue::Object(
+                        section.columns.iter().cloned()
+                            .zip(row.iter().map(|x: &ReportValue| x.as_json()))
+                            .collect()))
+                    .collect()),
+            ))
+            .collect();
+        return JsonValue::Object(vec![
+            ("title".to_string(), JsonValue::String(self.title.clone())),
+            ("sections".to_string(), JsonValue::Object(sections)),
+        ]);
+    }
+
+    /// Keeps only the named section, for piping a single table into other tools.
+    pub fn only_section(&mut self, name: &str) -> Result<(), String> {
+        if !self.sections.iter().any(|x: &ReportSection| x.name == name) {
+            let names: Vec<String> = self.sections.iter().map(|x: &ReportSection| x.name.clone()).collect();
+            return Err(format!("'{}' isn't a section of this report. Try one of: {}", name, names.join(", ")));
+        }
+        self.sections.retain(|x: &ReportSection| x.name == name);
+        return Ok(());
+    }
+}
+
+
+pub trait ReportRenderer {
+    fn render(&self, report: &Report) -> String;
+}
+
+/// Lays the sections out as aligned columns. With a `max_width`, the widest columns are
+/// narrowed to fit and their cells wrapped onto more lines.
+pub struct TableRenderer {
+    pub max_width: Option<usize>,
+}
+pub struct JsonRenderer;
+pub struct CsvRenderer;
+pub struct MarkdownRenderer;
+
+impl ReportRenderer for TableRenderer {
+    fn render(&self, report: &Report) -> String {
+        let mut out: String = format!("{}\n", report.title);
+        for section in &report.sections {
+            out += &format!("\n{}:\n", section.get_title());
+            if section.rows.is_empty() {
+                out += "\t(none)\n";
+                continue;
+            }
+            let cells: Vec<Vec<String>> = section.rows.iter()
+                .map(|row: &Vec<ReportValue>| row.iter().map(|x: &ReportValue| x.as_display_string()).collect())
+                .collect();
+            let mut widths: Vec<usize> = (0..section.columns.len())
+                .map(|col: usize| cells.iter()
+                    .map(|row: &Vec<String>| row[col].chars().count())
+                    .chain([section.columns[col].chars().count()])
+                    .max()
+                    .unwrap_or(0))
+                .collect();
+            if let Some(max_width) = self.max_width {
+                fit_widths(&mut widths, max_width);
+            }
+            let pad_row = |row: &Vec<String>| -> String {
+                let wrapped: Vec<Vec<String>> = row.iter().zip(&widths)
+                    .map(|(cell, width): (&String, &usize)| wrap_text(cell, *width))
+                    .collect();
+                let num_lines: usize = wrapped.iter().map(|lines: &Vec<String>| lines.len()).max().unwrap_or(1);
+                return (0..num_lines)
+                    .map(|line: usize| wrapped.iter().zip(&widths)
+                        .map(|(lines, width): (&Vec<String>, &usize)| format!("{:<width$}", lines.get(line).map(|x: &String| x.as_str()).unwrap_or(""), width = width))
+                        .collect::<Vec<String>>()
+                        .join("  ")
+                        .trim_end()
+                        .to_string())
+                    .map(|line: String| format!("\t{}\n", line))
+                    .collect();
+            };
+            out += &pad_row(&section.columns);
+            for row in &cells {
+                out += &pad_row(row);
+            }
+        }
+        return out;
+    }
+}
+
+/// Narrows the widest columns, one character at a time, until a row fits in `max_width`
+/// (counting the leading tab as 8), but never below `MIN_COLUMN_WIDTH`.
+fn fit_widths(widths: &mut Vec<usize>, max_width: usize) {
+    let available: usize = max_width.saturating_sub(TAB_WIDTH + COLUMN_GAP * widths.len().saturating_sub(1));
+    while widths.iter().sum::<usize>() > available {
+        let Some(widest) = widths.iter_mut().max().filter(|width: &&mut usize| **width > MIN_COLUMN_WIDTH) else {
+            return;
+        };
+        *widest -= 1;
+    }
+}
+
+/// Splits `text` into lines of at most `width` characters, at spaces where it can.
+fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut rest: Vec<char> = text.chars().collect();
+    while rest.len() > width {
+        let break_at: usize = rest[..=width].iter().rposition(|c: &char| *c == ' ').filter(|ind: &usize| *ind > 0).unwrap_or(width);
+        lines.push(rest[..break_at].iter().collect::<String>().trim_end().to_string());
+        rest = rest[break_at..].iter().collect::<String>().trim_start().chars().collect();
+    }
+    lines.push(rest.into_iter().collect());
+    return lines;
+}
+
+impl ReportRenderer for JsonRenderer {
+    fn render(&self, report: &Report) -> String {
+        return report.as_json().as_string() + "\n";
+    }
+}
+
+impl ReportRenderer for CsvRenderer {
+    fn render(&self, report: &Report) -> String {
+        let mut blocks: Vec<String> = Vec::new();
+        for section in &report.sections {
+            let mut block: String = String::new();
+            if report.sections.len() > 1 {
+                block += &format!("# {}\n", section.name);
+            }
+            block += &format!("{}\n", section.columns.iter().map(|x: &String| escape_csv(x)).collect::<Vec<String>>().join(","));
+            for row in &section.rows {
+                block += &format!(
+                    "{}\n", row.iter().map(|x: &ReportValue| escape_csv(&x.as_machine_string())).collect::<Vec<String>>().join(","));
+            }
+            blocks.push(block);
+        }
+        return blocks.join("\n");
+    }
+}
+
+impl ReportRenderer for MarkdownRenderer {
+    fn render(&self, report: &Report) -> String {
+        let mut out: String = format!("# {}\n", report.title);
+        for section in &report.sections {
+            out += &format!("\n## {}\n\n", section.get_title());
+            out += &format!("| {} |\n", section.columns.join(" | "));
+            out += &format!("|{}\n", " --- |".repeat(section.columns.len()));
+            for row in &section.rows {
+                out += &format!(
+                    "| {} |\n",
+                    row.iter().map(|x: &ReportValue| x.as_display_string().replace('|', "\\|")).collect::<Vec<String>>().join(" | "));
+            }
+        }
+        return out;
+    }
+}
+
+pub fn escape_csv(value: &str) -> String {
+    return if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    }
+    else {
+        value.to_string()
+    };
+}
+
+pub fn get_renderer(format: &str) -> Result<Box<dyn ReportRenderer>, String> {
+    return match format.trim() {
+        "table" => Ok(Box::new(TableRenderer {max_width: get_output_width()})),
+        "json" => Ok(Box::new(JsonRenderer)),
+        "csv" => Ok(Box::new(CsvRenderer)),
+        "markdown" | "md" => Ok(Box::new(MarkdownRenderer)),
+        other => Err(format!("'{}' is not a valid format. Try one of: table, json, csv, markdown", other)),
+    };
+}
+
+/// Renders the report with the renderer picked by `--format` (table by default),
+/// narrowed to `--section` if one was given.
+pub fn render_report_from_args(mut report: Report, args: &Vec<String>) -> Result<String, String> {
+    let format: String = get_flag_value(args, "--format").unwrap_or("table".to_string());
+    let renderer: Box<dyn ReportRenderer> = get_renderer(&format)?;
+    if let Some(section) = get_flag_value(args, "--section") {
+        report.only_section(&section)?;
+    }
+    return Ok(renderer.render(&report));
+}
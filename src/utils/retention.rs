@@ -0,0 +1,66 @@
+use chrono::prelude::NaiveDate;
+use serde::{Serialize, Deserialize};
+
+use crate::units::day::{delete_day_for_date, get_day_file_path_for_date, list_day_records_for_date, list_recorded_dates, read_day_for_date, write_day_unchecked};
+use crate::utils::event_log::forget_events;
+use crate::utils::file_io::delete_file;
+use crate::utils::samples::prune_samples;
+
+/// How long to keep days for, enforced by the daemon once a day, e.g.
+/// `retention: {older_than: 2y, keep_aggregates: true}`.
+#[derive(Debug,Serialize,Deserialize,Clone)]
+pub struct RetentionConfig {
+    /// An age like "90d", "6w", "18m" or "2y".
+    pub older_than: String,
+    /// Anonymize old days instead of deleting them, see `Day::anonymize`.
+    #[serde(default)]
+    pub keep_aggregates: bool,
+}
+
+/// What a prune did.
+#[derive(Debug,Default,Clone,Copy,PartialEq)]
+pub struct PruneOutcome {
+    pub deleted: usize,
+    pub anonymized: usize,
+    pub sample_days_deleted: usize,
+}
+
+/// Deletes, or with `keep_aggregates` anonymizes, the days recorded before `oldest_kept`.
+/// Window samples from before then are always deleted. Minutes behind isn't changed,
+/// since the days still counted towards it when they happened.
+pub fn prune_days_before(oldest_kept: &NaiveDate, keep_aggregates: bool) -> PruneOutcome {
+    let mut outcome: PruneOutcome = PruneOutcome::default();
+    for date in get_dates_to_prune(oldest_kept) {
+        if keep_aggregates {
+            let Ok(mut day) = read_day_for_date(&date) else {
+                continue;
+            };
+            let before: String = day.as_string();
+            day.anonymize();
+            if day.as_string() != before {
+                write_day_unchecked(&day);
+                // The log still has the day as it was, so it starts again from the file.
+                forget_events(&date);
+                outcome.anonymized += 1;
+            }
+            continue;
+        }
+        // Sync conflict copies and the like go too, not just the day's own file.
+        let own_path: String = get_day_file_path_for_date(&date);
+        for path in list_day_records_for_date(&date).into_iter().filter(|path: &String| *path != own_path) {
+            let _ = delete_file(&path);
+        }
+        let deleted: bool = delete_day_for_date(&date).is_ok();
+        forget_events(&date);
+        if deleted {
+            outcome.deleted += 1;
+        }
+    }
+    outcome.sample_days_deleted = prune_samples(oldest_kept);
+    return outcome;
+}
+
+/// The recorded dates that `prune_days_before` would delete or anonymize.
+pub fn get_dates_to_prune(oldest_kept: &NaiveDate) -> Vec<NaiveDate> {
+    return list_recorded_dates().into_iter().filter(|date: &NaiveDate| date < oldest_kept).collect();
+}
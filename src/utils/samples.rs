@@ -0,0 +1,129 @@
+use chrono::prelude::{DateTime, Local, NaiveDate, NaiveTime};
+use serde::{Serialize, Deserialize};
+
+use crate::utils::file_io::{BASE_DIR, create_dir_if_not_exists, delete_file, list_dir, read_file, write_file};
+
+pub const SAMPLES_DIR: &str = "activity/";
+const SAMPLE_TIME_FMT: &str = "%H:%M:%S";
+const DEFAULT_RETENTION_DAYS: u64 = 14;
+/// Stretches of a suggestion shorter than this are folded into the one before.
+const MIN_SUGGESTION_MINS: i64 = 5;
+
+/// Has the daemon note the focused window's title while you're working, so that time you
+/// didn't label can be broken down into tasks when you punch out. The samples never leave
+/// this machine and are deleted after `retention_days`.
+#[derive(Debug,Serialize,Deserialize,Clone)]
+pub struct WindowSamplingConfig {
+    /// Defaults to 14.
+    #[serde(default)]
+    pub retention_days: Option<u64>,
+    #[serde(default)]
+    pub rules: Vec<WindowTaskRule>,
+}
+
+/// Time in a window whose title contains `window_title` (ignoring case) is suggested as `task`.
+#[derive(Debug,Serialize,Deserialize,Clone)]
+pub struct WindowTaskRule {
+    pub window_title: String,
+    pub task: String,
+}
+
+impl WindowSamplingConfig {
+    pub fn get_retention_days(&self) -> u64 {
+        return self.retention_days.unwrap_or(DEFAULT_RETENTION_DAYS);
+    }
+
+    /// The task to suggest for time in a window. Without a matching rule, this is the
+    /// application's part of the title, like "Firefox" in "Docs - Mozilla Firefox".
+    pub fn get_task_for_title(&self, title: &str) -> String {
+        let lowercase_title: String = title.to_lowercase();
+        if let Some(rule) = self.rules.iter().find(|rule: &&WindowTaskRule| lowercase_title.contains(&rule.window_title.to_lowercase())) {
+            return rule.task.clone();
+        }
+        let app: &str = title.rsplit(" - ").next().unwrap_or(title);
+        return app.rsplit(" — ").next().unwrap_or(app).trim().to_string();
+    }
+}
+
+/// A stretch of time and the task suggested for it.
+#[derive(Debug,Clone,PartialEq)]
+pub struct SuggestedSpan {
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    pub task: String,
+}
+
+fn get_samples_path(date: &NaiveDate) -> String {
+    return format!("{}{}{}", BASE_DIR, SAMPLES_DIR, date);
+}
+
+/// Adds a sample of the focused window's title to the day's file.
+pub fn record_sample(now: &DateTime<Local>, title: &str) {
+    create_dir_if_not_exists(&(BASE_DIR.to_string() + SAMPLES_DIR));
+    let path: String = get_samples_path(&now.date_naive());
+    let mut contents: String = read_file(&path).unwrap_or_default();
+    contents += &format!("{}\t{}\n", now.format(SAMPLE_TIME_FMT), title.replace(['\t', '\n'], " "));
+    write_file(&path, contents);
+}
+
+/// The samples taken on `date`, oldest first.
+pub fn read_samples(date: &NaiveDate) -> Vec<(DateTime<Local>, String)> {
+    let contents: String = read_file(&get_samples_path(date)).unwrap_or_default();
+    return contents.lines()
+        .filter_map(|line: &str| {
+            let (time_str, title) = line.split_once('\t')?;
+            let time: NaiveTime = NaiveTime::parse_from_str(time_str, SAMPLE_TIME_FMT).ok()?;
+            let at: DateTime<Local> = date.and_time(time).and_local_timezone(Local).earliest()?;
+            return Some((at, title.to_string()));
+        })
+        .collect();
+}
+
+/// Deletes the samples from before `oldest_kept`, returning how many days' worth went.
+pub fn prune_samples(oldest_kept: &NaiveDate) -> usize {
+    let dir: String = BASE_DIR.to_string() + SAMPLES_DIR;
+    let mut num_deleted: usize = 0;
+    for name in list_dir(&dir).unwrap_or_default() {
+        let is_old: bool = NaiveDate::parse_from_str(&name, "%Y-%m-%d").is_ok_and(|date: NaiveDate| date < *oldest_kept);
+        if is_old && delete_file(&(dir.clone() + &name)).is_ok() {
+            num_deleted += 1;
+        }
+    }
+    return num_deleted;
+}
+
+/// Splits `start` to `end` up by the windows sampled during it. Each sample counts until
+/// the next one, and short stretches are folded into the one before so that a quick look
+/// at another window doesn't become a task of its own. Empty if nothing was sampled.
+pub fn suggest_breakdown(
+    samples: &Vec<(DateTime<Local>, String)>,
+    start: &DateTime<Local>,
+    end: &DateTime<Local>,
+    config: &WindowSamplingConfig) -> Vec<SuggestedSpan>
+{
+    let mut spans: Vec<SuggestedSpan> = Vec::new();
+    for (at, title) in samples.iter().filter(|(at, _)| start <= at && at < end) {
+        let task: String = config.get_task_for_title(title);
+        match spans.last_mut() {
+            Some(last) if last.task == task => {},
+            Some(last) => {
+                last.end = *at;
+                spans.push(SuggestedSpan {start: *at, end: *end, task: task});
+            },
+            None => spans.push(SuggestedSpan {start: *start, end: *end, task: task}),
+        }
+    }
+    let mut merged: Vec<SuggestedSpan> = Vec::new();
+    for span in spans {
+        match merged.last_mut() {
+            Some(last) if last.task == span.task || (span.end - span.start).num_minutes() < MIN_SUGGESTION_MINS => last.end = span.end,
+            _ => merged.push(span),
+        }
+    }
+    // The first stretch can only be folded forwards.
+    if merged.len() > 1 && (merged[0].end - merged[0].start).num_minutes() < MIN_SUGGESTION_MINS {
+        merged[1].start = merged[0].start;
+        merged.remove(0);
+    }
+    return merged;
+}
@@ -0,0 +1,84 @@
+use chrono::prelude::{DateTime, Local, NaiveDate, NaiveTime};
+use chrono::Duration;
+use serde::{Serialize, Deserialize};
+
+use crate::units::day::Day;
+use crate::utils::clock::parse_time_on;
+use crate::utils::reminders::TIME_OF_DAY_FMT;
+
+/// The name time outside every shift is reported under, at the normal rate.
+pub const REGULAR_SHIFT: &str = "regular";
+
+/// When a shift starts and ends on a particular day.
+type ShiftWindow = (DateTime<Local>, DateTime<Local>);
+
+/// Hours paid at a premium, like a night shift from "22:00" to "06:00" at 1.3 times the normal
+/// rate. Shifts ending earlier in the day than they start go on past midnight.
+#[derive(Debug,Serialize,Deserialize,Clone)]
+pub struct Shift {
+    pub name: String,
+    pub start: String,
+    pub end: String,
+    pub multiplier: f64,
+}
+
+impl Shift {
+    fn get_times(&self) -> Result<(NaiveTime, NaiveTime), String> {
+        let parse = |time_str: &String| NaiveTime::parse_from_str(time_str.trim(), TIME_OF_DAY_FMT)
+            .map_err(|_| format!("Shift '{}' should have times like \"22:00\"! Given: '{}'", self.name, time_str));
+        return Ok((parse(&self.start)?, parse(&self.end)?));
+    }
+
+    /// The times this shift runs that could overlap `date`: the one starting the day before,
+    /// for shifts going past midnight, and the one starting on the date.
+    fn get_windows_around(&self, date: &NaiveDate) -> Result<Vec<ShiftWindow>, String> {
+        let (start, end): (NaiveTime, NaiveTime) = self.get_times()?;
+        let mut windows: Vec<ShiftWindow> = Vec::new();
+        for start_date in [*date - Duration::days(1), *date] {
+            let end_date: NaiveDate = if end <= start {start_date + Duration::days(1)} else {start_date};
+            windows.push((
+                parse_time_on(&start.format(TIME_OF_DAY_FMT).to_string(), &start_date)?,
+                parse_time_on(&end.format(TIME_OF_DAY_FMT).to_string(), &end_date)?,
+            ));
+        }
+        return Ok(windows);
+    }
+}
+
+/// Checks that every shift's times can be read.
+pub fn check_shifts(shifts: &Vec<Shift>) -> Result<(), String> {
+    return shifts.iter().try_for_each(|shift: &Shift| shift.get_times().map(|_| ()));
+}
+
+/// Time worked in each shift on a closed day, in the order of `shifts` and then the regular
+/// time outside them. Where shifts overlap, the first one in the config counts.
+pub fn get_shift_secs(day: &Day, shifts: &Vec<Shift>) -> Result<Vec<(String, i64)>, String> {
+    let date: NaiveDate = day.get_day_start().as_dt().date_naive();
+    let mut windows: Vec<Vec<ShiftWindow>> = Vec::new();
+    for shift in shifts {
+        windows.push(shift.get_windows_around(&date)?);
+    }
+    let mut secs: Vec<i64> = vec![0; shifts.len() + 1];
+    for (ind, block) in day.timeblocks.iter().enumerate() {
+        let Some(block_end) = block.get_end().map(|end| end.as_dt()).filter(|_| !day.is_break(ind)) else {
+            continue;
+        };
+        let block_start: DateTime<Local> = block.get_start().as_dt();
+        // Between consecutive cuts, the block is either in a shift or not the whole way.
+        let mut cuts: Vec<DateTime<Local>> = vec![block_start, block_end];
+        for (window_start, window_end) in windows.iter().flatten() {
+            cuts.extend([*window_start, *window_end].into_iter().filter(|cut| block_start < *cut && *cut < block_end));
+        }
+        cuts.sort();
+        for pair in cuts.windows(2) {
+            let shift_ind: usize = windows.iter()
+                .position(|shift_windows| shift_windows.iter().any(|(start, end)| *start <= pair[0] && pair[1] <= *end))
+                .unwrap_or(shifts.len());
+            secs[shift_ind] += (pair[1] - pair[0]).num_seconds();
+        }
+    }
+    return Ok(shifts.iter().map(|shift: &Shift| shift.name.clone())
+        .chain([REGULAR_SHIFT.to_string()])
+        .zip(secs)
+        .collect());
+}
@@ -0,0 +1,150 @@
+use std::path::PathBuf;
+use std::process::{Command, Output, id};
+use chrono::prelude::{DateTime, Local};
+use serde::{Serialize, Deserialize};
+
+use crate::utils::file_io::expand_path;
+
+const SIGNATURE_HEADER: &str = "punch signature v1";
+const BASE64_CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// The ed25519 key to sign exports with, made with e.g.
+/// `openssl genpkey -algorithm ed25519 -out ~/.punch-card/signing.pem`. Signing and
+/// verifying are done by `openssl`.
+#[derive(Debug,Serialize,Deserialize,Clone)]
+pub struct SigningConfig {
+    /// Path to the private key, in PEM.
+    pub private_key: String,
+}
+
+/// A detached signature, kept next to the file it signs as `<file>.sig`.
+#[derive(Debug,Clone,PartialEq)]
+pub struct Signature {
+    /// The signer's public key, as base64 DER.
+    pub public_key: String,
+    pub signature: Vec<u8>,
+    pub signed_at: String,
+}
+
+impl Signature {
+    pub fn as_string(&self) -> String {
+        return format!(
+            "{}\nalgorithm: ed25519\npublic-key: {}\nsigned-at: {}\nsignature: {}\n",
+            SIGNATURE_HEADER, self.public_key, self.signed_at, encode_base64(&self.signature));
+    }
+
+    pub fn from_string(text: &str) -> Result<Self, String> {
+        if text.lines().next() != Some(SIGNATURE_HEADER) {
+            return Err("This isn't a punch signature file".to_string());
+        }
+        let get = |key: &str| -> Result<String, String> {
+            return text.lines()
+                .find_map(|line: &str| line.strip_prefix(&format!("{}: ", key)))
+                .map(|value: &str| value.trim().to_string())
+                .ok_or(format!("The signature file has no '{}'", key));
+        };
+        return Ok(Self {
+            public_key: get("public-key")?,
+            signature: decode_base64(&get("signature")?)?,
+            signed_at: get("signed-at").unwrap_or_default(),
+        });
+    }
+}
+
+pub fn get_signature_path(path: &str) -> String {
+    return format!("{}.sig", path);
+}
+
+/// Signs the file at `path`, writing the signature to `<path>.sig`, whose path is returned.
+pub fn sign_file(path: &str, signing: &SigningConfig, now: &DateTime<Local>) -> Result<String, String> {
+    let key_path: String = expand_path(&signing.private_key);
+    let signature_tmp: PathBuf = get_temp_path("sig");
+    let signed: Result<Vec<u8>, String> = run_openssl(&["pkeyutl", "-sign", "-rawin", "-inkey", &key_path, "-in", path, "-out", &signature_tmp.to_string_lossy()])
+        .and_then(|_| std::fs::read(&signature_tmp).map_err(|err| err.to_string()));
+    let _ = std::fs::remove_file(&signature_tmp);
+    let public_key_der: Vec<u8> = run_openssl(&["pkey", "-in", &key_path, "-pubout", "-outform", "DER"])?;
+    let signature: Signature = Signature {
+        public_key: encode_base64(&public_key_der),
+        signature: signed?,
+        signed_at: now.to_rfc3339(),
+    };
+    let signature_path: String = get_signature_path(path);
+    std::fs::write(&signature_path, signature.as_string())
+        .map_err(|err| format!("Couldn't write '{}': {}", signature_path, err))?;
+    return Ok(signature_path);
+}
+
+/// Checks the file at `path` against a signature, using `public_key_pem` if given, or else
+/// the key in the signature itself.
+pub fn verify_file(path: &str, signature: &Signature, public_key_pem: Option<&str>) -> Result<(), String> {
+    let public_key: String = match public_key_pem {
+        Some(pem) => pem.to_string(),
+        None => format!("-----BEGIN PUBLIC KEY-----\n{}\n-----END PUBLIC KEY-----\n", signature.public_key),
+    };
+    let key_tmp: PathBuf = get_temp_path("pub.pem");
+    let signature_tmp: PathBuf = get_temp_path("sig");
+    let verified: Result<Vec<u8>, String> = std::fs::write(&key_tmp, public_key)
+        .and_then(|_| std::fs::write(&signature_tmp, &signature.signature))
+        .map_err(|err| err.to_string())
+        .and_then(|_| run_openssl(&[
+            "pkeyutl", "-verify", "-pubin", "-rawin", "-inkey", &key_tmp.to_string_lossy(),
+            "-in", path, "-sigfile", &signature_tmp.to_string_lossy()]));
+    let _ = std::fs::remove_file(&key_tmp);
+    let _ = std::fs::remove_file(&signature_tmp);
+    return verified.map(|_| ());
+}
+
+/// The SHA-256 of the file at `path`, as lowercase hex.
+pub fn get_sha256(path: &str) -> Result<String, String> {
+    let output: Vec<u8> = run_openssl(&["dgst", "-sha256", "-r", path])?;
+    return String::from_utf8_lossy(&output).split_whitespace().next()
+        .map(|hash: &str| hash.to_lowercase())
+        .ok_or("openssl printed no checksum".to_string());
+}
+
+fn get_temp_path(suffix: &str) -> PathBuf {
+    return std::env::temp_dir().join(format!("punch-{}-{}", id(), suffix));
+}
+
+/// Runs `openssl` and returns what it printed.
+fn run_openssl(args: &[&str]) -> Result<Vec<u8>, String> {
+    let output: Output = Command::new("openssl").args(args).output()
+        .map_err(|err| format!("Couldn't run openssl: {}", err))?;
+    if !output.status.success() {
+        let stderr: String = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        let stdout: String = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        return Err(format!("openssl failed: {}", if stderr.is_empty() {stdout} else {stderr}));
+    }
+    return Ok(output.stdout);
+}
+
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut encoded: String = String::new();
+    for chunk in bytes.chunks(3) {
+        let n: u32 = chunk.iter().enumerate().fold(0, |n: u32, (i, byte): (usize, &u8)| n | (*byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            encoded.push(match i <= chunk.len() {
+                true => BASE64_CHARS[(n >> (18 - 6 * i) & 63) as usize] as char,
+                false => '=',
+            });
+        }
+    }
+    return encoded;
+}
+
+fn decode_base64(text: &str) -> Result<Vec<u8>, String> {
+    let mut bytes: Vec<u8> = Vec::new();
+    let mut n: u32 = 0;
+    let mut num_bits: u32 = 0;
+    for c in text.trim().trim_end_matches('=').bytes() {
+        let value: usize = BASE64_CHARS.iter().position(|x: &u8| *x == c).ok_or(format!("Invalid base64: '{}'", text))?;
+        n = (n << 6) | value as u32;
+        num_bits += 6;
+        if num_bits >= 8 {
+            num_bits -= 8;
+            bytes.push((n >> num_bits) as u8);
+            n &= (1 << num_bits) - 1;
+        }
+    }
+    return Ok(bytes);
+}
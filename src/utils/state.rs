@@ -0,0 +1,104 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use serde::{Serialize, Deserialize};
+
+use crate::units::day::list_recorded_dates;
+use crate::utils::access::is_read_only;
+use crate::utils::checksums::{Checksummed, delete_checksum_at, warn_if_corrupted, write_checksum};
+use crate::utils::config::{Config, get_config, update_config};
+use crate::utils::file_io::{BASE_DIR, delete_file, expand_path, path_exists, read_file, write_file};
+use crate::utils::team::get_data_dir;
+
+pub const STATE_FILE: &str = "state";
+
+/// Whether this run has already warned that there's no state file.
+static WARNED_MISSING: AtomicBool = AtomicBool::new(false);
+
+/// What punch keeps count of as you work, apart from the days themselves. It's kept out of
+/// the config, but beside it rather than with the days, so that changing `data_dir` or
+/// joining a team doesn't lose it.
+#[derive(Debug,Serialize,Deserialize,Clone,Default)]
+pub struct State {
+    minutes_behind: i64,
+    minutes_behind_non_neg: u64,
+}
+
+impl State {
+    pub fn minutes_behind(&self) -> i64 {
+        return self.minutes_behind;
+    }
+
+    /// Minutes behind since you last caught up, never counting time ahead.
+    pub fn minutes_behind_non_neg(&self) -> u64 {
+        return self.minutes_behind_non_neg;
+    }
+
+    pub fn update_minutes_behind(&mut self, delta: i64) {
+        let true_time_behind: i64 = self.minutes_behind + delta;
+        let non_neg_time_behind: i64 = self.minutes_behind_non_neg as i64 + delta;
+        self.minutes_behind = true_time_behind;
+        self.minutes_behind_non_neg = if true_time_behind < 0 {0} else {non_neg_time_behind} as u64;
+    }
+}
+
+pub fn get_state_path() -> String {
+    return expand_path(BASE_DIR) + STATE_FILE;
+}
+
+/// Where versions before the state was kept beside the config kept it.
+fn get_old_state_path() -> String {
+    return get_data_dir() + STATE_FILE;
+}
+
+fn parse_state(path: &str, yaml_str: &str) -> State {
+    return serde_yaml::from_str(yaml_str).unwrap_or_else(|err| panic!("Couldn't read '{}': {}", path, err));
+}
+
+/// The state. Before there's a state file beside the config, it's moved there from the data
+/// folder, or from the config itself, where older versions kept minutes behind. With
+/// `--read-only`, what would be moved is only read.
+pub fn get_state() -> State {
+    if let Ok(yaml_str) = read_file(&get_state_path()) {
+        warn_if_corrupted(&Checksummed::State, &yaml_str);
+        return parse_state(&get_state_path(), &yaml_str);
+    }
+    let old_path: String = get_old_state_path();
+    if old_path != get_state_path() {
+        if let Ok(yaml_str) = read_file(&old_path) {
+            let state: State = parse_state(&old_path, &yaml_str);
+            if !is_read_only() {
+                update_state(&state);
+                let _ = delete_file(&old_path);
+                delete_checksum_at(&get_data_dir(), &Checksummed::State);
+            }
+            return state;
+        }
+    }
+    let mut config: Config = get_config();
+    if let Some((minutes_behind, minutes_behind_non_neg)) = config.take_legacy_minutes_behind() {
+        let state: State = State {minutes_behind, minutes_behind_non_neg};
+        if !is_read_only() {
+            update_state(&state);
+            update_config(config);
+        }
+        return state;
+    }
+    if !list_recorded_dates().is_empty() && !WARNED_MISSING.swap(true, Ordering::Relaxed) {
+        eprintln!(
+            "Warning: There's no state file at '{}', so your time behind starts from 0 even though days are recorded. If you've moved it, put it back, or set your balance with 'punch adjust-balance'.",
+            get_state_path());
+    }
+    return State::default();
+}
+/// Starts a new user off with an empty state, so that a missing one later means it's been
+/// lost rather than not written yet.
+pub fn create_state_if_not_exists() {
+    if !path_exists(&get_state_path()) && !path_exists(&get_old_state_path()) {
+        update_state(&State::default());
+    }
+}
+
+pub fn update_state(state: &State) {
+    let yaml_str: String = serde_yaml::to_string(state).expect("State should be serializable");
+    write_file(&get_state_path(), yaml_str.clone());
+    write_checksum(&Checksummed::State, &yaml_str);
+}
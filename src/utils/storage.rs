@@ -0,0 +1,166 @@
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions, create_dir_all, read, read_dir, remove_file, rename};
+use std::io::{Error, ErrorKind, Write};
+use std::path::Path;
+use std::process::id;
+use std::sync::{Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Counts the temp files written by this process, to give each its own name.
+static NUM_TEMP_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Where punch keeps its files. Paths are always expanded before they get here.
+pub trait Storage: Send + Sync {
+    fn read_bytes(&self, path: &str) -> Result<Vec<u8>, Error>;
+
+    fn write_bytes(&self, path: &str, contents: &[u8]);
+
+    fn read(&self, path: &str) -> Result<String, Error> {
+        return String::from_utf8(self.read_bytes(path)?)
+            .map_err(|_| Error::new(ErrorKind::InvalidData, format!("'{}' isn't text", path)));
+    }
+
+    fn write(&self, path: &str, contents: &str) {
+        self.write_bytes(path, contents.as_bytes());
+    }
+
+    fn exists(&self, path: &str) -> bool;
+
+    fn remove(&self, path: &str) -> Result<(), Error>;
+
+    fn create_dir(&self, path: &str);
+
+    /// Names of the entries directly inside `dir`.
+    fn list_dir(&self, dir: &str) -> Result<Vec<String>, Error>;
+}
+
+/// Where a file is written before it's renamed to `path`: a hidden file in the same folder,
+/// named apart from any other write's, in this process or another.
+fn get_temp_path(path: &str) -> String {
+    let (dir, name): (&str, &str) = path.rsplit_once(['/', '\\']).unwrap_or((".", path));
+    return format!("{}/.{}.{}-{}.tmp", dir, name, id(), NUM_TEMP_FILES.fetch_add(1, Ordering::SeqCst));
+}
+
+/// The real filesystem.
+pub struct FsStorage;
+
+impl Storage for FsStorage {
+    fn read_bytes(&self, path: &str) -> Result<Vec<u8>, Error> {
+        return read(path);
+    }
+
+    /// Writes to a temp file next to `path` and renames it over, so the file is never seen
+    /// half written, and two writers of the same file just leave whichever wrote last.
+    fn write_bytes(&self, path: &str, contents: &[u8]) {
+        let temp_path: String = get_temp_path(path);
+        let file_result: Result<File, Error> = OpenOptions::new()
+            .create(true).write(true).truncate(true)
+            .open(&temp_path);
+        if let Ok(mut file) = file_result {
+            file.write_all(contents).expect("Couldn't write to file!");
+        }
+        else {
+            panic!("Couldn't create file {temp_path}");
+        }
+        if let Err(err) = rename(&temp_path, path) {
+            let _ = remove_file(&temp_path);
+            panic!("Couldn't replace file {path}: {err}");
+        }
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        return Path::new(path).exists();
+    }
+
+    fn remove(&self, path: &str) -> Result<(), Error> {
+        return remove_file(path);
+    }
+
+    fn create_dir(&self, path: &str) {
+        let expect_msg: String = format!("Unable to create directory: '{path}'");
+        create_dir_all(path).expect(&expect_msg);
+    }
+
+    fn list_dir(&self, dir: &str) -> Result<Vec<String>, Error> {
+        return Ok(read_dir(dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect());
+    }
+}
+
+/// Keeps every file in memory, so that whole workflows can run without touching disk.
+#[allow(dead_code)]
+#[derive(Default)]
+pub struct MemoryStorage {
+    files: Mutex<BTreeMap<String, Vec<u8>>>,
+    dirs: Mutex<Vec<String>>,
+}
+
+#[allow(dead_code)]
+impl MemoryStorage {
+    pub fn new() -> Self {
+        return Self::default();
+    }
+
+    /// Every stored path, sorted.
+    pub fn paths(&self) -> Vec<String> {
+        return self.files.lock().unwrap().keys().cloned().collect();
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn read_bytes(&self, path: &str) -> Result<Vec<u8>, Error> {
+        return self.files.lock().unwrap().get(path).cloned()
+            .ok_or(Error::new(ErrorKind::NotFound, format!("No such file: '{}'", path)));
+    }
+
+    fn write_bytes(&self, path: &str, contents: &[u8]) {
+        self.files.lock().unwrap().insert(path.to_string(), contents.to_vec());
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        let path: &str = path.trim_end_matches('/');
+        return self.files.lock().unwrap().contains_key(path)
+            || self.dirs.lock().unwrap().iter().any(|dir: &String| dir.trim_end_matches('/') == path);
+    }
+
+    fn remove(&self, path: &str) -> Result<(), Error> {
+        return match self.files.lock().unwrap().remove(path) {
+            Some(_) => Ok(()),
+            None => Err(Error::new(ErrorKind::NotFound, format!("No such file: '{}'", path))),
+        };
+    }
+
+    fn create_dir(&self, path: &str) {
+        self.dirs.lock().unwrap().push(path.to_string());
+    }
+
+    fn list_dir(&self, dir: &str) -> Result<Vec<String>, Error> {
+        if !self.exists(dir) {
+            return Err(Error::new(ErrorKind::NotFound, format!("No such directory: '{}'", dir)));
+        }
+        let prefix: String = dir.trim_end_matches('/').to_string() + "/";
+        return Ok(self.files.lock().unwrap().keys()
+            .filter_map(|path: &String| path.strip_prefix(&prefix))
+            .filter(|name: &&str| !name.contains('/'))
+            .map(|name: &str| name.to_string())
+            .collect());
+    }
+}
+
+static STORAGE: RwLock<Option<Arc<dyn Storage>>> = RwLock::new(None);
+
+/// The storage in use: the filesystem unless another backend has been set.
+pub fn get_storage() -> Arc<dyn Storage> {
+    return match STORAGE.read().unwrap().as_ref() {
+        Some(storage) => storage.clone(),
+        None => Arc::new(FsStorage),
+    };
+}
+
+/// Swaps the storage for the whole process. `None` goes back to the filesystem.
+#[allow(dead_code)]
+pub fn set_storage(storage: Option<Arc<dyn Storage>>) {
+    *STORAGE.write().unwrap() = storage;
+}
@@ -0,0 +1,44 @@
+/// The option closest to `value` by edit distance, ignoring case, if it's close enough to
+/// likely be a typo: within a third of its length, and at least 2.
+pub fn get_nearest_match<'a>(value: &str, options: &'a Vec<String>) -> Option<&'a String> {
+    let max_distance: usize = (value.chars().count() / 3).max(2);
+    return options.iter()
+        .map(|option: &String| (get_edit_distance(&value.to_lowercase(), &option.to_lowercase()), option))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, option)| option);
+}
+
+/// What `value` was likely meant to be, out of `(word, suggestion)` pairs so synonyms can
+/// point at the name they stand for: the only word it abbreviates, or else the nearest one.
+pub fn get_suggestion<'a>(value: &str, words: &'a Vec<(String, String)>) -> Option<&'a String> {
+    let value: String = value.to_lowercase();
+    let mut abbreviated: Vec<&String> = words.iter()
+        .filter(|(word, _)| value.chars().count() >= 2 && word.to_lowercase().starts_with(&value))
+        .map(|(_, suggestion)| suggestion)
+        .collect();
+    abbreviated.sort();
+    abbreviated.dedup();
+    if let [suggestion] = abbreviated.as_slice() {
+        return Some(suggestion);
+    }
+    let options: Vec<String> = words.iter().map(|(word, _)| word.clone()).collect();
+    let nearest: &String = get_nearest_match(&value, &options)?;
+    return words.iter().find(|(word, _)| word == nearest).map(|(_, suggestion)| suggestion);
+}
+
+/// The Levenshtein distance: how many characters need adding, removing or changing to turn
+/// `a` into `b`.
+pub fn get_edit_distance(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b_chars.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current: Vec<usize> = vec![i + 1];
+        for (j, b_char) in b_chars.iter().enumerate() {
+            let substitution: usize = previous[j] + if a_char == *b_char {0} else {1};
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    return previous[b_chars.len()];
+}
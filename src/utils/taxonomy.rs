@@ -0,0 +1,39 @@
+use std::collections::BTreeMap;
+use serde::{Serialize, Deserialize};
+
+use crate::utils::suggestions::get_nearest_match;
+
+/// The categories summaries can use, each with the projects allowed in it (any, if none are
+/// listed), so typos don't split the time in reports.
+#[derive(Debug,Serialize,Deserialize,Clone,Default)]
+pub struct Taxonomy {
+    /// Reject unknown categories and projects, instead of only warning about them.
+    #[serde(default)]
+    pub strict: bool,
+    #[serde(default)]
+    pub categories: BTreeMap<String, Vec<String>>,
+}
+
+impl Taxonomy {
+    /// `Ok(None)` if the category and project are known, and otherwise what's wrong with them,
+    /// as a warning in `Ok` or, when strict, an error.
+    pub fn check(&self, category: &str, project: &str) -> Result<Option<String>, String> {
+        let problem: Option<String> = match self.categories.get(category) {
+            None => Some(describe_unknown("category", category, &self.categories.keys().cloned().collect())),
+            Some(projects) if !projects.is_empty() && !projects.iter().any(|x: &String| x == project) => Some(format!(
+                "{} (in '{}')", describe_unknown("project", project, projects), category)),
+            Some(_) => None,
+        };
+        return match (problem, self.strict) {
+            (Some(msg), true) => Err(msg),
+            (problem, _) => Ok(problem),
+        };
+    }
+}
+
+fn describe_unknown(kind: &str, value: &str, options: &Vec<String>) -> String {
+    return match get_nearest_match(value, options) {
+        Some(nearest) => format!("Unknown {} '{}'. Did you mean '{}'?", kind, value, nearest),
+        None => format!("Unknown {} '{}'. Expected one of: {}", kind, value, options.join(", ")),
+    };
+}
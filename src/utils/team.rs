@@ -0,0 +1,40 @@
+use serde::{Serialize, Deserialize};
+
+use crate::utils::config::{Config, get_config_path};
+use crate::utils::file_io::{BASE_DIR, FromString, expand_config_dir, expand_path, list_dir, read_file};
+
+/// Team mode, where everyone's days live side by side under a shared (synced) folder,
+/// each in a folder of their own named after `user`, e.g.
+/// `team: {root: ~/Dropbox/studio-punch, user: ravi}`. The config stays on each machine.
+#[derive(Debug,Serialize,Deserialize,Clone)]
+pub struct TeamConfig {
+    pub root: String,
+    pub user: String,
+}
+
+impl TeamConfig {
+    pub fn get_root_dir(&self) -> String {
+        return expand_config_dir(&self.root);
+    }
+
+    /// The folders in the root, one per person on the team.
+    pub fn list_members(&self) -> Vec<String> {
+        let mut members: Vec<String> = list_dir(&self.get_root_dir()).unwrap_or_default().into_iter()
+            .filter(|name: &String| !name.starts_with('.'))
+            .collect();
+        members.sort();
+        return members;
+    }
+}
+
+/// Where days and the index are kept: your own folder under the team root in team mode,
+/// otherwise the config's `data_dir`, or `~/.punch-card/`. Always ends in "/".
+pub fn get_data_dir() -> String {
+    let config: Option<Config> = read_file(&get_config_path()).ok()
+        .and_then(|yaml_str: String| Config::try_from_string(&yaml_str).ok());
+    return match (config.as_ref().and_then(|config: &Config| config.team()), config.as_ref().and_then(|config: &Config| config.data_dir())) {
+        (Some(team), _) => team.get_root_dir() + &team.user + "/",
+        (None, Some(data_dir)) => expand_config_dir(data_dir),
+        (None, None) => expand_path(BASE_DIR),
+    };
+}
@@ -0,0 +1,74 @@
+use serde::{Serialize, Deserialize};
+
+use crate::units::components::{PlannedBlock, TimeBlock};
+use crate::units::day::Day;
+use crate::utils::file_io::{BASE_DIR, create_dir_if_not_exists, delete_file, list_dir, path_exists, read_file, write_file};
+use crate::utils::work_summary::WorkSummary;
+
+pub const TEMPLATES_DIR: &str = "templates/";
+
+/// A typical day saved with `punch template save`, to start days like it from with
+/// `punch in --template`.
+#[derive(Debug,Serialize,Deserialize,Clone)]
+pub struct DayTemplate {
+    pub planned_blocks: Vec<PlannedBlock>,
+    /// Summaries to start the day with, so recurring tasks already have their category and project.
+    #[serde(default)]
+    pub summaries: Vec<WorkSummary>,
+    #[serde(default)]
+    pub time_to_do: Option<u64>,
+}
+
+impl DayTemplate {
+    /// Takes a closed day's blocks, leaving out breaks, as the plan for days like it.
+    pub fn from_day(day: &Day) -> Self {
+        return Self {
+            planned_blocks: day.timeblocks.iter().enumerate()
+                .filter(|(ind, _)| !day.is_break(*ind))
+                .map(|(_, block): (usize, &TimeBlock)| PlannedBlock::new(
+                    block.get_task_name(),
+                    block.get_length_mins().unwrap_or(0) as u64,
+                    Some(block.get_start().as_dt().format("%H:%M").to_string())))
+                .collect(),
+            summaries: day.summaries.clone(),
+            time_to_do: Some(day.time_to_do),
+        };
+    }
+}
+
+fn get_template_path(name: &str) -> Result<String, String> {
+    if name.trim().is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(format!("'{}' can't be used as a template name.", name));
+    }
+    return Ok(format!("{}{}{}", BASE_DIR, TEMPLATES_DIR, name.trim()));
+}
+
+pub fn read_template(name: &str) -> Result<DayTemplate, String> {
+    let path: String = get_template_path(name)?;
+    if !path_exists(&path) {
+        return Err(format!("There's no template called '{}'. See 'punch template list'.", name));
+    }
+    let yaml_str: String = read_file(&path).map_err(|err| format!("Couldn't read the template '{}': {}", name, err))?;
+    return serde_yaml::from_str(&yaml_str).map_err(|err| format!("The template '{}' isn't valid: {}", name, err));
+}
+
+pub fn write_template(name: &str, template: &DayTemplate) -> Result<(), String> {
+    let path: String = get_template_path(name)?;
+    create_dir_if_not_exists(&(BASE_DIR.to_string() + TEMPLATES_DIR));
+    write_file(&path, serde_yaml::to_string(template).expect("Templates should be serializable"));
+    return Ok(());
+}
+
+pub fn delete_template(name: &str) -> Result<(), String> {
+    let path: String = get_template_path(name)?;
+    if !path_exists(&path) {
+        return Err(format!("There's no template called '{}'.", name));
+    }
+    return delete_file(&path).map_err(|err| format!("Couldn't delete the template '{}': {}", name, err));
+}
+
+pub fn list_templates() -> Vec<String> {
+    let mut names: Vec<String> = list_dir(&(BASE_DIR.to_string() + TEMPLATES_DIR)).unwrap_or_default();
+    names.sort();
+    return names;
+}
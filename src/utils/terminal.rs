@@ -0,0 +1,64 @@
+use std::env::var;
+use std::fs::File;
+use std::io::{IsTerminal, Write, stdout};
+use std::process::{Command, Stdio};
+
+use crate::utils::config::get_config;
+
+const DEFAULT_PAGER: &str = "less -FRX";
+/// Set as the config's `pager` to always print straight to the terminal.
+const PAGER_OFF: &str = "off";
+
+/// The width to fit tables to: the config's `output_width`, or else the terminal's width when
+/// printing to one. `None` when the output goes to a file or a pipe.
+pub fn get_output_width() -> Option<usize> {
+    if let Some(width) = get_config().output_width() {
+        return Some(width);
+    }
+    if !stdout().is_terminal() {
+        return None;
+    }
+    return var("COLUMNS").ok()
+        .and_then(|columns: String| columns.trim().parse().ok())
+        .or(get_terminal_size().map(|(_, columns): (usize, usize)| columns));
+}
+
+/// Prints `text`, through the config's `pager` or else `PAGER` (or `less`) if it's too long to
+/// fit on the terminal. Goes straight to stdout when that isn't a terminal.
+pub fn print_paged(text: &str) {
+    let pager: Vec<String> = get_config().pager().cloned()
+        .or(var("PAGER").ok())
+        .filter(|pager: &String| !pager.trim().is_empty())
+        .unwrap_or(DEFAULT_PAGER.to_string())
+        .split_whitespace()
+        .map(|x: &str| x.to_string())
+        .collect();
+    let num_rows: Option<usize> = var("LINES").ok()
+        .and_then(|lines: String| lines.trim().parse().ok())
+        .or(get_terminal_size().map(|(rows, _): (usize, usize)| rows));
+    let fits: bool = num_rows.is_none_or(|rows: usize| text.lines().count() < rows);
+    if !stdout().is_terminal() || fits || pager[0] == PAGER_OFF {
+        print!("{}", text);
+        return;
+    }
+    let child = Command::new(&pager[0]).args(&pager[1..]).stdin(Stdio::piped()).spawn();
+    let Ok(mut child) = child else {
+        // No pager to be found, so everything is printed after all.
+        print!("{}", text);
+        return;
+    };
+    if let Some(mut pager_stdin) = child.stdin.take() {
+        // The pager closes its input when quit early, which isn't a problem.
+        let _ = pager_stdin.write_all(text.as_bytes());
+    }
+    let _ = child.wait();
+}
+
+/// The terminal's rows and columns, from `stty`. `None` if there's no terminal to ask.
+fn get_terminal_size() -> Option<(usize, usize)> {
+    let tty: File = File::open("/dev/tty").ok()?;
+    let output = Command::new("stty").arg("size").stdin(tty).stderr(Stdio::null()).output().ok()?;
+    let size: String = String::from_utf8_lossy(&output.stdout).to_string();
+    let (rows, columns): (&str, &str) = size.trim().split_once(' ')?;
+    return Some((rows.parse().ok()?, columns.parse().ok()?));
+}
@@ -0,0 +1,37 @@
+use serde::{Serialize, Deserialize};
+
+use crate::units::interval::Interval;
+use crate::utils::file_io::{read_file, write_file};
+use crate::utils::team::get_data_dir;
+
+pub const TIMERS_FILE: &str = "timers";
+
+/// A stopwatch started with `punch timer start`. Timers are kept apart from the days, so
+/// they never change the time done.
+#[derive(Debug,Serialize,Deserialize,Clone)]
+pub struct Timer {
+    pub label: String,
+    pub interval: Interval,
+}
+
+impl Timer {
+    pub fn is_running(&self) -> bool {
+        return !self.interval.has_end();
+    }
+}
+
+pub fn get_timers_path() -> String {
+    return get_data_dir() + TIMERS_FILE;
+}
+
+/// Every timer so far, oldest first.
+pub fn read_timers() -> Vec<Timer> {
+    return match read_file(&get_timers_path()) {
+        Ok(yaml_str) => serde_yaml::from_str(&yaml_str).unwrap_or_default(),
+        Err(_) => Vec::new(),
+    };
+}
+
+pub fn write_timers(timers: &Vec<Timer>) {
+    write_file(&get_timers_path(), serde_yaml::to_string(timers).expect("Timers should be serializable"));
+}
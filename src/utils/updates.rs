@@ -0,0 +1,162 @@
+use std::process::{Command, Output};
+use serde::{Serialize, Deserialize};
+
+use crate::utils::json::{JsonValue, parse_json};
+
+const GITHUB_API: &str = "https://api.github.com/repos";
+const DEFAULT_REPOSITORY: &str = "Ravi-d-B/punch-card";
+
+/// Where `punch self-update` looks for new releases, and how it checks them.
+#[derive(Debug,Serialize,Deserialize,Clone)]
+pub struct UpdateConfig {
+    /// Set to false to turn update checks off entirely, e.g. where punch is installed by a
+    /// package manager.
+    #[serde(default="get_true")]
+    pub enabled: bool,
+    /// The GitHub repository to take releases from, as `owner/name`.
+    #[serde(default)]
+    pub repository: Option<String>,
+    /// The ed25519 public key (PEM) releases are signed with. When set, a release is only
+    /// installed if its binary has a matching `.sig` next to it.
+    #[serde(default)]
+    pub public_key: Option<String>,
+}
+
+impl Default for UpdateConfig {
+    fn default() -> Self {
+        return Self {enabled: true, repository: None, public_key: None};
+    }
+}
+
+impl UpdateConfig {
+    pub fn get_repository(&self) -> &str {
+        return self.repository.as_deref().unwrap_or(DEFAULT_REPOSITORY);
+    }
+}
+
+fn get_true() -> bool {
+    return true;
+}
+
+#[derive(Debug,Clone)]
+pub struct Release {
+    pub tag: String,
+    pub assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Debug,Clone)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub url: String,
+}
+
+impl Release {
+    /// The binary (or archive of it) built for this OS and architecture, going by the usual
+    /// names in release assets, like `punch-x86_64-unknown-linux-gnu.tar.gz`.
+    pub fn get_binary_asset(&self) -> Option<&ReleaseAsset> {
+        let os_names: Vec<&str> = match std::env::consts::OS {
+            "macos" => vec!["macos", "darwin", "apple"],
+            "windows" => vec!["windows", "win64", "msvc"],
+            other => vec![other],
+        };
+        return self.assets.iter().find(|asset: &&ReleaseAsset| {
+            let name: String = asset.name.to_lowercase();
+            return !is_sidecar(&name)
+                && name.contains(std::env::consts::ARCH)
+                && os_names.iter().any(|os: &&str| name.contains(os));
+        });
+    }
+
+    /// The checksums published for `asset`: its own `<asset>.sha256`, or else a list of them
+    /// all, like `SHA256SUMS` or `checksums.txt`.
+    pub fn get_checksum_asset(&self, asset: &ReleaseAsset) -> Option<&ReleaseAsset> {
+        let own_name: String = format!("{}.sha256", asset.name);
+        return self.assets.iter().find(|x: &&ReleaseAsset| x.name == own_name)
+            .or_else(|| self.assets.iter().find(|x: &&ReleaseAsset| {
+                let name: String = x.name.to_lowercase();
+                return name.contains("sha256sums") || name.contains("checksums");
+            }));
+    }
+
+    pub fn get_signature_asset(&self, asset: &ReleaseAsset) -> Option<&ReleaseAsset> {
+        let signature_name: String = format!("{}.sig", asset.name);
+        return self.assets.iter().find(|x: &&ReleaseAsset| x.name == signature_name);
+    }
+
+    pub fn get_version(&self) -> &str {
+        return self.tag.trim_start_matches('v');
+    }
+}
+
+fn is_sidecar(name: &str) -> bool {
+    return [".sha256", ".sig", ".txt", ".asc", ".sbom", ".json"].iter().any(|ext: &&str| name.ends_with(ext))
+        || name.contains("sha256sums");
+}
+
+/// The latest release of `repository`, from the GitHub API.
+pub fn fetch_latest_release(repository: &str) -> Result<Release, String> {
+    let url: String = format!("{}/{}/releases/latest", GITHUB_API, repository);
+    let body: Vec<u8> = run_curl(&["-H", "Accept: application/vnd.github+json", &url])?;
+    let response: JsonValue = parse_json(&String::from_utf8_lossy(&body))
+        .map_err(|msg: String| format!("Couldn't read GitHub's response: {}", msg))?;
+    let tag: String = response.get("tag_name").and_then(|x: &JsonValue| x.as_str())
+        .ok_or("GitHub's response had no release tag")?
+        .to_string();
+    let Some(JsonValue::Array(items)) = response.get("assets") else {
+        return Err(format!("Release {} has no assets", tag));
+    };
+    let assets: Vec<ReleaseAsset> = items.iter()
+        .filter_map(|item: &JsonValue| Some(ReleaseAsset {
+            name: item.get("name")?.as_str()?.to_string(),
+            url: item.get("browser_download_url")?.as_str()?.to_string(),
+        }))
+        .collect();
+    return Ok(Release {tag, assets});
+}
+
+pub fn download(asset: &ReleaseAsset, path: &str) -> Result<(), String> {
+    run_curl(&["--output", path, &asset.url])?;
+    return Ok(());
+}
+
+pub fn download_text(asset: &ReleaseAsset) -> Result<String, String> {
+    return Ok(String::from_utf8_lossy(&run_curl(&[&asset.url])?).to_string());
+}
+
+/// The SHA-256 listed for `name` in a checksum file, either `sha256sum`'s `<hash>  <name>`
+/// lines or a file with only the hash in it.
+pub fn find_checksum(checksums: &str, name: &str) -> Option<String> {
+    let entries: Vec<(&str, Option<&str>)> = checksums.lines()
+        .filter_map(|line: &str| {
+            let mut words = line.split_whitespace();
+            let hash: &str = words.next()?;
+            return Some((hash, words.next().map(|file: &str| file.trim_start_matches('*'))));
+        })
+        .collect();
+    return match entries.as_slice() {
+        [(hash, None)] => Some(hash.to_lowercase()),
+        _ => entries.iter()
+            .find(|(_, file)| file.is_some_and(|file: &str| file == name || file.ends_with(&format!("/{}", name))))
+            .map(|(hash, _)| hash.to_lowercase()),
+    };
+}
+
+/// Whether `candidate` is a later version than `current`, comparing their numbers in order.
+pub fn is_newer_version(candidate: &str, current: &str) -> bool {
+    let parse = |version: &str| -> Vec<u64> {
+        return version.trim_start_matches('v')
+            .split(['.', '-'])
+            .map_while(|part: &str| part.parse::<u64>().ok())
+            .collect();
+    };
+    return parse(candidate) > parse(current);
+}
+
+fn run_curl(args: &[&str]) -> Result<Vec<u8>, String> {
+    let output: Output = Command::new("curl").args(["--silent", "--show-error", "--fail", "--location"]).args(args).output()
+        .map_err(|err| format!("Couldn't run curl to check for updates: {}", err))?;
+    if !output.status.success() {
+        return Err(format!("Couldn't reach GitHub: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    return Ok(output.stdout);
+}
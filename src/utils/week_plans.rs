@@ -0,0 +1,57 @@
+use std::collections::BTreeMap;
+use chrono::prelude::{Datelike, NaiveDate};
+use chrono::Duration;
+use serde::{Serialize, Deserialize};
+
+use crate::units::interval::DATE_FMT;
+use crate::utils::file_io::{create_dir_if_not_exists, read_file, write_file};
+use crate::utils::index::DaySummary;
+use crate::utils::team::get_data_dir;
+
+pub const WEEK_PLANS_DIR: &str = "week_plans/";
+
+/// The hours you mean to spend on each project in a week, from `punch plan-week`.
+#[derive(Debug,Serialize,Deserialize,Clone,Default)]
+pub struct WeekPlan {
+    pub projects: BTreeMap<String, f64>,
+}
+
+impl WeekPlan {
+    /// Each planned project's planned and done seconds, given the week's days. Projects come
+    /// from the days' summaries, as in reports.
+    pub fn get_progress(&self, summaries: &Vec<DaySummary>) -> Vec<(String, i64, i64)> {
+        return self.projects.iter()
+            .map(|(project, hours): (&String, &f64)| {
+                let done_secs: i64 = summaries.iter()
+                    .flat_map(|summary: &DaySummary| summary.projects.iter())
+                    .filter(|project_time| project_time.project == *project)
+                    .map(|project_time| project_time.secs)
+                    .sum();
+                return (project.clone(), (hours * 3600.0).round() as i64, done_secs);
+            })
+            .collect();
+    }
+}
+
+/// The Monday the week of `date` starts on, which plans are kept under.
+pub fn get_week_start(date: &NaiveDate) -> NaiveDate {
+    return *date - Duration::days(date.weekday().num_days_from_monday() as i64);
+}
+
+fn get_week_plan_path(week_start: &NaiveDate) -> String {
+    return get_data_dir() + WEEK_PLANS_DIR + &week_start.format(DATE_FMT).to_string();
+}
+
+/// The plan for the week starting on `week_start`, if one was made.
+pub fn read_week_plan(week_start: &NaiveDate) -> Result<Option<WeekPlan>, String> {
+    return match read_file(&get_week_plan_path(week_start)) {
+        Ok(yaml_str) => serde_yaml::from_str(&yaml_str).map(Some)
+            .map_err(|err| format!("The plan for the week of {} isn't valid: {}", week_start, err)),
+        Err(_) => Ok(None),
+    };
+}
+
+pub fn write_week_plan(week_start: &NaiveDate, plan: &WeekPlan) {
+    create_dir_if_not_exists(&(get_data_dir() + WEEK_PLANS_DIR));
+    write_file(&get_week_plan_path(week_start), serde_yaml::to_string(plan).expect("Week plans should be serializable"));
+}
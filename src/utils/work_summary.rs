@@ -6,6 +6,9 @@ pub struct WorkSummary {
     project: String,
     task: String,
     summary: String,
+    /// Whether there's more to do on it, for `punch carry` to bring it over to the next day.
+    #[serde(default)]
+    unfinished: bool,
 }
 
 impl WorkSummary {
@@ -15,6 +18,52 @@ impl WorkSummary {
             project: project,
             task: task,
             summary: summary,
+            unfinished: false,
         };
     }
+
+    pub fn get_category(&self) -> &String {
+        return &self.category;
+    }
+
+    pub fn get_project(&self) -> &String {
+        return &self.project;
+    }
+
+    pub fn set_category(&mut self, category: String) {
+        self.category = category;
+    }
+
+    pub fn set_project(&mut self, project: String) {
+        self.project = project;
+    }
+
+    pub fn get_task(&self) -> &String {
+        return &self.task;
+    }
+
+    pub fn set_task(&mut self, task: String) {
+        self.task = task;
+    }
+
+    pub fn set_summary(&mut self, summary: String) {
+        self.summary = summary;
+    }
+
+    pub fn get_summary(&self) -> &String {
+        return &self.summary;
+    }
+
+    pub fn is_unfinished(&self) -> bool {
+        return self.unfinished;
+    }
+
+    pub fn set_unfinished(&mut self, unfinished: bool) {
+        self.unfinished = unfinished;
+    }
+
+    pub fn as_short_string(&self) -> String {
+        let status: &str = if self.unfinished {" (unfinished)"} else {""};
+        return format!("{}/{}/{}: {}{}", self.category, self.project, self.task, self.summary, status);
+    }
 }
@@ -0,0 +1,179 @@
+use crate::utils::zip::write_stored_zip;
+
+pub enum XlsxCell {
+    Text(String),
+    Number(f64),
+    /// A formula without the leading "=", like "SUM(E2:E10)".
+    Formula(String),
+    Empty,
+}
+
+pub struct XlsxRow {
+    pub cells: Vec<XlsxCell>,
+    pub bold: bool,
+}
+
+pub struct XlsxSheet {
+    pub name: String,
+    pub rows: Vec<XlsxRow>,
+}
+
+impl XlsxSheet {
+    pub fn new(name: &str) -> Self {
+        return Self {name: name.to_string(), rows: Vec::new()};
+    }
+
+    pub fn add_row(&mut self, cells: Vec<XlsxCell>, bold: bool) {
+        self.rows.push(XlsxRow {cells: cells, bold: bold});
+    }
+}
+
+/// Builds a minimal XLSX workbook with a worksheet for each sheet, in order. Formulas are
+/// left for the spreadsheet to calculate when it opens the file.
+pub fn build_xlsx(sheets: &Vec<XlsxSheet>) -> Vec<u8> {
+    let sheet_nums = 1..=sheets.len();
+    let content_types: String = format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">"#,
+            r#"<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>"#,
+            r#"<Default Extension="xml" ContentType="application/xml"/>"#,
+            r#"<Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>"#,
+            r#"<Override PartName="/xl/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml"/>"#,
+            "{}</Types>"),
+        sheet_nums.clone()
+            .map(|n: usize| format!(
+                r#"<Override PartName="/xl/worksheets/sheet{}.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>"#, n))
+            .collect::<String>());
+    let root_rels: String = concat!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+        r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+        r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/>"#,
+        "</Relationships>").to_string();
+    let workbook: String = format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            r#"<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">"#,
+            "<sheets>{}</sheets><calcPr fullCalcOnLoad=\"1\"/></workbook>"),
+        sheets.iter().zip(sheet_nums.clone())
+            .map(|(sheet, n): (&XlsxSheet, usize)| format!(
+                r#"<sheet name="{}" sheetId="{}" r:id="rId{}"/>"#, escape_xml(&sheet.name), n, n))
+            .collect::<String>());
+    // Styles come after the sheets in the workbook's relationships.
+    let workbook_rels: String = format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+            "{}",
+            r#"<Relationship Id="rId{}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/>"#,
+            "</Relationships>"),
+        sheet_nums.clone()
+            .map(|n: usize| format!(
+                r#"<Relationship Id="rId{}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet{}.xml"/>"#, n, n))
+            .collect::<String>(),
+        sheets.len() + 1);
+    // Style 0 is the default and style 1 is bold.
+    let styles: String = concat!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+        r#"<styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">"#,
+        r#"<fonts count="2"><font><sz val="11"/><name val="Calibri"/></font><font><b/><sz val="11"/><name val="Calibri"/></font></fonts>"#,
+        r#"<fills count="2"><fill><patternFill patternType="none"/></fill><fill><patternFill patternType="gray125"/></fill></fills>"#,
+        r#"<borders count="1"><border><left/><right/><top/><bottom/><diagonal/></border></borders>"#,
+        r#"<cellStyleXfs count="1"><xf numFmtId="0" fontId="0" fillId="0" borderId="0"/></cellStyleXfs>"#,
+        r#"<cellXfs count="2"><xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0"/><xf numFmtId="0" fontId="1" fillId="0" borderId="0" xfId="0" applyFont="1"/></cellXfs>"#,
+        "</styleSheet>").to_string();
+
+    let mut files: Vec<(String, Vec<u8>)> = vec![
+        ("[Content_Types].xml".to_string(), content_types.into_bytes()),
+        ("_rels/.rels".to_string(), root_rels.into_bytes()),
+        ("xl/workbook.xml".to_string(), workbook.into_bytes()),
+        ("xl/_rels/workbook.xml.rels".to_string(), workbook_rels.into_bytes()),
+        ("xl/styles.xml".to_string(), styles.into_bytes()),
+    ];
+    for (sheet, n) in sheets.iter().zip(sheet_nums) {
+        files.push((format!("xl/worksheets/sheet{}.xml", n), build_sheet_xml(sheet).into_bytes()));
+    }
+    return write_stored_zip(&files);
+}
+
+fn build_sheet_xml(sheet: &XlsxSheet) -> String {
+    let mut rows: String = String::new();
+    for (row_ind, row) in sheet.rows.iter().enumerate() {
+        let row_num: usize = row_ind + 1;
+        let style: &str = if row.bold {r#" s="1""#} else {""};
+        rows += &format!(r#"<row r="{}">"#, row_num);
+        for (col_ind, cell) in row.cells.iter().enumerate() {
+            let cell_ref: String = format!("{}{}", column_name(col_ind), row_num);
+            rows += &match cell {
+                XlsxCell::Text(text) => format!(
+                    r#"<c r="{}" t="inlineStr"{}><is><t xml:space="preserve">{}</t></is></c>"#, cell_ref, style, escape_xml(text)),
+                XlsxCell::Number(number) => format!(r#"<c r="{}"{}><v>{}</v></c>"#, cell_ref, style, number),
+                XlsxCell::Formula(formula) => format!(r#"<c r="{}"{}><f>{}</f></c>"#, cell_ref, style, escape_xml(formula)),
+                XlsxCell::Empty => String::new(),
+            };
+        }
+        rows += "</row>";
+    }
+    return format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">"#,
+            "<sheetData>{}</sheetData></worksheet>"),
+        rows);
+}
+
+/// The letters for a column counted from 0, e.g. 0 is "A" and 27 is "AB".
+pub fn column_name(col_ind: usize) -> String {
+    let mut name: String = String::new();
+    let mut n: usize = col_ind + 1;
+    while n > 0 {
+        name.insert(0, (b'A' + ((n - 1) % 26) as u8) as char);
+        n = (n - 1) / 26;
+    }
+    return name;
+}
+
+fn escape_xml(text: &str) -> String {
+    return text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_columns_like_spreadsheets_do() {
+        let names: Vec<String> = [0, 1, 25, 26, 27, 51, 52, 701, 702].iter().map(|i: &usize| column_name(*i)).collect();
+        assert_eq!(names, vec!["A", "B", "Z", "AA", "AB", "AZ", "BA", "ZZ", "AAA"]);
+    }
+
+    #[test]
+    fn escapes_text_for_xml() {
+        assert_eq!(escape_xml(r#"R&D <"x">"#), "R&amp;D &lt;&quot;x&quot;&gt;");
+    }
+
+    #[test]
+    fn writes_cells_by_reference_with_bold_rows_styled() {
+        let mut sheet: XlsxSheet = XlsxSheet::new("Days");
+        sheet.add_row(vec![XlsxCell::Text("Date".to_string()), XlsxCell::Text("Minutes".to_string())], true);
+        sheet.add_row(vec![XlsxCell::Text("a<b".to_string()), XlsxCell::Number(480.0)], false);
+        sheet.add_row(vec![XlsxCell::Empty, XlsxCell::Formula("SUM(B2:B2)".to_string())], false);
+        let xml: String = build_sheet_xml(&sheet);
+        assert!(xml.contains(r#"<row r="1"><c r="A1" t="inlineStr" s="1"><is><t xml:space="preserve">Date</t></is></c>"#), "{}", xml);
+        assert!(xml.contains(r#"<c r="A2" t="inlineStr"><is><t xml:space="preserve">a&lt;b</t></is></c><c r="B2"><v>480</v></c>"#), "{}", xml);
+        assert!(xml.contains(r#"<row r="3"><c r="B3"><f>SUM(B2:B2)</f></c></row>"#), "{}", xml);
+    }
+
+    #[test]
+    fn workbooks_list_every_sheet() {
+        let sheets: Vec<XlsxSheet> = vec![XlsxSheet::new("Days"), XlsxSheet::new("Q&A")];
+        let xlsx: Vec<u8> = build_xlsx(&sheets);
+        let text: String = String::from_utf8_lossy(&xlsx).to_string();
+        assert!(xlsx.starts_with(b"PK\x03\x04"));
+        for part in ["[Content_Types].xml", "_rels/.rels", "xl/workbook.xml", "xl/styles.xml", "xl/worksheets/sheet1.xml", "xl/worksheets/sheet2.xml"] {
+            assert!(text.contains(part), "{} is missing", part);
+        }
+        assert!(text.contains(r#"<sheet name="Q&amp;A" sheetId="2" r:id="rId2"/>"#));
+        assert!(text.contains(r#"Id="rId3" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles""#));
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::BTreeSet;
+use serde_yaml::{Mapping, Value};
+
+/// A top-level key of a YAML file with the lines that set it, and the comments and blank
+/// lines above it.
+struct Entry {
+    key: String,
+    leading: Vec<String>,
+    lines: Vec<String>,
+}
+
+/// Rewrites the top-level settings of `existing` to `values`, but only touches those whose
+/// values changed. Everything else, including comments, blank lines and key order, is kept as
+/// it was. Keys `values` doesn't have are dropped, along with the comments right above them,
+/// and new keys go at the end, unless they're null.
+///
+/// This is what a format-preserving editor like `toml_edit` would give a TOML config, done
+/// by hand for the YAML one, since punch has no TOML dependency. It only needs to understand
+/// top-level keys, as values are compared and written with serde_yaml.
+pub fn update_yaml_text(existing: &str, values: &Mapping) -> String {
+    let (entries, trailing): (Vec<Entry>, Vec<String>) = split_entries(existing);
+    let mut lines: Vec<String> = Vec::new();
+    let mut seen: BTreeSet<String> = BTreeSet::new();
+    for entry in entries {
+        let Some(new_value) = values.get(entry.key.as_str()) else {
+            lines.extend(entry.leading.into_iter().filter(|line: &String| line.trim().is_empty()));
+            continue;
+        };
+        lines.extend(entry.leading.iter().cloned());
+        seen.insert(entry.key.clone());
+        match parse_entry_value(&entry.key, &entry.lines.join("\n")).as_ref() == Some(new_value) {
+            true => lines.extend(entry.lines),
+            false => lines.extend(format_entry(&entry.key, new_value, get_inline_comment(&entry))),
+        }
+    }
+    for (key, value) in values {
+        let Some(key) = key.as_str() else {
+            continue;
+        };
+        if !seen.contains(key) && !value.is_null() {
+            lines.extend(format_entry(key, value, None));
+        }
+    }
+    lines.extend(trailing);
+    return lines.join("\n") + "\n";
+}
+
+/// Splits a YAML file into its top-level entries, plus any comments after the last one.
+fn split_entries(text: &str) -> (Vec<Entry>, Vec<String>) {
+    let mut entries: Vec<Entry> = Vec::new();
+    let mut pending: Vec<String> = Vec::new();
+    for line in text.lines() {
+        if let Some(key) = get_top_level_key(line) {
+            entries.push(Entry {key, leading: std::mem::take(&mut pending), lines: vec![line.to_string()]});
+        }
+        else if is_nested_line(line) && !entries.is_empty() {
+            // Blank lines and comments within a setting belong to it, not the next one.
+            let entry: &mut Entry = entries.last_mut().expect("There's an entry");
+            entry.lines.append(&mut pending);
+            entry.lines.push(line.to_string());
+        }
+        else {
+            pending.push(line.to_string());
+        }
+    }
+    return (entries, pending);
+}
+
+fn get_top_level_key(line: &str) -> Option<String> {
+    if line.starts_with(|c: char| c.is_whitespace() || c == '#' || c == '-') {
+        return None;
+    }
+    let (key, rest): (&str, &str) = line.split_once(':')?;
+    if !(rest.is_empty() || rest.starts_with(' ')) {
+        return None;
+    }
+    return Some(key.trim().trim_matches(['"', '\'']).to_string());
+}
+
+/// Lines of a setting after its key: indented, or list items, which serde_yaml doesn't indent.
+fn is_nested_line(line: &str) -> bool {
+    return line.starts_with([' ', '\t']) || line == "-" || line.starts_with("- ");
+}
+
+fn parse_entry_value(key: &str, text: &str) -> Option<Value> {
+    return serde_yaml::from_str::<Mapping>(text).ok()?.get(key).cloned();
+}
+
+/// The comment at the end of a one-line setting, like `minutes_behind: 30  # after the audit`,
+/// so it can be kept when the value changes.
+fn get_inline_comment(entry: &Entry) -> Option<String> {
+    let [line] = entry.lines.as_slice() else {
+        return None;
+    };
+    let position: usize = line.find(" #")?;
+    // A " #" inside a quoted string isn't a comment, and would change the value if cut off.
+    let same_value: bool = parse_entry_value(&entry.key, &line[..position]) == parse_entry_value(&entry.key, line);
+    return same_value.then(|| line[position..].trim().to_string());
+}
+
+fn format_entry(key: &str, value: &Value, comment: Option<String>) -> Vec<String> {
+    let mut mapping: Mapping = Mapping::new();
+    mapping.insert(Value::String(key.to_string()), value.clone());
+    let mut lines: Vec<String> = serde_yaml::to_string(&mapping).expect("A mapping is serializable")
+        .lines()
+        .map(|line: &str| line.to_string())
+        .collect();
+    if let (Some(comment), 1) = (comment, lines.len()) {
+        lines[0] = format!("{}  {}", lines[0], comment);
+    }
+    return lines;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_mapping(yaml_str: &str) -> Mapping {
+        return serde_yaml::from_str(yaml_str).unwrap();
+    }
+
+    const EXISTING: &str = "# How long a day is.
+day_in_minutes: 480  # the usual
+
+# Days off.
+holidays:
+- 2024-12-25
+# Where days are kept.
+data_dir: null
+# the end
+";
+
+    #[test]
+    fn leaves_the_text_alone_when_nothing_changed() {
+        assert_eq!(update_yaml_text(EXISTING, &to_mapping(EXISTING)), EXISTING);
+    }
+
+    #[test]
+    fn rewrites_only_the_changed_settings_and_keeps_their_comments() {
+        let values: Mapping = to_mapping("day_in_minutes: 450\nholidays: [2024-12-25, 2024-12-26]\ndata_dir: null\n");
+        assert_eq!(update_yaml_text(EXISTING, &values), "# How long a day is.
+day_in_minutes: 450  # the usual
+
+# Days off.
+holidays:
+- 2024-12-25
+- 2024-12-26
+# Where days are kept.
+data_dir: null
+# the end
+");
+    }
+
+    #[test]
+    fn drops_removed_settings_and_adds_new_ones_at_the_end() {
+        let values: Mapping = to_mapping("day_in_minutes: 480\ndata_dir: null\neditor: nano\nteam: null\n");
+        assert_eq!(update_yaml_text(EXISTING, &values), "# How long a day is.
+day_in_minutes: 480  # the usual
+
+# Where days are kept.
+data_dir: null
+editor: nano
+# the end
+");
+    }
+
+    #[test]
+    fn a_hash_inside_a_quoted_value_isnt_a_comment() {
+        let existing: &str = "editor: \"code #1\"\n";
+        assert_eq!(update_yaml_text(existing, &to_mapping("editor: vim\n")), "editor: vim\n");
+    }
+}
@@ -0,0 +1,102 @@
+/// Packs files into an uncompressed ("stored") zip archive, which is all that formats
+/// built on zip, like XLSX, need.
+pub fn write_stored_zip(files: &Vec<(String, Vec<u8>)>) -> Vec<u8> {
+    let mut out: Vec<u8> = Vec::new();
+    let mut central_directory: Vec<u8> = Vec::new();
+    for (name, data) in files {
+        let offset: u32 = out.len() as u32;
+        let crc: u32 = crc32(data);
+        // Version needed, flags, method (stored), time and date (1980-01-01 00:00).
+        let common: Vec<u8> = [
+            &20u16.to_le_bytes()[..], &0u16.to_le_bytes(), &0u16.to_le_bytes(), &0u16.to_le_bytes(), &0x21u16.to_le_bytes(),
+            &crc.to_le_bytes(), &(data.len() as u32).to_le_bytes(), &(data.len() as u32).to_le_bytes(),
+            &(name.len() as u16).to_le_bytes(), &0u16.to_le_bytes(),
+        ].concat();
+
+        out.extend_from_slice(&0x04034b50u32.to_le_bytes());
+        out.extend_from_slice(&common);
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(data);
+
+        central_directory.extend_from_slice(&0x02014b50u32.to_le_bytes());
+        central_directory.extend_from_slice(&20u16.to_le_bytes());
+        central_directory.extend_from_slice(&common);
+        // Comment length, disk number, internal and external attributes.
+        central_directory.extend_from_slice(&[0u8; 10]);
+        central_directory.extend_from_slice(&offset.to_le_bytes());
+        central_directory.extend_from_slice(name.as_bytes());
+    }
+    let central_directory_offset: u32 = out.len() as u32;
+    out.extend_from_slice(&central_directory);
+    out.extend_from_slice(&0x06054b50u32.to_le_bytes());
+    out.extend_from_slice(&[0u8; 4]);
+    out.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(central_directory.len() as u32).to_le_bytes());
+    out.extend_from_slice(&central_directory_offset.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes());
+    return out;
+}
+
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0xffffffff;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = match crc & 1 {
+                1 => (crc >> 1) ^ 0xedb88320,
+                _ => crc >> 1,
+            };
+        }
+    }
+    return !crc;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_u16(bytes: &[u8], at: usize) -> u16 {
+        return u16::from_le_bytes([bytes[at], bytes[at + 1]]);
+    }
+
+    fn read_u32(bytes: &[u8], at: usize) -> u32 {
+        return u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+    }
+
+    #[test]
+    fn crc32_matches_the_standard_check_values() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf43926);
+        assert_eq!(crc32(b"The quick brown fox jumps over the lazy dog"), 0x414fa339);
+    }
+
+    #[test]
+    fn archives_point_at_each_of_their_files() {
+        let files: Vec<(String, Vec<u8>)> = vec![
+            ("a.txt".to_string(), b"first".to_vec()),
+            ("dir/b.xml".to_string(), b"<second/>".to_vec()),
+        ];
+        let zip: Vec<u8> = write_stored_zip(&files);
+
+        // The end of central directory record is the last 22 bytes.
+        let end: usize = zip.len() - 22;
+        assert_eq!(read_u32(&zip, end), 0x06054b50);
+        assert_eq!((read_u16(&zip, end + 8), read_u16(&zip, end + 10)), (2, 2));
+        let mut entry: usize = read_u32(&zip, end + 16) as usize;
+        assert_eq!(entry + read_u32(&zip, end + 12) as usize, end);
+
+        for (name, data) in &files {
+            assert_eq!(read_u32(&zip, entry), 0x02014b50);
+            assert_eq!(read_u32(&zip, entry + 16), crc32(data));
+            let name_len: usize = read_u16(&zip, entry + 28) as usize;
+            assert_eq!(&zip[entry + 46..entry + 46 + name_len], name.as_bytes());
+
+            let local: usize = read_u32(&zip, entry + 42) as usize;
+            assert_eq!(read_u32(&zip, local), 0x04034b50);
+            let data_start: usize = local + 30 + read_u16(&zip, local + 26) as usize;
+            assert_eq!(&zip[data_start..data_start + data.len()], data.as_slice());
+            entry += 46 + name_len;
+        }
+    }
+}
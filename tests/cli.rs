@@ -0,0 +1,230 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::Output;
+
+use punch::testing::CliRunner;
+
+fn cli() -> CliRunner {
+    return CliRunner::new(env!("CARGO_BIN_EXE_punch"));
+}
+
+#[test]
+fn undo_takes_back_the_latest_change() {
+    let cli: CliRunner = cli();
+    cli.ok("2024-03-04 09:00", &["in", "coding"]);
+    cli.ok("2024-03-04 17:00", &["out"]);
+    let day_file: PathBuf = cli.data_path("days/2024-03-04");
+    assert!(fs::read_to_string(&day_file).unwrap().contains("17:00:00"));
+
+    let output: String = cli.ok("2024-03-04 17:05", &["undo"]);
+    assert!(output.contains("Undid the"), "{}", output);
+    assert!(!fs::read_to_string(&day_file).unwrap().contains("17:00:00"));
+
+    cli.ok("2024-03-04 17:06", &["undo"]);
+    assert!(!day_file.exists());
+    assert!(!cli.run("2024-03-04 17:07", &["undo"]).status.success());
+}
+
+#[test]
+fn recalc_rebuilds_day_files_edited_by_hand() {
+    let cli: CliRunner = cli();
+    cli.ok("2024-03-04 09:00", &["in"]);
+    cli.ok("2024-03-04 17:00", &["out"]);
+    let day_file: PathBuf = cli.data_path("days/2024-03-04");
+    let original: String = fs::read_to_string(&day_file).unwrap();
+    fs::write(&day_file, original.replace("17:00:00", "18:00:00")).unwrap();
+
+    let output: String = cli.ok("2024-03-05 09:00", &["recalc"]);
+    assert!(output.contains("2024-03-04: rebuilt"), "{}", output);
+    assert_eq!(fs::read_to_string(&day_file).unwrap(), original);
+    assert!(cli.ok("2024-03-05 09:00", &["recalc"]).contains("already matches"));
+}
+
+#[test]
+fn other_devices_changes_show_up_without_recalc() {
+    let cli: CliRunner = cli();
+    cli.ok("2024-03-04 09:00", &["in"]);
+    // The day was finished on the laptop, and its log synced over.
+    fs::write(cli.data_path("events/2024-03-04.laptop"), "- id: laptop-1
+  recorded: 2024-03-04 18:00:00 +0000
+  change:
+    event: out
+    at: 2024-03-04 18:00:00 +0000
+").unwrap();
+
+    let report: String = cli.ok("2024-03-05 09:00", &["report", "--from", "2024-03-04", "--to", "2024-03-04", "--format", "csv"]);
+    assert!(report.contains("2024-03-04,540,"), "{}", report);
+    // Only commands holding the data lock bring the file up to date.
+    assert!(!fs::read_to_string(cli.data_path("days/2024-03-04")).unwrap().contains("18:00:00"));
+    cli.ok("2024-03-05 09:00", &["reindex"]);
+    assert!(fs::read_to_string(cli.data_path("days/2024-03-04")).unwrap().contains("18:00:00"));
+}
+
+#[test]
+fn unreadable_event_logs_are_skipped_and_reported() {
+    let cli: CliRunner = cli();
+    cli.ok("2024-03-04 09:00", &["in"]);
+    cli.ok("2024-03-04 17:00", &["out"]);
+    fs::write(cli.data_path("events/2024-03-04.laptop"), "- id: [half synced").unwrap();
+
+    let output: Output = cli.run("2024-03-05 09:00", &["report", "--from", "2024-03-04", "--to", "2024-03-04", "--format", "csv"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("2024-03-04,480,"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("2024-03-04.laptop"));
+
+    let doctor: String = cli.ok("2024-03-05 09:00", &["doctor"]);
+    assert!(doctor.contains("Couldn't read the event log"), "{}", doctor);
+    assert!(!cli.run("2024-03-05 09:00", &["undo", "2024-03-04"]).status.success());
+}
+
+#[test]
+fn doctor_reports_event_logs_and_state_changed_outside_punch() {
+    let cli: CliRunner = cli();
+    cli.ok("2024-03-04 09:00", &["in"]);
+    cli.ok("2024-03-04 17:00", &["out"]);
+    let log: PathBuf = cli.data_path("events/2024-03-04.desktop");
+    fs::write(&log, fs::read_to_string(&log).unwrap().replace("17:00:00", "19:00:00")).unwrap();
+    let state: PathBuf = cli.data_path("state");
+    fs::write(&state, fs::read_to_string(&state).unwrap() + "# edited\n").unwrap();
+
+    let doctor: String = cli.ok("2024-03-05 09:00", &["doctor"]);
+    assert!(doctor.contains("The event log '2024-03-04.desktop' doesn't match its checksum"), "{}", doctor);
+    assert!(doctor.contains("The state file doesn't match its checksum"), "{}", doctor);
+    // The mangled log isn't replayed, so the day still ends when the file says.
+    assert!(fs::read_to_string(cli.data_path("days/2024-03-04")).unwrap().contains("17:00:00"));
+
+    cli.ok("2024-03-05 09:00", &["doctor", "--update-checksums"]);
+    assert!(cli.ok("2024-03-05 09:00", &["doctor"]).contains("No problems found."));
+    assert!(fs::read_to_string(cli.data_path("days/2024-03-04")).unwrap().contains("19:00:00"));
+}
+
+#[test]
+fn days_cant_be_added_to_a_closed_week_without_reopening_it() {
+    let cli: CliRunner = cli();
+    cli.ok("2024-03-11 09:00", &["add-day", "2024-03-04", "--in", "09:00", "--out", "17:00"]);
+    cli.ok("2024-03-11 09:00", &["close-week", "--week", "2024-03-04"]);
+
+    let output: Output = cli.run("2024-03-11 09:00", &["add-day", "2024-03-05", "--in", "09:00", "--out", "17:00"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--reopen"));
+    assert!(!cli.data_path("days/2024-03-05").exists());
+
+    cli.ok("2024-03-11 09:00", &["add-day", "2024-03-05", "--in", "09:00", "--out", "17:00", "--reopen"]);
+    assert!(cli.ok("2024-03-11 09:00", &["close-week", "--list"]).contains("1 correction"));
+}
+
+#[test]
+fn read_only_refuses_every_command_that_writes() {
+    let cli: CliRunner = cli();
+    cli.ok("2024-03-04 09:00", &["in"]);
+    for args in [&["--read-only", "out"][..], &["--read-only", "template", "save", "usual"], &["--read-only", "daemon"], &["--read-only", "serve", "--daemon", "--http", "127.0.0.1:0"]] {
+        let output: Output = cli.run("2024-03-04 17:00", args);
+        assert!(!output.status.success(), "punch {:?} ran", args);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("--read-only"));
+    }
+    assert!(!cli.data_path("templates").exists());
+    assert!(cli.ok("2024-03-04 17:00", &["--read-only", "status"]).contains("since 09:00"));
+}
+
+#[test]
+fn read_only_commands_dont_move_the_state_file() {
+    let cli: CliRunner = cli();
+    cli.ok("2024-03-04 09:00", &["in"]);
+    // Like a config from before there was a state file.
+    let state: PathBuf = cli.data_path("state");
+    fs::remove_file(&state).unwrap();
+    let config: PathBuf = cli.data_path("punch.cfg");
+    fs::write(&config, fs::read_to_string(&config).unwrap() + "minutes_behind: 45\nminutes_behind_non_neg: 45\n").unwrap();
+
+    // The day so far counts too: 45 minutes behind from before, and 420 left today.
+    let summary: String = cli.ok("2024-03-04 10:00", &["--read-only", "summary"]);
+    assert!(summary.contains("Minutes behind overall: 465"), "{}", summary);
+    assert!(!state.exists());
+    assert!(fs::read_to_string(&config).unwrap().contains("minutes_behind: 45"));
+
+    fs::write(&config, fs::read_to_string(&config).unwrap().replace("minutes_behind: 45\nminutes_behind_non_neg: 45\n", "")).unwrap();
+    let output: Output = cli.run("2024-03-04 10:00", &["summary"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("There's no state file"));
+}
+
+#[test]
+fn compressed_days_say_when_zstd_is_missing() {
+    let cli: CliRunner = cli();
+    cli.ok("2024-06-03 09:00", &["add-day", "2024-01-08", "--in", "09:00", "--out", "17:00"]);
+    assert!(cli.ok("2024-06-03 09:00", &["compress", "--older-than", "90d"]).contains("Compressed 2 files"));
+    assert!(cli.data_path("days/2024-01-08.zst").exists());
+    assert!(cli.ok("2024-06-03 09:00", &["view", "2024-01-08"]).contains("17:00"));
+
+    let cli: CliRunner = cli.with_env("PATH", "/nonexistent");
+    let output: Output = cli.run("2024-06-03 09:00", &["view", "2024-01-08"]);
+    assert!(!output.status.success());
+    let stderr: String = String::from_utf8_lossy(&output.stderr).to_string();
+    assert!(stderr.contains("zstd is needed to read"), "{}", stderr);
+    assert!(!stderr.contains("No day recorded"), "{}", stderr);
+}
+
+#[test]
+fn heatmap_cells_scale_to_the_day_and_arent_colored_when_piped() {
+    let cli: CliRunner = cli();
+    cli.ok("2024-03-11 09:00", &["add-day", "2024-03-04", "--in", "09:00", "--out", "11:00"]);
+    let before: String = cli.ok("2024-03-11 09:00", &["heatmap", "2024"]);
+    assert!(before.lines().nth(2).unwrap().contains("▒"), "{}", before);
+    assert!(!before.contains('\x1B'));
+
+    let config: PathBuf = cli.data_path("punch.cfg");
+    fs::write(&config, fs::read_to_string(&config).unwrap().replace("day_in_minutes: 480", "day_in_minutes: 240")).unwrap();
+    // Two hours is half of a 4 hour day.
+    let after: String = cli.ok("2024-03-11 09:00", &["heatmap", "2024"]);
+    assert!(after.lines().nth(2).unwrap().contains("▓"), "{}", after);
+}
+
+#[test]
+fn demo_data_only_runs_with_options_it_knows() {
+    let cli: CliRunner = cli();
+    let dir: PathBuf = cli.data_path("demo");
+    let dir_str: &str = dir.to_str().unwrap();
+    assert!(cli.ok("2024-03-04 09:00", &["demo-data", "--dir", dir_str, "--help"]).contains("Usage: punch demo-data"));
+    let output: Output = cli.run("2024-03-04 09:00", &["demo-data", "--dir", dir_str, "--dayz", "30"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("'--dayz' isn't an option"));
+    assert!(!dir.exists());
+
+    assert!(cli.ok("2024-03-04 09:00", &["demo-data", "--dir", dir_str, "--days", "30", "--seed", "1"]).contains("Made up"));
+    assert!(dir.join(".punch-card/days").exists());
+}
+
+#[test]
+fn repeating_commands_need_an_interval_of_at_least_a_second() {
+    let cli: CliRunner = cli();
+    cli.ok("2024-03-04 09:00", &["in"]);
+    for args in [&["status", "--watch", "--interval", "0"][..], &["watch", "--interval", "0"], &["daemon", "--interval", "0"], &["status", "--watch", "--interval", "-5"]] {
+        let output: Output = cli.run("2024-03-04 10:00", args);
+        assert!(!output.status.success(), "punch {:?} ran", args);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("at least 1"));
+    }
+}
+
+#[test]
+fn reindexing_many_days_at_once_brings_each_file_up_to_date() {
+    let cli: CliRunner = cli();
+    let dates: Vec<String> = (1..=20).flat_map(|day: u32| [format!("2024-01-{:02}", day), format!("2024-02-{:02}", day)]).collect();
+    for date_str in &dates {
+        cli.ok(&format!("{} 09:00", date_str), &["in"]);
+        // Each day was finished on the laptop, and its log synced over.
+        fs::write(cli.data_path(&format!("events/{}.laptop", date_str)), format!("- id: laptop-{date}
+  recorded: {date} 18:00:00 +0000
+  change:
+    event: out
+    at: {date} 18:00:00 +0000
+", date = date_str)).unwrap();
+    }
+
+    assert!(cli.ok("2024-03-01 09:00", &["reindex"]).contains("40"));
+    for date_str in &dates {
+        assert!(fs::read_to_string(cli.data_path(&format!("days/{}", date_str))).unwrap().contains("18:00:00"), "{}", date_str);
+    }
+    let names: Vec<String> = fs::read_dir(cli.data_path("days")).unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    assert_eq!(names.len(), dates.len(), "{:?}", names);
+}
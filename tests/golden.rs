@@ -0,0 +1,58 @@
+//! Golden tests: what commands print for a fixed week, compared with the files under
+//! `tests/golden/`. After a change that's meant to alter the output, run them with
+//! `UPDATE_GOLDEN=1` to write the files afresh, and check the diff before committing it.
+
+use std::env::var;
+use std::fs;
+
+use punch::testing::CliRunner;
+
+fn assert_golden(name: &str, output: &str) {
+    let path: String = format!("{}/tests/golden/{}", env!("CARGO_MANIFEST_DIR"), name);
+    if var("UPDATE_GOLDEN").is_ok() {
+        fs::write(&path, output).unwrap();
+        return;
+    }
+    let expected: String = fs::read_to_string(&path).unwrap_or_else(|err| panic!("Couldn't read '{}' ({}). Run with UPDATE_GOLDEN=1 to write it.", path, err));
+    assert!(output == expected, "The output differs from '{}':\n{}", path, output);
+}
+
+/// Three days of the same routine, and the morning of a fourth.
+fn record_week() -> CliRunner {
+    let cli: CliRunner = CliRunner::new(env!("CARGO_BIN_EXE_punch"));
+    for date in ["2024-03-04", "2024-03-05", "2024-03-06"] {
+        cli.ok(&format!("{} 09:00", date), &["in", "coding"]);
+        cli.ok(&format!("{} 12:00", date), &["pause", "lunch"]);
+        cli.ok(&format!("{} 12:45", date), &["resume"]);
+        cli.ok(&format!("{} 15:00", date), &["task", "review"]);
+        cli.ok(&format!("{} 17:30", date), &["out"]);
+    }
+    cli.ok("2024-03-07 09:00", &["in", "coding"]);
+    return cli;
+}
+
+#[test]
+fn report_formats() {
+    let cli: CliRunner = record_week();
+    for (format, name) in [("table", "report.txt"), ("csv", "report.csv"), ("json", "report.json"), ("markdown", "report.md")] {
+        let output: String = cli.ok("2024-03-07 11:00", &["report", "--from", "2024-03-04", "--to", "2024-03-06", "--format", format]);
+        assert_golden(name, &output);
+    }
+}
+
+#[test]
+fn status_and_summary_of_the_current_day() {
+    let cli: CliRunner = record_week();
+    assert_golden("status.txt", &cli.ok("2024-03-07 11:00", &["status"]));
+    assert_golden("summary.txt", &cli.ok("2024-03-07 11:00", &["summary"]));
+}
+
+#[test]
+fn standup_and_forecast_as_reports() {
+    let cli: CliRunner = record_week();
+    cli.ok("2024-03-07 09:05", &["plan", "finish the review"]);
+    assert_golden("standup.txt", &cli.ok("2024-03-07 11:00", &["standup"]));
+    assert_golden("standup.json", &cli.ok("2024-03-07 11:00", &["standup", "--format", "json"]));
+    assert_golden("forecast.txt", &cli.ok("2024-03-07 11:00", &["forecast", "--extra", "30"]));
+    assert_golden("forecast.csv", &cli.ok("2024-03-07 11:00", &["forecast", "--extra", "30", "--format", "csv"]));
+}
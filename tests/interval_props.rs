@@ -0,0 +1,114 @@
+//! Property checks for block and interval arithmetic, over generated inputs. They're drawn
+//! from a seeded xorshift generator rather than proptest, which isn't a dependency, so there's
+//! no shrinking: a failure reports the seed of the case as it was generated.
+
+use chrono::prelude::{DateTime, Local, TimeZone};
+use chrono::Duration;
+
+use punch::units::interval::Interval;
+use punch::Day;
+
+const CASES: u64 = 256;
+
+/// A small xorshift generator so that failing cases are reproducible from their seed.
+struct Gen(u64);
+
+impl Gen {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        return self.0;
+    }
+
+    fn below(&mut self, max: u64) -> u64 {
+        return self.next() % max;
+    }
+
+    fn start_time(&mut self) -> DateTime<Local> {
+        // Some time in 2020-2030, at a whole second.
+        let secs: i64 = 1_577_836_800 + self.below(10 * 365 * 24 * 60 * 60) as i64;
+        return Local.timestamp_opt(secs, 0).unwrap();
+    }
+}
+
+fn for_each_case(mut check: impl FnMut(u64, &mut Gen)) {
+    for seed in 1..=CASES {
+        check(seed, &mut Gen(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15)));
+    }
+}
+
+#[test]
+fn interval_length_is_end_minus_start() {
+    for_each_case(|seed: u64, gen: &mut Gen| {
+        let start: DateTime<Local> = gen.start_time();
+        let length: i64 = gen.below(48 * 60 * 60) as i64;
+        let mut interval: Interval = Interval::new(&start);
+        assert_eq!(interval.get_length_secs(), None, "seed {seed}");
+        interval.end_at(&(start + Duration::seconds(length)));
+        assert_eq!(interval.get_length_secs(), Some(length), "seed {seed}");
+        assert_eq!(interval.get_length_mins(), Some(length / 60), "seed {seed}");
+    });
+}
+
+#[test]
+fn interval_survives_a_round_trip_through_yaml() {
+    for_each_case(|seed: u64, gen: &mut Gen| {
+        let start: DateTime<Local> = gen.start_time();
+        let mut interval: Interval = Interval::new(&start);
+        interval.end_at(&(start + Duration::seconds(gen.below(24 * 60 * 60) as i64)));
+        let parsed: Interval = Interval::from_string(&interval.as_string());
+        assert_eq!(parsed.get_start_as_str(), interval.get_start_as_str(), "seed {seed}");
+        assert_eq!(parsed.get_length_secs(), interval.get_length_secs(), "seed {seed}");
+    });
+}
+
+#[test]
+fn work_and_breaks_add_up_to_the_day() {
+    for_each_case(|seed: u64, gen: &mut Gen| {
+        let mut at: DateTime<Local> = gen.start_time();
+        let mut day: Day = Day::new(&at, "start".to_string(), 480);
+        let mut on_break: bool = false;
+        for block in 0..gen.below(12) {
+            at += Duration::seconds(1 + gen.below(3 * 60 * 60) as i64);
+            if on_break || gen.below(2) == 0 {
+                day.start_new_block(format!("task-{block}"), &at).unwrap();
+                on_break = false;
+            }
+            else {
+                day.start_break_at("break".to_string(), &at).unwrap();
+                on_break = true;
+            }
+        }
+        at += Duration::seconds(1 + gen.below(3 * 60 * 60) as i64);
+        day.end_day_at(&at).unwrap();
+
+        let day_secs: i64 = day.get_day_length_secs().unwrap();
+        let done_secs: i64 = day.get_time_done_secs().unwrap();
+        let break_secs: i64 = day.get_total_break_time_secs().unwrap();
+        assert_eq!(done_secs + break_secs, day_secs, "seed {seed}");
+        assert_eq!(day.get_time_left_secs(), Some(480 * 60 - done_secs), "seed {seed}");
+        let block_secs: i64 = day.timeblocks.iter().map(|block| block.get_length_secs().unwrap()).sum();
+        assert_eq!(block_secs, day_secs, "seed {seed}");
+    });
+}
+
+#[test]
+fn time_away_can_be_taken_back_as_work() {
+    for_each_case(|seed: u64, gen: &mut Gen| {
+        let start: DateTime<Local> = gen.start_time();
+        let mut day: Day = Day::new(&start, "start".to_string(), 480);
+        let away_start: DateTime<Local> = start + Duration::seconds(1 + gen.below(4 * 60 * 60) as i64);
+        let away_end: DateTime<Local> = away_start + Duration::seconds(1 + gen.below(2 * 60 * 60) as i64);
+        day.end_day_at(&(away_end + Duration::seconds(gen.below(60 * 60) as i64))).unwrap();
+        let done_secs: i64 = day.get_time_done_secs().unwrap();
+
+        day.set_span(&away_start, &away_end, &"break".to_string(), true).unwrap();
+        assert_eq!(day.get_total_break_time_secs(), Some((away_end - away_start).num_seconds()), "seed {seed}");
+        assert_eq!(day.get_time_done_secs(), Some(done_secs - (away_end - away_start).num_seconds()), "seed {seed}");
+
+        day.set_span(&away_start, &away_end, &"start".to_string(), false).unwrap();
+        assert_eq!(day.get_time_done_secs(), Some(done_secs), "seed {seed}");
+        assert_eq!(day.timeblocks.len(), 1, "seed {seed}");
+    });
+}
@@ -0,0 +1,298 @@
+use std::sync::Arc;
+use chrono::prelude::NaiveDate;
+use chrono::Duration;
+
+use punch::testing::CommandRunner;
+use punch::utils::clock::{FixedClock, parse_timestamp};
+use punch::utils::compression::compress_days_before;
+use punch::utils::index::get_summaries_in_range;
+use punch::utils::checksums::{Checksummed, check_checksum};
+use punch::utils::event_log::{DayChange, DayEvent, get_events_dir, read_events, undo_last_change};
+use punch::utils::ledger::{ClosedWeek, close_week, get_closed_week};
+use punch::units::day::{DAY_FORMAT_VERSION, delete_day_for_date, get_day_file_path_for_date};
+use punch::utils::config::{Config, get_config_path, update_config};
+use punch::utils::file_io::{FromString, delete_file, expand_path, read_file, write_file};
+use punch::utils::team::get_data_dir;
+use punch::{DateRange, Day};
+
+fn date(date_str: &str) -> NaiveDate {
+    return NaiveDate::parse_from_str(date_str, "%Y-%m-%d").unwrap();
+}
+
+#[test]
+fn full_day_with_a_break() {
+    let runner: CommandRunner = CommandRunner::new();
+    runner.run_all(&[
+        "in coding --at '2024-03-04 09:00'",
+        "pause lunch --at '2024-03-04 12:00'",
+        "resume --at '2024-03-04 12:30'",
+        "task review --at '2024-03-04 15:00'",
+        "out --at '2024-03-04 17:30'",
+    ]).unwrap();
+
+    let day: Day = runner.day(&date("2024-03-04")).unwrap();
+    assert!(day.has_ended());
+    assert_eq!(day.get_total_break_time_secs(), Some(30 * 60));
+    assert_eq!(day.get_time_done_secs(), Some(8 * 60 * 60));
+    assert_eq!(day.get_tasks_in_chronological_order(), vec!["coding", "lunch", "review"]);
+    assert_eq!(runner.state().minutes_behind(), 0);
+}
+
+#[test]
+fn edits_apply_to_the_current_day() {
+    let runner: CommandRunner = CommandRunner::new();
+    runner.run_all(&[
+        "in --at '2024-03-05 08:00'",
+        "update-task planning --at '2024-03-05 08:05'",
+        "note 'kick-off went well' --at '2024-03-05 08:30'",
+        "out --at '2024-03-05 14:00'",
+    ]).unwrap();
+
+    let day: Day = runner.day(&date("2024-03-05")).unwrap();
+    assert_eq!(day.get_latest_task_name(), "planning");
+    assert_eq!(day.timeblocks[0].get_notes()[0].get_msg(), "kick-off went well");
+    assert_eq!(runner.state().minutes_behind(), 2 * 60);
+}
+
+#[test]
+fn invalid_commands_leave_storage_untouched() {
+    let runner: CommandRunner = CommandRunner::new();
+    let paths_before: Vec<String> = runner.storage().paths();
+    assert!(runner.run("out --at '2024-03-06 17:00'").is_err());
+    assert!(runner.run("resume --at '2024-03-06 17:00'").is_err());
+    assert_eq!(runner.storage().paths(), paths_before);
+}
+
+#[test]
+fn finished_days_are_summarised_in_order() {
+    let runner: CommandRunner = CommandRunner::new();
+    for day_str in ["2024-03-13", "2024-03-11", "2024-03-12"] {
+        runner.run(&format!("in --at '{day_str} 09:00'")).unwrap();
+        runner.run(&format!("out --at '{day_str} 13:00'")).unwrap();
+    }
+
+    let range: DateRange = DateRange::new(date("2024-03-11"), date("2024-03-17")).unwrap();
+    let now = date("2024-03-18").and_hms_opt(0, 0, 0).unwrap().and_local_timezone(chrono::Local).unwrap();
+    let dates: Vec<String> = get_summaries_in_range(&range, &now).into_iter().map(|summary| summary.date).collect();
+    assert_eq!(dates, vec!["2024-03-11", "2024-03-12", "2024-03-13"]);
+}
+
+#[test]
+fn commands_without_at_use_the_runners_clock() {
+    let clock: Arc<FixedClock> = Arc::new(FixedClock::new(parse_timestamp("2024-03-07 09:15").unwrap()));
+    let runner: CommandRunner = CommandRunner::with_clock(clock.clone());
+    runner.run("in").unwrap();
+    clock.advance(Duration::hours(9));
+    runner.run("out").unwrap();
+
+    let day: Day = runner.day(&date("2024-03-07")).unwrap();
+    assert_eq!(day.get_day_start().as_dt(), parse_timestamp("2024-03-07 09:15").unwrap());
+    assert_eq!(day.get_time_done_secs(), Some(9 * 60 * 60));
+    assert_eq!(runner.state().minutes_behind(), -60);
+}
+
+#[test]
+fn days_saved_before_the_format_was_versioned_still_read() {
+    let legacy_yaml: String = concat!(
+        "overall_interval:\n  start: 2024-03-04 09:00:00 +0000\n  end: 2024-03-04 17:00:00 +0000\n",
+        "timeblocks:\n",
+        "- task_name: coding\n  interval:\n    start: 2024-03-04 09:00:00 +0000\n    end: 2024-03-04 12:00:00 +0000\n  notes: []\n",
+        "- task_name: lunch\n  interval:\n    start: 2024-03-04 12:00:00 +0000\n    end: 2024-03-04 13:00:00 +0000\n  notes: []\n",
+        "- task_name: coding\n  interval:\n    start: 2024-03-04 13:00:00 +0000\n    end: 2024-03-04 17:00:00 +0000\n  notes: []\n",
+        "tasks:\n  coding:\n  - 0\n  - 2\n  lunch:\n  - 1\n",
+        "breaks:\n- 1\non_break: false\ntime_to_do: 420\nsummaries: []\n").to_string();
+    let day: Day = Day::try_from_string(&legacy_yaml).unwrap();
+    assert_eq!(day.get_time_done_secs(), Some(7 * 60 * 60));
+    assert_eq!(day.get_task_times_secs_and_num_blocks()["coding"], (7 * 60 * 60, 2));
+
+    let saved: String = day.as_string();
+    assert!(saved.starts_with(&format!("version: {}\n", DAY_FORMAT_VERSION)));
+    assert!(!saved.contains("\ntasks:"));
+    assert_eq!(Day::try_from_string(&saved).unwrap().as_string(), saved);
+    assert!(Day::try_from_string(&saved.replace("version: 2", "version: 99")).is_err());
+}
+
+#[test]
+fn the_config_only_changes_when_a_setting_does() {
+    let runner: CommandRunner = CommandRunner::new();
+    let config_path: String = get_config_path();
+    write_file(&config_path, read_file(&config_path).unwrap().replace("editor: null\n", "editor: null  # the default\n"));
+    let config_before: String = read_file(&config_path).unwrap();
+    runner.run_all(&[
+        "in --at '2024-03-07 09:00'",
+        "out --at '2024-03-07 16:30'",
+    ]).unwrap();
+    assert_eq!(read_file(&config_path).unwrap(), config_before);
+    assert_eq!(runner.state().minutes_behind(), 30);
+
+    let mut config: Config = runner.config();
+    config.set_editor(Some("nano".to_string()));
+    update_config(config);
+    let text: String = read_file(&config_path).unwrap();
+    assert!(text.contains("# How many minutes of work a day needs.\nday_in_minutes: 480\n"));
+    assert!(text.contains("\neditor: nano  # the default\n"));
+}
+
+#[test]
+fn a_relative_data_dir_is_kept_next_to_the_config() {
+    let runner: CommandRunner = CommandRunner::new();
+    let mut config: Config = runner.config();
+    config.set_data_dir(Some("work/".to_string()));
+    update_config(config);
+    assert_eq!(get_data_dir(), expand_path("~/.punch-card/work/"));
+
+    runner.run("in --at '2024-03-04 09:00'").unwrap();
+    assert!(read_file("~/.punch-card/work/days/2024-03-04").is_ok());
+}
+
+#[test]
+fn days_started_in_batch_mode_follow_the_config_like_punch_in() {
+    let runner: CommandRunner = CommandRunner::new();
+    let config_path: String = get_config_path();
+    write_file(&config_path, read_file(&config_path).unwrap()
+        .replace("holidays: null", "holidays: ['2024-03-05']")
+        .replace("paid_break_minutes: null", "paid_break_minutes: 15")
+        .replace("work_days: null", "work_days: [Mon, Tue, Wed, Thu]"));
+    runner.run_all(&[
+        "in --at '2024-03-04 09:00'",
+        "in --at '2024-03-05 09:00'",
+        "in --at '2024-03-08 09:00'",
+    ]).unwrap();
+
+    let targets: Vec<u64> = ["2024-03-04", "2024-03-05", "2024-03-08"].iter()
+        .map(|date_str: &&str| runner.day(&date(date_str)).unwrap().get_time_to_do())
+        .collect();
+    assert_eq!(targets, vec![480, 0, 0]);
+    assert_eq!(runner.day(&date("2024-03-04")).unwrap().paid_break_minutes, 15);
+}
+
+#[test]
+fn minutes_behind_move_out_of_an_old_config() {
+    let runner: CommandRunner = CommandRunner::new();
+    delete_file("~/.punch-card/state").unwrap();
+    let config_path: String = get_config_path();
+    write_file(&config_path, read_file(&config_path).unwrap() + "minutes_behind: 45\nminutes_behind_non_neg: 45\n");
+    assert_eq!(runner.state().minutes_behind(), 45);
+    assert!(!read_file(&config_path).unwrap().contains("minutes_behind"));
+    runner.run_all(&[
+        "in --at '2024-03-08 09:00'",
+        "out --at '2024-03-08 17:15'",
+    ]).unwrap();
+    assert_eq!(runner.state().minutes_behind(), 30);
+}
+
+#[test]
+fn minutes_behind_survive_a_change_of_data_dir() {
+    let runner: CommandRunner = CommandRunner::new();
+    runner.run_all(&[
+        "in --at '2024-03-04 09:00'",
+        "out --at '2024-03-04 16:00'",
+    ]).unwrap();
+    let mut config: Config = runner.config();
+    config.set_data_dir(Some("work/".to_string()));
+    update_config(config);
+    assert_eq!(runner.state().minutes_behind(), 60);
+}
+
+#[test]
+fn a_state_file_in_the_data_dir_moves_next_to_the_config() {
+    let runner: CommandRunner = CommandRunner::new();
+    let mut config: Config = runner.config();
+    config.set_data_dir(Some("work/".to_string()));
+    update_config(config);
+    delete_file("~/.punch-card/state").unwrap();
+    write_file("~/.punch-card/work/state", "minutes_behind: 20\nminutes_behind_non_neg: 20\n".to_string());
+
+    assert_eq!(runner.state().minutes_behind(), 20);
+    assert!(read_file("~/.punch-card/state").is_ok());
+    assert!(read_file("~/.punch-card/work/state").is_err());
+    assert!(check_checksum(&Checksummed::State, &read_file("~/.punch-card/state").unwrap()).is_ok());
+}
+
+#[test]
+fn changes_to_a_closed_week_are_kept_as_corrections() {
+    let runner: CommandRunner = CommandRunner::new();
+    runner.run_all(&[
+        "in --at '2024-03-11 09:00'",
+        "out --at '2024-03-11 17:00'",
+        "in --at '2024-03-12 09:00'",
+        "out --at '2024-03-12 16:00'",
+    ]).unwrap();
+    let closed: ClosedWeek = close_week(&date("2024-03-11"), &parse_timestamp("2024-03-18 09:00").unwrap());
+    assert_eq!((closed.done_minutes, closed.to_do_minutes), (15 * 60, 16 * 60));
+    assert!(runner.run("pause --at '2024-03-12 12:00'").unwrap_err().contains("--reopen"));
+    assert!(runner.run("in --at '2024-03-13 09:00'").unwrap_err().contains("--reopen"));
+    assert!(runner.day(&date("2024-03-13")).is_none());
+
+    delete_day_for_date(&date("2024-03-12")).unwrap();
+    let corrected: ClosedWeek = get_closed_week(&date("2024-03-14")).unwrap();
+    assert_eq!((corrected.done_minutes, corrected.to_do_minutes, corrected.num_corrections), (8 * 60, 8 * 60, 1));
+}
+
+#[test]
+fn days_are_replayed_from_every_devices_event_log() {
+    let runner: CommandRunner = CommandRunner::new();
+    runner.run_all(&[
+        "in coding --at '2024-03-04 09:00'",
+        "pause lunch --at '2024-03-04 12:00'",
+        "resume --at '2024-03-04 12:30'",
+        "out --at '2024-03-04 17:00'",
+    ]).unwrap();
+    let events: Vec<DayEvent> = read_events(&date("2024-03-04"));
+    let kinds: Vec<&str> = events.iter().map(|event: &DayEvent| match event.change {
+        DayChange::In {..} => "in",
+        DayChange::Pause {..} => "pause",
+        DayChange::Switch {..} => "switch",
+        DayChange::Out {..} => "out",
+        _ => "other",
+    }).collect();
+    assert_eq!(kinds, vec!["in", "pause", "switch", "out"]);
+
+    let (undone, day): (DayEvent, Option<Day>) = undo_last_change(&date("2024-03-04")).unwrap();
+    assert!(matches!(undone.change, DayChange::Out {..}));
+    assert!(!day.unwrap().has_ended());
+
+    write_file(&(get_events_dir() + "2024-03-04.laptop"), "- id: laptop-1
+  recorded: 2099-01-01 09:00:00 +0000
+  change:
+    event: out
+    at: 2024-03-04 18:00:00 +0000
+".to_string());
+    let day: Day = runner.day(&date("2024-03-04")).unwrap();
+    assert_eq!(day.get_day_end().unwrap().as_dt(), parse_timestamp("2024-03-04 18:00").unwrap());
+
+    // Reports see the other device's change too, not the stale index entry.
+    let range: DateRange = DateRange::new(date("2024-03-04"), date("2024-03-04")).unwrap();
+    let now = date("2024-03-05").and_hms_opt(0, 0, 0).unwrap().and_local_timezone(chrono::Local).unwrap();
+    let done_secs: Vec<i64> = get_summaries_in_range(&range, &now).into_iter().map(|summary| summary.done_secs).collect();
+    assert_eq!(done_secs, vec![(8 * 60 + 30) * 60]);
+}
+
+#[test]
+fn day_files_changed_outside_punch_fail_their_checksum() {
+    let runner: CommandRunner = CommandRunner::new();
+    runner.run_all(&["in --at '2024-03-04 09:00'", "out --at '2024-03-04 17:00'"]).unwrap();
+    let path: String = get_day_file_path_for_date(&date("2024-03-04"));
+    let yaml_str: String = read_file(&path).unwrap();
+    assert!(check_checksum(&Checksummed::Day(date("2024-03-04")), &yaml_str).is_ok());
+
+    write_file(&path, yaml_str.replace("17:00:00", "18:00:00"));
+    assert!(check_checksum(&Checksummed::Day(date("2024-03-04")), &read_file(&path).unwrap()).is_err());
+}
+
+#[test]
+fn compressed_files_read_back_through_the_storage() {
+    let runner: CommandRunner = CommandRunner::new();
+    runner.run_all(&[
+        "in --at '2024-03-04 09:00'",
+        "out --at '2024-03-04 17:00'",
+    ]).unwrap();
+    let day_file: String = get_day_file_path_for_date(&date("2024-03-04"));
+    let before: String = read_file(&day_file).unwrap();
+
+    assert_eq!(compress_days_before(&date("2024-03-05")).unwrap(), 2);
+    assert!(runner.storage().paths().contains(&expand_path(&(day_file.clone() + ".zst"))));
+    assert!(!runner.storage().paths().contains(&expand_path(&day_file)));
+    assert_eq!(read_file(&day_file).unwrap(), before);
+    assert_eq!(runner.day(&date("2024-03-04")).unwrap().get_time_done_secs(), Some(8 * 3600));
+}